@@ -0,0 +1,14 @@
+// Tauri commands for system context
+
+use crate::context::{self, SystemContext};
+
+/// Get the user, host, distribution, kernel, and container/VM the terminal runs in
+///
+/// # Returns
+/// Everything the title bar and new-tab page show, in one round trip
+#[tauri::command]
+pub async fn get_context() -> Result<SystemContext, String> {
+    tokio::task::spawn_blocking(context::detect)
+        .await
+        .map_err(|e| format!("Failed to detect context: {}", e))
+}
@@ -0,0 +1,61 @@
+// Tauri commands for external control
+
+use crate::control::actions::{Action, ActionRegistry};
+use crate::control::{launch::PendingLaunch, ControlAction};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+/// Take the actions requested by the arguments the app was launched with
+///
+/// Later launches are delivered as `control://action` events instead.
+#[tauri::command]
+pub fn take_launch_actions(pending: State<'_, PendingLaunch>) -> Vec<ControlAction> {
+    pending.take()
+}
+
+/// List the actions the command palette offers
+///
+/// # Arguments
+/// * `query` - Only list actions whose ID, title, or description contains this text
+///
+/// # Returns
+/// Built-in actions followed by plugin actions, with their current key bindings
+#[tauri::command]
+pub fn list_actions(query: Option<String>, registry: State<'_, ActionRegistry>) -> Vec<Action> {
+    registry.list(query.as_deref())
+}
+
+/// Run an action from the registry, as choosing it in the command palette does
+///
+/// # Arguments
+/// * `id` - Action ID, e.g. "splitPane"
+/// * `args` - Arguments the action requires, e.g. `{ "direction": "vertical" }`
+#[tauri::command]
+pub fn run_action(
+    id: String,
+    args: Option<HashMap<String, String>>,
+    app: AppHandle,
+    registry: State<'_, ActionRegistry>,
+) -> Result<(), String> {
+    registry.run(&app, &id, args.unwrap_or_default())
+}
+
+/// Add a plugin action to the registry, or replace the one with the same ID
+///
+/// Running it emits a `runAction` control action for the plugin to handle.
+///
+/// # Arguments
+/// * `action` - The action; `plugin` must name the plugin registering it
+#[tauri::command]
+pub fn register_action(action: Action, registry: State<'_, ActionRegistry>) -> Result<(), String> {
+    registry.register(action)
+}
+
+/// Remove a plugin action from the registry
+///
+/// # Arguments
+/// * `id` - Action ID
+#[tauri::command]
+pub fn unregister_action(id: String, registry: State<'_, ActionRegistry>) -> Result<(), String> {
+    registry.unregister(&id)
+}
@@ -0,0 +1,16 @@
+// Tauri commands for non-interactive command execution
+
+use crate::exec::{self, ExecOptions, ExecOutput};
+
+/// Run a program without a visible session and capture its output
+///
+/// # Arguments
+/// * `options` - Program, arguments, working directory, environment, and timeout
+///
+/// # Returns
+/// Captured stdout/stderr, exit code, and whether the timeout was hit
+#[tauri::command]
+pub async fn exec_command(options: ExecOptions) -> Result<ExecOutput, String> {
+    log::debug!("exec_command: {} {:?}", options.program, options.args);
+    exec::run(&options).await
+}
@@ -0,0 +1,15 @@
+// Tauri commands for font inspection
+
+use crate::fonts::{self, FontCoverageReport};
+
+/// Check whether a font family covers Powerline, Nerd Font, and emoji glyphs
+///
+/// # Arguments
+/// * `font_family` - CSS font-family stack as stored in settings
+///
+/// # Returns
+/// Per-group coverage so the settings UI can warn about missing prompt icons
+#[tauri::command]
+pub async fn check_font_coverage(font_family: String) -> Result<FontCoverageReport, String> {
+    fonts::check_coverage(&font_family).await
+}
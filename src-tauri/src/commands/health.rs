@@ -0,0 +1,29 @@
+// Tauri commands for startup health checks
+
+use crate::health::{Health, HealthReport, RendererStatus};
+use tauri::{AppHandle, Manager};
+
+/// Get the results of the self-checks run at launch, for the first-run experience
+///
+/// # Arguments
+/// * `renderer` - What the frontend found out about WebGL and its renderer, which only it can tell
+/// * `refresh` - Run the checks again, e.g. after the user fixed something
+///
+/// # Returns
+/// Each check with its status, message, and suggested fix
+#[tauri::command]
+pub async fn get_health_report(
+    renderer: Option<RendererStatus>,
+    refresh: Option<bool>,
+    app: AppHandle,
+) -> Result<HealthReport, String> {
+    let health = app.state::<Health>();
+    if let Some(renderer) = renderer {
+        health.set_renderer(renderer);
+    }
+    // The launch checks may still be running; checking again then is harmless
+    match health.last() {
+        Some(report) if !refresh.unwrap_or(false) => Ok(report),
+        _ => Ok(health.check().await),
+    }
+}
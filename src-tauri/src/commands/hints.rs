@@ -0,0 +1,61 @@
+// Tauri commands for link hints and opening links
+
+use super::scope::{self, SHORT_TIMEOUT};
+use crate::hints::{self, LineToken};
+use crate::opener;
+use std::path::Path;
+use tauri::Webview;
+
+/// Find URLs, existing paths, commits, issue references, and IP addresses in a line of terminal
+/// output
+///
+/// # Arguments
+/// * `line` - Text of the line as displayed
+/// * `session_id` - Session showing the line; paths, commits, and issues are looked up in its
+///   working directory unless it is connected to a remote host
+///
+/// # Returns
+/// Tokens in line order, with UTF-16 ranges, resolved links and previews, and the actions to offer
+#[tauri::command]
+pub async fn analyze_line(line: String, session_id: Option<String>, webview: Webview) -> Result<Vec<LineToken>, String> {
+    let cwd = match session_id {
+        Some(session_id) => {
+            let location = scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.location(&session_id)).await?;
+            location.cwd.filter(|_| location.host.is_none())
+        }
+        None => None,
+    };
+    Ok(hints::analyze(&line, cwd.as_deref()).await)
+}
+
+/// Open a link from terminal output, such as an OSC 8 hyperlink, in its default application
+///
+/// # Arguments
+/// * `url` - An http, https, ftp, mailto, or local file URL
+///
+/// # Returns
+/// Nothing; fails for other schemes, missing files, or when no application could open it
+#[tauri::command]
+pub async fn open_url(url: String) -> Result<(), String> {
+    opener::open_url(&url).await
+}
+
+/// Open a file or directory from terminal output in its default application
+///
+/// # Arguments
+/// * `path` - Absolute, `~/`, or relative path
+/// * `session_id` - Session that printed it, whose working directory relative paths are in
+///
+/// # Returns
+/// Nothing; fails when the path does not exist or no application could open it
+#[tauri::command]
+pub async fn open_path(path: String, session_id: Option<String>, webview: Webview) -> Result<(), String> {
+    let cwd = match session_id {
+        Some(session_id) => {
+            let location = scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.location(&session_id)).await?;
+            location.cwd.filter(|_| location.host.is_none())
+        }
+        None => None,
+    };
+    opener::open_path(Path::new(&path), cwd.as_deref()).await
+}
@@ -0,0 +1,33 @@
+// Tauri commands for the command history database
+
+use crate::history::{History, HistoryEntry, HistoryQuery, HistoryStats};
+use tauri::{AppHandle, Manager};
+
+/// Search commands recorded across all sessions, for a Ctrl+R style history
+///
+/// # Arguments
+/// * `query` - Text to match and filters; each command line is listed once at its latest run
+///   unless `allRuns` is set
+///
+/// # Returns
+/// Matching commands, most recent first
+#[tauri::command]
+pub async fn history_search(query: HistoryQuery, app: AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    tokio::task::spawn_blocking(move || app.state::<History>().search(&query))
+        .await
+        .map_err(|e| format!("Failed to search history: {}", e))?
+}
+
+/// Summarize recorded commands
+///
+/// # Arguments
+/// * `since` - Only count commands started from this Unix time in milliseconds
+///
+/// # Returns
+/// Counts, total run time, and the most used programs and directories
+#[tauri::command]
+pub async fn history_stats(since: Option<u64>, app: AppHandle) -> Result<HistoryStats, String> {
+    tokio::task::spawn_blocking(move || app.state::<History>().stats(since))
+        .await
+        .map_err(|e| format!("Failed to read history stats: {}", e))?
+}
@@ -0,0 +1,29 @@
+// Tauri commands for session logs
+
+use crate::logs::{self, chain, LogVerification};
+use std::path::Path;
+
+/// Check a log against its hash chain and, if present, its GPG signature
+///
+/// # Arguments
+/// * `path` - Path of the log file; the chain is read from `<path>.chain`
+///
+/// # Returns
+/// Whether the log is unmodified, and where the first mismatch is otherwise
+#[tauri::command]
+pub async fn verify_log(path: String) -> Result<LogVerification, String> {
+    chain::verify(Path::new(&path)).await
+}
+
+/// Read a session log or recording, decrypting it if it was encrypted at rest
+///
+/// # Arguments
+/// * `path` - Path of the log or recording
+///
+/// # Returns
+/// The contents as text, with invalid UTF-8 replaced
+#[tauri::command]
+pub async fn read_log(path: String) -> Result<String, String> {
+    let contents = logs::read(Path::new(&path)).await?;
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
@@ -0,0 +1,14 @@
+// Tauri commands for background maintenance
+
+use crate::maintenance::{self, MaintenanceReport};
+
+/// Apply retention policies immediately instead of waiting for the next scheduled run
+///
+/// # Returns
+/// Every file that was deleted and why
+#[tauri::command]
+pub async fn run_maintenance_now() -> Result<MaintenanceReport, String> {
+    tokio::task::spawn_blocking(maintenance::run)
+        .await
+        .map_err(|e| format!("Failed to run maintenance: {}", e))
+}
@@ -1,10 +1,47 @@
 // Tauri commands module
 
+pub mod context;
+pub mod control;
+pub mod exec;
+pub mod fonts;
+pub mod health;
+pub mod hints;
+pub mod history;
+pub mod logs;
+pub mod maintenance;
+pub mod notifications;
+pub mod profiles;
 pub mod pty;
+pub mod recents;
+pub mod scope;
+pub mod serial;
+pub mod session_state;
 pub mod settings;
+pub mod shell;
+pub mod ssh;
+pub mod status;
+pub mod themes;
 
-pub use pty::{spawn_pty, pty_write, pty_resize, pty_close};
-pub use settings::{load_settings, save_settings, load_window_state, save_window_state};
+pub use context::get_context;
+pub use control::{take_launch_actions, list_actions, run_action, register_action, unregister_action};
+pub use exec::exec_command;
+pub use fonts::check_font_coverage;
+pub use health::get_health_report;
+pub use hints::{analyze_line, open_path, open_url};
+pub use history::{history_search, history_stats};
+pub use logs::{verify_log, read_log};
+pub use maintenance::run_maintenance_now;
+pub use notifications::{notify_session, set_focused_session};
+pub use profiles::{list_profiles, save_profile, delete_profile, set_profile_rules};
+pub use pty::{spawn_pty, pty_write, pty_set_latency_tracking, pty_get_latency, pty_write_many, pty_write_broadcast, set_broadcast_group, list_broadcast_groups, pty_paste, pty_resize, pty_close, pty_kill, pty_signal_foreground, pty_start_logging, pty_stop_logging, start_recording, stop_recording, get_session_info, pty_get_foreground_process, pty_get_cwd, list_jobs, list_sessions, rename_session, set_session_labels, set_session_monitor, get_session_appearance, set_session_appearance, check_terminfo, get_memory_usage, pty_get_bandwidth, pty_get_scrollback, pty_wake, pty_export_script, diff_command_outputs, pty_open_input_pipe, pty_close_input_pipe, view_file, view_man_page, follow_file, follow_set_paused, follow_seek};
+pub use recents::get_new_tab_suggestions;
+pub use serial::{list_bluetooth_serial_ports, list_serial_ports, spawn_serial};
+pub use session_state::{restore_sessions, save_session_layout};
+pub use shell::{profile_shell_startup, detect_shell_frameworks, bootstrap_prompt};
+pub use ssh::{spawn_ssh, list_ssh_hosts};
+pub use status::{list_status_segments, get_status, register_status_segment, unregister_status_segment, set_status_segment};
+pub use themes::preview_theme;
+pub use settings::{load_settings, save_settings, validate_settings, get_settings_conflict, resolve_settings_conflict, load_window_state, save_window_state, set_window_placement};
 
 #[tauri::command]
 pub fn get_hostname() -> String {
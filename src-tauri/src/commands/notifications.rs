@@ -0,0 +1,22 @@
+// Tauri commands for desktop notifications
+
+use crate::notifications::{self, FocusedSession, SessionNotification};
+use tauri::{AppHandle, State};
+
+/// Show a desktop notification that jumps to a session when clicked
+///
+/// # Arguments
+/// * `notification` - Session, summary, body, and urgency of the notification
+#[tauri::command]
+pub fn notify_session(notification: SessionNotification, app: AppHandle) {
+    notifications::notify(&app, notification);
+}
+
+/// Record which session's terminal has focus, so commands finishing there are not notified
+///
+/// # Arguments
+/// * `session_id` - The focused session, or None when no terminal has focus
+#[tauri::command]
+pub fn set_focused_session(session_id: Option<String>, focused: State<'_, FocusedSession>) {
+    focused.set(session_id);
+}
@@ -0,0 +1,51 @@
+// Tauri commands for shell profiles
+
+use crate::profiles::{Profile, ProfileList, ProfileRule, ProfileStore};
+use tauri::State;
+
+/// List shell profiles
+///
+/// # Returns
+/// Saved profiles, the default profile ID, and the auto-switching rules; until a profile is
+/// saved, the supported shells found in /etc/shells with `detected` set
+#[tauri::command]
+pub fn list_profiles(store: State<'_, ProfileStore>) -> ProfileList {
+    store.list()
+}
+
+/// Add or replace a shell profile
+///
+/// # Arguments
+/// * `profile` - The profile; an empty ID creates a new one
+/// * `make_default` - Use this profile when a spawn names neither a shell nor a profile
+///
+/// # Returns
+/// The saved profile with its ID
+#[tauri::command]
+pub fn save_profile(
+    profile: Profile,
+    make_default: Option<bool>,
+    store: State<'_, ProfileStore>,
+) -> Result<Profile, String> {
+    store.save(profile, make_default.unwrap_or(false))
+}
+
+/// Delete a shell profile
+///
+/// # Arguments
+/// * `id` - The ID of the profile to delete
+#[tauri::command]
+pub fn delete_profile(id: String, store: State<'_, ProfileStore>) -> Result<(), String> {
+    store.delete(&id)
+}
+
+/// Replace the rules that pick a profile for spawns that do not name one
+///
+/// Rules are checked in order; the first whose conditions all match wins.
+///
+/// # Arguments
+/// * `rules` - Rules matching the starting directory, git remote, ssh host, or command
+#[tauri::command]
+pub fn set_profile_rules(rules: Vec<ProfileRule>, store: State<'_, ProfileStore>) -> Result<(), String> {
+    store.set_rules(rules)
+}
@@ -1,8 +1,24 @@
 // Tauri commands for PTY operations
 // These commands are called from the frontend via Tauri IPC
 
-use crate::pty::{PtyManager, SessionInfo, SpawnOptions};
-use tauri::State;
+use crate::logs::session::{LogFormat, SessionLogInfo};
+use crate::pty::{FollowInfo, JobInfo, MemoryUsage, PtyManager, SessionAppearance, SessionInfo, SpawnOptions};
+use crate::pty::activity::MonitorSettings;
+use crate::pty::bandwidth::BandwidthStats;
+use crate::pty::broadcast::{BroadcastGroup, BroadcastReport, BroadcastRules};
+use crate::pty::close_hooks;
+use crate::pty::jobs::ForegroundProcess;
+use crate::pty::latency::LatencyReport;
+use crate::pty::man::ManPage;
+use crate::pty::recording::RecordingInfo;
+use crate::pty::script::{CommandOutputDiff, ExportedScript};
+use crate::pty::scrollback::ScrollbackChunk;
+use crate::pty::session::TERM;
+use crate::pty::signal::Signal;
+use crate::pty::terminfo::{self, TerminfoReport};
+use crate::recents::{RecentKind, Recents};
+use super::scope::{self, SHORT_TIMEOUT, SPAWN_TIMEOUT};
+use tauri::{AppHandle, Manager, State, Webview};
 
 /// Spawn a new PTY session
 ///
@@ -14,19 +30,37 @@ use tauri::State;
 #[tauri::command]
 pub async fn spawn_pty(
     options: SpawnOptions,
-    manager: State<'_, PtyManager>,
+    app: AppHandle,
+    webview: Webview,
 ) -> Result<SessionInfo, String> {
     log::info!("spawn_pty called with options: {:?}", options);
-    manager.spawn(options)
+    let profile_id = options.profile_id.clone();
+    let info = scope::run_with_undo(
+        &webview,
+        SPAWN_TIMEOUT,
+        move |manager| manager.spawn(options),
+        |manager, info| {
+            let _ = manager.close(&info.id);
+        },
+    )
+    .await?;
+
+    if let Some(profile_id) = profile_id.filter(|_| !info.incognito) {
+        app.state::<Recents>().record(RecentKind::Profile, &profile_id);
+    }
+    Ok(info)
 }
 
 /// Write data to a PTY session
 ///
+/// Runs on the main thread, which takes IPC calls in the order they were sent, and only queues
+/// the data: the session writes its input in that order, however long a write waits.
+///
 /// # Arguments
 /// * `session_id` - The ID of the session to write to
 /// * `data` - The data string to write to the PTY
 #[tauri::command]
-pub async fn pty_write(
+pub fn pty_write(
     session_id: String,
     data: String,
     manager: State<'_, PtyManager>,
@@ -34,6 +68,139 @@ pub async fn pty_write(
     manager.write(&session_id, &data)
 }
 
+/// Turn keystroke-to-echo latency measurement on or off for a PTY session
+///
+/// While enabled, short writes from `pty_write` are timestamped and matched to the next
+/// output the session produces. Turning it off discards the samples.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to measure
+/// * `enabled` - Whether to measure
+#[tauri::command]
+pub async fn pty_set_latency_tracking(
+    session_id: String,
+    enabled: bool,
+    webview: Webview,
+) -> Result<(), String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.set_latency_tracking(&session_id, enabled)).await
+}
+
+/// Get keystroke-to-echo latency percentiles of a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+#[tauri::command]
+pub async fn pty_get_latency(
+    session_id: String,
+    webview: Webview,
+) -> Result<LatencyReport, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.latency(&session_id)).await
+}
+
+/// Write the same input to several PTY sessions, as broadcast input does
+///
+/// Each target is checked against the rules first: read-only and alternate-screen sessions are
+/// skipped, and root sessions only receive input once the user confirmed them.
+///
+/// # Arguments
+/// * `session_ids` - The sessions to write to
+/// * `data` - The data string to write
+/// * `rules` - Filters to apply; all are enabled when omitted
+/// * `confirmed` - Root sessions the user agreed to send to
+///
+/// # Returns
+/// Which sessions received the input, which were skipped and why, and which failed
+#[tauri::command]
+pub async fn pty_write_many(
+    session_ids: Vec<String>,
+    data: String,
+    rules: Option<BroadcastRules>,
+    confirmed: Option<Vec<String>>,
+    webview: Webview,
+) -> Result<BroadcastReport, String> {
+    let rules = rules.unwrap_or_default();
+    let confirmed = confirmed.unwrap_or_default();
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| {
+        Ok(manager.write_many(&session_ids, &data, &rules, &confirmed))
+    })
+    .await
+}
+
+/// Write input typed into some sessions to them and to the rest of their broadcast groups
+///
+/// The sessions typed into receive the input as it is; the other members of their groups are
+/// checked against the rules first, as with `pty_write_many`.
+///
+/// # Arguments
+/// * `session_ids` - The sessions the input was typed into
+/// * `data` - The data string to write
+/// * `rules` - Filters to apply to group members; all are enabled when omitted
+/// * `confirmed` - Root sessions the user agreed to send to
+///
+/// # Returns
+/// Which sessions received the input, which were skipped and why, and which failed
+#[tauri::command]
+pub async fn pty_write_broadcast(
+    session_ids: Vec<String>,
+    data: String,
+    rules: Option<BroadcastRules>,
+    confirmed: Option<Vec<String>>,
+    webview: Webview,
+) -> Result<BroadcastReport, String> {
+    let rules = rules.unwrap_or_default();
+    let confirmed = confirmed.unwrap_or_default();
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| {
+        Ok(manager.write_broadcast(&session_ids, &data, &rules, &confirmed))
+    })
+    .await
+}
+
+/// Put a PTY session in a broadcast group, or take it out of its group
+///
+/// # Arguments
+/// * `session_id` - The ID of the session
+/// * `group` - Name of the group to join; `null` or empty leaves the current one
+///
+/// # Returns
+/// The session info with the new group
+#[tauri::command]
+pub async fn set_broadcast_group(
+    session_id: String,
+    group: Option<String>,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| {
+        manager.set_broadcast_group(&session_id, group.as_deref())
+    })
+    .await
+}
+
+/// List the broadcast groups that have sessions in them
+///
+/// # Returns
+/// Each group's name and member sessions, sorted by name
+#[tauri::command]
+pub async fn list_broadcast_groups(webview: Webview) -> Result<Vec<BroadcastGroup>, String> {
+    scope::run(&webview, SHORT_TIMEOUT, |manager| Ok(manager.broadcast_groups())).await
+}
+
+/// Paste text into a PTY session
+///
+/// Wraps the text in bracketed-paste markers when the application enabled mode 2004. Queued
+/// with typed input, like `pty_write`.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to paste into
+/// * `text` - The clipboard text
+#[tauri::command]
+pub fn pty_paste(
+    session_id: String,
+    text: String,
+    manager: State<'_, PtyManager>,
+) -> Result<(), String> {
+    manager.paste(&session_id, &text)
+}
+
 /// Resize a PTY session
 ///
 /// # Arguments
@@ -45,21 +212,564 @@ pub async fn pty_resize(
     session_id: String,
     cols: u16,
     rows: u16,
-    manager: State<'_, PtyManager>,
+    webview: Webview,
 ) -> Result<(), String> {
     log::debug!("pty_resize: {} to {}x{}", session_id, cols, rows);
-    manager.resize(&session_id, cols, rows)
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.resize(&session_id, cols, rows)).await
 }
 
 /// Close a PTY session
 ///
+/// The close hooks of the session's profile run first, such as typing `exit`; the session is
+/// hung up and killed if it has not exited by their timeout.
+///
 /// # Arguments
 /// * `session_id` - The ID of the session to close
 #[tauri::command]
 pub async fn pty_close(
     session_id: String,
-    manager: State<'_, PtyManager>,
+    app: AppHandle,
+    webview: Webview,
 ) -> Result<(), String> {
     log::info!("pty_close: {}", session_id);
-    manager.close(&session_id)
+    close_hooks::run(&app, &session_id).await;
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.close(&session_id)).await
+}
+
+/// Send a signal to a PTY session's shell and its foreground job
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to signal
+/// * `signal` - One of "SIGINT", "SIGTERM", "SIGKILL", "SIGHUP"
+#[tauri::command]
+pub async fn pty_kill(
+    session_id: String,
+    signal: Signal,
+    webview: Webview,
+) -> Result<(), String> {
+    log::info!("pty_kill: {} with {:?}", session_id, signal);
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.signal(&session_id, signal)).await
+}
+
+/// Send a signal to the job in the foreground of a PTY session, but not its shell
+///
+/// Used to interrupt or kill a program flagged on `pty://{id}/unresponsive`.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to signal
+/// * `signal` - One of "SIGINT", "SIGTERM", "SIGKILL", "SIGHUP"
+#[tauri::command]
+pub async fn pty_signal_foreground(
+    session_id: String,
+    signal: Signal,
+    webview: Webview,
+) -> Result<(), String> {
+    log::info!("pty_signal_foreground: {} with {:?}", session_id, signal);
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.signal_foreground(&session_id, signal)).await
+}
+
+/// Start writing a PTY session's output to a log file
+///
+/// Encryption, hash chaining, and signing follow the `logging` section of settings.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to log
+/// * `path` - Log file to create; relative paths are placed in the logs directory
+/// * `format` - "raw", "plain" (escape sequences removed), or "timestamped" (plain, with the time of each line)
+///
+/// # Returns
+/// The log being written
+#[tauri::command]
+pub async fn pty_start_logging(
+    session_id: String,
+    path: String,
+    format: LogFormat,
+    webview: Webview,
+) -> Result<SessionLogInfo, String> {
+    log::info!("pty_start_logging: {} to {}", session_id, path);
+    // Loading the encryption key may wait on the keyring
+    scope::run(&webview, SPAWN_TIMEOUT, move |manager| manager.start_logging(&session_id, &path, format)).await
+}
+
+/// Stop logging a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the logged session
+///
+/// # Returns
+/// The finished log, after its chain was written and signed if configured
+#[tauri::command]
+pub async fn pty_stop_logging(
+    session_id: String,
+    webview: Webview,
+) -> Result<SessionLogInfo, String> {
+    log::info!("pty_stop_logging: {}", session_id);
+    let log = scope::run_with_undo(
+        &webview,
+        SHORT_TIMEOUT,
+        move |manager| manager.stop_logging(&session_id),
+        // Logging stopped all the same: the log is closed properly so its chain still verifies
+        |_, unclaimed| {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = unclaimed.finish().await {
+                    log::warn!("Failed to finish a session log: {}", e);
+                }
+            });
+        },
+    )
+    .await?;
+    log.finish().await
+}
+
+/// Start recording a PTY session as an asciicast v2 file for asciinema players
+///
+/// Encryption, hash chaining, and signing follow the `logging` section of settings.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to record
+/// * `path` - File to create; defaults to a file named after the session in the recordings directory
+///
+/// # Returns
+/// The recording being made
+#[tauri::command]
+pub async fn start_recording(
+    session_id: String,
+    path: Option<String>,
+    webview: Webview,
+) -> Result<RecordingInfo, String> {
+    log::info!("start_recording: {}", session_id);
+    // Loading the encryption key may wait on the keyring
+    scope::run(&webview, SPAWN_TIMEOUT, move |manager| manager.start_recording(&session_id, path.as_deref())).await
+}
+
+/// Stop recording a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the recorded session
+///
+/// # Returns
+/// The finished recording
+#[tauri::command]
+pub async fn stop_recording(
+    session_id: String,
+    webview: Webview,
+) -> Result<RecordingInfo, String> {
+    log::info!("stop_recording: {}", session_id);
+    let recorder = scope::run_with_undo(
+        &webview,
+        SHORT_TIMEOUT,
+        move |manager| manager.stop_recording(&session_id),
+        // Recording stopped all the same: the cast is closed properly, with its held-back tail
+        |_, recorder| {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = recorder.finish().await {
+                    log::warn!("Failed to finish a recording: {}", e);
+                }
+            });
+        },
+    )
+    .await?;
+    recorder.finish().await
+}
+
+/// Get current information about a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+///
+/// # Returns
+/// Session information including tracked terminal modes
+#[tauri::command]
+pub async fn get_session_info(
+    session_id: String,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.info(&session_id)).await
+}
+
+/// List background jobs of a PTY session's shell
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+#[tauri::command]
+pub async fn list_jobs(
+    session_id: String,
+    webview: Webview,
+) -> Result<Vec<JobInfo>, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.list_jobs(&session_id)).await
+}
+
+/// Get the process in the foreground of a PTY session's terminal
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+///
+/// # Returns
+/// Name, pid, and argv of the foreground process; `isShell` is set when nothing but the
+/// shell is running, so closing the tab needs no confirmation
+#[tauri::command]
+pub async fn pty_get_foreground_process(
+    session_id: String,
+    webview: Webview,
+) -> Result<ForegroundProcess, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.foreground_process(&session_id)).await
+}
+
+/// Get the working directory of a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+///
+/// # Returns
+/// The directory of the foreground process, e.g. for opening a new tab in the same place
+#[tauri::command]
+pub async fn pty_get_cwd(
+    session_id: String,
+    webview: Webview,
+) -> Result<String, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.cwd(&session_id)).await
+}
+
+/// List all active PTY sessions
+#[tauri::command]
+pub async fn list_sessions(webview: Webview) -> Result<Vec<SessionInfo>, String> {
+    scope::run(&webview, SHORT_TIMEOUT, |manager| Ok(manager.list())).await
+}
+
+/// Rename a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to rename
+/// * `name` - New name, or an empty string to clear it
+#[tauri::command]
+pub async fn rename_session(
+    session_id: String,
+    name: String,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.rename(&session_id, &name)).await
+}
+
+/// Replace the labels attached to a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to label
+/// * `labels` - The full set of labels
+#[tauri::command]
+pub async fn set_session_labels(
+    session_id: String,
+    labels: Vec<String>,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.set_labels(&session_id, labels)).await
+}
+
+/// Choose whether a PTY session reports output resuming and stopping
+///
+/// With `activity`, output after a quiet spell is reported on `pty://{id}/activity`; with
+/// `silenceSeconds`, that long without output is reported on `pty://{id}/silence`.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to watch
+/// * `monitor` - What to report; both off stops watching
+///
+/// # Returns
+/// The session info with the new settings
+#[tauri::command]
+pub async fn set_session_monitor(
+    session_id: String,
+    monitor: MonitorSettings,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.set_monitor(&session_id, monitor)).await
+}
+
+/// Get the tab color/icon metadata of a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+#[tauri::command]
+pub async fn get_session_appearance(
+    session_id: String,
+    webview: Webview,
+) -> Result<SessionAppearance, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.appearance(&session_id)).await
+}
+
+/// Set the tab color/icon metadata of a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to update
+/// * `appearance` - Color, icon, and emoji; `null` fields clear the marker
+#[tauri::command]
+pub async fn set_session_appearance(
+    session_id: String,
+    appearance: SessionAppearance,
+    webview: Webview,
+) -> Result<(), String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.set_appearance(&session_id, appearance)).await
+}
+
+/// Verify the terminfo entry for the TERM advertised to shells
+///
+/// # Returns
+/// Whether the entry exists and how its key capabilities compare with the renderer
+#[tauri::command]
+pub async fn check_terminfo() -> Result<TerminfoReport, String> {
+    terminfo::check(TERM).await
+}
+
+/// Get the backend buffer memory held for a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+#[tauri::command]
+pub async fn get_memory_usage(
+    session_id: String,
+    webview: Webview,
+) -> Result<MemoryUsage, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.memory_usage(&session_id)).await
+}
+
+/// Get the estimated network traffic of a session's ssh, mosh, and telnet clients
+///
+/// Updates are also published on `pty://{id}/bandwidth` while the traffic changes.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to inspect
+///
+/// # Returns
+/// Totals since the session started and the clients running now
+#[tauri::command]
+pub async fn pty_get_bandwidth(
+    session_id: String,
+    webview: Webview,
+) -> Result<BandwidthStats, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.bandwidth(&session_id)).await
+}
+
+/// Read back output kept in the backend for a PTY session
+///
+/// Lets a terminal re-render its history after a webview reload or a move to another window.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to read
+/// * `offset` - Position in the session's output stream to start at (default: oldest kept)
+/// * `len` - Maximum number of bytes to return (default: everything after `offset`)
+///
+/// # Returns
+/// The output in the session's output encoding, with the stream offsets it covers
+#[tauri::command]
+pub async fn pty_get_scrollback(
+    session_id: String,
+    offset: Option<u64>,
+    len: Option<usize>,
+    webview: Webview,
+) -> Result<ScrollbackChunk, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.scrollback(&session_id, offset, len)).await
+}
+
+/// Read a hibernating PTY session's scrollback back into memory
+///
+/// Sessions wake on their own when output arrives or their scrollback is read; calling this
+/// when a terminal is focused has it ready before it is needed.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to wake
+#[tauri::command]
+pub async fn pty_wake(session_id: String, webview: Webview) -> Result<(), String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.wake(&session_id)).await
+}
+
+/// Export the commands run in a range of a PTY session's output as a shell script
+///
+/// Commands are found through the prompt marks (OSC 133) of shell integration; prompts and
+/// output are left out.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session
+/// * `offset` - Position in the session's output stream to start at (default: oldest kept)
+/// * `end` - Position to stop at (default: the latest output)
+/// * `skip_failed` - Leave out commands that exited with a non-zero status (default false)
+///
+/// # Returns
+/// The script, with the number of commands in it and the stream offsets it covers
+#[tauri::command]
+pub async fn pty_export_script(
+    session_id: String,
+    offset: Option<u64>,
+    end: Option<u64>,
+    skip_failed: Option<bool>,
+    webview: Webview,
+) -> Result<ExportedScript, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| {
+        manager.export_script(&session_id, offset, end, skip_failed.unwrap_or(false))
+    })
+    .await
+}
+
+/// Diff the outputs of two earlier commands of a PTY session
+///
+/// Commands are found through the prompt marks (OSC 133) of shell integration, e.g. to compare
+/// two runs of a flaky test or two config dumps.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session
+/// * `cmd_a` - Index of the first command among those in the scrollback, oldest first;
+///   negative counts back from the latest (-1 is the last command)
+/// * `cmd_b` - Index of the second command
+///
+/// # Returns
+/// A unified diff from the first command's output to the second's, and the commands compared
+#[tauri::command]
+pub async fn diff_command_outputs(
+    session_id: String,
+    cmd_a: i64,
+    cmd_b: i64,
+    webview: Webview,
+) -> Result<CommandOutputDiff, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| {
+        manager.diff_command_outputs(&session_id, cmd_a, cmd_b)
+    })
+    .await
+}
+
+/// Open an external input FIFO for a PTY session
+///
+/// Anything written to the returned path is sent to the session as input.
+///
+/// # Arguments
+/// * `session_id` - The ID of the session to receive input
+///
+/// # Returns
+/// The filesystem path of the FIFO
+#[tauri::command]
+pub async fn pty_open_input_pipe(
+    session_id: String,
+    webview: Webview,
+) -> Result<String, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.open_input_pipe(&session_id)).await
+}
+
+/// Close and remove the external input FIFO of a PTY session
+///
+/// # Arguments
+/// * `session_id` - The ID of the session
+#[tauri::command]
+pub async fn pty_close_input_pipe(
+    session_id: String,
+    webview: Webview,
+) -> Result<(), String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.close_input_pipe(&session_id)).await
+}
+
+/// Open a file in a read-only, scrollable, searchable pager session
+///
+/// # Arguments
+/// * `path` - File to view; ANSI colors in it are rendered
+/// * `cols` - Number of columns
+/// * `rows` - Number of rows
+#[tauri::command]
+pub async fn view_file(
+    path: String,
+    cols: u16,
+    rows: u16,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    log::info!("view_file: {}", path);
+    scope::run_with_undo(
+        &webview,
+        SPAWN_TIMEOUT,
+        move |manager| manager.view_file(&path, cols, rows),
+        |manager, info| {
+            let _ = manager.close(&info.id);
+        },
+    )
+    .await
+}
+
+/// Open a man page in a read-only, scrollable pager session
+///
+/// Use this instead of running `man` in the current shell, e.g. to show the page for the word
+/// under the cursor in a split.
+///
+/// # Arguments
+/// * `page` - Page name, e.g. "tar"
+/// * `section` - Manual section, e.g. "1" or "3p"
+/// * `cols` - Number of columns
+/// * `rows` - Number of rows
+///
+/// # Returns
+/// The pager session and the page's headings with the lines they start on
+#[tauri::command]
+pub async fn view_man_page(
+    page: String,
+    section: Option<String>,
+    cols: u16,
+    rows: u16,
+    webview: Webview,
+) -> Result<ManPage, String> {
+    log::info!("view_man_page: {} {:?}", page, section);
+    scope::run_with_undo(
+        &webview,
+        SPAWN_TIMEOUT,
+        move |manager| manager.view_man(&page, section.as_deref(), cols, rows),
+        |manager, man| {
+            let _ = manager.close(&man.session.id);
+        },
+    )
+    .await
+}
+
+/// Open a tab that follows a file like `tail -F`, without spawning a shell
+///
+/// Output arrives on the usual `pty://{id}/data` events and the follower is closed with `pty_close`.
+///
+/// # Arguments
+/// * `path` - File to follow
+/// * `tail_lines` - Number of existing lines to show first (default 10)
+#[tauri::command]
+pub async fn follow_file(
+    path: String,
+    tail_lines: Option<usize>,
+    webview: Webview,
+) -> Result<FollowInfo, String> {
+    log::info!("follow_file: {}", path);
+    scope::run_with_undo(
+        &webview,
+        SHORT_TIMEOUT,
+        move |manager| manager.follow_file(&path, tail_lines.unwrap_or(10)),
+        |manager, info| {
+            let _ = manager.close(&info.id);
+        },
+    )
+    .await
+}
+
+/// Pause or resume a file follower
+///
+/// # Arguments
+/// * `session_id` - The ID of the follower
+/// * `paused` - Whether to stop reading new data
+#[tauri::command]
+pub async fn follow_set_paused(
+    session_id: String,
+    paused: bool,
+    webview: Webview,
+) -> Result<FollowInfo, String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.set_follow_paused(&session_id, paused)).await
+}
+
+/// Continue a file follower from another position
+///
+/// # Arguments
+/// * `session_id` - The ID of the follower
+/// * `offset` - Byte offset to read from; negative values count back from the end
+#[tauri::command]
+pub async fn follow_seek(
+    session_id: String,
+    offset: i64,
+    webview: Webview,
+) -> Result<(), String> {
+    scope::run(&webview, SHORT_TIMEOUT, move |manager| manager.follow_seek(&session_id, offset)).await
 }
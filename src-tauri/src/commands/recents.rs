@@ -0,0 +1,24 @@
+// Tauri commands for recent locations and profiles
+
+use crate::recents::{NewTabSuggestions, Recents};
+use tauri::State;
+
+/// Suggest entries for the new tab dropdown from recent activity
+///
+/// Directories, SSH hosts, and workspaces come from where sessions went; profiles from what
+/// was opened. Each kind is ranked by frecency.
+///
+/// # Arguments
+/// * `query` - Only suggest entries containing this text (case-insensitive)
+/// * `limit` - Suggestions per kind (default 5)
+///
+/// # Returns
+/// Ranked suggestions per kind
+#[tauri::command]
+pub fn get_new_tab_suggestions(
+    query: Option<String>,
+    limit: Option<usize>,
+    recents: State<'_, Recents>,
+) -> NewTabSuggestions {
+    recents.suggestions(query.as_deref(), limit)
+}
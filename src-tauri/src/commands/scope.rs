@@ -0,0 +1,112 @@
+// Command scope
+// Runs PTY manager work for Tauri commands on the blocking pool with a timeout, and cancels what
+// a webview has in flight when it reloads or navigates away; other windows' commands carry on.
+// Work that finishes after its caller gave up is undone, so a reload cannot leave orphaned
+// sessions behind.
+
+use crate::pty::PtyManager;
+use crate::sync::MutexExt;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Webview};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+/// Limit for quick operations such as writes, resizes, and lookups
+pub const SHORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Limit for operations that start processes
+pub const SPAWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Cancellation state for commands issued by each webview's current page, by webview label
+#[derive(Default)]
+pub struct CommandScope {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl CommandScope {
+    /// Token cancelled when the page that issued the command goes away
+    pub fn token(&self, webview: &str) -> CancellationToken {
+        self.tokens.locked().entry(webview.to_string()).or_default().clone()
+    }
+
+    /// Cancel the commands a webview has in flight; its next page gets a fresh scope
+    pub fn cancel(&self, webview: &str) {
+        if let Some(previous) = self.tokens.locked().remove(webview) {
+            previous.cancel();
+        }
+    }
+}
+
+/// Run `f` against the PTY manager for a webview, giving up after `limit` or when the webview's
+/// page goes away
+pub async fn run<T, F>(webview: &Webview, limit: Duration, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&PtyManager) -> Result<T, String> + Send + 'static,
+{
+    run_with_undo(webview, limit, f, |_, _| {}).await
+}
+
+/// Like [`run`], calling `undo` with the result if the caller gave up before it was ready
+pub async fn run_with_undo<T, F, U>(webview: &Webview, limit: Duration, f: F, undo: U) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&PtyManager) -> Result<T, String> + Send + 'static,
+    U: FnOnce(&PtyManager, T) + Send + 'static,
+{
+    let token = webview.state::<CommandScope>().token(webview.label());
+    execute(webview.app_handle(), Some(token), limit, f, undo).await
+}
+
+/// Run `f` for the backend itself, which no page reload can cancel, giving up after `limit`
+pub async fn run_for_app<T, F>(app: &AppHandle, limit: Duration, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&PtyManager) -> Result<T, String> + Send + 'static,
+{
+    execute(app, None, limit, f, |_, _| {}).await
+}
+
+async fn execute<T, F, U>(
+    app: &AppHandle,
+    token: Option<CancellationToken>,
+    limit: Duration,
+    f: F,
+    undo: U,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&PtyManager) -> Result<T, String> + Send + 'static,
+    U: FnOnce(&PtyManager, T) + Send + 'static,
+{
+    let token = token.unwrap_or_default();
+    if token.is_cancelled() {
+        return Err("Cancelled: the frontend was reloaded".to_string());
+    }
+
+    let (sender, receiver) = oneshot::channel();
+    let handle = app.clone();
+    tokio::task::spawn_blocking(move || {
+        let manager = handle.state::<PtyManager>();
+        let result = f(&manager);
+        if let Err(Ok(value)) = sender.send(result) {
+            log::info!("Undoing a command whose caller went away");
+            undo(&manager, value);
+        }
+    });
+
+    tokio::select! {
+        result = receiver => result.map_err(|_| "Command failed: worker panicked".to_string())?,
+        _ = token.cancelled() => Err("Cancelled: the frontend was reloaded".to_string()),
+        _ = tokio::time::sleep(limit) => Err(format!("Timed out after {}s", limit.as_secs())),
+    }
+}
+
+/// Cancel a webview's in-flight commands when it starts loading a page
+pub fn on_page_load(webview: &Webview) {
+    if let Some(scope) = webview.try_state::<CommandScope>() {
+        scope.cancel(webview.label());
+    }
+}
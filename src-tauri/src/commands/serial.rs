@@ -0,0 +1,56 @@
+// Tauri commands for serial devices
+
+use crate::pty::SessionInfo;
+use crate::serial::port::{self, SerialOptions, SerialPortInfo};
+use crate::serial::{bluetooth, BluetoothSerialPort};
+use super::scope::{self, SPAWN_TIMEOUT};
+use tauri::Webview;
+
+/// List Bluetooth serial (RFCOMM) devices bound on this machine
+///
+/// # Returns
+/// One entry per /dev/rfcommN with the remote address and channel when known
+#[tauri::command]
+pub async fn list_bluetooth_serial_ports() -> Vec<BluetoothSerialPort> {
+    bluetooth::list_ports().await
+}
+
+/// List serial devices: USB adapters, built-in UARTs, and bound Bluetooth ports
+///
+/// # Returns
+/// One entry per device node, with USB vendor, product, and serial number when known
+#[tauri::command]
+pub fn list_serial_ports() -> Vec<SerialPortInfo> {
+    port::list_ports()
+}
+
+/// Open a serial port as a session
+///
+/// The session is used like a PTY session: output arrives on `pty://{id}/data`, input is
+/// sent with pty_write, and `pty://{id}/exit` fires when the device goes away.
+///
+/// # Arguments
+/// * `options` - Device path, baud rate, and line settings (8N1 by default)
+/// * `cols` - Number of columns
+/// * `rows` - Number of rows
+///
+/// # Returns
+/// Session information; `serial` holds the line settings
+#[tauri::command]
+pub async fn spawn_serial(
+    options: SerialOptions,
+    cols: u16,
+    rows: u16,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    log::info!("spawn_serial: {:?}", options);
+    scope::run_with_undo(
+        &webview,
+        SPAWN_TIMEOUT,
+        move |manager| manager.spawn_serial(options, cols, rows),
+        |manager, info| {
+            let _ = manager.close(&info.id);
+        },
+    )
+    .await
+}
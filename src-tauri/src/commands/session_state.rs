@@ -0,0 +1,31 @@
+// Tauri commands for session restore
+
+use crate::session_state::{self, RestoreReport, SessionRestore};
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+/// Respawn the sessions that were open when the app last exited
+///
+/// Only the first call after startup restores anything, so reloading the frontend does not
+/// duplicate sessions. Pane session IDs in the returned layout refer to `previousId`.
+///
+/// # Arguments
+/// * `discard` - Drop the saved sessions instead of restoring them
+///
+/// # Returns
+/// The restored sessions, failures, and the saved tab layout
+#[tauri::command]
+pub async fn restore_sessions(discard: Option<bool>, app: AppHandle) -> Result<RestoreReport, String> {
+    tokio::task::spawn_blocking(move || session_state::restore(&app, discard.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Failed to restore sessions: {}", e))
+}
+
+/// Record the current tab and pane layout, saved with the next session snapshot
+///
+/// # Arguments
+/// * `layout` - Layout as the frontend wants it back; stored as is
+#[tauri::command]
+pub fn save_session_layout(layout: Value, restore: State<'_, SessionRestore>) {
+    restore.set_layout(layout);
+}
@@ -1,12 +1,19 @@
 // Settings persistence commands
 // Handles loading and saving settings to disk
 
-use serde_json::Value;
+use crate::settings_schema::{self, SettingsValidationError};
+use crate::settings_sync::{ConflictSide, SaveStatus, SettingsConflict, SettingsSaveResult, SettingsSync};
+use crate::window_state::{self, DisplayServer, WindowPlacement, WindowState};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use crate::control::MAIN_WINDOW;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Get the settings file path
-fn get_settings_path() -> Result<PathBuf, String> {
+pub fn get_settings_path() -> Result<PathBuf, String> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| "Could not find config directory".to_string())?;
     
@@ -22,7 +29,7 @@ fn get_settings_path() -> Result<PathBuf, String> {
 }
 
 /// Get the window state file path
-fn get_window_state_path() -> Result<PathBuf, String> {
+pub fn get_window_state_path() -> Result<PathBuf, String> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| "Could not find config directory".to_string())?;
     
@@ -36,13 +43,30 @@ fn get_window_state_path() -> Result<PathBuf, String> {
     Ok(app_config_dir.join("window-state.json"))
 }
 
-/// Load settings from disk
-#[tauri::command]
-pub fn load_settings() -> Result<Option<Value>, String> {
+/// Read one top-level section of the settings file for use by the backend
+///
+/// Missing files, sections, or fields fall back to the section's defaults.
+pub fn read_section<T: DeserializeOwned + Default>(key: &str) -> T {
+    let section = read_settings()
+        .ok()
+        .flatten()
+        .and_then(|settings| settings.get(key).cloned());
+
+    match section.map(serde_json::from_value) {
+        Some(Ok(value)) => value,
+        Some(Err(e)) => {
+            log::warn!("Invalid {} settings, using defaults: {}", key, e);
+            T::default()
+        }
+        None => T::default(),
+    }
+}
+
+/// Read the settings file without touching the sync state
+fn read_settings() -> Result<Option<Value>, String> {
     let path = get_settings_path()?;
     
     if !path.exists() {
-        log::info!("No settings file found, using defaults");
         return Ok(None);
     }
     
@@ -52,53 +76,235 @@ pub fn load_settings() -> Result<Option<Value>, String> {
     let settings: Value = serde_json::from_str(&contents)
         .map_err(|e| format!("Failed to parse settings: {}", e))?;
     
-    log::info!("Loaded settings from {:?}", path);
     Ok(Some(settings))
 }
 
-/// Save settings to disk
+/// Layout of settings.json written by this release; raise it with a migration when keys are
+/// renamed or sections move
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// An upgrade of settings.json from one layout to the next
+struct Migration {
+    /// Version upgraded from, to `from + 1`
+    from: u64,
+    description: &'static str,
+    apply: fn(&mut Value),
+}
+
+/// Every layout change, oldest first; files skip the ones before their version
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "files from before versioning",
+        apply: |_| {},
+    },
+    Migration {
+        from: 1,
+        description: "version renamed to schemaVersion",
+        apply: |settings| move_value(settings, "/version", "/schemaVersion"),
+    },
+];
+
+/// Bring settings written by an older release up to the current layout
+///
+/// Files from a newer release are left as they are; settings this release does not know are kept
+/// either way.
+pub fn migrate(settings: &mut Value) {
+    let Some(object) = settings.as_object() else {
+        return;
+    };
+    // Version 1 files recorded it as `version`
+    let version = object
+        .get("schemaVersion")
+        .or_else(|| object.get("version"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version > SCHEMA_VERSION {
+        log::warn!(
+            "Settings were written by a newer version (schema {}); unknown settings are kept as they are",
+            version
+        );
+        return;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        log::info!("Migrating settings from schema {}: {}", migration.from, migration.description);
+        (migration.apply)(settings);
+    }
+    if let Value::Object(object) = settings {
+        object.insert("schemaVersion".to_string(), Value::from(SCHEMA_VERSION));
+    }
+}
+
+/// Migrate settings read from disk and drop invalid values, ready for the frontend
+pub fn upgrade(mut settings: Value) -> Value {
+    migrate(&mut settings);
+    settings_schema::sanitize(settings)
+}
+
+/// Move the value at one JSON pointer to another, for renamed keys and moved sections
+///
+/// Missing parent objects are created. A value already at the destination is kept, and the moved
+/// value stays where it was if the destination is inside something that is not an object.
+fn move_value(settings: &mut Value, from: &str, to: &str) {
+    let Some((parent, key)) = from.rsplit_once('/') else {
+        return;
+    };
+    let Some(value) = settings.pointer_mut(parent).and_then(Value::as_object_mut).and_then(|o| o.remove(key)) else {
+        return;
+    };
+
+    let Some((to_parent, to_key)) = to.rsplit_once('/') else {
+        return;
+    };
+    let mut target = &mut *settings;
+    for segment in to_parent.split('/').skip(1) {
+        match target {
+            Value::Object(object) => {
+                target = object.entry(segment).or_insert_with(|| Value::Object(Map::new()));
+            }
+            _ => break,
+        }
+    }
+    match target {
+        Value::Object(object) => {
+            object.entry(to_key).or_insert(value);
+        }
+        _ => {
+            log::warn!("Cannot move setting {} to {}", from, to);
+            if let Some(object) = settings.pointer_mut(parent).and_then(Value::as_object_mut) {
+                object.insert(key.to_string(), value);
+            }
+        }
+    }
+}
+
+/// Load settings from disk
+///
+/// The loaded file becomes the base that later saves are merged against. Files from older
+/// releases are migrated, and invalid values are left out so the frontend's defaults replace them. If the file is corrupt, the backup kept by
+/// the last save is loaded and `settings://recovered` is emitted.
 #[tauri::command]
-pub fn save_settings(settings: Value) -> Result<(), String> {
+pub fn load_settings(app: AppHandle, sync: State<'_, SettingsSync>) -> Result<Option<Value>, String> {
     let path = get_settings_path()?;
     
-    let contents = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    fs::write(&path, contents)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
-    
-    log::info!("Saved settings to {:?}", path);
-    Ok(())
+    let loaded = sync.load(&path)?;
+    match (&loaded.settings, loaded.recovered) {
+        (_, Some(recovered)) => {
+            let _ = app.emit("settings://recovered", recovered);
+        }
+        (Some(_), None) => log::info!("Loaded settings from {:?}", path),
+        (None, None) => log::info!("No settings file found, using defaults"),
+    }
+    Ok(loaded.settings.map(upgrade))
+}
+
+/// Save settings to disk
+///
+/// Settings that fail validation are not written; the result lists what is wrong.
+/// If another program changed the file since it was loaded, its changes are merged in.
+/// Settings both sides changed are reported as conflicts and nothing is written until
+/// `resolve_settings_conflict` is called.
+#[tauri::command]
+pub fn save_settings(mut settings: Value, sync: State<'_, SettingsSync>) -> Result<SettingsSaveResult, String> {
+    let errors = settings_schema::validate(&settings);
+    if !errors.is_empty() {
+        log::warn!("Not saving invalid settings: {} error(s)", errors.len());
+        return Ok(SettingsSaveResult {
+            status: SaveStatus::Invalid,
+            settings: None,
+            conflicts: Vec::new(),
+            errors,
+        });
+    }
+
+    let path = get_settings_path()?;
+    // Settings from the frontend were migrated on load; ones from a newer release keep its version
+    if let Value::Object(object) = &mut settings {
+        object.entry("schemaVersion").or_insert_with(|| Value::from(SCHEMA_VERSION));
+    }
+    sync.save(&path, settings)
+}
+
+/// Check settings without saving them
+///
+/// # Returns
+/// What is wrong with the settings, empty if they are valid
+#[tauri::command]
+pub fn validate_settings(settings: Value) -> Vec<SettingsValidationError> {
+    settings_schema::validate(&settings)
+}
+
+/// Get the conflicts left by the last save, if it could not be written
+#[tauri::command]
+pub fn get_settings_conflict(sync: State<'_, SettingsSync>) -> Vec<SettingsConflict> {
+    sync.conflicts()
+}
+
+/// Resolve the pending settings conflict and write the result
+///
+/// # Arguments
+/// * `prefer` - Side kept for conflicts not listed in `choices`
+/// * `choices` - Side kept per conflicting setting, keyed by its JSON pointer
+///
+/// # Returns
+/// The settings now on disk
+#[tauri::command]
+pub fn resolve_settings_conflict(
+    prefer: ConflictSide,
+    choices: Option<HashMap<String, ConflictSide>>,
+    sync: State<'_, SettingsSync>,
+) -> Result<Value, String> {
+    let path = get_settings_path()?;
+    sync.resolve(&path, prefer, &choices.unwrap_or_default())
 }
 
 /// Load window state from disk
+///
+/// # Returns
+/// The saved geometry, adjusted for the current display server and monitors: no position under
+/// Wayland or when it would be off-screen, and a size that fits
 #[tauri::command]
-pub fn load_window_state() -> Result<Option<Value>, String> {
+pub fn load_window_state(app: AppHandle) -> Result<Option<WindowState>, String> {
     let path = get_window_state_path()?;
-    
-    if !path.exists() {
-        return Ok(None);
-    }
-    
-    let contents = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read window state: {}", e))?;
-    
-    let state: Value = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse window state: {}", e))?;
-    
-    Ok(Some(state))
+    window_state::load(&path, &app)
 }
 
 /// Save window state to disk
+///
+/// The position is dropped under Wayland, where it cannot be read, and a maximized window keeps
+/// the geometry it had before maximizing.
 #[tauri::command]
-pub fn save_window_state(state: Value) -> Result<(), String> {
+pub fn save_window_state(state: WindowState) -> Result<(), String> {
     let path = get_window_state_path()?;
-    
-    let contents = serde_json::to_string_pretty(&state)
-        .map_err(|e| format!("Failed to serialize window state: {}", e))?;
-    
-    fs::write(&path, contents)
-        .map_err(|e| format!("Failed to write window state: {}", e))?;
-    
+    window_state::save(&path, state)
+}
+
+/// Save where the main window goes when it is created, and apply what can be applied now
+///
+/// # Arguments
+/// * `placement` - Preferred monitor and workspace, and whether the window shows on every
+///   workspace or stays on top as the quake window
+#[tauri::command]
+pub fn set_window_placement(app: AppHandle, placement: WindowPlacement) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW)
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let position = window.outer_position().ok();
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    let current = WindowState {
+        x: position.map(|position| position.x),
+        y: position.map(|position| position.y),
+        width: size.width,
+        height: size.height,
+        is_maximized: window.is_maximized().unwrap_or(false),
+        display_server: DisplayServer::detect(),
+        placement: None,
+    };
+    let path = get_window_state_path()?;
+    window_state::set_placement(&path, placement.clone(), current)?;
+    window_state::apply_placement(&app, &placement);
     Ok(())
 }
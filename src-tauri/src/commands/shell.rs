@@ -0,0 +1,46 @@
+// Tauri commands for shell inspection
+
+use crate::shell::{frameworks, prompt, startup, PromptBootstrapOptions, PromptBootstrapReport, ShellFrameworks, StartupProfile};
+
+/// Measure how long a shell takes to start interactively
+///
+/// # Arguments
+/// * `shell` - Path to the shell binary to profile
+///
+/// # Returns
+/// Timings of several runs plus a per-file breakdown where supported
+#[tauri::command]
+pub async fn profile_shell_startup(shell: String) -> Result<StartupProfile, String> {
+    log::info!("profile_shell_startup: {}", shell);
+    startup::profile(&shell).await
+}
+
+/// Detect prompt frameworks and themes in the user's zsh, bash, and fish configuration
+///
+/// # Returns
+/// Frameworks such as oh-my-zsh, powerlevel10k, or starship, plus the files that already emit
+/// OSC 133 prompt marks, so shell integration can be tailored and marks are not doubled
+#[tauri::command]
+pub async fn detect_shell_frameworks() -> Result<ShellFrameworks, String> {
+    tokio::task::spawn_blocking(frameworks::detect)
+        .await
+        .map_err(|e| format!("Failed to detect shell frameworks: {}", e))
+}
+
+/// Set up a minimal starship prompt matching a color scheme
+///
+/// Writes `starship.toml` and, for each requested shell, a guarded init line in its startup
+/// file. Existing files are backed up first.
+///
+/// # Arguments
+/// * `options` - Scheme colors and the shells to hook the prompt into
+///
+/// # Returns
+/// The files written, their backups, and whether starship is installed
+#[tauri::command]
+pub async fn bootstrap_prompt(options: PromptBootstrapOptions) -> Result<PromptBootstrapReport, String> {
+    log::info!("bootstrap_prompt for shells: {:?}", options.shells);
+    tokio::task::spawn_blocking(move || prompt::bootstrap(&options))
+        .await
+        .map_err(|e| format!("Failed to set up prompt: {}", e))?
+}
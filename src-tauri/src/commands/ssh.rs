@@ -0,0 +1,54 @@
+// Tauri commands for SSH sessions
+
+use crate::pty::SessionInfo;
+use crate::ssh::{self, SshHost, SshTarget};
+use super::scope::{self, SPAWN_TIMEOUT};
+use tauri::{AppHandle, Webview};
+
+/// Open an SSH session with the system ssh client
+///
+/// The session behaves like any PTY session: output, exit, and the other events arrive under
+/// its ID, and it is written to, resized, and closed with the usual commands. With `probe` set,
+/// the remote system is looked up once connected and arrives on `pty://{id}/remote-host`.
+///
+/// # Arguments
+/// * `target` - Host or ~/.ssh/config alias, with optional user, port, identity file, and probe
+/// * `cols` - Number of columns
+/// * `rows` - Number of rows
+///
+/// # Returns
+/// Session information of the ssh process
+#[tauri::command]
+pub async fn spawn_ssh(
+    target: SshTarget,
+    cols: u16,
+    rows: u16,
+    app: AppHandle,
+    webview: Webview,
+) -> Result<SessionInfo, String> {
+    log::info!("spawn_ssh: {}", target.host);
+    let options = target.spawn_options(cols, rows)?;
+    let info = scope::run_with_undo(
+        &webview,
+        SPAWN_TIMEOUT,
+        move |manager| manager.spawn(options),
+        |manager, info| {
+            let _ = manager.close(&info.id);
+        },
+    )
+    .await?;
+
+    if target.probe {
+        tauri::async_runtime::spawn(ssh::probe(app, target, info.id.clone()));
+    }
+    Ok(info)
+}
+
+/// List the hosts defined in ~/.ssh/config, for the new tab dropdown
+///
+/// # Returns
+/// Host aliases in config order, with their host name, user, port, and identity file when set
+#[tauri::command]
+pub fn list_ssh_hosts() -> Vec<SshHost> {
+    ssh::config_hosts()
+}
@@ -0,0 +1,62 @@
+// Tauri commands for status bar segments
+
+use crate::status::{SegmentInfo, SegmentValue, StatusSegments, StatusUpdate};
+use tauri::{AppHandle, State};
+
+/// List the segments the status bar can show
+///
+/// # Returns
+/// Built-in segments followed by plugin segments
+#[tauri::command]
+pub fn list_status_segments(segments: State<'_, StatusSegments>) -> Vec<SegmentInfo> {
+    segments.list()
+}
+
+/// Get every segment value currently shown
+///
+/// # Returns
+/// The values, as `status://update` delivered them; later changes arrive there
+#[tauri::command]
+pub fn get_status(segments: State<'_, StatusSegments>) -> Vec<StatusUpdate> {
+    segments.values()
+}
+
+/// Add a plugin segment, or replace the one with the same ID
+///
+/// # Arguments
+/// * `segment` - The segment; `plugin` must name the plugin registering it
+#[tauri::command]
+pub fn register_status_segment(segment: SegmentInfo, segments: State<'_, StatusSegments>) -> Result<(), String> {
+    segments.register(segment)
+}
+
+/// Remove a plugin segment, hiding its values
+///
+/// # Arguments
+/// * `id` - Segment ID
+#[tauri::command]
+pub fn unregister_status_segment(
+    id: String,
+    app: AppHandle,
+    segments: State<'_, StatusSegments>,
+) -> Result<(), String> {
+    segments.unregister(&app, &id)
+}
+
+/// Set or hide the value of a plugin segment
+///
+/// # Arguments
+/// * `id` - Segment ID
+/// * `session_id` - Session the value is for; required for session segments, not allowed for
+///   global ones
+/// * `value` - What to show; None hides the segment
+#[tauri::command]
+pub fn set_status_segment(
+    id: String,
+    session_id: Option<String>,
+    value: Option<SegmentValue>,
+    app: AppHandle,
+    segments: State<'_, StatusSegments>,
+) -> Result<(), String> {
+    segments.set(&app, &id, session_id, value)
+}
@@ -0,0 +1,15 @@
+// Tauri commands for color themes
+
+use crate::themes::{self, TerminalTheme, ThemePreview};
+
+/// Render a sample of terminal output in a theme's colors
+///
+/// # Arguments
+/// * `theme` - Color scheme or xterm.js theme; unset colors use the xterm.js defaults
+///
+/// # Returns
+/// A self-contained HTML snippet for the theme gallery
+#[tauri::command]
+pub async fn preview_theme(theme: TerminalTheme) -> Result<ThemePreview, String> {
+    themes::preview(&theme)
+}
@@ -0,0 +1,149 @@
+// Context module - Who and where the terminal is running
+// Collects user, host, distribution, kernel, and container/VM details in one pass
+
+use serde::Serialize;
+use std::ffi::CStr;
+use std::fs;
+use std::path::Path;
+
+/// Files describing the distribution, in lookup order
+const OS_RELEASE_PATHS: &[&str] = &["/etc/os-release", "/usr/lib/os-release"];
+
+/// DMI vendor/product substrings identifying a hypervisor
+const HYPERVISORS: &[(&str, &str)] = &[
+    ("QEMU", "qemu"),
+    ("KVM", "kvm"),
+    ("VirtualBox", "virtualbox"),
+    ("VMware", "vmware"),
+    ("Microsoft Corporation Virtual Machine", "hyper-v"),
+    ("Xen", "xen"),
+    ("Parallels", "parallels"),
+    ("Amazon EC2", "amazon"),
+    ("Google Compute Engine", "google"),
+];
+
+/// Environment the terminal runs in
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemContext {
+    pub username: String,
+    pub hostname: String,
+    /// `PRETTY_NAME` from os-release, e.g. "Fedora Linux 41 (Workstation Edition)"
+    pub distro: Option<String>,
+    /// Kernel release, e.g. "6.11.4-301.fc41.x86_64"
+    pub kernel: Option<String>,
+    /// Container runtime, e.g. "docker", "podman", "flatpak", "toolbox"
+    pub container: Option<String>,
+    /// Virtualization, e.g. "kvm", "vmware", "wsl"
+    pub virtualization: Option<String>,
+}
+
+/// Gather the current context
+pub fn detect() -> SystemContext {
+    let kernel = fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|release| release.trim().to_string());
+
+    SystemContext {
+        username: username(),
+        hostname: hostname::get()
+            .map(|h| h.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "Terminal".to_string()),
+        distro: distro(),
+        virtualization: virtualization(kernel.as_deref()),
+        kernel,
+        container: container(),
+    }
+}
+
+/// Name of the user running the app, from `USER` or the password database
+pub fn username() -> String {
+    if let Ok(user) = std::env::var("USER") {
+        if !user.is_empty() {
+            return user;
+        }
+    }
+
+    // SAFETY: passwd is plain C data, for which all zeroes is a valid value
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut result = std::ptr::null_mut();
+    // SAFETY: every pointer refers to a live local sized as passed; on success pw_name points
+    // into `buffer`, which outlives the copy below
+    unsafe {
+        let status = libc::getpwuid_r(
+            libc::getuid(),
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        );
+        if status == 0 && !result.is_null() && !passwd.pw_name.is_null() {
+            return CStr::from_ptr(passwd.pw_name).to_string_lossy().to_string();
+        }
+    }
+    "user".to_string()
+}
+
+fn distro() -> Option<String> {
+    let contents = OS_RELEASE_PATHS
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())?;
+
+    let value = |key: &str| {
+        contents.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?;
+            Some(value.trim().trim_matches('"').to_string())
+        })
+    };
+    value("PRETTY_NAME").or_else(|| value("NAME"))
+}
+
+fn container() -> Option<String> {
+    container_at(Path::new("/"))
+}
+
+/// Container runtime of the filesystem tree at `root`, e.g. `/proc/<pid>/root` for a process
+pub fn container_at(root: &Path) -> Option<String> {
+    if fs::metadata(root.join(".flatpak-info")).is_ok() {
+        return Some("flatpak".to_string());
+    }
+    if fs::metadata(root.join("run/.toolboxenv")).is_ok() {
+        return Some("toolbox".to_string());
+    }
+    if fs::metadata(root.join("run/.containerenv")).is_ok() {
+        return Some("podman".to_string());
+    }
+    if fs::metadata(root.join(".dockerenv")).is_ok() {
+        return Some("docker".to_string());
+    }
+
+    // Set by systemd-nspawn, LXC, and other runtimes that follow the container interface
+    fs::read_to_string(root.join("run/systemd/container"))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn virtualization(kernel: Option<&str>) -> Option<String> {
+    if kernel.is_some_and(|k| k.to_ascii_lowercase().contains("microsoft")) {
+        return Some("wsl".to_string());
+    }
+
+    let dmi: String = ["sys_vendor", "product_name", "bios_vendor"]
+        .iter()
+        .filter_map(|file| fs::read_to_string(format!("/sys/class/dmi/id/{}", file)).ok())
+        .map(|value| value.trim().to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Some((_, name)) = HYPERVISORS.iter().find(|(marker, _)| dmi.contains(marker)) {
+        return Some(name.to_string());
+    }
+
+    // Some hypervisors hide from DMI but still set the CPU flag
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let flagged = cpuinfo
+        .lines()
+        .any(|line| line.starts_with("flags") && line.split_whitespace().any(|flag| flag == "hypervisor"));
+    flagged.then(|| "unknown".to_string())
+}
@@ -0,0 +1,324 @@
+// Action registry
+// The catalog of actions the command palette offers, with their titles and key bindings, so
+// D-Bus, the control socket, scripts, and plugins can list and trigger the same actions.
+// Actions that need the UI are forwarded to the frontend as `runAction` control actions.
+
+use super::{dispatch, ControlAction};
+use crate::commands::settings;
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Palette group of an action
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionCategory {
+    Tab,
+    Pane,
+    Settings,
+    View,
+    Terminal,
+}
+
+/// An action the palette and external callers can run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Action {
+    /// Same as the `command` of key bindings in settings, e.g. "newTab"
+    pub id: String,
+    pub title: String,
+    pub category: ActionCategory,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Arguments the action requires, e.g. "host" for connectHost
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Key bindings from settings, or the defaults when settings have none
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// Plugin that registered the action; unset for built-in actions
+    #[serde(default)]
+    pub plugin: Option<String>,
+}
+
+struct Builtin {
+    id: &'static str,
+    title: &'static str,
+    category: ActionCategory,
+    description: Option<&'static str>,
+    args: &'static [&'static str],
+    keys: &'static [&'static str],
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        id: "newTab",
+        title: "New Tab",
+        category: ActionCategory::Tab,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+t"],
+    },
+    Builtin {
+        id: "closeTab",
+        title: "Close Tab",
+        category: ActionCategory::Tab,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+w"],
+    },
+    Builtin {
+        id: "nextTab",
+        title: "Next Tab",
+        category: ActionCategory::Tab,
+        description: None,
+        args: &[],
+        keys: &["ctrl+tab", "ctrl+pagedown"],
+    },
+    Builtin {
+        id: "prevTab",
+        title: "Previous Tab",
+        category: ActionCategory::Tab,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+tab", "ctrl+pageup"],
+    },
+    Builtin {
+        id: "connectHost",
+        title: "Connect to Host",
+        category: ActionCategory::Tab,
+        description: Some("Open an SSH session in a new tab"),
+        args: &["host"],
+        keys: &[],
+    },
+    Builtin {
+        id: "splitPane",
+        title: "Split Pane",
+        category: ActionCategory::Pane,
+        description: Some("Split the focused pane \"horizontal\" or \"vertical\""),
+        args: &["direction"],
+        keys: &["alt+shift+plus", "alt+shift+minus"],
+    },
+    Builtin {
+        id: "closePane",
+        title: "Close Pane",
+        category: ActionCategory::Pane,
+        description: None,
+        args: &[],
+        keys: &[],
+    },
+    Builtin {
+        id: "toggleBroadcast",
+        title: "Toggle Broadcast Input",
+        category: ActionCategory::Pane,
+        description: Some("Send input to all panes"),
+        args: &[],
+        keys: &["alt+shift+b"],
+    },
+    Builtin {
+        id: "zoomIn",
+        title: "Zoom In",
+        category: ActionCategory::View,
+        description: None,
+        args: &[],
+        keys: &["ctrl+plus"],
+    },
+    Builtin {
+        id: "zoomOut",
+        title: "Zoom Out",
+        category: ActionCategory::View,
+        description: None,
+        args: &[],
+        keys: &["ctrl+minus"],
+    },
+    Builtin {
+        id: "zoomReset",
+        title: "Reset Zoom",
+        category: ActionCategory::View,
+        description: None,
+        args: &[],
+        keys: &["ctrl+0"],
+    },
+    Builtin {
+        id: "changeTheme",
+        title: "Change Theme",
+        category: ActionCategory::View,
+        description: None,
+        args: &["theme"],
+        keys: &[],
+    },
+    Builtin {
+        id: "toggleQuake",
+        title: "Toggle Quake Window",
+        category: ActionCategory::View,
+        description: None,
+        args: &[],
+        keys: &[],
+    },
+    Builtin {
+        id: "openWindow",
+        title: "New Window",
+        category: ActionCategory::View,
+        description: None,
+        args: &[],
+        keys: &[],
+    },
+    Builtin {
+        id: "openSearch",
+        title: "Find in Terminal",
+        category: ActionCategory::Terminal,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+f"],
+    },
+    Builtin {
+        id: "clear",
+        title: "Clear Terminal",
+        category: ActionCategory::Terminal,
+        description: None,
+        args: &[],
+        keys: &["ctrl+l"],
+    },
+    Builtin {
+        id: "runTask",
+        title: "Run Task",
+        category: ActionCategory::Terminal,
+        description: Some("Run a command in a new tab"),
+        args: &["command"],
+        keys: &[],
+    },
+    Builtin {
+        id: "openSettings",
+        title: "Open Settings",
+        category: ActionCategory::Settings,
+        description: None,
+        args: &[],
+        keys: &["ctrl+comma"],
+    },
+    Builtin {
+        id: "openPlugins",
+        title: "Open Plugins",
+        category: ActionCategory::Settings,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+e"],
+    },
+    Builtin {
+        id: "openCommandPalette",
+        title: "Command Palette",
+        category: ActionCategory::Settings,
+        description: None,
+        args: &[],
+        keys: &["ctrl+shift+p"],
+    },
+];
+
+/// Only the fields needed to look up an action's keys
+#[derive(Debug, Deserialize)]
+struct KeyBindingRef {
+    command: String,
+    #[serde(default)]
+    keys: Vec<String>,
+}
+
+/// Actions registered by plugins, managed by Tauri
+#[derive(Default)]
+pub struct ActionRegistry {
+    plugin_actions: Mutex<Vec<Action>>,
+}
+
+impl ActionRegistry {
+    /// Built-in and plugin actions, optionally only those whose ID, title, or description
+    /// contains `query`
+    pub fn list(&self, query: Option<&str>) -> Vec<Action> {
+        let bindings = settings::read_section::<Vec<KeyBindingRef>>("keyBindings");
+        let query = query.map(str::to_lowercase).filter(|q| !q.is_empty());
+
+        let builtins = BUILTINS.iter().map(|builtin| {
+            let bound: Vec<String> = bindings
+                .iter()
+                .filter(|binding| binding.command == builtin.id)
+                .flat_map(|binding| binding.keys.iter().cloned())
+                .collect();
+            Action {
+                id: builtin.id.to_string(),
+                title: builtin.title.to_string(),
+                category: builtin.category,
+                description: builtin.description.map(str::to_string),
+                args: builtin.args.iter().map(|arg| arg.to_string()).collect(),
+                keys: if bindings.is_empty() {
+                    builtin.keys.iter().map(|key| key.to_string()).collect()
+                } else {
+                    bound
+                },
+                plugin: None,
+            }
+        });
+        let plugin_actions = self.plugin_actions.locked().clone();
+
+        builtins
+            .chain(plugin_actions)
+            .filter(|action| {
+                query.as_ref().map_or(true, |q| {
+                    action.id.to_lowercase().contains(q)
+                        || action.title.to_lowercase().contains(q)
+                        || action.description.as_ref().is_some_and(|d| d.to_lowercase().contains(q))
+                })
+            })
+            .collect()
+    }
+
+    /// Add a plugin action, or replace the one with the same ID
+    pub fn register(&self, action: Action) -> Result<(), String> {
+        if action.plugin.is_none() {
+            return Err(format!("Action {} does not name its plugin", action.id));
+        }
+        if action.id.trim().is_empty() || action.title.trim().is_empty() {
+            return Err("Action ID and title must not be empty".to_string());
+        }
+        if BUILTINS.iter().any(|builtin| builtin.id == action.id) {
+            return Err(format!("Action {} is built in", action.id));
+        }
+        let mut actions = self.plugin_actions.locked();
+        actions.retain(|existing| existing.id != action.id);
+        actions.push(action);
+        Ok(())
+    }
+
+    /// Remove a plugin action
+    pub fn unregister(&self, id: &str) -> Result<(), String> {
+        let mut actions = self.plugin_actions.locked();
+        let before = actions.len();
+        actions.retain(|action| action.id != id);
+        if actions.len() == before {
+            return Err(format!("Action not found: {}", id));
+        }
+        Ok(())
+    }
+
+    /// Run an action by ID after checking its required arguments
+    pub fn run(&self, app: &AppHandle, id: &str, args: HashMap<String, String>) -> Result<(), String> {
+        let action = self
+            .list(None)
+            .into_iter()
+            .find(|action| action.id == id)
+            .ok_or_else(|| format!("Unknown action: {}", id))?;
+        if let Some(missing) = action.args.iter().find(|arg| args.get(*arg).map_or(true, |v| v.trim().is_empty())) {
+            return Err(format!("Action {} requires argument: {}", id, missing));
+        }
+
+        // Actions that already have a control action go through it, the rest to the frontend
+        let control = match id {
+            "toggleQuake" => ControlAction::ToggleQuake,
+            "openWindow" => ControlAction::OpenWindow,
+            "openSettings" => ControlAction::OpenPreferences,
+            _ => ControlAction::RunAction {
+                id: id.to_string(),
+                args,
+            },
+        };
+        dispatch(app, control)
+    }
+}
@@ -0,0 +1,112 @@
+// D-Bus control service
+// Exposes org.xterminal.Control on the session bus for window managers, scripts, and launchers
+//
+//   busctl --user call org.xterminal.Control /org/xterminal/Control \
+//       org.xterminal.Control OpenTab sss "" "/tmp" "htop"
+
+use super::actions::ActionRegistry;
+use super::{dispatch, non_empty, ControlAction};
+use crate::pty::PtyManager;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+use zbus::fdo;
+
+/// Well-known bus name and interface name
+pub const SERVICE_NAME: &str = "org.xterminal.Control";
+
+/// Object path of the control interface
+pub const OBJECT_PATH: &str = "/org/xterminal/Control";
+
+struct ControlService {
+    app: AppHandle,
+}
+
+#[zbus::interface(name = "org.xterminal.Control")]
+impl ControlService {
+    /// Open a tab; empty arguments use the defaults
+    async fn open_tab(&self, profile: &str, cwd: &str, command: &str) -> fdo::Result<()> {
+        self.dispatch(ControlAction::OpenTab {
+            profile: non_empty(profile),
+            cwd: non_empty(cwd),
+            command: non_empty(command),
+            context: None,
+        })
+    }
+
+    async fn open_window(&self) -> fdo::Result<()> {
+        self.dispatch(ControlAction::OpenWindow)
+    }
+
+    async fn toggle_quake(&self) -> fdo::Result<()> {
+        self.dispatch(ControlAction::ToggleQuake)
+    }
+
+    /// Run a command palette action by ID, e.g. RunAction sa{ss} "splitPane" 1 "direction" "vertical"
+    async fn run_action(&self, id: &str, args: HashMap<String, String>) -> fdo::Result<()> {
+        let registry = self.app.state::<ActionRegistry>();
+        registry.run(&self.app, id, args).map_err(fdo::Error::Failed)
+    }
+
+    /// Command palette actions as (id, title, required args)
+    async fn list_actions(&self) -> Vec<(String, String, Vec<String>)> {
+        let registry = self.app.state::<ActionRegistry>();
+        registry
+            .list(None)
+            .into_iter()
+            .map(|action| (action.id, action.title, action.args))
+            .collect()
+    }
+
+    /// Type a command line into a session and press enter
+    async fn run_command(&self, session_id: &str, command: &str) -> fdo::Result<()> {
+        let manager = self.app.state::<PtyManager>();
+        manager
+            .write(session_id, &format!("{}\r", command))
+            .map_err(fdo::Error::Failed)
+    }
+
+    /// Active sessions as (id, title, shell, pid)
+    async fn list_sessions(&self) -> Vec<(String, String, String, u32)> {
+        let manager = self.app.state::<PtyManager>();
+        manager
+            .list()
+            .into_iter()
+            .map(|info| {
+                let title = info.name.or(info.title).unwrap_or_default();
+                (info.id, title, info.shell, info.pid)
+            })
+            .collect()
+    }
+}
+
+impl ControlService {
+    fn dispatch(&self, action: ControlAction) -> fdo::Result<()> {
+        dispatch(&self.app, action).map_err(fdo::Error::Failed)
+    }
+}
+
+/// Register the service on the session bus for the lifetime of the app
+///
+/// A missing session bus or a name already owned by another instance is logged, not fatal.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let service = ControlService { app };
+        let connection = zbus::connection::Builder::session()
+            .and_then(|builder| builder.name(SERVICE_NAME))
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, service));
+
+        let connection = match connection {
+            Ok(builder) => builder.build().await,
+            Err(e) => Err(e),
+        };
+
+        match connection {
+            Ok(_connection) => {
+                log::info!("D-Bus control service registered as {}", SERVICE_NAME);
+                // Keep the connection alive; dropping it releases the name
+                std::future::pending::<()>().await;
+            }
+            Err(e) => log::warn!("D-Bus control service unavailable: {}", e),
+        }
+    });
+}
@@ -0,0 +1,146 @@
+// Launch intents
+// Command-line flags (also used by the .desktop file actions) and links mapped to control actions.
+// A second launch forwards its arguments to the running instance.
+
+use super::links;
+use super::{dispatch, focus_main_window, ControlAction};
+use crate::profiles::SpawnContext;
+use crate::sync::MutexExt;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+/// Actions requested at launch, held until the frontend is ready for them
+///
+/// Once the frontend has taken the queue, later actions are dispatched as events.
+pub struct PendingLaunch(Mutex<Option<Vec<ControlAction>>>);
+
+impl Default for PendingLaunch {
+    fn default() -> Self {
+        Self(Mutex::new(Some(Vec::new())))
+    }
+}
+
+impl PendingLaunch {
+    pub fn take(&self) -> Vec<ControlAction> {
+        self.0.locked().take().unwrap_or_default()
+    }
+}
+
+/// Map a command-line flag to a control action
+///
+/// Supported flags: `--new-window`, `--quake`, `--preferences`. `--view <file>` and
+/// `--working-directory <dir>` are handled separately since they take a value.
+fn parse_flag(arg: &str) -> Option<ControlAction> {
+    match arg {
+        "--new-window" => Some(ControlAction::OpenWindow),
+        "--quake" => Some(ControlAction::ToggleQuake),
+        "--preferences" => Some(ControlAction::OpenPreferences),
+        _ => None,
+    }
+}
+
+/// Record the intents of the first launch
+pub fn init(app: &AppHandle) {
+    app.manage(PendingLaunch::default());
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cwd = std::env::current_dir().unwrap_or_default();
+    handle_args(app, &args, &cwd);
+}
+
+/// Handle a later launch forwarded by the single-instance plugin
+pub fn handle_second_instance(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    let args = argv.get(1..).unwrap_or_default();
+
+    // Launching again without arguments brings the existing window forward
+    if args.is_empty() {
+        focus_main_window(app);
+    }
+    handle_args(app, args, Path::new(&cwd));
+}
+
+/// Handle launch arguments; relative paths are resolved against the launching process's `cwd`
+fn handle_args(app: &AppHandle, args: &[String], cwd: &Path) {
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--view" {
+            match args.next() {
+                Some(path) => deliver(
+                    app,
+                    ControlAction::ViewFile {
+                        path: cwd.join(path).display().to_string(),
+                    },
+                ),
+                None => log::warn!("--view needs a file path"),
+            }
+        } else if arg == "--working-directory" || arg.starts_with("--working-directory=") {
+            // File managers' "Open in Terminal" pass either form
+            let dir = match arg.split_once('=') {
+                Some((_, dir)) => Some(dir),
+                None => args.next().map(String::as_str),
+            };
+            match dir.filter(|dir| !dir.is_empty()) {
+                Some(dir) => deliver(
+                    app,
+                    ControlAction::OpenTab {
+                        profile: None,
+                        cwd: Some(cwd.join(dir).display().to_string()),
+                        command: None,
+                        context: Some(SpawnContext::WorkingDirectory),
+                    },
+                ),
+                None => log::warn!("--working-directory needs a directory"),
+            }
+        } else if let Some(action) = parse_flag(arg) {
+            deliver(app, action);
+        } else if links::is_link(arg) {
+            match links::parse(arg) {
+                Ok(link) => match link.confirm_command {
+                    Some(command) => confirm_and_deliver(app, command, link.action),
+                    None => deliver(app, link.action),
+                },
+                Err(e) => log::warn!("Ignoring link: {}", e),
+            }
+        } else {
+            log::warn!("Ignoring unknown launch argument: {}", arg);
+        }
+    }
+}
+
+/// Queue an action for the frontend, or dispatch it if the frontend is already running
+fn deliver(app: &AppHandle, action: ControlAction) {
+    let pending = app.state::<PendingLaunch>();
+    if let Some(queue) = pending.0.locked().as_mut() {
+        queue.push(action);
+        return;
+    }
+
+    if let Err(e) = dispatch(app, action) {
+        log::warn!("Failed to handle launch action: {}", e);
+    }
+}
+
+/// Ask the user before running a command that came from a link
+fn confirm_and_deliver(app: &AppHandle, command: String, action: ControlAction) {
+    let app = app.clone();
+
+    // The dialog blocks until answered, so keep it off the main thread
+    std::thread::spawn(move || {
+        let approved = app
+            .dialog()
+            .message(format!("A link wants to run this command in a new tab:\n\n{}", command))
+            .title("Run command from link?")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom("Run".into(), "Cancel".into()))
+            .blocking_show();
+
+        if approved {
+            deliver(&app, action);
+        } else {
+            log::info!("Declined command from link: {}", command);
+        }
+    });
+}
@@ -0,0 +1,150 @@
+// URL handling
+// ssh:// and xterminal:// links passed on the command line by the desktop, parsed into control actions
+//
+//   ssh://[user@]host[:port]
+//   xterminal://open?path=/some/dir[&profile=name]
+//   xterminal://run?command=htop[&cwd=/some/dir][&profile=name]   (asks before running)
+//   xterminal://workspace?name=dev                                (a recent project root by name)
+
+use super::ControlAction;
+use std::path::Path;
+use url::Url;
+
+/// A link resolved to an action
+#[derive(Debug)]
+pub struct Link {
+    pub action: ControlAction,
+    /// Command the link would run, which the user must approve first
+    pub confirm_command: Option<String>,
+}
+
+/// Whether an argument looks like a link we handle
+pub fn is_link(arg: &str) -> bool {
+    arg.starts_with("ssh://") || arg.starts_with("xterminal://")
+}
+
+/// Parse and validate a link
+pub fn parse(link: &str) -> Result<Link, String> {
+    let url = Url::parse(link).map_err(|e| format!("Invalid link {}: {}", link, e))?;
+
+    match url.scheme() {
+        "ssh" => parse_ssh(&url),
+        "xterminal" => parse_xterminal(&url),
+        scheme => Err(format!("Unsupported link scheme: {}", scheme)),
+    }
+}
+
+fn parse_ssh(url: &Url) -> Result<Link, String> {
+    let host = url.host_str().filter(|h| !h.is_empty()).ok_or("ssh link has no host")?;
+    // A leading '-' would be read by ssh as an option, e.g. ssh://-oProxyCommand=...
+    if host.starts_with('-') || !host.chars().all(|c| c.is_ascii_alphanumeric() || ".-:[]".contains(c)) {
+        return Err(format!("Invalid ssh host: {}", host));
+    }
+
+    let user = url.username();
+    if !user.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c)) || user.starts_with('-') {
+        return Err(format!("Invalid ssh user: {}", user));
+    }
+
+    let mut command = String::from("ssh");
+    if let Some(port) = url.port() {
+        command.push_str(&format!(" -p {}", port));
+    }
+    command.push_str(" -- ");
+    if !user.is_empty() {
+        command.push_str(user);
+        command.push('@');
+    }
+    command.push_str(host.trim_start_matches('[').trim_end_matches(']'));
+
+    Ok(Link {
+        action: ControlAction::OpenTab {
+            profile: None,
+            cwd: None,
+            command: Some(command),
+            context: None,
+        },
+        confirm_command: None,
+    })
+}
+
+fn parse_xterminal(url: &Url) -> Result<Link, String> {
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    // xterminal://open?... puts the verb in the host, xterminal:open?... in the path
+    let verb = url.host_str().unwrap_or_else(|| url.path().trim_matches('/'));
+
+    match verb {
+        "open" => Ok(Link {
+            action: ControlAction::OpenTab {
+                profile: query("profile"),
+                cwd: validate_dir(query("path"))?,
+                command: None,
+                context: None,
+            },
+            confirm_command: None,
+        }),
+        "run" => {
+            let command = query("command").ok_or("run link has no command")?;
+            Ok(Link {
+                action: ControlAction::OpenTab {
+                    profile: query("profile"),
+                    cwd: validate_dir(query("cwd"))?,
+                    command: Some(command.clone()),
+                    context: None,
+                },
+                confirm_command: Some(command),
+            })
+        }
+        "workspace" => Ok(Link {
+            action: ControlAction::OpenWorkspace {
+                name: query("name").ok_or("workspace link has no name")?,
+            },
+            confirm_command: None,
+        }),
+        other => Err(format!("Unknown xterminal link action: {}", other)),
+    }
+}
+
+/// Require directories from links to be absolute and to exist
+fn validate_dir(path: Option<String>) -> Result<Option<String>, String> {
+    match path {
+        Some(path) if Path::new(&path).is_absolute() && Path::new(&path).is_dir() => Ok(Some(path)),
+        Some(path) => Err(format!("Not an existing absolute directory: {}", path)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ssh_command(link: &str) -> Result<String, String> {
+        match parse(link)?.action {
+            ControlAction::OpenTab { command, .. } => Ok(command.unwrap_or_default()),
+            action => panic!("ssh link gave {:?}", action),
+        }
+    }
+
+    #[test]
+    fn ssh_link_ends_options_before_the_host() {
+        assert_eq!(ssh_command("ssh://example.com").unwrap(), "ssh -- example.com");
+        assert_eq!(ssh_command("ssh://me@example.com:2222").unwrap(), "ssh -p 2222 -- me@example.com");
+        assert_eq!(ssh_command("ssh://[::1]").unwrap(), "ssh -- ::1");
+    }
+
+    #[test]
+    fn ssh_link_rejects_option_injection_and_shell_characters() {
+        assert!(ssh_command("ssh://-oProxyCommand=touch%20x").is_err());
+        assert!(ssh_command("ssh://-oFoo").is_err());
+        assert!(ssh_command("ssh://-l@example.com").is_err());
+        assert!(ssh_command("ssh://example.com;rm").is_err());
+        assert!(ssh_command("ssh://me$(id)@example.com").is_err());
+        assert!(ssh_command("ssh://").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+// Control module - external requests to drive the terminal (D-Bus, control socket, launch arguments, etc.)
+// Actions that need the UI are forwarded to the frontend as `control://action` events
+
+pub mod actions;
+pub mod dbus;
+pub mod launch;
+pub mod links;
+pub mod socket;
+
+use crate::commands::settings::get_window_state_path;
+use crate::profiles::SpawnContext;
+use crate::window_state;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying a control action to the frontend
+pub const ACTION_EVENT: &str = "control://action";
+
+/// Label of the main window in tauri.conf.json
+pub const MAIN_WINDOW: &str = "main";
+
+/// Request from outside the app to change what the terminal shows
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ControlAction {
+    /// Open a tab, optionally with a profile, directory, and command to run
+    OpenTab {
+        profile: Option<String>,
+        cwd: Option<String>,
+        command: Option<String>,
+        /// Passed on to the spawn, for the context's default profile
+        #[serde(default)]
+        context: Option<SpawnContext>,
+    },
+    /// Open a tab with the window context's default profile; the app has a single window
+    OpenWindow,
+    ToggleQuake,
+    OpenPreferences,
+    /// Open a read-only viewer tab for a file
+    ViewFile { path: String },
+    /// Open a tab in a recent workspace (project root), by directory name or path
+    OpenWorkspace { name: String },
+    /// Switch to the tab showing a session
+    FocusSession { session_id: String },
+    /// Run a palette action the frontend implements, by registry ID
+    RunAction { id: String, args: HashMap<String, String> },
+}
+
+/// Carry out a control action
+pub fn dispatch(app: &AppHandle, action: ControlAction) -> Result<(), String> {
+    log::info!("Control action: {:?}", action);
+
+    if matches!(action, ControlAction::ToggleQuake) {
+        toggle_main_window(app)?;
+    } else {
+        focus_main_window(app);
+    }
+
+    app.emit(ACTION_EVENT, action)
+        .map_err(|e| format!("Failed to emit control action: {}", e))
+}
+
+/// Bring the main window to the front so the result of an action is visible
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Show the main window if hidden or unfocused, hide it otherwise
+fn toggle_main_window(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_WINDOW)
+        .ok_or_else(|| "Main window not found".to_string())?;
+
+    let visible = window.is_visible().unwrap_or(false);
+    let focused = window.is_focused().unwrap_or(false);
+    if visible && focused {
+        window.hide().map_err(|e| format!("Failed to hide window: {}", e))
+    } else {
+        let on_top = get_window_state_path()
+            .map(|path| window_state::placement(&path).quake_always_on_top)
+            .unwrap_or(false);
+        let _ = window.set_always_on_top(on_top);
+        focus_main_window(app);
+        Ok(())
+    }
+}
+
+/// Treat empty strings from untyped callers as "not given"
+pub fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
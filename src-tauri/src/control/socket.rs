@@ -0,0 +1,302 @@
+// Unix socket control service
+// Opt-in JSON-lines protocol for scripting the terminal without D-Bus. Each request line names
+// a method mirroring a Tauri command; the first one on a connection must authenticate with the
+// token written next to the socket.
+//
+//   token=$(cat "$XDG_RUNTIME_DIR/xterminal/control.token")
+//   printf '{"id":1,"method":"auth","params":{"token":"%s"}}\n{"id":2,"method":"list_sessions"}\n' "$token" \
+//       | socat - UNIX-CONNECT:"$XDG_RUNTIME_DIR/xterminal/control.sock"
+
+use super::actions::ActionRegistry;
+use super::{dispatch, ControlAction};
+use crate::commands::settings;
+use crate::pty::PtyManager;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, DirBuilder, Permissions};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Longest request line accepted, so a client cannot make the app buffer without bound
+const MAX_LINE_BYTES: u64 = 1024 * 1024;
+
+/// The `controlSocket` section of settings
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ControlSocketSettings {
+    pub enabled: bool,
+}
+
+/// One request line
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Echoed in the response so clients can pipeline requests
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// One response line; exactly one of `result` and `error` is set
+#[derive(Debug, Serialize)]
+struct Response {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionParams {
+    session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteParams {
+    session_id: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct OpenTabParams {
+    profile: Option<String>,
+    cwd: Option<String>,
+    command: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct ListActionsParams {
+    query: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunActionParams {
+    id: String,
+    #[serde(default)]
+    args: HashMap<String, String>,
+}
+
+/// Start listening if the socket is enabled in settings
+///
+/// Failures are logged, not fatal; the rest of the app works without the socket.
+pub fn start(app: AppHandle) {
+    if !settings::read_section::<ControlSocketSettings>("controlSocket").enabled {
+        return;
+    }
+    match listen() {
+        Ok((listener, token)) => {
+            std::thread::spawn(move || accept_loop(app, listener, token));
+        }
+        Err(e) => log::warn!("Control socket unavailable: {}", e),
+    }
+}
+
+/// Directory holding the socket and token, private to the user
+fn runtime_dir() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join("xterminal"))
+}
+
+/// Bind the socket and write a fresh token, both readable only by the user
+fn listen() -> Result<(UnixListener, String), String> {
+    let dir = runtime_dir().ok_or_else(|| "Could not find runtime directory".to_string())?;
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+    let token_path = dir.join("control.token");
+    let _ = fs::remove_file(&token_path);
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&token_path)
+        .and_then(|mut file| file.write_all(token.as_bytes()))
+        .map_err(|e| format!("Failed to write control token: {}", e))?;
+
+    // A socket left behind by a previous run that crashed
+    let socket_path = dir.join("control.sock");
+    let _ = fs::remove_file(&socket_path);
+    let listener =
+        UnixListener::bind(&socket_path).map_err(|e| format!("Failed to bind {:?}: {}", socket_path, e))?;
+    fs::set_permissions(&socket_path, Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {:?}: {}", socket_path, e))?;
+
+    log::info!("Control socket listening on {:?}", socket_path);
+    Ok((listener, token))
+}
+
+fn accept_loop(app: AppHandle, listener: UnixListener, token: String) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("Control socket accept failed: {}", e);
+                continue;
+            }
+        };
+        if !same_user(&stream) {
+            log::warn!("Control socket connection from another user refused");
+            continue;
+        }
+        let app = app.clone();
+        let token = token.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = serve(&app, stream, &token) {
+                log::debug!("Control socket connection ended: {}", e);
+            }
+        });
+    }
+}
+
+/// Whether the peer runs as the same user as the app
+fn same_user(stream: &UnixStream) -> bool {
+    // SAFETY: ucred is plain C data, for which all zeroes is a valid value
+    let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: the fd is a live socket owned by `stream`, and `cred`/`len` describe a buffer of
+    // the size SO_PEERCRED writes
+    let status = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    // SAFETY: getuid has no preconditions
+    status == 0 && cred.uid == unsafe { libc::getuid() }
+}
+
+/// Answer requests on one connection until the client disconnects
+fn serve(app: &AppHandle, stream: UnixStream, token: &str) -> Result<(), String> {
+    let mut writer = stream.try_clone().map_err(|e| format!("Failed to clone stream: {}", e))?;
+    let mut reader = BufReader::new(stream);
+    let mut authenticated = false;
+
+    loop {
+        let mut line = String::new();
+        let read = (&mut reader)
+            .take(MAX_LINE_BYTES)
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read request: {}", e))?;
+        if read == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && read as u64 == MAX_LINE_BYTES {
+            return Err("Request line too long".to_string());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) if request.method == "auth" => {
+                let given = request.params.get("token").and_then(Value::as_str).unwrap_or_default();
+                authenticated = constant_time_eq(given.as_bytes(), token.as_bytes());
+                reply(request.id, if authenticated { Ok(json!(true)) } else { Err("Invalid token".to_string()) })
+            }
+            Ok(request) if !authenticated => reply(request.id, Err("Not authenticated".to_string())),
+            Ok(request) => {
+                let result = handle(app, &request.method, request.params);
+                reply(request.id, result)
+            }
+            Err(e) => reply(Value::Null, Err(format!("Invalid request: {}", e))),
+        };
+
+        let mut out = serde_json::to_string(&response).map_err(|e| format!("Failed to serialize response: {}", e))?;
+        out.push('\n');
+        writer
+            .write_all(out.as_bytes())
+            .map_err(|e| format!("Failed to write response: {}", e))?;
+    }
+}
+
+fn reply(id: Value, result: Result<Value, String>) -> Response {
+    match result {
+        Ok(result) => Response { id, result: Some(result), error: None },
+        Err(error) => Response { id, result: None, error: Some(error) },
+    }
+}
+
+/// Run one method, named like the Tauri command it mirrors
+fn handle(app: &AppHandle, method: &str, params: Value) -> Result<Value, String> {
+    let manager = app.state::<PtyManager>();
+    match method {
+        "list_sessions" => to_value(manager.list()),
+        "get_session_info" => {
+            let params: SessionParams = parse(params)?;
+            to_value(manager.info(&params.session_id)?)
+        }
+        "pty_write" => {
+            let params: WriteParams = parse(params)?;
+            manager.write(&params.session_id, &params.data)?;
+            Ok(Value::Null)
+        }
+        "pty_get_cwd" => {
+            let params: SessionParams = parse(params)?;
+            to_value(manager.cwd(&params.session_id)?)
+        }
+        "open_tab" => {
+            let params: OpenTabParams = parse(params)?;
+            dispatch(
+                app,
+                ControlAction::OpenTab {
+                    profile: params.profile,
+                    cwd: params.cwd,
+                    command: params.command,
+                    context: None,
+                },
+            )?;
+            Ok(Value::Null)
+        }
+        "focus_session" => {
+            let params: SessionParams = parse(params)?;
+            dispatch(app, ControlAction::FocusSession { session_id: params.session_id })?;
+            Ok(Value::Null)
+        }
+        "list_actions" => {
+            let params: ListActionsParams = parse(params)?;
+            to_value(app.state::<ActionRegistry>().list(params.query.as_deref()))
+        }
+        "run_action" => {
+            let params: RunActionParams = parse(params)?;
+            app.state::<ActionRegistry>().run(app, &params.id, params.args)?;
+            Ok(Value::Null)
+        }
+        _ => Err(format!("Unknown method: {}", method)),
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, String> {
+    // Requests may leave out params when none are required
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| format!("Invalid params: {}", e))
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Compare secrets without leaking where they first differ through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,174 @@
+// Line diffs
+// Unified diffs of two texts, as `diff -u` prints them, using Myers' algorithm on the lines
+// between the common prefix and suffix
+
+/// Unchanged lines shown around each change
+const CONTEXT: usize = 3;
+
+/// Most changed lines searched for a minimal diff; beyond it the differing middle is shown as
+/// removed and added whole
+const MAX_EDITS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    /// Line of both texts, by its index in each
+    Equal(usize, usize),
+    /// Line of the old text only
+    Delete(usize),
+    /// Line of the new text only
+    Insert(usize),
+}
+
+/// Unified diff from `old` to `new`, with the given file labels; empty when they are the same
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let edits = edits(&a, &b);
+    if edits.iter().all(|edit| matches!(edit, Edit::Equal(..))) {
+        return String::new();
+    }
+
+    // Lines of each text before every edit, for hunk headers
+    let mut before = Vec::with_capacity(edits.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for edit in &edits {
+        before.push((old_line, new_line));
+        match edit {
+            Edit::Equal(..) => (old_line, new_line) = (old_line + 1, new_line + 1),
+            Edit::Delete(_) => old_line += 1,
+            Edit::Insert(_) => new_line += 1,
+        }
+    }
+    before.push((old_line, new_line));
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let changes: Vec<usize> = (0..edits.len())
+        .filter(|i| !matches!(edits[*i], Edit::Equal(..)))
+        .collect();
+    let mut group_start = 0;
+    for i in 0..changes.len() {
+        // Changes close enough to share their context go in one hunk
+        let last_of_group = changes.get(i + 1).map_or(true, |next| next - changes[i] > 2 * CONTEXT + 1);
+        if !last_of_group {
+            continue;
+        }
+        let start = changes[group_start].saturating_sub(CONTEXT);
+        let end = (changes[i] + CONTEXT + 1).min(edits.len());
+        hunk(&mut out, &edits[start..end], before[start], before[end], &a, &b);
+        group_start = i + 1;
+    }
+    out
+}
+
+fn hunk(out: &mut String, edits: &[Edit], start: (usize, usize), end: (usize, usize), a: &[&str], b: &[&str]) {
+    // An empty range is given by the line before it
+    let range = |from: usize, to: usize| {
+        let len = to - from;
+        format!("{},{}", if len == 0 { from } else { from + 1 }, len)
+    };
+    out.push_str(&format!("@@ -{} +{} @@\n", range(start.0, end.0), range(start.1, end.1)));
+    for edit in edits {
+        let (sign, line) = match *edit {
+            Edit::Equal(i, _) => (' ', a[i]),
+            Edit::Delete(i) => ('-', a[i]),
+            Edit::Insert(j) => ('+', b[j]),
+        };
+        out.push(sign);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Edits turning `a` into `b`
+fn edits(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Equal(i, i)).collect();
+    let middle = myers(a_mid, b_mid).unwrap_or_else(|| {
+        (0..a_mid.len())
+            .map(Edit::Delete)
+            .chain((0..b_mid.len()).map(Edit::Insert))
+            .collect()
+    });
+    edits.extend(middle.into_iter().map(|edit| match edit {
+        Edit::Equal(i, j) => Edit::Equal(i + prefix, j + prefix),
+        Edit::Delete(i) => Edit::Delete(i + prefix),
+        Edit::Insert(j) => Edit::Insert(j + prefix),
+    }));
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+    edits.extend((0..suffix).map(|i| Edit::Equal(a_end + i, b_end + i)));
+    edits
+}
+
+/// Shortest edit script by Myers' O(ND) algorithm, or None if it needs more than `MAX_EDITS`
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDITS) as isize;
+    let offset = max + 1;
+    // Furthest x reached on each diagonal k = x - y
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // Diagonals -d-1..=d+1 of `v` as each round d started, for walking the path back
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        // The snapshot of round d starts at diagonal -d-1
+        let get = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert((y - 1) as usize));
+            } else {
+                edits.push(Edit::Delete((x - 1) as usize));
+            }
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    edits.reverse();
+    edits
+}
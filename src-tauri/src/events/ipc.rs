@@ -0,0 +1,91 @@
+// IPC event emitter
+// Forwards bus events to the frontend as `pty://{id}/{kind}` Tauri events
+
+use super::{EventKind, SessionEvent};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter};
+
+/// Subscriber that emits every session event to the frontend
+pub fn emitter(app_handle: AppHandle) -> impl Fn(&SessionEvent) + Send + Sync + 'static {
+    move |event| forward(&app_handle, event)
+}
+
+fn forward(app: &AppHandle, event: &SessionEvent) {
+    let id = &event.session_id;
+
+    match &event.kind {
+        EventKind::Data(data) => emit(app, id, "data", data.as_str()),
+        EventKind::Binary(bytes) => emit(app, id, "binary", BASE64_STANDARD.encode(bytes)),
+        EventKind::Exit { exit_code, signal } => {
+            emit(app, id, "exit", json!({ "exitCode": exit_code, "signal": signal }))
+        }
+        EventKind::AltScreen { active } => emit(app, id, "alt-screen", json!({ "active": active })),
+        EventKind::MouseMode { tracking, encoding } => emit(
+            app,
+            id,
+            "mouse-mode",
+            json!({ "tracking": tracking, "encoding": encoding }),
+        ),
+        EventKind::BracketedPaste { enabled } => {
+            emit(app, id, "bracketed-paste", json!({ "enabled": enabled }))
+        }
+        EventKind::ComputedTitle { title } => emit(app, id, "computed-title", json!({ "title": title })),
+        EventKind::Title { title } => emit(app, id, "title", json!({ "title": title })),
+        EventKind::Bell(settings) => emit(app, id, "bell", settings),
+        EventKind::Cwd(cwd) => emit(app, id, "cwd", cwd),
+        EventKind::Prompt => emit(app, id, "prompt", json!({})),
+        EventKind::CommandStarted(started) => emit(app, id, "command-started", started),
+        EventKind::CommandFinished(finished) => emit(app, id, "command-finished", finished),
+        EventKind::Links(lines) => emit(app, id, "links", lines),
+        EventKind::Progress(progress) => emit(app, id, "progress", json!({ "progress": progress })),
+        EventKind::Clipboard { request, allowed } => emit(
+            app,
+            id,
+            "clipboard",
+            json!({ "access": request.access(), "allowed": allowed }),
+        ),
+        EventKind::Location(location) => emit(
+            app,
+            id,
+            "location",
+            json!({ "cwd": location.cwd, "host": location.host }),
+        ),
+        EventKind::Elevated { elevated } => emit(app, id, "elevated", json!({ "elevated": elevated })),
+        EventKind::Container(container) => emit(app, id, "container", json!({ "container": container })),
+        EventKind::RemoteHost(info) => emit(app, id, "remote-host", info),
+        EventKind::Unresponsive(state) => emit(app, id, "unresponsive", state),
+        EventKind::Activity { quiet_seconds } => {
+            emit(app, id, "activity", json!({ "quietSeconds": quiet_seconds }))
+        }
+        EventKind::Silence { seconds } => emit(app, id, "silence", json!({ "seconds": seconds })),
+        EventKind::MemoryWarning(usage) => emit(app, id, "memory-warning", usage),
+        EventKind::Bandwidth(stats) => emit(app, id, "bandwidth", stats),
+        EventKind::Hibernation(state) => emit(app, id, "hibernation", state),
+        EventKind::JobExit { pid, command } => {
+            emit(app, id, "job-exit", json!({ "pid": pid, "command": command }))
+        }
+        EventKind::Jobs(jobs) => emit(app, id, "jobs", jobs),
+        EventKind::Heartbeat(heartbeat) => emit(app, id, "heartbeat", heartbeat),
+        EventKind::Appearance(appearance) => emit(app, id, "appearance", appearance),
+        EventKind::FollowReset { reason, position } => emit(
+            app,
+            id,
+            "follow-reset",
+            json!({ "reason": reason, "position": position }),
+        ),
+        EventKind::LogStopped { path, error } => {
+            emit(app, id, "log-stopped", json!({ "path": path, "error": error }))
+        }
+        EventKind::RecordingStopped { path, error } => {
+            emit(app, id, "recording-stopped", json!({ "path": path, "error": error }))
+        }
+    }
+}
+
+/// Emit `pty://{id}/{kind}` to the frontend
+fn emit(app: &AppHandle, session_id: &str, kind: &str, payload: impl Serialize + Clone) {
+    let event_name = format!("pty://{}/{}", session_id, kind);
+    let _ = app.emit(event_name.as_str(), payload);
+}
@@ -0,0 +1,124 @@
+// Events module - internal typed event bus
+// Subsystems (reader, parsers, monitor, followers) publish session events here, and consumers
+// (the IPC emitter, and later history, logging, notifications) subscribe to them.
+
+pub mod ipc;
+
+use crate::pty::bandwidth::BandwidthStats;
+use crate::pty::bell::BellSettings;
+use crate::pty::clipboard::ClipboardRequest;
+use crate::pty::container::SessionContainer;
+use crate::pty::hang::HangState;
+use crate::pty::hibernate::HibernationState;
+use crate::pty::links::LinkedLine;
+use crate::pty::marks::{CommandFinished, CommandStarted};
+use crate::pty::modes::{MouseEncoding, MouseTracking};
+use crate::pty::progress::TerminalProgress;
+use crate::pty::title::{ReportedCwd, SessionLocation};
+use crate::pty::{JobInfo, MemoryUsage, SessionAppearance, SessionHeartbeat};
+use crate::ssh::RemoteHostInfo;
+use crate::sync::RwLockExt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, RwLock};
+
+/// Something that happened in a session
+#[derive(Debug, Clone)]
+pub struct SessionEvent {
+    pub session_id: String,
+    pub kind: EventKind,
+}
+
+/// The typed payload of a session event
+#[derive(Debug, Clone)]
+pub enum EventKind {
+    /// Output decoded for display
+    Data(String),
+    /// Raw output of a session using the base64 output encoding
+    Binary(Vec<u8>),
+    /// The command ended; `exit_code` is None when it was killed by a signal or not known
+    Exit { exit_code: Option<i32>, signal: Option<String> },
+    AltScreen { active: bool },
+    MouseMode { tracking: MouseTracking, encoding: MouseEncoding },
+    BracketedPaste { enabled: bool },
+    ComputedTitle { title: String },
+    /// A program rang the bell; the session's settings say what to do about it
+    Bell(BellSettings),
+    /// The program set the window title with OSC 0 or 2
+    Title { title: String },
+    /// The foreground process changed directory or remote host
+    Location(SessionLocation),
+    /// The shell reported a new working directory with OSC 7
+    Cwd(ReportedCwd),
+    /// Shell integration marked the start of a prompt
+    Prompt,
+    /// Shell integration marked a command as running
+    CommandStarted(CommandStarted),
+    /// Shell integration marked the end of a command, or a new prompt came without one
+    CommandFinished(CommandFinished),
+    /// Lines of output with hyperlinks, URLs, or paths
+    Links(Vec<LinkedLine>),
+    /// A program reported progress with OSC 9;4; None when it is done
+    Progress(Option<TerminalProgress>),
+    /// A program asked to set or read the clipboard with OSC 52, and whether its profile allows it
+    Clipboard { request: ClipboardRequest, allowed: bool },
+    Elevated { elevated: bool },
+    /// The session entered or left a container; None means the host
+    Container(Option<SessionContainer>),
+    /// A probe found out which system an SSH session is connected to
+    RemoteHost(RemoteHostInfo),
+    MemoryWarning(MemoryUsage),
+    /// The foreground program stopped reading input, or responded again after that
+    Unresponsive(HangState),
+    /// Output resumed after this long without any, in a session monitored for activity
+    Activity { quiet_seconds: u64 },
+    /// No output for this long, in a session monitored for silence
+    Silence { seconds: u64 },
+    /// The session's scrollback moved to disk or back into memory
+    Hibernation(HibernationState),
+    /// Estimated traffic of the session's ssh, mosh, and telnet clients changed
+    Bandwidth(BandwidthStats),
+    JobExit { pid: u32, command: String },
+    Jobs(Vec<JobInfo>),
+    Appearance(SessionAppearance),
+    Heartbeat(SessionHeartbeat),
+    /// A followed file was rotated, truncated, removed, or seeked
+    FollowReset { reason: String, position: Option<u64> },
+    /// A session log ended because the session did or writing failed, not by request
+    LogStopped { path: String, error: Option<String> },
+    /// A recording ended because the session did or writing failed, not by request
+    RecordingStopped { path: String, error: Option<String> },
+}
+
+type Subscriber = Arc<dyn Fn(&SessionEvent) + Send + Sync>;
+
+/// Synchronous publish/subscribe broker
+///
+/// Subscribers run on the publishing thread in subscription order, so they must not block.
+/// They stay subscribed for the lifetime of the app.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self, subscriber: impl Fn(&SessionEvent) + Send + Sync + 'static) {
+        self.subscribers.write_locked().push(Arc::new(subscriber));
+    }
+
+    /// Deliver an event to every subscriber
+    pub fn publish(&self, session_id: &str, kind: EventKind) {
+        let event = SessionEvent {
+            session_id: session_id.to_string(),
+            kind,
+        };
+
+        // Snapshot so subscribers may publish or subscribe without deadlocking
+        let subscribers: Vec<Subscriber> = self.subscribers.read_locked().clone();
+        // A subscriber that panics must not take the publisher, usually a session's reader, with it
+        for subscriber in subscribers {
+            if panic::catch_unwind(AssertUnwindSafe(|| subscriber(&event))).is_err() {
+                log::error!("Event subscriber panicked handling an event of session {}", session_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// Exec module - Non-interactive command execution
+// Runs a program without a PTY, capturing its output with a timeout
+
+use crate::pty::signal::{self, Signal};
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Default timeout for quick commands
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Output kept per stream; the rest is read and dropped so the program never blocks on a full pipe
+const MAX_OUTPUT_BYTES: usize = 8 * 1024 * 1024;
+
+/// Least time given to reading the output left in the pipes once the program exited
+const DRAIN_GRACE: Duration = Duration::from_millis(500);
+
+/// Options for running a non-interactive command
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecOptions {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: Option<HashMap<String, String>>,
+    pub timeout_ms: Option<u64>,
+    /// Text written to the program's standard input, which is empty otherwise
+    #[serde(default)]
+    pub stdin: Option<String>,
+}
+
+/// Captured result of a non-interactive command
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, `None` when the process was killed by a signal or timed out
+    pub exit_code: Option<i32>,
+    /// The process was killed at the timeout; stdout and stderr hold what it wrote until then
+    pub timed_out: bool,
+    /// stdout or stderr went past the size kept and was cut short
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+impl ExecOutput {
+    /// Whether the command exited with status 0
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run a command to completion, killing it if the timeout elapses
+///
+/// The program runs in a process group of its own, so the processes it started are killed with
+/// it at the timeout. Once it exits, its status stands: reading what is left in the pipes is
+/// bounded by the same deadline, as a process it left running may hold them open.
+pub async fn run(options: &ExecOptions) -> Result<ExecOutput, String> {
+    let mut cmd = Command::new(&options.program);
+    cmd.args(&options.args)
+        .stdin(if options.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .kill_on_drop(true);
+
+    if let Some(cwd) = &options.cwd {
+        cmd.current_dir(cwd);
+    }
+
+    if let Some(env) = &options.env {
+        cmd.envs(env);
+    }
+
+    let timeout = Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let started = Instant::now();
+    let deadline = started + timeout;
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", options.program, e))?;
+    // The group was created with the program as its leader
+    let group = child.id().and_then(|pid| i32::try_from(pid).ok());
+
+    // Written concurrently so a program that answers as it reads cannot fill its pipes and stall
+    if let (Some(input), Some(mut pipe)) = (options.stdin.clone(), child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let (stdout, mut stdout_read) = collect(child.stdout.take());
+    let (stderr, mut stderr_read) = collect(child.stderr.take());
+    let finished = tokio::time::timeout_at(deadline, child.wait()).await;
+
+    let timed_out = finished.is_err();
+    let exit_code = match finished {
+        Ok(status) => {
+            let code = status
+                .map_err(|e| format!("Failed to wait for {}: {}", options.program, e))?
+                .code();
+            // Output the program wrote just before exiting may not have been read yet
+            let drained = tokio::time::timeout_at(deadline.max(Instant::now() + DRAIN_GRACE), async {
+                let _ = (&mut stdout_read).await;
+                let _ = (&mut stderr_read).await;
+            })
+            .await;
+            if drained.is_err() {
+                log::debug!("{} exited, but a process it started still holds its output", options.program);
+            }
+            code
+        }
+        Err(_) => {
+            log::warn!("{} timed out after {:?}", options.program, timeout);
+            kill_group(group, &options.program);
+            if let Err(e) = child.kill().await {
+                log::warn!("Failed to kill {}: {}", options.program, e);
+            }
+            None
+        }
+    };
+    // A process that left the group may still hold the pipes open
+    stdout_read.abort();
+    stderr_read.abort();
+
+    let (stdout, stdout_truncated) = stdout.locked().text();
+    let (stderr, stderr_truncated) = stderr.locked().text();
+    Ok(ExecOutput {
+        stdout,
+        stderr,
+        exit_code,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Kill what is left of the program's process group
+fn kill_group(group: Option<i32>, program: &str) {
+    let Some(group) = group else {
+        return;
+    };
+    // Fails when every process in the group has already exited
+    if let Err(e) = signal::send_group(group, Signal::Sigkill) {
+        log::debug!("Failed to kill the processes of {}: {}", program, e);
+    }
+}
+
+/// Output read from a pipe, up to MAX_OUTPUT_BYTES
+#[derive(Default)]
+struct Captured {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl Captured {
+    fn push(&mut self, data: &[u8]) {
+        let room = MAX_OUTPUT_BYTES - self.bytes.len();
+        if data.len() > room {
+            self.truncated = true;
+        }
+        self.bytes.extend_from_slice(&data[..data.len().min(room)]);
+    }
+
+    /// The output as text, and whether it was cut short
+    fn text(&self) -> (String, bool) {
+        (String::from_utf8_lossy(&self.bytes).to_string(), self.truncated)
+    }
+}
+
+/// Read a pipe into a buffer as output arrives, so what was read is kept if the program is killed
+fn collect(pipe: Option<impl AsyncRead + Unpin + Send + 'static>) -> (Arc<Mutex<Captured>>, JoinHandle<()>) {
+    let buffer = Arc::new(Mutex::new(Captured::default()));
+    let sink = buffer.clone();
+    let reader = tokio::spawn(async move {
+        let Some(mut pipe) = pipe else {
+            return;
+        };
+        let mut chunk = [0u8; 8192];
+        while let Ok(n) = pipe.read(&mut chunk).await {
+            if n == 0 {
+                break;
+            }
+            sink.locked().push(&chunk[..n]);
+        }
+    });
+    (buffer, reader)
+}
+
+/// Where an executable program is: the path itself if it has a slash, else the first match on
+/// $PATH with the exec bit set
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Some(PathBuf::from(program)).filter(|path| is_executable(path));
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
@@ -0,0 +1,123 @@
+// Fonts module - Glyph coverage checks for the configured font family
+// Resolves the font through fontconfig and inspects its cmap table
+
+use crate::exec::{self, ExecOptions};
+use serde::Serialize;
+
+/// Codepoint groups commonly used by shell prompts
+const GLYPH_GROUPS: &[(&str, &[u32])] = &[
+    ("powerline", &[0xE0A0, 0xE0A1, 0xE0A2, 0xE0B0, 0xE0B1, 0xE0B2, 0xE0B3]),
+    ("nerdFont", &[0xE5FF, 0xF015, 0xF07C, 0xF113, 0xF418]),
+    ("emoji", &[0x2705, 0x1F4C1, 0x1F600, 0x1F680]),
+];
+
+/// CSS generic families that fontconfig cannot resolve to a concrete font
+const GENERIC_FAMILIES: &[&str] = &["monospace", "serif", "sans-serif", "cursive", "fantasy", "system-ui"];
+
+/// Coverage of one group of glyphs
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GlyphGroupCoverage {
+    pub group: String,
+    /// Codepoints present in the configured font itself
+    pub covered: usize,
+    pub total: usize,
+    /// Codepoints missing from the font but available in some installed fallback
+    pub fallback: usize,
+    /// Codepoints no installed font can render
+    pub missing: Vec<String>,
+}
+
+/// Coverage report for a font family
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FontCoverageReport {
+    pub requested_family: String,
+    /// Family fontconfig actually resolved to
+    pub resolved_family: String,
+    pub file: String,
+    /// Whether fontconfig had to substitute a different family
+    pub substituted: bool,
+    pub groups: Vec<GlyphGroupCoverage>,
+}
+
+/// Check Powerline/Nerd Font/emoji coverage of the first concrete family in a CSS font stack
+pub async fn check_coverage(font_family: &str) -> Result<FontCoverageReport, String> {
+    let family = font_family
+        .split(',')
+        .map(|f| f.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .find(|f| !f.is_empty() && !GENERIC_FAMILIES.contains(f))
+        .unwrap_or("monospace")
+        .to_string();
+
+    let matched = fc_output("fc-match", &["--format=%{family[0]}\n%{file}\n%{index}", &family]).await?;
+    let mut lines = matched.lines();
+    let resolved_family = lines.next().unwrap_or_default().to_string();
+    let file = lines.next().unwrap_or_default().to_string();
+    let index = lines.next().and_then(|i| i.parse().ok()).unwrap_or(0);
+
+    let data = std::fs::read(&file).map_err(|e| format!("Failed to read font {}: {}", file, e))?;
+    let face = ttf_parser::Face::parse(&data, index)
+        .map_err(|e| format!("Failed to parse font {}: {}", file, e))?;
+
+    let mut groups = Vec::with_capacity(GLYPH_GROUPS.len());
+    for (group, codepoints) in GLYPH_GROUPS {
+        let mut covered = 0;
+        let mut fallback = 0;
+        let mut missing = Vec::new();
+
+        for &codepoint in codepoints.iter() {
+            let in_font = char::from_u32(codepoint)
+                .and_then(|c| face.glyph_index(c))
+                .is_some();
+
+            if in_font {
+                covered += 1;
+            } else if installed_font_covers(codepoint).await {
+                fallback += 1;
+            } else {
+                missing.push(format!("U+{:04X}", codepoint));
+            }
+        }
+
+        groups.push(GlyphGroupCoverage {
+            group: group.to_string(),
+            covered,
+            total: codepoints.len(),
+            fallback,
+            missing,
+        });
+    }
+
+    Ok(FontCoverageReport {
+        substituted: !resolved_family.eq_ignore_ascii_case(&family),
+        requested_family: family,
+        resolved_family,
+        file,
+        groups,
+    })
+}
+
+/// Whether any installed font has a glyph for the codepoint
+async fn installed_font_covers(codepoint: u32) -> bool {
+    let pattern = format!(":charset={:x}", codepoint);
+    fc_output("fc-list", &[&pattern, "family"])
+        .await
+        .map(|out| !out.trim().is_empty())
+        .unwrap_or(false)
+}
+
+/// Run a fontconfig tool and return its stdout
+async fn fc_output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = exec::run(&ExecOptions {
+        program: program.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        ..Default::default()
+    })
+    .await?;
+
+    if !output.success() {
+        return Err(format!("{} failed: {}", program, output.stderr.trim()));
+    }
+    Ok(output.stdout)
+}
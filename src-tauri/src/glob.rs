@@ -0,0 +1,47 @@
+// Wildcard matching
+// Shell-style `*` and `?` patterns, as used by ssh_config and profile rules
+
+use std::path::Path;
+
+/// Match `*` (any run of characters) and `?` (one character) against the whole of `text`
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((after, tried)) => {
+                    p = after;
+                    t = tried + 1;
+                    star = Some((after, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Match a path pattern one component at a time, so wildcards do not cross `/`
+pub fn matches_path(pattern: &Path, path: &Path) -> bool {
+    let mut pattern = pattern.components();
+    let mut path = path.components();
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(p), Some(c)) if matches(&p.as_os_str().to_string_lossy(), &c.as_os_str().to_string_lossy()) => {}
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,254 @@
+// Health checks
+// Self-checks run at launch so the first-run experience can point at exactly what is
+// misconfigured: the config directory, the default shell, the terminfo entry for the TERM we
+// advertise, pseudo-terminal support, and whether the webview loaded and can render.
+
+use crate::commands::settings;
+use crate::exec;
+use crate::profiles;
+use crate::pty::session::TERM;
+use crate::pty::terminfo;
+use crate::pty::SpawnOptions;
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    Ok,
+    /// Works, but something will behave worse than it should
+    Warning,
+    /// Terminals will not work until this is fixed
+    Error,
+}
+
+/// Outcome of one check
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    /// Stable identifier: `config`, `shell`, `terminfo`, `pty`, or `renderer`
+    pub id: String,
+    pub status: HealthStatus,
+    pub message: String,
+    /// What the user can do about it, when not ok
+    pub fix: Option<String>,
+}
+
+/// Result of all checks
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub checks: Vec<HealthCheck>,
+    /// No check found an error
+    pub ok: bool,
+    /// Unix time in milliseconds
+    pub checked_at: u64,
+}
+
+/// What the frontend found out about rendering, which the backend cannot see
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RendererStatus {
+    /// WebGL is available for the terminal renderer
+    pub webgl: bool,
+    /// Error the renderer failed with, if any
+    pub error: Option<String>,
+}
+
+/// Checks run so far and what is known about the webview, managed by Tauri
+#[derive(Default)]
+pub struct Health {
+    report: Mutex<Option<HealthReport>>,
+    page_loaded: Mutex<bool>,
+    renderer: Mutex<Option<RendererStatus>>,
+}
+
+impl Health {
+    /// The webview finished loading the UI
+    pub fn on_page_loaded(&self) {
+        *self.page_loaded.locked() = true;
+    }
+
+    pub fn set_renderer(&self, status: RendererStatus) {
+        *self.renderer.locked() = Some(status);
+    }
+
+    /// The last report, if checks have run, with what is now known about the webview
+    pub fn last(&self) -> Option<HealthReport> {
+        let mut report = self.report.locked().clone()?;
+        let renderer = self.check_renderer();
+        report.checks.retain(|check| check.id != renderer.id);
+        report.checks.push(renderer);
+        report.ok = !report.checks.iter().any(|check| check.status == HealthStatus::Error);
+        Some(report)
+    }
+
+    /// Run every check and keep the report
+    pub async fn check(&self) -> HealthReport {
+        let mut checks = tokio::task::spawn_blocking(|| vec![check_config(), check_shell(), check_pty()])
+            .await
+            .unwrap_or_default();
+        checks.insert(2, check_terminfo().await);
+        checks.push(self.check_renderer());
+
+        let report = HealthReport {
+            ok: !checks.iter().any(|check| check.status == HealthStatus::Error),
+            checks,
+            checked_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+        };
+        for check in report.checks.iter().filter(|check| check.status != HealthStatus::Ok) {
+            log::warn!("Health check {} found a problem: {}", check.id, check.message);
+        }
+        *self.report.locked() = Some(report.clone());
+        report
+    }
+
+    fn check_renderer(&self) -> HealthCheck {
+        if !*self.page_loaded.locked() {
+            return problem(
+                "renderer",
+                HealthStatus::Warning,
+                "The interface has not finished loading yet".to_string(),
+                "If the window stays blank, check that WebKitGTK is installed and try WEBKIT_DISABLE_DMABUF_RENDERER=1",
+            );
+        }
+        match &*self.renderer.locked() {
+            Some(RendererStatus { error: Some(error), .. }) => problem(
+                "renderer",
+                HealthStatus::Error,
+                format!("The terminal renderer failed: {}", error),
+                "Switch terminal.rendererType to \"dom\" in settings",
+            ),
+            Some(RendererStatus { webgl: false, .. }) => problem(
+                "renderer",
+                HealthStatus::Warning,
+                "WebGL is not available, so terminals render more slowly".to_string(),
+                "Enable hardware acceleration for WebKitGTK, or update the graphics drivers",
+            ),
+            _ => ok("renderer", "The interface loaded".to_string()),
+        }
+    }
+}
+
+fn check_config() -> HealthCheck {
+    let path = match settings::get_settings_path() {
+        Ok(path) => path,
+        Err(e) => {
+            return problem(
+                "config",
+                HealthStatus::Error,
+                e,
+                "Make sure $XDG_CONFIG_HOME or ~/.config exists and belongs to you",
+            )
+        }
+    };
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    let probe = dir.join(".health-check");
+    if let Err(e) = fs::write(&probe, b"") {
+        return problem(
+            "config",
+            HealthStatus::Error,
+            format!("Settings cannot be saved to {}: {}", dir.display(), e),
+            "Fix the permissions of the directory so your user can write to it",
+        );
+    }
+    let _ = fs::remove_file(&probe);
+    match fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<serde_json::Value>(&contents) {
+            Ok(_) => ok("config", format!("Settings are read from {}", path.display())),
+            Err(e) => problem(
+                "config",
+                HealthStatus::Warning,
+                format!("{} is not valid JSON, so defaults are used: {}", path.display(), e),
+                "Fix or remove the file; saving settings from the app rewrites it",
+            ),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            ok("config", format!("Settings will be saved to {}", path.display()))
+        }
+        Err(e) => problem(
+            "config",
+            HealthStatus::Error,
+            format!("{} cannot be read: {}", path.display(), e),
+            "Fix the permissions of the file so your user can read it",
+        ),
+    }
+}
+
+fn check_shell() -> HealthCheck {
+    // The shell a new tab gets: the default profile's, else $SHELL
+    let mut options = SpawnOptions::default();
+    profiles::resolve(&mut options);
+    let shell = options.shell.unwrap_or_default();
+    match exec::find_program(&shell) {
+        Some(path) => ok("shell", format!("New tabs run {}", path.display())),
+        None => problem(
+            "shell",
+            HealthStatus::Error,
+            format!("The default shell {} is not an executable program", shell),
+            "Pick another default profile, or set $SHELL to an installed shell",
+        ),
+    }
+}
+
+async fn check_terminfo() -> HealthCheck {
+    match terminfo::check(TERM).await {
+        Ok(report) if !report.found => problem(
+            "terminfo",
+            HealthStatus::Warning,
+            format!("No terminfo entry for {}; programs will fall back to plain output", TERM),
+            "Install ncurses-base or ncurses-term",
+        ),
+        Ok(report) if !report.mismatches.is_empty() => problem(
+            "terminfo",
+            HealthStatus::Warning,
+            report.mismatches.join("; "),
+            "Update the ncurses terminfo database",
+        ),
+        Ok(_) => ok("terminfo", format!("{} is described correctly", TERM)),
+        Err(e) => problem("terminfo", HealthStatus::Warning, e, "Install ncurses-bin for infocmp"),
+    }
+}
+
+fn check_pty() -> HealthCheck {
+    // Opening the multiplexer allocates a pseudo-terminal pair, which closing releases again
+    match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open("/dev/ptmx")
+    {
+        Ok(_) => ok("pty", "Pseudo-terminals can be created".to_string()),
+        Err(e) => problem(
+            "pty",
+            HealthStatus::Error,
+            format!("/dev/ptmx cannot be opened: {}", e),
+            "Mount devpts at /dev/pts; in a container or sandbox, allow access to /dev/ptmx",
+        ),
+    }
+}
+
+fn ok(id: &str, message: String) -> HealthCheck {
+    HealthCheck {
+        id: id.to_string(),
+        status: HealthStatus::Ok,
+        message,
+        fix: None,
+    }
+}
+
+fn problem(id: &str, status: HealthStatus, message: String, fix: &str) -> HealthCheck {
+    HealthCheck {
+        id: id.to_string(),
+        status,
+        message,
+        fix: Some(fix.to_string()),
+    }
+}
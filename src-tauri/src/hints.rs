@@ -0,0 +1,428 @@
+// Link hints
+// Finds the clickable parts of a line of terminal output: URLs, paths that exist on disk,
+// commits and issue references of the current repository, and IP addresses. Checking against
+// the session's filesystem and repository keeps Ctrl+click from offering words that merely look
+// like links.
+
+use crate::commands::settings;
+use crate::glob;
+use git2::Repository;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Only this much of a line is analyzed
+const MAX_LINE_BYTES: usize = 8192;
+
+/// Commit candidates checked against the repository per line
+const MAX_HASHES: usize = 16;
+
+/// Characters that end a word; paths containing them are not detected
+const WORD_DELIMITERS: &[char] = &['"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', ',', ';', '|'];
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenKind {
+    Url,
+    File,
+    Directory,
+    GitHash,
+    /// Issue reference such as `#1234` or `PROJ-123`
+    Issue,
+    Ip,
+}
+
+/// What the frontend can offer for a token, most likely first
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HintAction {
+    /// Open a URL in the browser or a file in its default application
+    Open,
+    /// Change the session's directory
+    Cd,
+    /// Open a new tab in the directory
+    OpenTab,
+    /// Run `git show` for the commit
+    ShowCommit,
+    /// Connect to the address
+    Ssh,
+    Copy,
+}
+
+/// A clickable part of a line
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LineToken {
+    pub kind: TokenKind,
+    /// UTF-16 offsets into the line, as JavaScript indexes strings
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    /// Absolute path for files and directories, full hash for commits
+    pub target: Option<String>,
+    /// Web page of an issue, or of a commit on a known forge
+    pub link: Option<String>,
+    /// One-line description, e.g. the subject of a commit
+    pub preview: Option<String>,
+    /// Position from a `path:line:column` suffix
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub actions: Vec<HintAction>,
+}
+
+/// An entry of the `issueLinks` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueLinkRule {
+    /// Wildcard pattern for the origin remote as `host/owner/name`, or the repository's path
+    pub repository: String,
+    /// Regex for references, e.g. `\bPROJ-\d+\b`
+    pub pattern: String,
+    /// Link with `$0`, `$1`, or `${name}` replaced by what the pattern captured
+    pub url: String,
+    /// Name of the tracker for previews; defaults to the link's host
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Find the tokens of a line of output
+///
+/// Paths are resolved against `cwd` and commits and issues looked up in its repository; without
+/// a local `cwd`, e.g. in an ssh session, only URLs and addresses are found.
+pub async fn analyze(line: &str, cwd: Option<&Path>) -> Vec<LineToken> {
+    let mut end = line.len().min(MAX_LINE_BYTES);
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let line = &line[..end];
+
+    let mut found: Vec<(usize, usize, LineToken)> = Vec::new();
+    let mut add = |start: usize, end: usize, token: LineToken| {
+        if !found.iter().any(|(s, e, _)| start < *e && *s < end) {
+            found.push((start, end, token));
+        }
+    };
+
+    for (start, end) in urls(line) {
+        add(start, end, token(TokenKind::Url, &line[start..end], vec![HintAction::Open, HintAction::Copy]));
+    }
+    let words = words(line);
+    if let Some(cwd) = cwd {
+        for &(start, end) in &words {
+            if let Some((path_end, found_token)) = path_token(&line[start..end], cwd) {
+                add(start, start + path_end, found_token);
+            }
+        }
+    }
+    for &(start, end) in &words {
+        let (start, end) = trim_punctuation(line, start, end);
+        if is_ip(&line[start..end]) {
+            add(start, end, token(TokenKind::Ip, &line[start..end], vec![HintAction::Ssh, HintAction::Copy]));
+        }
+    }
+    if let Some(cwd) = cwd {
+        // libgit2 and the settings file block, so the repository is read off the async runtime
+        let (line_owned, cwd) = (line.to_string(), cwd.to_path_buf());
+        let found = tokio::task::spawn_blocking(move || repository_tokens(&line_owned, &cwd))
+            .await
+            .unwrap_or_default();
+        for (start, end, found_token) in found {
+            add(start, end, found_token);
+        }
+    }
+
+    found.sort_by_key(|(start, _, _)| *start);
+    found
+        .into_iter()
+        .map(|(start, end, mut token)| {
+            token.start = utf16_len(&line[..start]);
+            token.end = token.start + utf16_len(&line[start..end]);
+            token
+        })
+        .collect()
+}
+
+fn token(kind: TokenKind, text: &str, actions: Vec<HintAction>) -> LineToken {
+    LineToken {
+        kind,
+        start: 0,
+        end: 0,
+        text: text.to_string(),
+        target: None,
+        link: None,
+        preview: None,
+        line: None,
+        column: None,
+        actions,
+    }
+}
+
+/// Byte ranges of URLs, without trailing punctuation
+pub(crate) fn urls(line: &str) -> Vec<(usize, usize)> {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    let url = URL.get_or_init(|| Regex::new(r#"\b(?:https?|ftp|file)://[^\s<>"'`]+"#).unwrap());
+    url.find_iter(line)
+        .map(|m| {
+            let mut end = m.end();
+            loop {
+                let text = &line[m.start()..end];
+                let Some(last) = text.chars().last() else {
+                    break;
+                };
+                // Closing brackets belong to the URL only when it opened them, as in Wikipedia links
+                let unbalanced = match last {
+                    ')' => text.matches('(').count() < text.matches(')').count(),
+                    ']' => text.matches('[').count() < text.matches(']').count(),
+                    '.' | ',' | ';' | ':' | '!' | '?' | '}' => true,
+                    _ => false,
+                };
+                if !unbalanced {
+                    break;
+                }
+                end -= last.len_utf8();
+            }
+            (m.start(), end)
+        })
+        .collect()
+}
+
+/// Byte ranges of whitespace- and bracket-separated words
+pub(crate) fn words(line: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (index, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        let delimiter = c.is_whitespace() || WORD_DELIMITERS.contains(&c);
+        match (start, delimiter) {
+            (None, false) => start = Some(index),
+            (Some(s), true) => {
+                words.push((s, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Shrink a range to exclude punctuation that ends sentences
+fn trim_punctuation(line: &str, start: usize, mut end: usize) -> (usize, usize) {
+    while end > start && line[..end].ends_with(['.', ',', ':', '!', '?']) {
+        end -= 1;
+    }
+    (start, end)
+}
+
+/// A word that names an existing path, with an optional `:line:column` suffix, and the length
+/// of the part to highlight
+pub(crate) fn path_token(word: &str, cwd: &Path) -> Option<(usize, LineToken)> {
+    static LOCATION: OnceLock<Regex> = OnceLock::new();
+    let location = LOCATION.get_or_init(|| Regex::new(r"^(.+?)(?::(\d+))?(?::(\d+))?[.,:!?]*$").unwrap());
+    if word.contains("://") {
+        return None;
+    }
+    let captures = location.captures(word)?;
+    let path_text = captures.get(1)?.as_str();
+    // `.` and `..` are mostly punctuation left over from prose
+    if path_text.chars().all(|c| c == '.') {
+        return None;
+    }
+    let line = captures.get(2).and_then(|m| m.as_str().parse().ok());
+    let column = captures.get(3).and_then(|m| m.as_str().parse().ok());
+    let end = captures.get(3).or(captures.get(2)).map_or(path_text.len(), |m| m.end());
+
+    let path = resolve_path(path_text, cwd)?;
+    let is_dir = path.is_dir();
+    let mut found = token(
+        if is_dir { TokenKind::Directory } else { TokenKind::File },
+        &word[..end],
+        if is_dir {
+            vec![HintAction::Cd, HintAction::OpenTab, HintAction::Copy]
+        } else {
+            vec![HintAction::Open, HintAction::Copy]
+        },
+    );
+    found.target = Some(path.display().to_string());
+    found.line = line;
+    found.column = column;
+    Some((end, found))
+}
+
+/// The existing path a word refers to, trying it without the `a/` and `b/` prefixes of git diffs
+fn resolve_path(text: &str, cwd: &Path) -> Option<PathBuf> {
+    let candidates = [Some(text), text.strip_prefix("a/"), text.strip_prefix("b/")];
+    candidates.into_iter().flatten().find_map(|text| {
+        let path = match (text.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ if text == "~" => dirs::home_dir()?,
+            _ => cwd.join(text),
+        };
+        fs::symlink_metadata(&path).ok().map(|_| path)
+    })
+}
+
+fn is_ip(text: &str) -> bool {
+    // An optional port after IPv4 addresses
+    let address = match text.rsplit_once(':') {
+        Some((address, port)) if address.contains('.') && port.parse::<u16>().is_ok() => address,
+        _ => text,
+    };
+    if address.parse::<Ipv4Addr>().is_ok() {
+        return true;
+    }
+    // Rust paths like `std::fs` are valid IPv6 text, so require a digit and two colons
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    address.matches(':').count() >= 2
+        && address.contains(|c: char| c.is_ascii_digit())
+        && address.parse::<Ipv6Addr>().is_ok()
+}
+
+/// Commits and issue references of the repository containing `cwd`
+fn repository_tokens(line: &str, cwd: &Path) -> Vec<(usize, usize, LineToken)> {
+    let Ok(repo) = Repository::discover(cwd) else {
+        return Vec::new();
+    };
+    let remote = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().and_then(remote_slug));
+    let forge = remote.as_deref().and_then(Forge::from_remote);
+    let mut found = Vec::new();
+
+    for (start, end) in hash_candidates(line).take(MAX_HASHES) {
+        // Fails for unknown and ambiguous prefixes as well as objects that are not commits
+        let Ok(commit) = repo.find_commit_by_prefix(&line[start..end]) else {
+            continue;
+        };
+        let hash = commit.id().to_string();
+        let mut token = token(TokenKind::GitHash, &line[start..end], vec![HintAction::ShowCommit, HintAction::Copy]);
+        token.preview = Some(match commit.author().name() {
+            Some(author) if !author.is_empty() => format!("{} ({})", commit.summary().unwrap_or(""), author),
+            _ => commit.summary().unwrap_or("").to_string(),
+        });
+        if let Some(forge) = &forge {
+            token.link = Some(format!("{}{}", forge.commits, hash));
+            token.actions.insert(1, HintAction::Open);
+        }
+        token.target = Some(hash);
+        found.push((start, end, token));
+    }
+
+    let root = repo.workdir().unwrap_or_else(|| repo.path()).display().to_string();
+    let root = root.trim_end_matches('/');
+    let rules = settings::read_section::<Vec<IssueLinkRule>>("issueLinks");
+    for rule in &rules {
+        let applies = remote.as_deref().is_some_and(|remote| glob::matches(&rule.repository, remote))
+            || glob::matches(&rule.repository, root);
+        if !applies {
+            continue;
+        }
+        let pattern = match Regex::new(&rule.pattern) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                log::warn!("Invalid issue link pattern {:?}: {}", rule.pattern, e);
+                continue;
+            }
+        };
+        for captures in pattern.captures_iter(line) {
+            let Some(m) = captures.get(0).filter(|m| !m.is_empty()) else {
+                continue;
+            };
+            let mut link = String::new();
+            captures.expand(&rule.url, &mut link);
+            let site = rule
+                .name
+                .clone()
+                .or_else(|| url::Url::parse(&link).ok().and_then(|url| url.host_str().map(str::to_string)))
+                .unwrap_or_else(|| link.clone());
+            found.push((m.start(), m.end(), issue_token(m.as_str(), link, &site)));
+        }
+    }
+
+    // Without a rule of their own, `#1234` references link to the issues of a known forge
+    if let (Some(forge), Some(remote)) = (&forge, &remote) {
+        let name = remote.split_once('/').map_or(remote.as_str(), |(_, name)| name);
+        for (start, end) in issue_numbers(line) {
+            let link = format!("{}{}", forge.issues, &line[start + 1..end]);
+            found.push((start, end, issue_token(&line[start..end], link, name)));
+        }
+    }
+    found
+}
+
+fn issue_token(text: &str, link: String, site: &str) -> LineToken {
+    let mut token = token(TokenKind::Issue, text, vec![HintAction::Open, HintAction::Copy]);
+    token.preview = Some(format!("{} on {}", text, site));
+    token.link = Some(link);
+    token
+}
+
+/// Hex words that could be abbreviated commit hashes
+fn hash_candidates(line: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    static HASH: OnceLock<Regex> = OnceLock::new();
+    let hash = HASH.get_or_init(|| Regex::new(r"\b[0-9a-f]{7,40}\b").unwrap());
+    hash.find_iter(line)
+        .filter(|m| {
+            let text = m.as_str();
+            text.contains(|c: char| c.is_ascii_digit()) && text.contains(|c: char| c.is_ascii_lowercase())
+        })
+        .map(|m| (m.start(), m.end()))
+}
+
+/// `#1234` words, leaving out HTML character references and anchors within paths
+fn issue_numbers(line: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    static ISSUE: OnceLock<Regex> = OnceLock::new();
+    let issue = ISSUE.get_or_init(|| Regex::new(r"#\d+\b").unwrap());
+    issue
+        .find_iter(line)
+        .filter(|m| {
+            let before = line[..m.start()].chars().last();
+            !before.is_some_and(|c| c.is_alphanumeric() || matches!(c, '&' | '/' | '#' | '_'))
+        })
+        .map(|m| (m.start(), m.end()))
+}
+
+/// Link prefixes of a hosting service that issue numbers and commit hashes are appended to
+struct Forge {
+    issues: String,
+    commits: String,
+}
+
+impl Forge {
+    fn from_remote(remote: &str) -> Option<Self> {
+        let host = remote.split('/').next()?;
+        let (issues, commits) = if host == "github.com" || host == "codeberg.org" {
+            ("issues", "commit")
+        } else if host.contains("gitlab") {
+            ("-/issues", "-/commit")
+        } else {
+            return None;
+        };
+        Some(Self {
+            issues: format!("https://{}/{}/", remote, issues),
+            commits: format!("https://{}/{}/", remote, commits),
+        })
+    }
+}
+
+/// `host/path` of a remote URL in any of git's notations, without credentials, port, or `.git`
+fn remote_slug(url: &str) -> Option<String> {
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-like `git@github.com:owner/name.git`
+        None => url.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?.split(':').next()?.to_lowercase();
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || path.is_empty() || host.contains('/') {
+        return None;
+    }
+    Some(format!("{}/{}", host, path))
+}
+
+pub(crate) fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
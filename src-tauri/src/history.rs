@@ -0,0 +1,494 @@
+// Command history
+// Every command shell integration reports as run is recorded, with the directory it ran in, how
+// long it took, its exit status, and its session, in a SQLite database shared by all sessions.
+// Searching it backs the global Ctrl+R history; incognito sessions record nothing. Commands run
+// over SSH, reported by shell integration on the remote end, are only recorded when enabled, and
+// are tagged with their host.
+
+use crate::commands::settings;
+use crate::glob;
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::maintenance::HistoryRetention;
+use crate::pty::marks::CommandFinished;
+use crate::pty::PtyManager;
+use crate::sync::MutexExt;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// Entries returned by a search when the caller does not say
+const DEFAULT_LIMIT: usize = 100;
+
+/// Most entries a search returns
+const MAX_LIMIT: usize = 1000;
+
+/// Programs and directories listed in stats
+const TOP_COUNT: usize = 10;
+
+/// How long a query waits for another connection (maintenance) to finish writing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS commands (
+    id INTEGER PRIMARY KEY,
+    command TEXT NOT NULL,
+    cwd TEXT,
+    session_id TEXT NOT NULL,
+    exit_code INTEGER,
+    duration_ms INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    host TEXT
+);
+CREATE INDEX IF NOT EXISTS commands_started_at ON commands (started_at);
+CREATE INDEX IF NOT EXISTS commands_command ON commands (command);
+";
+
+/// Columns added since the first schema, for databases created before them
+const MIGRATIONS: &[(&str, &str)] = &[("host", "ALTER TABLE commands ADD COLUMN host TEXT")];
+
+/// The `history` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    /// Programs whose commands are not recorded, e.g. password managers
+    pub ignore: Vec<String>,
+    /// Record commands run over SSH, when the remote shell reports them
+    pub remote: bool,
+    /// Hosts whose commands are not recorded, as patterns with `*` and `?`
+    pub ignore_hosts: Vec<String>,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ignore: Vec::new(),
+            remote: false,
+            ignore_hosts: Vec::new(),
+        }
+    }
+}
+
+impl HistorySettings {
+    fn records(&self, command: &str, host: Option<&str>) -> bool {
+        let host_recorded = host.map_or(true, |host| {
+            self.remote && !self.ignore_hosts.iter().any(|pattern| glob::matches(pattern, host))
+        });
+        self.enabled && host_recorded && !self.ignore.iter().any(|program| program == self::program(command))
+    }
+}
+
+/// A recorded command
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub command: String,
+    /// Directory the command started in, on `host` for remote commands
+    pub cwd: Option<String>,
+    pub session_id: String,
+    /// Host a command run over SSH ran on; None for this machine
+    pub host: Option<String>,
+    /// None when the shell did not report it
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Unix time in milliseconds
+    pub started_at: u64,
+    /// Times the command line was run, when each is listed once; otherwise 1
+    pub runs: u64,
+}
+
+/// What `history_search` looks for; every filter is optional
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    /// Text the command line contains, ignoring ASCII case
+    pub text: Option<String>,
+    /// Only commands started in this directory
+    pub cwd: Option<String>,
+    pub session_id: Option<String>,
+    /// Only commands run on this host
+    pub host: Option<String>,
+    /// Only commands run on this machine
+    pub local_only: bool,
+    /// Only commands that exited with status 0
+    pub successful_only: bool,
+    /// Every run of a command line rather than its latest one
+    pub all_runs: bool,
+    pub limit: Option<usize>,
+}
+
+/// How often a program or directory was used
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryCount {
+    pub value: String,
+    pub count: u64,
+}
+
+/// Totals over the recorded commands
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryStats {
+    pub commands: u64,
+    pub unique_commands: u64,
+    /// Commands that reported a non-zero exit code
+    pub failed_commands: u64,
+    pub total_duration_ms: u64,
+    /// Unix time in milliseconds of the oldest command counted
+    pub first_started_at: Option<u64>,
+    /// Most used programs, by the first word of the command line
+    pub top_programs: Vec<HistoryCount>,
+    pub top_directories: Vec<HistoryCount>,
+}
+
+/// Where a running command started, recorded when it finishes
+struct Running {
+    id: u64,
+    cwd: Option<String>,
+    host: Option<String>,
+    started_at: u64,
+}
+
+/// The history database, managed by Tauri
+pub struct History {
+    /// None when the database could not be opened; history is then not recorded
+    conn: Mutex<Option<Connection>>,
+    running: Mutex<HashMap<String, Running>>,
+}
+
+impl History {
+    /// Open the database, creating it on first use
+    pub fn load() -> Self {
+        let conn = match open() {
+            Ok(conn) => Some(conn),
+            Err(e) => {
+                log::warn!("Command history is disabled: {}", e);
+                None
+            }
+        };
+        Self {
+            conn: Mutex::new(conn),
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let conn = self.conn.locked();
+        let conn = conn.as_ref().ok_or("History database is unavailable")?;
+        f(conn).map_err(|e| format!("Failed to query history: {}", e))
+    }
+
+    fn insert(&self, entry: &HistoryEntry) -> Result<(), String> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO commands (command, cwd, session_id, exit_code, duration_ms, started_at, host)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    entry.command,
+                    entry.cwd,
+                    entry.session_id,
+                    entry.exit_code,
+                    entry.duration_ms as i64,
+                    entry.started_at as i64,
+                    entry.host
+                ],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Recorded commands matching the query, most recent first
+    pub fn search(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, String> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(text) = query.text.as_deref().filter(|text| !text.is_empty()) {
+            conditions.push("command LIKE ? ESCAPE '\\'");
+            values.push(Value::Text(format!("%{}%", escape_like(text))));
+        }
+        if let Some(cwd) = &query.cwd {
+            conditions.push("cwd = ?");
+            values.push(Value::Text(cwd.clone()));
+        }
+        if let Some(session_id) = &query.session_id {
+            conditions.push("session_id = ?");
+            values.push(Value::Text(session_id.clone()));
+        }
+        if let Some(host) = &query.host {
+            conditions.push("host = ?");
+            values.push(Value::Text(host.clone()));
+        }
+        if query.local_only {
+            conditions.push("host IS NULL");
+        }
+        if query.successful_only {
+            conditions.push("exit_code = 0");
+        }
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sql = if query.all_runs {
+            format!(
+                "SELECT id, command, cwd, session_id, exit_code, duration_ms, started_at, 1, host FROM commands {}
+                 ORDER BY started_at DESC, id DESC LIMIT ?",
+                filter
+            )
+        } else {
+            // With MAX, SQLite takes the other columns from the row holding the maximum, so each
+            // command line comes with its latest run
+            format!(
+                "SELECT id, command, cwd, session_id, exit_code, duration_ms, MAX(started_at), COUNT(*), host
+                 FROM commands {} GROUP BY command ORDER BY 7 DESC LIMIT ?",
+                filter
+            )
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        values.push(Value::Integer(limit as i64));
+
+        self.with_conn(|conn| {
+            let mut statement = conn.prepare(&sql)?;
+            let rows = statement.query_map(params_from_iter(values), entry_from_row)?;
+            rows.collect()
+        })
+    }
+
+    /// Totals over commands started since `since` (Unix time in milliseconds), or all of them
+    pub fn stats(&self, since: Option<u64>) -> Result<HistoryStats, String> {
+        let since = since.unwrap_or(0) as i64;
+        self.with_conn(|conn| {
+            let mut stats = conn.query_row(
+                "SELECT COUNT(*), COUNT(DISTINCT command), COUNT(CASE WHEN exit_code <> 0 THEN 1 END),
+                        COALESCE(SUM(duration_ms), 0), MIN(started_at)
+                 FROM commands WHERE started_at >= ?1",
+                [since],
+                |row| {
+                    Ok(HistoryStats {
+                        commands: row.get::<_, i64>(0)? as u64,
+                        unique_commands: row.get::<_, i64>(1)? as u64,
+                        failed_commands: row.get::<_, i64>(2)? as u64,
+                        total_duration_ms: row.get::<_, i64>(3)? as u64,
+                        first_started_at: row.get::<_, Option<i64>>(4)?.map(|at| at as u64),
+                        ..HistoryStats::default()
+                    })
+                },
+            )?;
+            let counts = |sql: &str| -> rusqlite::Result<Vec<HistoryCount>> {
+                let mut statement = conn.prepare(sql)?;
+                let rows = statement.query_map(params![since, TOP_COUNT as i64], |row| {
+                    Ok(HistoryCount {
+                        value: row.get(0)?,
+                        count: row.get::<_, i64>(1)? as u64,
+                    })
+                })?;
+                rows.collect()
+            };
+            stats.top_programs = counts(
+                "SELECT substr(command, 1, instr(command || ' ', ' ') - 1) AS program, COUNT(*) AS n
+                 FROM commands WHERE started_at >= ?1 GROUP BY program ORDER BY n DESC, program LIMIT ?2",
+            )?;
+            stats.top_directories = counts(
+                "SELECT cwd, COUNT(*) AS n FROM commands WHERE started_at >= ?1 AND cwd IS NOT NULL
+                 GROUP BY cwd ORDER BY n DESC, cwd LIMIT ?2",
+            )?;
+            Ok(stats)
+        })
+    }
+
+    fn on_started(&self, manager: &PtyManager, session_id: &str, id: u64, started_at: u64) {
+        // Read now: the command may well change directory
+        let (cwd, host) = match remote_place(manager, session_id) {
+            Some((cwd, host)) => (cwd, Some(host)),
+            None => (manager.cwd(session_id).ok(), None),
+        };
+        let running = Running {
+            id,
+            cwd,
+            host,
+            started_at,
+        };
+        self.running.locked().insert(session_id.to_string(), running);
+    }
+
+    /// The entry to record for a finished command, if it is recorded at all
+    fn on_finished(&self, manager: &PtyManager, session_id: &str, finished: &CommandFinished) -> Option<HistoryEntry> {
+        let running = self
+            .running
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .filter(|running| running.id == finished.id);
+        let command = finished.command.as_deref()?;
+        let (cwd, host, started_at) = match running {
+            Some(running) => (running.cwd, running.host, running.started_at),
+            None => (
+                None,
+                remote_place(manager, session_id).map(|(_, host)| host),
+                unix_millis().saturating_sub(finished.duration_ms),
+            ),
+        };
+        if !settings::read_section::<HistorySettings>("history").records(command, host.as_deref()) {
+            return None;
+        }
+        // Incognito sessions leave no trace
+        let command = manager.storable(session_id, command)?;
+        Some(HistoryEntry {
+            id: 0,
+            command,
+            cwd,
+            session_id: session_id.to_string(),
+            host,
+            exit_code: finished.exit_code,
+            duration_ms: finished.duration_ms,
+            started_at,
+            runs: 1,
+        })
+    }
+}
+
+/// Record finished commands of every session
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    events.subscribe(move |event: &SessionEvent| {
+        let history = app_handle.state::<History>();
+        match &event.kind {
+            EventKind::CommandStarted(started) => {
+                let manager = app_handle.state::<PtyManager>();
+                history.on_started(&manager, &event.session_id, started.id, started.started_at);
+            }
+            EventKind::CommandFinished(finished) => {
+                let manager = app_handle.state::<PtyManager>();
+                let Some(entry) = history.on_finished(&manager, &event.session_id, finished) else {
+                    return;
+                };
+                // Keep database writes off the reader
+                let handle = app_handle.clone();
+                tauri::async_runtime::spawn_blocking(move || {
+                    if let Err(e) = handle.state::<History>().insert(&entry) {
+                        log::warn!("Failed to record command: {}", e);
+                    }
+                });
+            }
+            EventKind::Exit { .. } => {
+                history.running.locked().remove(&event.session_id);
+            }
+            _ => {}
+        }
+    });
+}
+
+/// Directory and host of a session's shell when it runs on another machine, None when local
+///
+/// The host comes from the remote shell's OSC 7 reports, which name the machine the command
+/// really runs on even through jump hosts, else from the ssh command in the foreground.
+fn remote_place(manager: &PtyManager, session_id: &str) -> Option<(Option<String>, String)> {
+    let reported = manager
+        .info(session_id)
+        .ok()
+        .and_then(|info| info.reported_cwd)
+        .filter(|cwd| cwd.remote);
+    if let Some(host) = reported.as_ref().and_then(|cwd| cwd.host.clone()) {
+        return Some((reported.map(|cwd| cwd.path), host));
+    }
+    let host = manager.location(session_id).ok()?.host?;
+    Some((None, host))
+}
+
+/// Delete commands outside the retention policy, returning how many were deleted
+pub fn prune(policy: &HistoryRetention) -> Result<u64, String> {
+    if policy.max_age_days.is_none() && policy.max_entries.is_none() {
+        return Ok(0);
+    }
+    let conn = open()?;
+    let mut deleted = 0;
+    if let Some(days) = policy.max_age_days {
+        let cutoff = unix_millis().saturating_sub(u64::from(days) * 24 * 60 * 60 * 1000);
+        deleted += conn
+            .execute("DELETE FROM commands WHERE started_at < ?1", [cutoff as i64])
+            .map_err(|e| format!("Failed to prune history: {}", e))?;
+    }
+    if let Some(max_entries) = policy.max_entries {
+        deleted += conn
+            .execute(
+                "DELETE FROM commands WHERE id NOT IN
+                 (SELECT id FROM commands ORDER BY started_at DESC, id DESC LIMIT ?1)",
+                [max_entries as i64],
+            )
+            .map_err(|e| format!("Failed to prune history: {}", e))?;
+    }
+    Ok(deleted as u64)
+}
+
+fn open() -> Result<Connection, String> {
+    let path = history_path().ok_or("No data directory")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    // Command lines can hold anything typed, so only the user may read them
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .mode(0o600)
+        .open(&path)
+        .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+
+    let conn = Connection::open(&path).map_err(|e| format!("Failed to open history database: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .and_then(|_| conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(())))
+        .and_then(|_| conn.execute_batch(SCHEMA))
+        .and_then(|_| migrate(&conn))
+        .map_err(|e| format!("Failed to set up history database: {}", e))?;
+    Ok(conn)
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    for (column, sql) in MIGRATIONS {
+        // Selecting a column the table lacks fails to prepare
+        if conn.prepare(&format!("SELECT {} FROM commands LIMIT 0", column)).is_err() {
+            conn.execute_batch(sql)?;
+        }
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS commands_host ON commands (host)")
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        command: row.get(1)?,
+        cwd: row.get(2)?,
+        session_id: row.get(3)?,
+        host: row.get(8)?,
+        exit_code: row.get(4)?,
+        duration_ms: row.get::<_, i64>(5)? as u64,
+        started_at: row.get::<_, i64>(6)? as u64,
+        runs: row.get::<_, i64>(7)? as u64,
+    })
+}
+
+/// `text` matched literally by LIKE with `\` as the escape character
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// First word of a command line
+fn program(command: &str) -> &str {
+    command.split_whitespace().next().unwrap_or("")
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xterminal").join("history.db"))
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
@@ -1,22 +1,146 @@
 // Xterminal - Windows Terminal-inspired terminal emulator for Linux
 
 mod commands;
+mod context;
+mod control;
+mod diff;
+mod events;
+mod exec;
+mod fonts;
+mod glob;
+mod health;
+mod hints;
+mod history;
+mod logs;
+mod maintenance;
+mod notifications;
+mod opener;
+mod profiles;
 mod pty;
+mod recents;
+mod serial;
+mod session_state;
+mod settings_schema;
+mod settings_sync;
+mod shell;
+mod ssh;
+mod status;
+mod sync;
+mod themes;
+mod window_state;
 
-use commands::{spawn_pty, pty_write, pty_resize, pty_close, get_hostname, load_settings, save_settings, load_window_state, save_window_state};
+use commands::scope::CommandScope;
+use commands::{exec_command, spawn_pty, pty_write, pty_set_latency_tracking, pty_get_latency, pty_write_many, pty_write_broadcast, set_broadcast_group, list_broadcast_groups, pty_paste, pty_resize, pty_close, pty_kill, pty_signal_foreground, pty_start_logging, pty_stop_logging, start_recording, stop_recording, get_session_info, pty_get_foreground_process, pty_get_cwd, list_jobs, list_sessions, rename_session, set_session_labels, set_session_monitor, get_session_appearance, set_session_appearance, check_terminfo, get_memory_usage, pty_get_bandwidth, pty_get_scrollback, pty_wake, pty_export_script, diff_command_outputs, pty_open_input_pipe, pty_close_input_pipe, view_file, view_man_page, follow_file, follow_set_paused, follow_seek, get_hostname, get_context, get_health_report, load_settings, save_settings, validate_settings, get_settings_conflict, resolve_settings_conflict, load_window_state, save_window_state, set_window_placement, profile_shell_startup, detect_shell_frameworks, bootstrap_prompt, check_font_coverage, analyze_line, open_url, open_path, notify_session, set_focused_session, list_profiles, save_profile, delete_profile, set_profile_rules, take_launch_actions, list_actions, run_action, register_action, unregister_action, list_bluetooth_serial_ports, list_serial_ports, spawn_serial, spawn_ssh, list_ssh_hosts, list_status_segments, get_status, register_status_segment, unregister_status_segment, set_status_segment, preview_theme, get_new_tab_suggestions, history_search, history_stats, restore_sessions, save_session_layout, verify_log, read_log, run_maintenance_now};
+use control::actions::ActionRegistry;
+use events::EventBus;
+use health::Health;
+use profiles::ProfileStore;
 use pty::PtyManager;
-use tauri::Manager;
+use pty::progress::TaskbarProgress;
+use history::History;
+use notifications::FocusedSession;
+use recents::Recents;
+use session_state::SessionRestore;
+use settings_sync::SettingsSync;
+use status::StatusSegments;
+use std::sync::Arc;
+use tauri::webview::PageLoadEvent;
+use tauri::{Manager, RunEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must be registered first so later launches are forwarded before anything else starts
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            control::launch::handle_second_instance(app, argv, cwd);
+        }))
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(CommandScope::default())
+        .manage(Health::default())
+        // A reload or navigation abandons whatever the previous page was waiting for
+        .on_page_load(|webview, payload| {
+            match payload.event() {
+                PageLoadEvent::Started => commands::scope::on_page_load(webview),
+                PageLoadEvent::Finished => webview.app_handle().state::<Health>().on_page_loaded(),
+            }
+        })
         .setup(|app| {
+            // Pin the window where the user asked before anything is shown in it
+            if let Ok(path) = commands::settings::get_window_state_path() {
+                window_state::apply_placement(app.handle(), &window_state::placement(&path));
+            }
+
+            // Check the setup in the background so problems are logged and ready for the UI
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                handle.state::<Health>().check().await;
+            });
+
+            // Route session events to the frontend
+            let events = Arc::new(EventBus::default());
+            events.subscribe(events::ipc::emitter(app.handle().clone()));
+            app.manage(events.clone());
+
+            // Remember where sessions go for new tab suggestions
+            app.manage(Recents::load());
+            recents::start(app.handle().clone(), &events);
+
+            // Serve the OSC 52 clipboard requests session profiles allow
+            pty::clipboard::start(app.handle().clone(), &events);
+
+            // Record commands shell integration reports for the global history
+            app.manage(History::load());
+            history::start(app.handle().clone(), &events);
+
+            // Play the bell sound and set urgency hints as profiles ask
+            pty::bell::start(app.handle().clone(), &events);
+
+            // Show the progress programs report on the taskbar entry
+            app.manage(TaskbarProgress::default());
+            pty::progress::start(app.handle().clone(), &events);
+
+            // Tell the user about long commands that finish out of sight
+            app.manage(FocusedSession::default());
+            notifications::start(app.handle().clone(), &events);
+
             // Initialize PTY manager
-            let pty_manager = PtyManager::new(app.handle().clone());
+            let pty_manager = PtyManager::new(events);
             app.manage(pty_manager);
 
+            // Status bar segments, refreshed for every session
+            app.manage(StatusSegments::default());
+            status::start(app.handle().clone());
+
+            // Shell profiles, resolved by the PTY manager at spawn time
+            app.manage(ProfileStore::default());
+
+            // Palette actions that D-Bus, the control socket, and plugins can run too
+            app.manage(ActionRegistry::default());
+
+            // Remember what this launch asked for until the frontend picks it up
+            control::launch::init(app.handle());
+
+            // Merge outside edits to the settings file instead of overwriting them
+            app.manage(SettingsSync::default());
+            if let Ok(path) = commands::settings::get_settings_path() {
+                settings_sync::watch(app.handle().clone(), path);
+            }
+
+            // Snapshot open sessions for restore on the next start
+            app.manage(SessionRestore::default());
+            session_state::start(app.handle().clone());
+
+            // Apply retention policies in the background
+            maintenance::start(app.handle().clone());
+
+            // Expose the D-Bus control interface
+            control::dbus::start(app.handle().clone());
+
+            // Expose the control socket for scripts, if enabled
+            control::socket::start(app.handle().clone());
+
             // Setup logging in debug mode
             if cfg!(debug_assertions) {
                 app.handle().plugin(
@@ -33,14 +157,106 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             spawn_pty,
             pty_write,
+            pty_set_latency_tracking,
+            pty_get_latency,
+            pty_write_many,
+            pty_write_broadcast,
+            set_broadcast_group,
+            list_broadcast_groups,
+            pty_paste,
             pty_resize,
             pty_close,
+            pty_kill,
+            pty_signal_foreground,
+            pty_start_logging,
+            pty_stop_logging,
+            start_recording,
+            stop_recording,
+            get_session_info,
+            pty_get_foreground_process,
+            pty_get_cwd,
+            list_jobs,
+            list_sessions,
+            rename_session,
+            set_session_labels,
+            set_session_monitor,
+            get_session_appearance,
+            set_session_appearance,
+            check_terminfo,
+            get_memory_usage,
+            pty_get_bandwidth,
+            pty_get_scrollback,
+            pty_wake,
+            pty_export_script,
+            diff_command_outputs,
+            pty_open_input_pipe,
+            pty_close_input_pipe,
+            view_file,
+            view_man_page,
+            follow_file,
+            follow_set_paused,
+            follow_seek,
             get_hostname,
+            get_context,
+            get_health_report,
             load_settings,
             save_settings,
+            validate_settings,
+            get_settings_conflict,
+            resolve_settings_conflict,
             load_window_state,
             save_window_state,
+            set_window_placement,
+            exec_command,
+            profile_shell_startup,
+            detect_shell_frameworks,
+            bootstrap_prompt,
+            check_font_coverage,
+            analyze_line,
+            open_url,
+            open_path,
+            notify_session,
+            set_focused_session,
+            list_profiles,
+            save_profile,
+            delete_profile,
+            set_profile_rules,
+            take_launch_actions,
+            list_actions,
+            run_action,
+            register_action,
+            unregister_action,
+            list_bluetooth_serial_ports,
+            list_serial_ports,
+            spawn_serial,
+            spawn_ssh,
+            list_ssh_hosts,
+            list_status_segments,
+            get_status,
+            register_status_segment,
+            unregister_status_segment,
+            set_status_segment,
+            preview_theme,
+            get_new_tab_suggestions,
+            history_search,
+            history_stats,
+            restore_sessions,
+            save_session_layout,
+            verify_log,
+            read_log,
+            run_maintenance_now,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Sessions are still alive here, after the last window closed
+            if let RunEvent::ExitRequested { .. } = event {
+                if let Err(e) = session_state::save(app_handle) {
+                    log::warn!("Failed to save session state on exit: {}", e);
+                }
+                if let Err(e) = app_handle.state::<Recents>().flush() {
+                    log::warn!("Failed to save recents on exit: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,245 @@
+// Tamper-evident log chains
+// Each chunk appended to a log is hashed together with the previous hash into a `<log>.chain` sidecar,
+// so removing, reordering, or editing any part of the log breaks every hash after it.
+//
+// Sidecar format, one line per chunk after the header:
+//   xterminal-chain 1
+//   <offset> <length> <sha256(previous hash || chunk) as hex>
+
+use crate::exec::{self, ExecOptions};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "xterminal-chain 1";
+
+/// Path of the chain sidecar for a log
+pub fn chain_path(log_path: &Path) -> PathBuf {
+    let mut path = log_path.as_os_str().to_owned();
+    path.push(".chain");
+    PathBuf::from(path)
+}
+
+/// Path of the detached signature of a chain
+pub fn signature_path(log_path: &Path) -> PathBuf {
+    let mut path = chain_path(log_path).into_os_string();
+    path.push(".sig");
+    PathBuf::from(path)
+}
+
+/// Appends chain entries for the bytes written to a log
+pub struct ChainWriter {
+    log_path: PathBuf,
+    sidecar: BufWriter<File>,
+    previous: [u8; 32],
+    offset: u64,
+}
+
+impl ChainWriter {
+    /// Start a new chain for a log that is about to be written from offset 0
+    pub fn create(log_path: &Path) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(chain_path(log_path))
+            .map_err(|e| format!("Failed to create log chain: {}", e))?;
+
+        let mut sidecar = BufWriter::new(file);
+        writeln!(sidecar, "{}", HEADER).map_err(|e| format!("Failed to write log chain: {}", e))?;
+
+        Ok(Self {
+            log_path: log_path.to_path_buf(),
+            sidecar,
+            previous: [0; 32],
+            offset: 0,
+        })
+    }
+
+    /// Record a chunk exactly as it was written to the log
+    pub fn append(&mut self, chunk: &[u8]) -> Result<(), String> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+
+        self.previous = link(&self.previous, chunk);
+        writeln!(self.sidecar, "{} {} {}", self.offset, chunk.len(), hex::encode(self.previous))
+            .map_err(|e| format!("Failed to write log chain: {}", e))?;
+        self.offset += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Flush the chain, optionally signing it with the user's default GPG key
+    pub async fn finish(mut self, sign: bool) -> Result<(), String> {
+        self.sidecar
+            .flush()
+            .map_err(|e| format!("Failed to write log chain: {}", e))?;
+        drop(self.sidecar);
+
+        if sign {
+            sign_chain(&self.log_path).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Next hash in the chain
+fn link(previous: &[u8; 32], chunk: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    hasher.update(chunk);
+    hasher.finalize().into()
+}
+
+async fn sign_chain(log_path: &Path) -> Result<(), String> {
+    let chain = chain_path(log_path).display().to_string();
+    let signature = signature_path(log_path).display().to_string();
+
+    let output = exec::run(&ExecOptions {
+        program: "gpg".to_string(),
+        args: vec![
+            "--batch".into(),
+            "--yes".into(),
+            "--detach-sign".into(),
+            "--output".into(),
+            signature,
+            chain,
+        ],
+        ..Default::default()
+    })
+    .await?;
+
+    if !output.success() {
+        return Err(format!("Failed to sign log chain: {}", output.stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Outcome of verifying a log against its chain
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogVerification {
+    pub path: String,
+    /// Whether every chain entry matched and nothing follows the last entry
+    pub intact: bool,
+    pub entries: usize,
+    pub verified_bytes: u64,
+    /// Bytes at the end of the log not covered by the chain
+    pub unchained_bytes: u64,
+    /// Index of the first entry that failed to match
+    pub first_mismatch: Option<usize>,
+    /// GPG verdict when a signature exists
+    pub signature: Option<SignatureCheck>,
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureCheck {
+    pub valid: bool,
+    /// gpg's description of the signer
+    pub details: String,
+}
+
+/// Verify a log against its `.chain` sidecar and signature
+pub async fn verify(log_path: &Path) -> Result<LogVerification, String> {
+    let log = fs::read(log_path).map_err(|e| format!("Failed to read log: {}", e))?;
+    let chain = fs::read_to_string(chain_path(log_path))
+        .map_err(|e| format!("Failed to read log chain: {}", e))?;
+
+    let mut report = LogVerification {
+        path: log_path.display().to_string(),
+        ..Default::default()
+    };
+
+    let mut lines = chain.lines();
+    if lines.next() != Some(HEADER) {
+        return Err("Unrecognized log chain format".to_string());
+    }
+
+    let mut previous = [0u8; 32];
+    let mut expected_offset = 0u64;
+
+    for (index, line) in lines.enumerate() {
+        report.entries += 1;
+        let Some((offset, length, hash)) = parse_entry(line) else {
+            report.problems.push(format!("Entry {} is malformed", index));
+            report.first_mismatch.get_or_insert(index);
+            break;
+        };
+
+        if offset != expected_offset {
+            report.problems.push(format!("Entry {} starts at {}, expected {}", index, offset, expected_offset));
+            report.first_mismatch.get_or_insert(index);
+            break;
+        }
+
+        let end = offset + length;
+        let Some(chunk) = log.get(offset as usize..end as usize) else {
+            report.problems.push(format!("Log is truncated before entry {}", index));
+            report.first_mismatch.get_or_insert(index);
+            break;
+        };
+
+        previous = link(&previous, chunk);
+        if hex::encode(previous) != hash {
+            report.problems.push(format!("Entry {} (bytes {}..{}) was modified", index, offset, end));
+            report.first_mismatch.get_or_insert(index);
+            break;
+        }
+
+        expected_offset = end;
+        report.verified_bytes = end;
+    }
+
+    report.unchained_bytes = (log.len() as u64).saturating_sub(report.verified_bytes);
+    if report.first_mismatch.is_none() && report.unchained_bytes > 0 {
+        report.problems.push(format!("{} bytes were appended after the last entry", report.unchained_bytes));
+    }
+
+    if signature_path(log_path).exists() {
+        let signature = verify_signature(log_path).await;
+        if !signature.valid {
+            report.problems.push("Chain signature does not verify".to_string());
+        }
+        report.signature = Some(signature);
+    }
+
+    report.intact = report.problems.is_empty();
+    Ok(report)
+}
+
+fn parse_entry(line: &str) -> Option<(u64, u64, &str)> {
+    let mut fields = line.split(' ');
+    let offset = fields.next()?.parse().ok()?;
+    let length = fields.next()?.parse().ok()?;
+    let hash = fields.next()?;
+    Some((offset, length, hash))
+}
+
+async fn verify_signature(log_path: &Path) -> SignatureCheck {
+    let output = exec::run(&ExecOptions {
+        program: "gpg".to_string(),
+        args: vec![
+            "--batch".into(),
+            "--verify".into(),
+            signature_path(log_path).display().to_string(),
+            chain_path(log_path).display().to_string(),
+        ],
+        ..Default::default()
+    })
+    .await;
+
+    match output {
+        Ok(output) => SignatureCheck {
+            valid: output.success(),
+            details: output.stderr.trim().to_string(),
+        },
+        Err(e) => SignatureCheck {
+            valid: false,
+            details: e,
+        },
+    }
+}
@@ -0,0 +1,142 @@
+// Log encryption at rest
+// Logs and recordings can be written as AES-256-GCM records with a key kept in the system keyring
+// (Secret Service), so copying the files off the machine does not expose their contents.
+//
+// File format:
+//   XTENC1\n
+//   repeated: <u32 big-endian record length> <12-byte nonce> <ciphertext + 16-byte tag>
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::io::Write;
+
+pub const MAGIC: &[u8] = b"XTENC1\n";
+const NONCE_LEN: usize = 12;
+
+/// Keyring entry holding the hex-encoded key
+const KEYRING_SERVICE: &str = "xterminal";
+const KEYRING_USER: &str = "log-encryption-key";
+
+/// Key used to encrypt logs and recordings
+#[derive(Clone)]
+pub struct LogKey(Key<Aes256Gcm>);
+
+impl LogKey {
+    /// Load the key from the keyring
+    ///
+    /// This talks to the Secret Service synchronously, so call it from a blocking task.
+    pub fn load() -> Result<Self, String> {
+        let secret = keyring_entry()?
+            .get_password()
+            .map_err(|e| format!("Failed to read log encryption key: {}", e))?;
+        let bytes = hex::decode(secret.trim()).map_err(|_| "Log encryption key is corrupt".to_string())?;
+        if bytes.len() != 32 {
+            return Err("Log encryption key is corrupt".to_string());
+        }
+        Ok(Self(*Key::<Aes256Gcm>::from_slice(&bytes)))
+    }
+
+    /// Load the key, generating and storing one on first use
+    pub fn load_or_create() -> Result<Self, String> {
+        match keyring_entry()?.get_password() {
+            Ok(_) => Self::load(),
+            Err(keyring::Error::NoEntry) => {
+                let key = Aes256Gcm::generate_key(OsRng);
+                keyring_entry()?
+                    .set_password(&hex::encode(key))
+                    .map_err(|e| format!("Failed to store log encryption key: {}", e))?;
+                log::info!("Generated log encryption key in the system keyring");
+                Ok(Self(key))
+            }
+            Err(e) => Err(format!("Failed to read log encryption key: {}", e)),
+        }
+    }
+}
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| format!("Failed to open keyring: {}", e))
+}
+
+/// Writes encrypted records to an underlying file
+pub struct EncryptedWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    /// Write the file header and return a writer for records
+    pub fn new(mut inner: W, key: &LogKey) -> Result<Self, String> {
+        inner
+            .write_all(MAGIC)
+            .map_err(|e| format!("Failed to write encrypted log: {}", e))?;
+        Ok(Self {
+            inner,
+            cipher: Aes256Gcm::new(&key.0),
+        })
+    }
+
+    /// Encrypt one chunk as a record, returning the bytes written to disk
+    ///
+    /// The returned bytes are what a log chain should hash, so integrity can be
+    /// verified without the key.
+    pub fn write_record(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Failed to encrypt log record".to_string())?;
+
+        let length = (NONCE_LEN + ciphertext.len()) as u32;
+        let mut record = Vec::with_capacity(4 + length as usize);
+        record.extend_from_slice(&length.to_be_bytes());
+        record.extend_from_slice(&nonce);
+        record.extend_from_slice(&ciphertext);
+
+        self.inner
+            .write_all(&record)
+            .map_err(|e| format!("Failed to write encrypted log: {}", e))?;
+        Ok(record)
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.inner
+            .flush()
+            .map_err(|e| format!("Failed to write encrypted log: {}", e))
+    }
+}
+
+/// Whether file contents are in the encrypted format
+pub fn is_encrypted(contents: &[u8]) -> bool {
+    contents.starts_with(MAGIC)
+}
+
+/// Decrypt the contents of an encrypted file
+///
+/// A partially written final record (e.g. after a crash) is ignored.
+pub fn decrypt(contents: &[u8], key: &LogKey) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new(&key.0);
+    let mut rest = contents
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| "Not an encrypted log".to_string())?;
+    let mut plaintext = Vec::new();
+
+    while rest.len() >= 4 {
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let Some(record) = rest.get(4..4 + length) else {
+            log::warn!("Encrypted log ends with a partial record");
+            break;
+        };
+        if record.len() < NONCE_LEN {
+            return Err("Encrypted log record is malformed".to_string());
+        }
+
+        let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+        let chunk = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt log: wrong key or modified file".to_string())?;
+        plaintext.extend_from_slice(&chunk);
+        rest = &rest[4 + length..];
+    }
+
+    Ok(plaintext)
+}
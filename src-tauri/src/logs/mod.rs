@@ -0,0 +1,37 @@
+// Logs module - on-disk session logs and recordings, and their integrity/protection
+
+pub mod chain;
+pub mod crypto;
+pub mod session;
+pub mod writer;
+
+pub use chain::LogVerification;
+
+use std::path::{Path, PathBuf};
+
+/// Directory holding session logs
+pub fn logs_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xterminal").join("logs"))
+}
+
+/// Directory holding session recordings
+pub fn recordings_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xterminal").join("recordings"))
+}
+
+/// Read a log or recording, decrypting it with the keyring key if it is encrypted
+pub async fn read(path: &Path) -> Result<Vec<u8>, String> {
+    let contents = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+    if !crypto::is_encrypted(&contents) {
+        return Ok(contents);
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let key = crypto::LogKey::load()?;
+        crypto::decrypt(&contents, &key)
+    })
+    .await
+    .map_err(|e| format!("Failed to decrypt log: {}", e))?
+}
@@ -0,0 +1,326 @@
+// Session output logs
+// Tees a session's output to a file as it is read: raw, or as plain text with escape sequences
+// removed, optionally with a timestamp on every line. Where shell integration marks where commands
+// start and end, a command's output past the configured size is replaced by a single truncation
+// marker, so `cat /dev/urandom` cannot fill the disk; the terminal still shows all of it.
+
+use super::writer::{LogWriter, LoggingSettings};
+use crate::pty::redact::Redactor;
+use crate::pty::utf8::Utf8Decoder;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Longest line held back waiting for its end in the text formats
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// How output is written to a session log
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    /// Bytes exactly as the session produced them, escape sequences included
+    Raw,
+    /// Text with escape sequences and carriage-return overwrites resolved away
+    Plain,
+    /// Plain text with the UTC time each line started, e.g. `[2024-05-01T12:00:00.000Z] `
+    Timestamped,
+}
+
+/// A log being written, or the summary of one that was stopped
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLogInfo {
+    pub path: String,
+    pub format: LogFormat,
+    pub encrypted: bool,
+    pub chained: bool,
+    /// Size of the file so far, headers and encryption overhead included
+    pub bytes_written: u64,
+}
+
+/// Output log of one session, fed by its reader
+pub struct SessionLog {
+    info: SessionLogInfo,
+    writer: LogWriter,
+    redactor: Option<Arc<Redactor>>,
+    decoder: Utf8Decoder,
+    stripper: AnsiStripper,
+    /// Text of the current line in the text formats
+    line: String,
+    line_started: Option<SystemTime>,
+    /// A CR was seen; the line ends if LF follows and is overwritten otherwise
+    pending_cr: bool,
+    max_command_output: Option<u64>,
+    /// Output of the running command so far, while one runs
+    command_output: Option<u64>,
+    /// The running command's output went past the limit and is no longer written
+    truncated: bool,
+}
+
+/// Resolve a log path; relative paths are placed in the logs directory so retention applies
+pub fn resolve_path(path: &str) -> Result<PathBuf, String> {
+    let path = Path::new(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err("Log path must not be empty".to_string());
+    }
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let dir = super::logs_dir().ok_or_else(|| "Could not find data directory".to_string())?;
+    Ok(dir.join(path))
+}
+
+impl SessionLog {
+    /// Create the log file; an existing file is never overwritten
+    ///
+    /// Loading the encryption key may talk to the keyring, so call this from a blocking task.
+    pub fn create(
+        path: &Path,
+        format: LogFormat,
+        settings: &LoggingSettings,
+        redactor: Option<Arc<Redactor>>,
+    ) -> Result<Self, String> {
+        let writer = LogWriter::create(path, settings)?;
+        Ok(Self {
+            info: SessionLogInfo {
+                path: path.display().to_string(),
+                format,
+                encrypted: writer.encrypted(),
+                chained: writer.chained(),
+                bytes_written: writer.bytes_written(),
+            },
+            writer,
+            redactor,
+            decoder: Utf8Decoder::default(),
+            stripper: AnsiStripper::default(),
+            line: String::new(),
+            line_started: None,
+            pending_cr: false,
+            max_command_output: settings.max_command_output,
+            command_output: None,
+            truncated: false,
+        })
+    }
+
+    pub fn info(&self) -> SessionLogInfo {
+        self.info.clone()
+    }
+
+    /// A command began running; its output counts towards the limit
+    pub fn command_started(&mut self) {
+        self.command_output = Some(0);
+        self.truncated = false;
+    }
+
+    /// The running command ended; output is written in full again
+    pub fn command_finished(&mut self) {
+        self.command_output = None;
+        if std::mem::take(&mut self.truncated) {
+            // Output was cut off at an arbitrary byte, perhaps inside a character or sequence
+            self.decoder = Utf8Decoder::default();
+            self.stripper = AnsiStripper::default();
+            self.pending_cr = false;
+        }
+    }
+
+    /// Log a chunk of output as read from the terminal
+    ///
+    /// With redaction enabled, raw logs are decoded as UTF-8 so secrets can be masked.
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if let (Some(output), Some(max)) = (self.command_output.as_mut(), self.max_command_output) {
+            *output += bytes.len() as u64;
+            if *output > max {
+                return self.truncate(max);
+            }
+        }
+        match (self.info.format, &self.redactor) {
+            (LogFormat::Raw, None) => self.emit(bytes),
+            (LogFormat::Raw, Some(redactor)) => {
+                let text = self.decoder.decode(bytes);
+                let text = redactor.redact(&text).into_owned();
+                self.emit(text.as_bytes())
+            }
+            _ => {
+                let text = self.decoder.decode(bytes);
+                let text = self.stripper.strip(&text);
+                let out = self.lines(&text);
+                self.emit(out.as_bytes())
+            }
+        }
+    }
+
+    /// Write what is still held back and close the log, signing its chain if configured
+    pub async fn finish(mut self) -> Result<SessionLogInfo, String> {
+        let tail = self.decoder.finish();
+        let mut out = match self.info.format {
+            LogFormat::Raw => tail,
+            _ => {
+                let text = self.stripper.strip(&tail);
+                self.lines(&text)
+            }
+        };
+        if self.info.format != LogFormat::Raw && !self.line.is_empty() {
+            self.end_line(&mut out);
+        }
+        self.emit(out.as_bytes())?;
+
+        self.writer.finish().await?;
+        Ok(self.info)
+    }
+
+    /// Write the truncation marker, once per command
+    fn truncate(&mut self, max: u64) -> Result<(), String> {
+        if std::mem::replace(&mut self.truncated, true) {
+            return Ok(());
+        }
+        log::info!("Command output in {} passed {} bytes; truncating it in the log", self.info.path, max);
+        let marker = format!("[xterminal: output truncated after {} bytes]", max);
+        match self.info.format {
+            LogFormat::Raw => self.emit(format!("\r\n{}\r\n", marker).as_bytes()),
+            _ => {
+                let mut out = String::new();
+                if !self.line.is_empty() {
+                    self.end_line(&mut out);
+                }
+                self.line = marker;
+                self.end_line(&mut out);
+                self.emit(out.as_bytes())
+            }
+        }
+    }
+
+    /// Turn text into complete log lines, holding back the unfinished last one
+    fn lines(&mut self, text: &str) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            if std::mem::take(&mut self.pending_cr) {
+                if c == '\n' {
+                    self.end_line(&mut out);
+                    continue;
+                }
+                // A bare CR returns to the start of the line, so what follows replaces it
+                self.line.clear();
+            }
+            match c {
+                '\r' => self.pending_cr = true,
+                '\n' => self.end_line(&mut out),
+                '\u{8}' => {
+                    self.line.pop();
+                }
+                '\t' => self.line.push(c),
+                c if c.is_control() => {}
+                c => {
+                    self.line_started.get_or_insert_with(SystemTime::now);
+                    self.line.push(c);
+                    if self.line.len() >= MAX_LINE_BYTES {
+                        self.end_line(&mut out);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn end_line(&mut self, out: &mut String) {
+        if self.info.format == LogFormat::Timestamped {
+            let started = self.line_started.unwrap_or_else(SystemTime::now);
+            out.push_str(&format!("[{}] ", utc_timestamp(started)));
+        }
+        match &self.redactor {
+            Some(redactor) => out.push_str(&redactor.redact(&self.line)),
+            None => out.push_str(&self.line),
+        }
+        out.push('\n');
+        self.line.clear();
+        self.line_started = None;
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.writer.write(bytes)?;
+        self.info.bytes_written = self.writer.bytes_written();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum StripState {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, terminated by BEL or ST
+    String,
+    StringEscape,
+}
+
+/// Removes escape sequences from decoded output, including ones split across chunks
+#[derive(Default)]
+struct AnsiStripper {
+    state: StripState,
+}
+
+impl AnsiStripper {
+    fn strip(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        for c in text.chars() {
+            self.state = match (self.state, c) {
+                (StripState::Ground, '\u{1b}') => StripState::Escape,
+                (StripState::Ground, c) => {
+                    out.push(c);
+                    StripState::Ground
+                }
+                (StripState::Escape, '[') => StripState::Csi,
+                (StripState::Escape, ']' | 'P' | '_' | '^' | 'X') => StripState::String,
+                (StripState::Escape, '\u{1b}') => StripState::Escape,
+                (StripState::Escape, '\u{20}'..='\u{2f}') => StripState::EscapeIntermediate,
+                (StripState::Escape, _) => StripState::Ground,
+                (StripState::EscapeIntermediate, '\u{20}'..='\u{2f}') => StripState::EscapeIntermediate,
+                (StripState::EscapeIntermediate, '\u{1b}') => StripState::Escape,
+                (StripState::EscapeIntermediate, _) => StripState::Ground,
+                (StripState::Csi, '\u{1b}') => StripState::Escape,
+                // CAN and SUB abort the sequence
+                (StripState::Csi, '\u{18}' | '\u{1a}' | '\u{40}'..='\u{7e}') => StripState::Ground,
+                (StripState::Csi, _) => StripState::Csi,
+                (StripState::String, '\u{7}' | '\u{18}' | '\u{1a}') => StripState::Ground,
+                (StripState::String, '\u{1b}') => StripState::StringEscape,
+                (StripState::String, _) => StripState::String,
+                (StripState::StringEscape, '\\') => StripState::Ground,
+                (StripState::StringEscape, '[') => StripState::Csi,
+                (StripState::StringEscape, '\u{1b}') => StripState::StringEscape,
+                (StripState::StringEscape, _) => StripState::String,
+            };
+        }
+        out
+    }
+}
+
+/// RFC 3339 UTC time with milliseconds
+fn utc_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs() as i64;
+    let (days, day_secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since the epoch, after Howard Hinnant's days_from_civil inverse
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        day_secs / 3600,
+        day_secs % 3600 / 60,
+        day_secs % 60,
+        since_epoch.subsec_millis()
+    )
+}
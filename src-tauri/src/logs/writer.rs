@@ -0,0 +1,148 @@
+// Protected log files
+// Creates log and recording files readable only by the user and writes them through the
+// encryption and hash chain configured in the `logging` section of settings.
+
+use super::chain::ChainWriter;
+use super::crypto::{self, EncryptedWriter, LogKey};
+use serde::Deserialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+/// Output of one command logged before the rest is left out, by default
+const DEFAULT_MAX_COMMAND_OUTPUT: u64 = 16 * 1024 * 1024;
+
+/// The `logging` section of settings, applied to session logs and recordings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LoggingSettings {
+    /// Encrypt files with the key in the system keyring
+    pub encrypt: bool,
+    /// Write a `.chain` sidecar that verify_log checks the file against
+    pub chain: bool,
+    /// Sign the chain with the default GPG key when writing stops; implies `chain`
+    pub sign: bool,
+    /// Bytes of a command's output written to a session log, for sessions with shell
+    /// integration; None logs everything
+    pub max_command_output: Option<u64>,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            encrypt: false,
+            chain: false,
+            sign: false,
+            max_command_output: Some(DEFAULT_MAX_COMMAND_OUTPUT),
+        }
+    }
+}
+
+enum Sink {
+    Plain(BufWriter<File>),
+    /// Boxed since the cipher state is large
+    Encrypted(Box<EncryptedWriter<BufWriter<File>>>),
+}
+
+/// Writes a new file, encrypting and chaining it as configured
+pub struct LogWriter {
+    sink: Sink,
+    chain: Option<ChainWriter>,
+    sign: bool,
+    bytes_written: u64,
+}
+
+impl LogWriter {
+    /// Create the file; an existing file is never overwritten
+    ///
+    /// Loading the encryption key may talk to the keyring, so call this from a blocking task.
+    pub fn create(path: &Path, settings: &LoggingSettings) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        // Logs hold everything the session printed, so only the user may read them
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| format!("Failed to create {:?}: {}", path, e))?;
+
+        let mut chain = if settings.chain || settings.sign {
+            Some(ChainWriter::create(path)?)
+        } else {
+            None
+        };
+        let mut bytes_written = 0;
+        let sink = if settings.encrypt {
+            let key = LogKey::load_or_create()?;
+            let writer = EncryptedWriter::new(BufWriter::new(file), &key)?;
+            // The chain covers the whole file, header included
+            if let Some(chain) = chain.as_mut() {
+                chain.append(crypto::MAGIC)?;
+            }
+            bytes_written = crypto::MAGIC.len() as u64;
+            Sink::Encrypted(Box::new(writer))
+        } else {
+            Sink::Plain(BufWriter::new(file))
+        };
+
+        Ok(Self {
+            sink,
+            chain,
+            sign: settings.sign,
+            bytes_written,
+        })
+    }
+
+    pub fn encrypted(&self) -> bool {
+        matches!(self.sink, Sink::Encrypted(_))
+    }
+
+    pub fn chained(&self) -> bool {
+        self.chain.is_some()
+    }
+
+    /// Size of the file so far, headers and encryption overhead included
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Write bytes to the file and the chain, flushing both to the file
+    pub fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let written = match &mut self.sink {
+            Sink::Plain(writer) => {
+                writer
+                    .write_all(bytes)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| format!("Failed to write log: {}", e))?;
+                if let Some(chain) = self.chain.as_mut() {
+                    chain.append(bytes)?;
+                }
+                bytes.len()
+            }
+            Sink::Encrypted(writer) => {
+                let record = writer.write_record(bytes)?;
+                writer.flush()?;
+                if let Some(chain) = self.chain.as_mut() {
+                    chain.append(&record)?;
+                }
+                record.len()
+            }
+        };
+        self.bytes_written += written as u64;
+        Ok(())
+    }
+
+    /// Close the file, signing its chain if configured
+    pub async fn finish(mut self) -> Result<(), String> {
+        if let Some(chain) = self.chain.take() {
+            chain.finish(self.sign).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+// Background maintenance
+// Applies retention policies from settings, pruning old session logs, recordings, and command
+// history
+
+use crate::commands::settings;
+use crate::history;
+use crate::logs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+
+/// Delay before the first run so startup is not slowed down
+const INITIAL_DELAY: Duration = Duration::from_secs(60);
+
+/// How often maintenance runs while the app is open
+const INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Sidecar files that belong to a log and are removed with it
+const SIDECAR_SUFFIXES: &[&str] = &[".chain", ".chain.sig"];
+
+/// Limits for one kind of stored data; unset limits keep everything
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    pub max_age_days: Option<u32>,
+    pub max_total_mb: Option<u64>,
+}
+
+/// The `retention` section of settings
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionSettings {
+    pub logs: RetentionPolicy,
+    pub recordings: RetentionPolicy,
+    pub history: HistoryRetention,
+}
+
+/// Limits for recorded command history; unset limits keep everything
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryRetention {
+    pub max_age_days: Option<u32>,
+    pub max_entries: Option<u64>,
+}
+
+/// A file removed by maintenance
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedItem {
+    pub category: String,
+    pub path: String,
+    pub bytes: u64,
+    /// "age" or "size"
+    pub reason: String,
+}
+
+/// What a maintenance run removed
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub deleted: Vec<DeletedItem>,
+    pub freed_bytes: u64,
+    /// Commands deleted from history
+    pub history_deleted: u64,
+    pub errors: Vec<String>,
+}
+
+/// Run maintenance periodically for the lifetime of the app
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(INITIAL_DELAY).await;
+        let mut interval = tokio::time::interval(INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let report = match tokio::task::spawn_blocking(run).await {
+                Ok(report) => report,
+                Err(e) => {
+                    log::error!("Maintenance task failed: {}", e);
+                    continue;
+                }
+            };
+            if !report.deleted.is_empty() || report.history_deleted > 0 || !report.errors.is_empty() {
+                let _ = app_handle.emit("maintenance://completed", report);
+            }
+        }
+    });
+}
+
+/// Apply the configured retention policies now
+pub fn run() -> MaintenanceReport {
+    let retention: RetentionSettings = settings::read_section("retention");
+    let mut report = MaintenanceReport::default();
+
+    if let Some(dir) = logs::logs_dir() {
+        prune(&dir, "logs", &retention.logs, &mut report);
+    }
+    if let Some(dir) = logs::recordings_dir() {
+        prune(&dir, "recordings", &retention.recordings, &mut report);
+    }
+    match history::prune(&retention.history) {
+        Ok(deleted) => report.history_deleted = deleted,
+        Err(e) => report.errors.push(e),
+    }
+
+    report.freed_bytes = report.deleted.iter().map(|item| item.bytes).sum();
+    if !report.deleted.is_empty() {
+        log::info!(
+            "Maintenance removed {} files ({} bytes)",
+            report.deleted.len(),
+            report.freed_bytes
+        );
+    }
+    if report.history_deleted > 0 {
+        log::info!("Maintenance removed {} commands from history", report.history_deleted);
+    }
+    report
+}
+
+/// A stored file together with its sidecars
+struct Entry {
+    path: PathBuf,
+    modified: SystemTime,
+    bytes: u64,
+}
+
+fn prune(dir: &Path, category: &str, policy: &RetentionPolicy, report: &mut MaintenanceReport) {
+    if policy.max_age_days.is_none() && policy.max_total_mb.is_none() {
+        return;
+    }
+
+    let mut entries = match list_entries(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report.errors.push(e);
+            return;
+        }
+    };
+    // Oldest first
+    entries.sort_by_key(|entry| entry.modified);
+
+    let mut remaining: Vec<Entry> = Vec::new();
+    let max_age = policy
+        .max_age_days
+        .map(|days| Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+
+    for entry in entries {
+        let age = entry.modified.elapsed().unwrap_or_default();
+        if max_age.is_some_and(|max_age| age > max_age) {
+            delete(entry, category, "age", report);
+        } else {
+            remaining.push(entry);
+        }
+    }
+
+    if let Some(max_mb) = policy.max_total_mb {
+        let limit = max_mb * 1024 * 1024;
+        let mut total: u64 = remaining.iter().map(|entry| entry.bytes).sum();
+        for entry in remaining {
+            if total <= limit {
+                break;
+            }
+            total -= entry.bytes;
+            delete(entry, category, "size", report);
+        }
+    }
+}
+
+fn list_entries(dir: &Path) -> Result<Vec<Entry>, String> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {:?}: {}", dir, e)),
+    };
+
+    let entries = read_dir
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            !SIDECAR_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let path = entry.path();
+            let sidecar_bytes: u64 = sidecars(&path)
+                .iter()
+                .filter_map(|sidecar| fs::metadata(sidecar).ok())
+                .map(|m| m.len())
+                .sum();
+            Some(Entry {
+                modified: metadata.modified().ok()?,
+                bytes: metadata.len() + sidecar_bytes,
+                path,
+            })
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+fn sidecars(path: &Path) -> Vec<PathBuf> {
+    SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| {
+            let mut sidecar = path.as_os_str().to_owned();
+            sidecar.push(suffix);
+            PathBuf::from(sidecar)
+        })
+        .collect()
+}
+
+fn delete(entry: Entry, category: &str, reason: &str, report: &mut MaintenanceReport) {
+    if let Err(e) = fs::remove_file(&entry.path) {
+        report.errors.push(format!("Failed to delete {:?}: {}", entry.path, e));
+        return;
+    }
+    for sidecar in sidecars(&entry.path) {
+        let _ = fs::remove_file(sidecar);
+    }
+
+    report.deleted.push(DeletedItem {
+        category: category.to_string(),
+        path: entry.path.display().to_string(),
+        bytes: entry.bytes,
+        reason: reason.to_string(),
+    });
+}
@@ -0,0 +1,197 @@
+// Desktop notifications
+// Notifications raised for a session carry an action that brings that session to the front.
+// Commands that ran for a while and finished where the user was not looking are reported too,
+// from the command lifecycle of shell integration.
+
+use crate::commands::settings;
+use crate::control::{self, ControlAction, MAIN_WINDOW};
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::pty::marks::CommandFinished;
+use crate::pty::PtyManager;
+use crate::sync::MutexExt;
+use notify_rust::{Notification, Urgency};
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Action invoked by clicking the notification itself
+const DEFAULT_ACTION: &str = "default";
+
+/// Action identifier for the "Dismiss" button
+const DISMISS_ACTION: &str = "dismiss";
+
+/// The `commandNotifications` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandNotificationSettings {
+    pub enabled: bool,
+    /// Commands that ran at least this long are reported
+    pub min_seconds: u32,
+    /// Programs never reported, mostly interactive ones that run until the user quits them
+    pub ignore: Vec<String>,
+}
+
+impl Default for CommandNotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_seconds: 30,
+            ignore: [
+                "vi", "vim", "nvim", "nano", "emacs", "less", "more", "man", "top", "htop", "btop", "watch", "ssh",
+                "mosh", "tmux", "screen",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+impl CommandNotificationSettings {
+    fn reports(&self, finished: &CommandFinished) -> bool {
+        let program = finished
+            .command
+            .as_deref()
+            .and_then(|command| command.split_whitespace().next())
+            .map(|program| program.rsplit('/').next().unwrap_or(program));
+        self.enabled
+            && finished.duration_ms >= u64::from(self.min_seconds.max(1)) * 1000
+            && !program.is_some_and(|program| self.ignore.iter().any(|ignored| ignored == program))
+    }
+}
+
+/// The session whose terminal has focus in the window, as reported by the frontend
+#[derive(Default)]
+pub struct FocusedSession(Mutex<Option<String>>);
+
+impl FocusedSession {
+    pub fn set(&self, session_id: Option<String>) {
+        *self.0.locked() = session_id;
+    }
+
+    fn is(&self, session_id: &str) -> bool {
+        self.0.locked().as_deref() == Some(session_id)
+    }
+}
+
+/// A notification about something that happened in a session
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionNotification {
+    pub session_id: String,
+    pub summary: String,
+    #[serde(default)]
+    pub body: String,
+    /// Ask the notification server to keep it visible until acted on
+    #[serde(default)]
+    pub urgent: bool,
+}
+
+/// Show a notification; clicking it focuses the session, its "Dismiss" action only closes it
+pub fn notify(app: &AppHandle, notification: SessionNotification) {
+    let app = app.clone();
+
+    // The notification server is waited on synchronously until the notification closes
+    std::thread::spawn(move || {
+        let handle = Notification::new()
+            .appname("Xterminal")
+            .summary(&notification.summary)
+            .body(&notification.body)
+            .icon("utilities-terminal")
+            .urgency(if notification.urgent { Urgency::Critical } else { Urgency::Normal })
+            .action(DEFAULT_ACTION, "Focus tab")
+            .action(DISMISS_ACTION, "Dismiss")
+            .show();
+
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                log::warn!("Failed to show notification: {}", e);
+                return;
+            }
+        };
+
+        handle.wait_for_action(|action| match action {
+            DEFAULT_ACTION => {
+                let focus = ControlAction::FocusSession {
+                    session_id: notification.session_id.clone(),
+                };
+                if let Err(e) = control::dispatch(&app, focus) {
+                    log::warn!("Failed to focus session from notification: {}", e);
+                }
+            }
+            // The server closes the notification once an action is invoked; nothing else to do
+            DISMISS_ACTION => log::debug!("Notification for session {} dismissed", notification.session_id),
+            // Closed without an action, e.g. expired
+            _ => {}
+        });
+    });
+}
+
+/// Report long commands that finish while their session is out of sight: in another tab, or with
+/// the window in the background
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    events.subscribe(move |event: &SessionEvent| {
+        let EventKind::CommandFinished(finished) = &event.kind else {
+            return;
+        };
+        // No threshold is below a second, so quick commands skip the settings file
+        if finished.duration_ms < 1000 {
+            return;
+        }
+        let (app, session_id, finished) = (app_handle.clone(), event.session_id.clone(), finished.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            let settings = settings::read_section::<CommandNotificationSettings>("commandNotifications");
+            if !settings.reports(&finished) || in_view(&app, &session_id) {
+                return;
+            }
+            notify(&app, command_notification(&app, &session_id, &finished));
+        });
+    });
+}
+
+fn in_view(app: &AppHandle, session_id: &str) -> bool {
+    let focused = app
+        .get_webview_window(MAIN_WINDOW)
+        .is_some_and(|window| window.is_focused().unwrap_or(false));
+    focused && app.state::<FocusedSession>().is(session_id)
+}
+
+fn command_notification(app: &AppHandle, session_id: &str, finished: &CommandFinished) -> SessionNotification {
+    let manager = app.state::<PtyManager>();
+    // Notification centers keep a history, so incognito sessions do not name the command
+    let command = finished
+        .command
+        .as_deref()
+        .and_then(|command| manager.storable(session_id, command));
+    let session = manager.info(session_id).ok().and_then(|info| info.name.or(info.title));
+
+    let summary = match finished.exit_code {
+        Some(0) => "Command finished".to_string(),
+        Some(code) => format!("Command failed with exit status {}", code),
+        None => "Command ended".to_string(),
+    };
+    let mut body = match command {
+        Some(command) => format!("{} ran for {}", command, duration(finished.duration_ms)),
+        None => format!("Ran for {}", duration(finished.duration_ms)),
+    };
+    if let Some(session) = session {
+        body.push_str(&format!(" in {}", session));
+    }
+    SessionNotification {
+        session_id: session_id.to_string(),
+        summary,
+        body,
+        urgent: false,
+    }
+}
+
+/// A duration like `1h 5m`, `3m 20s`, or `45s`
+fn duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
@@ -0,0 +1,88 @@
+// Opener
+// Hands links and files from terminal output to the desktop's default handler (xdg-open). Output
+// is written by whatever runs in the terminal, so links are checked first: only web, mail, and
+// file URLs are opened, and paths must exist.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use url::Url;
+
+/// Schemes opened in the default application; others could launch arbitrary URL handlers
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "ftp", "mailto"];
+/// How long xdg-open is given to report a failure
+const LAUNCH_WAIT: Duration = Duration::from_secs(2);
+
+/// Open a URL with the system handler
+pub async fn open_url(link: &str) -> Result<(), String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("Invalid URL {}: {}", link, e))?;
+    if url.scheme() == "file" {
+        // Only links to this machine; a host names a file elsewhere
+        if url.host_str().is_some_and(|host| !host.is_empty() && host != "localhost") {
+            return Err(format!("Not a local file: {}", link));
+        }
+        let path = url.to_file_path().map_err(|_| format!("Invalid file URL: {}", link))?;
+        return open_path(&path, None).await;
+    }
+    if !ALLOWED_SCHEMES.contains(&url.scheme()) {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+    if url.scheme() != "mailto" && url.host_str().map_or(true, str::is_empty) {
+        return Err(format!("URL has no host: {}", link));
+    }
+    launch(url.as_str()).await
+}
+
+/// Open a file or directory with the system handler
+///
+/// Relative paths and `~` are resolved against `cwd`.
+pub async fn open_path(path: &Path, cwd: Option<&Path>) -> Result<(), String> {
+    let path = resolve(path, cwd)?;
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    launch(&path.to_string_lossy()).await
+}
+
+fn resolve(path: &Path, cwd: Option<&Path>) -> Result<PathBuf, String> {
+    if let Ok(rest) = path.strip_prefix("~") {
+        let home = dirs::home_dir().ok_or("Failed to find the home directory")?;
+        return Ok(home.join(rest));
+    }
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    cwd.map(|cwd| cwd.join(path))
+        .ok_or_else(|| format!("Relative path without a working directory: {}", path.display()))
+}
+
+async fn launch(target: &str) -> Result<(), String> {
+    let mut child = Command::new("xdg-open")
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        // The application launched inherits these, and would outlive a pipe
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run xdg-open: {}", e))?;
+
+    // Some handlers keep xdg-open running as long as they are open, so it is only waited on
+    // briefly to report quick failures, and reaped in the background after that
+    match tokio::time::timeout(LAUNCH_WAIT, child.wait()).await {
+        // xdg-open exits with 3 when no handler is found, 4 when the handler failed
+        Ok(Ok(status)) if !status.success() => Err(format!(
+            "Failed to open {}: xdg-open exited with {}",
+            target,
+            status.code().map_or("a signal".to_string(), |code| code.to_string())
+        )),
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => Err(format!("Failed to open {}: {}", target, e)),
+        Err(_) => {
+            tauri::async_runtime::spawn(async move {
+                let _ = child.wait().await;
+            });
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,499 @@
+// Shell profiles
+// Typed profiles persisted in the config directory, resolved by the backend at spawn time.
+// Until the user saves one, the shells installed on the system (from /etc/shells) are offered.
+// Rules pick a profile for spawns that do not name one, by directory, git remote, host, or command,
+// and settings can give the quake window, new windows, and file manager launches defaults of their own.
+
+use crate::commands::settings;
+use crate::glob;
+use crate::pty::bell::BellSettings;
+use crate::pty::clipboard::ClipboardPolicy;
+use crate::pty::title;
+use crate::pty::SpawnOptions;
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Shells offered as default profiles, by program name, in the order they are listed
+const KNOWN_SHELLS: &[(&str, &str, &str)] = &[
+    ("bash", "bash", "Bash"),
+    ("zsh", "zsh", "Zsh"),
+    ("fish", "fish", "Fish"),
+    ("nu", "nushell", "Nushell"),
+];
+
+/// Used when neither a profile nor `$SHELL` names a shell
+const FALLBACK_SHELL: &str = "/bin/bash";
+
+/// How the shell is started
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum StartupMode {
+    /// An interactive shell reading its rc files
+    #[default]
+    Interactive,
+    /// A login shell, which also reads the profile files (`-l`)
+    Login,
+}
+
+/// A way to start a session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// Generated when saving a profile without one
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Program to run, a path or a name looked up in PATH
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Starting directory; the home directory when unset
+    pub cwd: Option<PathBuf>,
+    pub icon: Option<String>,
+    /// Color scheme ID from settings
+    pub color_scheme: Option<String>,
+    #[serde(default)]
+    pub startup_mode: StartupMode,
+    /// What programs may do with the clipboard through OSC 52
+    #[serde(default)]
+    pub clipboard: ClipboardPolicy,
+    /// Steps run before a session of the profile is closed
+    #[serde(default)]
+    pub close_hooks: CloseHooks,
+    /// What ringing the bell does
+    #[serde(default)]
+    pub bell: BellSettings,
+}
+
+/// A step run before a session is closed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CloseHook {
+    /// Type a line into the session, e.g. `exit`, `logout`, or `tmux detach`
+    Send { text: String },
+    /// Run a command with `sh -c` beside the session, in its working directory, e.g. to save a
+    /// tmux layout
+    Run { command: String },
+}
+
+/// What happens before a session is closed, in order; the session is hung up and killed as usual
+/// if it has not exited by the timeout
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CloseHooks {
+    pub steps: Vec<CloseHook>,
+    /// Milliseconds the steps and the session's own exit may take together
+    pub timeout_ms: u64,
+}
+
+impl Default for CloseHooks {
+    fn default() -> Self {
+        Self {
+            steps: Vec::new(),
+            timeout_ms: 5000,
+        }
+    }
+}
+
+/// Picks a profile for spawns that do not name one; every condition that is set must match
+///
+/// Patterns use `*` and `?`. A spawn that names its program keeps it and takes the profile's
+/// variables and colors.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfileRule {
+    /// Profile to use
+    pub profile: String,
+    /// Starting directory, e.g. "~/work/*"; directories inside a match match too
+    pub cwd: Option<String>,
+    /// Any remote URL of the git repository the directory is in, e.g. "*github.com*acme/*"
+    pub git_remote: Option<String>,
+    /// Host an ssh or mosh session connects to
+    pub ssh_host: Option<String>,
+    /// Program name or whole command line
+    pub command: Option<String>,
+}
+
+/// Where a spawn was asked for, which can have a default profile of its own
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SpawnContext {
+    /// A new tab in a window
+    Tab,
+    /// The first tab of a new window
+    Window,
+    /// A tab of the quake (drop-down) window
+    Quake,
+    /// A tab opened on a directory with `--working-directory`, e.g. by a file manager
+    WorkingDirectory,
+}
+
+/// The `defaultProfiles` section of settings; contexts without a profile use the default profile
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContextDefaults {
+    pub tab: Option<String>,
+    pub window: Option<String>,
+    pub quake: Option<String>,
+    pub working_directory: Option<String>,
+}
+
+impl ContextDefaults {
+    fn profile(self, context: SpawnContext) -> Option<String> {
+        match context {
+            SpawnContext::Tab => self.tab,
+            SpawnContext::Window => self.window,
+            SpawnContext::Quake => self.quake,
+            SpawnContext::WorkingDirectory => self.working_directory,
+        }
+    }
+}
+
+/// Contents of profiles.json
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    /// Profile used when a spawn names neither a shell nor a profile
+    pub default_profile: Option<String>,
+    /// Checked in order before falling back to the default profile
+    pub rules: Vec<ProfileRule>,
+    /// The profiles were detected from the system rather than saved by the user
+    #[serde(skip_deserializing, skip_serializing_if = "std::ops::Not::not")]
+    pub detected: bool,
+}
+
+/// Serializes profile edits, managed by Tauri
+#[derive(Default)]
+pub struct ProfileStore {
+    lock: Mutex<()>,
+}
+
+impl ProfileStore {
+    /// Saved profiles, or the detected shells if none were saved yet
+    pub fn list(&self) -> ProfileList {
+        let _guard = self.lock.locked();
+        load()
+    }
+
+    /// Add a profile, or replace the one with the same ID; returns the saved profile
+    pub fn save(&self, mut profile: Profile, make_default: bool) -> Result<Profile, String> {
+        if profile.name.trim().is_empty() {
+            return Err("Profile name must not be empty".to_string());
+        }
+        if profile.command.trim().is_empty() {
+            return Err("Profile command must not be empty".to_string());
+        }
+        if profile.id.is_empty() {
+            profile.id = Uuid::new_v4().to_string();
+        }
+
+        let _guard = self.lock.locked();
+        let mut list = load();
+        match list.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile.clone(),
+            None => list.profiles.push(profile.clone()),
+        }
+        if make_default || list.default_profile.is_none() {
+            list.default_profile = Some(profile.id.clone());
+        }
+        // Detected profiles become saved ones along with the first edit
+        list.detected = false;
+        write(&list)?;
+        Ok(profile)
+    }
+
+    /// Remove a profile; the first remaining one becomes the default if it was the default
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        let _guard = self.lock.locked();
+        let mut list = load();
+        let before = list.profiles.len();
+        list.profiles.retain(|p| p.id != id);
+        if list.profiles.len() == before {
+            return Err(format!("Profile not found: {}", id));
+        }
+        if list.default_profile.as_deref() == Some(id) {
+            list.default_profile = list.profiles.first().map(|p| p.id.clone());
+        }
+        // Rules for the profile would otherwise fall through to another one
+        list.rules.retain(|rule| rule.profile != id);
+        list.detected = false;
+        write(&list)
+    }
+
+    /// Replace the auto-switching rules
+    pub fn set_rules(&self, rules: Vec<ProfileRule>) -> Result<(), String> {
+        let _guard = self.lock.locked();
+        let mut list = load();
+        for rule in &rules {
+            if !list.profiles.iter().any(|p| p.id == rule.profile) {
+                return Err(format!("Profile not found: {}", rule.profile));
+            }
+            if rule.cwd.is_none() && rule.git_remote.is_none() && rule.ssh_host.is_none() && rule.command.is_none() {
+                return Err(format!("Rule for profile {} has no conditions", rule.profile));
+            }
+        }
+        list.rules = rules;
+        list.detected = false;
+        write(&list)
+    }
+}
+
+/// Close hooks of a saved profile, if it has any steps
+pub fn close_hooks(profile_id: &str) -> Option<CloseHooks> {
+    load()
+        .profiles
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .map(|p| p.close_hooks)
+        .filter(|hooks| !hooks.steps.is_empty())
+}
+
+/// Fill in what a spawn leaves unset from its profile: the saved profile it names, else the
+/// first matching rule's, else, if it names no shell either, its context's default profile or
+/// the default profile
+///
+/// The profile used is recorded in `profile_id`.
+pub fn resolve(options: &mut SpawnOptions) {
+    let list = load();
+    let id = match &options.profile_id {
+        Some(id) if list.profiles.iter().any(|p| p.id == *id) => Some(id.clone()),
+        _ => matching_rule(&list.rules, options).or_else(|| {
+            if options.shell.is_some() {
+                return None;
+            }
+            options
+                .context
+                .and_then(|context| settings::read_section::<ContextDefaults>("defaultProfiles").profile(context))
+                .filter(|id| list.profiles.iter().any(|p| p.id == *id))
+                .or_else(|| list.default_profile.clone())
+        }),
+    };
+    let profile = id.and_then(|id| list.profiles.into_iter().find(|p| p.id == id));
+
+    if let Some(profile) = profile {
+        options.profile_id = Some(profile.id.clone());
+        if options.shell.is_none() {
+            options.shell = Some(profile.command.clone());
+            options.args.get_or_insert_with(|| startup_args(&profile));
+        }
+        if options.cwd.is_none() {
+            options.cwd = profile.cwd.clone();
+        }
+        options.clipboard.get_or_insert(profile.clipboard);
+        options.bell.get_or_insert(profile.bell);
+        // The caller's variables win over the profile's
+        let env = options.env.get_or_insert_with(HashMap::new);
+        for (key, value) in profile.env {
+            env.entry(key).or_insert(value);
+        }
+    }
+
+    if options.shell.is_none() {
+        options.shell = Some(std::env::var("SHELL").unwrap_or_else(|_| FALLBACK_SHELL.to_string()));
+    }
+}
+
+/// Profile of the first rule matching a spawn
+fn matching_rule(rules: &[ProfileRule], options: &SpawnOptions) -> Option<String> {
+    if rules.is_empty() {
+        return None;
+    }
+    // Without a directory the shell starts in the home directory
+    let cwd = options.cwd.clone().or_else(dirs::home_dir).unwrap_or_default();
+    let argv: Vec<String> = options
+        .shell
+        .iter()
+        .chain(options.args.iter().flatten())
+        .cloned()
+        .collect();
+    let program = argv
+        .first()
+        .map(Path::new)
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().to_string());
+    let ssh_host = match program.as_deref() {
+        Some("ssh" | "mosh") => title::remote_host(&argv),
+        _ => None,
+    };
+    // Read lazily; most rules never get to the remote condition
+    let mut remotes: Option<Vec<String>> = None;
+
+    rules
+        .iter()
+        .find(|rule| {
+            if let Some(pattern) = &rule.cwd {
+                let pattern = expand_home(pattern);
+                if !cwd.ancestors().any(|dir| glob::matches_path(&pattern, dir)) {
+                    return false;
+                }
+            }
+            if let Some(pattern) = &rule.git_remote {
+                let remotes = remotes.get_or_insert_with(|| git_remotes(&cwd));
+                if !remotes.iter().any(|url| glob::matches(pattern, url)) {
+                    return false;
+                }
+            }
+            if let Some(pattern) = &rule.ssh_host {
+                if !ssh_host.as_deref().is_some_and(|host| glob::matches(pattern, host)) {
+                    return false;
+                }
+            }
+            if let Some(pattern) = &rule.command {
+                let line = argv.join(" ");
+                if !program.as_deref().is_some_and(|p| glob::matches(pattern, p)) && !glob::matches(pattern, &line) {
+                    return false;
+                }
+            }
+            true
+        })
+        .map(|rule| rule.profile.clone())
+}
+
+/// Working tree and git directory of the repository containing `dir`
+pub fn git_dir(dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    let git = dir.ancestors().map(|d| d.join(".git")).find(|git| git.exists())?;
+    let root = git.parent()?.to_path_buf();
+    // Worktrees and submodules have a `.git` file pointing at the real git directory
+    if git.is_file() {
+        let contents = fs::read_to_string(&git).ok()?;
+        let target = contents.trim().strip_prefix("gitdir:")?.trim();
+        let git_dir = root.join(target);
+        return Some((root, git_dir));
+    }
+    Some((root, git))
+}
+
+/// Remote URLs of the git repository containing `dir`
+fn git_remotes(dir: &Path) -> Vec<String> {
+    let Some((_, git_dir)) = git_dir(dir) else {
+        return Vec::new();
+    };
+    // Linked worktrees share the main repository's config
+    let config_dir = fs::read_to_string(git_dir.join("commondir"))
+        .map(|common| git_dir.join(common.trim()))
+        .unwrap_or(git_dir);
+
+    let config = fs::read_to_string(config_dir.join("config")).unwrap_or_default();
+    let mut in_remote = false;
+    let mut urls = Vec::new();
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_remote = line.starts_with("[remote ");
+        } else if in_remote {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("url") {
+                    urls.push(value.trim().trim_matches('"').to_string());
+                }
+            }
+        }
+    }
+    urls
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(pattern: &str) -> PathBuf {
+    match (pattern.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(pattern),
+    }
+}
+
+/// Arguments of a profile, with the login flag if it starts a login shell
+fn startup_args(profile: &Profile) -> Vec<String> {
+    let mut args = profile.args.clone();
+    if profile.startup_mode == StartupMode::Login && !args.iter().any(|a| a == "-l" || a == "--login") {
+        args.insert(0, "-l".to_string());
+    }
+    args
+}
+
+/// Profiles for the supported shells listed in /etc/shells, the user's login shell first
+fn detect_shells() -> Vec<Profile> {
+    let listed = fs::read_to_string("/etc/shells").unwrap_or_default();
+    let login_shell = std::env::var("SHELL").ok();
+
+    let mut paths: Vec<&str> = listed
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/') && Path::new(line).exists())
+        .collect();
+    // /etc/shells often lists the same shell under /bin and /usr/bin
+    paths.sort_by_key(|path| (Some(*path) != login_shell.as_deref(), !path.starts_with("/usr/bin")));
+
+    let mut profiles: Vec<Profile> = Vec::new();
+    for (program, id, name) in KNOWN_SHELLS {
+        let Some(path) = paths.iter().find(|path| path.rsplit('/').next() == Some(*program)) else {
+            continue;
+        };
+        profiles.push(Profile {
+            id: id.to_string(),
+            name: name.to_string(),
+            command: path.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            icon: None,
+            color_scheme: None,
+            startup_mode: StartupMode::default(),
+            clipboard: ClipboardPolicy::default(),
+            close_hooks: CloseHooks::default(),
+            bell: BellSettings::default(),
+        });
+    }
+
+    let is_login = |p: &Profile| Some(p.command.as_str()) == login_shell.as_deref();
+    profiles.sort_by_key(|p| !is_login(p));
+    profiles
+}
+
+fn profiles_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("xterminal").join("profiles.json"))
+}
+
+/// Read profiles.json, falling back to the detected shells
+fn load() -> ProfileList {
+    let saved = profiles_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| match serde_json::from_str::<ProfileList>(&contents) {
+            Ok(list) => Some(list),
+            Err(e) => {
+                log::warn!("Ignoring unreadable profiles file: {}", e);
+                None
+            }
+        });
+    if let Some(list) = saved.filter(|list| !list.profiles.is_empty()) {
+        return list;
+    }
+
+    let profiles = detect_shells();
+    ProfileList {
+        default_profile: profiles.first().map(|p| p.id.clone()),
+        profiles,
+        rules: Vec::new(),
+        detected: true,
+    }
+}
+
+fn write(list: &ProfileList) -> Result<(), String> {
+    let path = profiles_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    let contents = serde_json::to_string_pretty(list).map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).map_err(|e| format!("Failed to write profiles: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write profiles: {}", e))?;
+    log::info!("Saved {} profile(s) to {:?}", list.profiles.len(), path);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// Activity and silence monitoring
+// Like tmux's monitor-activity and monitor-silence: a session can report when its output resumes
+// after a quiet spell, on `pty://{id}/activity`, and when it has printed nothing for a while, on
+// `pty://{id}/silence`, for keeping an eye on a long build from another tab. The monitor checks
+// the output counter once a tick, so both are as precise as a tick.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Output after this long without any counts as resuming
+const ACTIVITY_QUIET: Duration = Duration::from_secs(5);
+
+/// What a session is watched for; both are off by default
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MonitorSettings {
+    /// Report output that resumes after a quiet spell
+    pub activity: bool,
+    /// Report this many seconds without output, once until output comes again
+    pub silence_seconds: Option<u32>,
+}
+
+/// What a check of the output counter found
+#[derive(Debug, PartialEq)]
+pub enum MonitorAlert {
+    /// Output came after `quiet` without any
+    Activity { quiet: Duration },
+    /// No output for `quiet`
+    Silence { quiet: Duration },
+}
+
+/// Output seen by the previous check, owned by the session
+#[derive(Debug)]
+pub struct ActivityWatch {
+    settings: MonitorSettings,
+    bytes_read: u64,
+    output_at: Instant,
+    silence_reported: bool,
+}
+
+impl ActivityWatch {
+    pub fn new(bytes_read: u64) -> Self {
+        Self {
+            settings: MonitorSettings::default(),
+            bytes_read,
+            output_at: Instant::now(),
+            silence_reported: false,
+        }
+    }
+
+    pub fn settings(&self) -> MonitorSettings {
+        self.settings
+    }
+
+    /// Watch for something else; quiet time is counted from now
+    pub fn set(&mut self, settings: MonitorSettings, bytes_read: u64) {
+        *self = Self {
+            settings,
+            ..Self::new(bytes_read)
+        };
+    }
+
+    /// Compare the session's output counter with the previous check
+    pub fn check(&mut self, bytes_read: u64) -> Option<MonitorAlert> {
+        let quiet = self.output_at.elapsed();
+        if bytes_read != self.bytes_read {
+            self.bytes_read = bytes_read;
+            self.output_at = Instant::now();
+            self.silence_reported = false;
+            return (self.settings.activity && quiet >= ACTIVITY_QUIET).then_some(MonitorAlert::Activity { quiet });
+        }
+        let limit = Duration::from_secs(u64::from(self.settings.silence_seconds?.max(1)));
+        if self.silence_reported || quiet < limit {
+            return None;
+        }
+        self.silence_reported = true;
+        Some(MonitorAlert::Silence { quiet })
+    }
+}
@@ -0,0 +1,390 @@
+// PTY backends
+// Abstracts how a command is started on a pseudo-terminal, so the manager, reader, and parsers run
+// the same way against the system PTY, a deterministic in-memory mock, or future backends
+// (ConPTY, SSH channels).
+
+use super::priority::Priority;
+use super::signal::{self, Signal};
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use std::ffi::CStr;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+
+#[cfg(test)]
+pub use mock::{MockBackend, MockHandle};
+
+/// Starts commands on pseudo-terminals
+pub trait PtyBackend: Send + Sync {
+    /// Start `cmd` on a new terminal of the given size
+    fn spawn(&self, cmd: CommandBuilder, size: PtySize) -> Result<SpawnedPty, String>;
+}
+
+/// A started command and the two ends of its terminal
+pub struct SpawnedPty {
+    pub process: Box<dyn PtyProcess>,
+    pub reader: Box<dyn Read + Send>,
+    pub writer: Box<dyn Write + Send>,
+    /// Blocks until the command ends and reaps it; run on its own thread
+    pub waiter: Box<dyn FnOnce() -> ExitStatus + Send>,
+}
+
+/// How a command ended
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExitStatus {
+    /// Exit code, `None` when killed by a signal or the status could not be read
+    pub code: Option<i32>,
+    /// Description of the signal that killed the command, e.g. "Killed" or "Hangup"
+    pub signal: Option<String>,
+}
+
+impl ExitStatus {
+    /// Decode a status from waitpid(2)
+    fn from_wait_status(status: libc::c_int) -> Self {
+        if libc::WIFSIGNALED(status) {
+            Self {
+                code: None,
+                signal: Some(signal::describe(libc::WTERMSIG(status))),
+            }
+        } else {
+            Self {
+                code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+                signal: None,
+            }
+        }
+    }
+}
+
+/// Control over a running command and its terminal
+pub trait PtyProcess: Send {
+    fn pid(&self) -> Option<u32>;
+
+    fn resize(&self, size: PtySize) -> Result<(), String>;
+
+    /// Process group in the foreground of the terminal
+    fn foreground_pgrp(&self) -> Option<i32>;
+
+    /// Change the CPU and I/O scheduling priority of the command
+    fn set_priority(&self, priority: &Priority) -> Result<(), String>;
+
+    /// Send a signal to the command's process group and the terminal's foreground group
+    fn signal(&self, signal: Signal) -> Result<(), String>;
+
+    /// Send a signal to the terminal's foreground group only, failing if that is the command's
+    fn signal_foreground(&self, signal: Signal) -> Result<(), String>;
+
+    /// Bytes written to the terminal that the program in it has not read yet; a line being
+    /// edited in canonical mode counts once it is complete
+    fn unread_input(&self) -> Option<usize>;
+}
+
+// ==================== System PTY ====================
+
+/// The host's pseudo-terminals via portable-pty
+pub struct NativeBackend;
+
+impl PtyBackend for NativeBackend {
+    fn spawn(&self, cmd: CommandBuilder, size: PtySize) -> Result<SpawnedPty, String> {
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+
+        // Get the writer upfront - take_writer can only be called once
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| format!("Failed to get writer: {}", e))?;
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| format!("Failed to get reader: {}", e))?;
+
+        let pid = child.process_id();
+        Ok(SpawnedPty {
+            process: Box::new(NativeProcess {
+                pid,
+                master: pair.master,
+            }),
+            reader,
+            writer,
+            waiter: Box::new(move || {
+                // Reaped here instead of with `child.wait()`, whose status has no signal number
+                let _child = child;
+                let Some(pid) = pid else {
+                    return ExitStatus::default();
+                };
+                wait_for(pid).unwrap_or_else(|e| {
+                    log::warn!("Failed to wait for child process: {}", e);
+                    ExitStatus::default()
+                })
+            }),
+        })
+    }
+}
+
+/// Block until the child `pid` ends and reap it
+fn wait_for(pid: u32) -> Result<ExitStatus, String> {
+    let pid = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid PID: {}", pid))?;
+    let mut status: libc::c_int = 0;
+    // SAFETY: waitpid only writes to `status`
+    while unsafe { libc::waitpid(pid, &mut status, 0) } == -1 {
+        let e = std::io::Error::last_os_error();
+        if e.kind() != std::io::ErrorKind::Interrupted {
+            return Err(e.to_string());
+        }
+    }
+    Ok(ExitStatus::from_wait_status(status))
+}
+
+struct NativeProcess {
+    /// The child itself is owned by the waiter
+    pid: Option<u32>,
+    master: Box<dyn MasterPty + Send>,
+}
+
+impl PtyProcess for NativeProcess {
+    fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    fn resize(&self, size: PtySize) -> Result<(), String> {
+        self.master
+            .resize(size)
+            .map_err(|e| format!("Failed to resize PTY: {}", e))
+    }
+
+    fn foreground_pgrp(&self) -> Option<i32> {
+        self.master.process_group_leader()
+    }
+
+    fn set_priority(&self, priority: &Priority) -> Result<(), String> {
+        let pid = self.pid.ok_or_else(|| "Process has no PID".to_string())?;
+        priority.apply(pid)
+    }
+
+    fn signal(&self, signal: Signal) -> Result<(), String> {
+        let pid = self.pid.ok_or_else(|| "Process has no PID".to_string())?;
+        signal::send(pid, self.master.process_group_leader(), signal)
+    }
+
+    fn signal_foreground(&self, signal: Signal) -> Result<(), String> {
+        let foreground = self
+            .master
+            .process_group_leader()
+            .ok_or_else(|| "Terminal has no foreground process group".to_string())?;
+        if self.pid.and_then(|pid| i32::try_from(pid).ok()) == Some(foreground) {
+            return Err("No job runs in the foreground".to_string());
+        }
+        signal::send_group(foreground, signal)
+    }
+
+    fn unread_input(&self) -> Option<usize> {
+        let fd = self.master.as_raw_fd()?;
+        let mut name = [0 as libc::c_char; 128];
+        // SAFETY: the buffer outlives the call and its length is passed along
+        if unsafe { libc::ptsname_r(fd, name.as_mut_ptr(), name.len()) } != 0 {
+            return None;
+        }
+        // SAFETY: ptsname_r succeeded, so the buffer holds a NUL-terminated path
+        let path = unsafe { CStr::from_ptr(name.as_ptr()) }.to_str().ok()?;
+        // Opened without becoming our controlling terminal; the input queue belongs to the
+        // terminal, so this end sees what the program has not read
+        let slave = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(path)
+            .ok()?;
+        let mut count: libc::c_int = 0;
+        // SAFETY: FIONREAD writes a single int
+        if unsafe { libc::ioctl(slave.as_raw_fd(), libc::FIONREAD, &mut count) } != 0 {
+            return None;
+        }
+        usize::try_from(count).ok()
+    }
+}
+
+// ==================== In-memory mock ====================
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use crate::sync::MutexExt;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+
+    /// Deterministic backend for headless tests: each "process" echoes its input back as output,
+    /// like a terminal in echo mode, and tests can inject output of their own
+    #[derive(Default)]
+    pub struct MockBackend {
+        next_pid: AtomicU32,
+        spawned: Mutex<Vec<MockHandle>>,
+    }
+
+    /// Test-side view of a mock process
+    #[derive(Clone)]
+    pub struct MockHandle {
+        pub pid: u32,
+        pub command: CommandBuilder,
+        pub size: Arc<Mutex<PtySize>>,
+        /// Last scheduling priority applied
+        pub priority: Arc<Mutex<Priority>>,
+        /// Signals sent, oldest first
+        pub signals: Arc<Mutex<Vec<Signal>>>,
+        /// Everything written to the terminal
+        pub input: Arc<Mutex<Vec<u8>>>,
+        output: Arc<Mutex<Option<Sender<Vec<u8>>>>>,
+        exit_status: Arc<Mutex<Option<Sender<ExitStatus>>>>,
+    }
+
+    impl MockBackend {
+        /// Processes spawned so far, oldest first
+        pub fn spawned(&self) -> Vec<MockHandle> {
+            self.spawned.locked().clone()
+        }
+    }
+
+    impl MockHandle {
+        /// Make the process print `data`
+        pub fn emit(&self, data: &[u8]) {
+            if let Some(sender) = self.output.locked().as_ref() {
+                let _ = sender.send(data.to_vec());
+            }
+        }
+
+        /// End the process with exit code 0; the session's reader sees EOF
+        pub fn exit(&self) {
+            self.exit_with(ExitStatus {
+                code: Some(0),
+                signal: None,
+            });
+        }
+
+        /// End the process with the given status
+        pub fn exit_with(&self, status: ExitStatus) {
+            if let Some(sender) = self.exit_status.locked().take() {
+                let _ = sender.send(status);
+            }
+            self.output.locked().take();
+        }
+    }
+
+    impl PtyBackend for MockBackend {
+        fn spawn(&self, cmd: CommandBuilder, size: PtySize) -> Result<SpawnedPty, String> {
+            let (sender, receiver) = mpsc::channel();
+            let (exit_sender, exit_receiver) = mpsc::channel();
+            let handle = MockHandle {
+                pid: 1000 + self.next_pid.fetch_add(1, Ordering::Relaxed),
+                command: cmd,
+                size: Arc::new(Mutex::new(size)),
+                priority: Arc::new(Mutex::new(Priority::default())),
+                signals: Arc::new(Mutex::new(Vec::new())),
+                input: Arc::new(Mutex::new(Vec::new())),
+                output: Arc::new(Mutex::new(Some(sender))),
+                exit_status: Arc::new(Mutex::new(Some(exit_sender))),
+            };
+            self.spawned.locked().push(handle.clone());
+
+            Ok(SpawnedPty {
+                process: Box::new(MockProcess(handle.clone())),
+                reader: Box::new(MockReader {
+                    receiver,
+                    pending: Vec::new(),
+                }),
+                writer: Box::new(MockWriter(handle)),
+                waiter: Box::new(move || exit_receiver.recv().unwrap_or_default()),
+            })
+        }
+    }
+
+    struct MockProcess(MockHandle);
+
+    impl PtyProcess for MockProcess {
+        fn pid(&self) -> Option<u32> {
+            Some(self.0.pid)
+        }
+
+        fn resize(&self, size: PtySize) -> Result<(), String> {
+            *self.0.size.locked() = size;
+            Ok(())
+        }
+
+        fn foreground_pgrp(&self) -> Option<i32> {
+            i32::try_from(self.0.pid).ok()
+        }
+
+        fn set_priority(&self, priority: &Priority) -> Result<(), String> {
+            *self.0.priority.locked() = *priority;
+            Ok(())
+        }
+
+        /// Every signal but SIGINT ends the mock process, as the default action would
+        fn signal(&self, signal: Signal) -> Result<(), String> {
+            self.0.signals.locked().push(signal);
+            let name = match signal {
+                Signal::Sigint => return Ok(()),
+                Signal::Sigterm => "Terminated",
+                Signal::Sigkill => "Killed",
+                Signal::Sighup => "Hangup",
+            };
+            self.0.exit_with(ExitStatus {
+                code: None,
+                signal: Some(name.to_string()),
+            });
+            Ok(())
+        }
+
+        /// The mock runs no jobs, so only its own process could be in the foreground
+        fn signal_foreground(&self, _signal: Signal) -> Result<(), String> {
+            Err("No job runs in the foreground".to_string())
+        }
+
+        /// Input is echoed as soon as it is written
+        fn unread_input(&self) -> Option<usize> {
+            Some(0)
+        }
+    }
+
+    struct MockReader {
+        receiver: Receiver<Vec<u8>>,
+        /// Output not yet consumed by a short read
+        pending: Vec<u8>,
+    }
+
+    impl Read for MockReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                match self.receiver.recv() {
+                    Ok(data) => self.pending = data,
+                    // All senders gone: the process exited
+                    Err(_) => return Ok(0),
+                }
+            }
+
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    struct MockWriter(MockHandle);
+
+    impl Write for MockWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.input.locked().extend_from_slice(buf);
+            self.0.emit(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Remote bandwidth accounting
+// Estimates the network traffic of a session's ssh, mosh, and telnet clients from their I/O
+// counters, less what they exchanged with the terminal, so users on metered connections can see
+// what their remote sessions cost. Counts are payload as the client sees it: encryption is
+// included, TCP/IP headers are not.
+
+use super::procfs::{self, ProcStat};
+use super::title;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Programs whose traffic is counted; `mosh` itself is a wrapper that hands over to mosh-client
+const CLIENTS: &[&str] = &["ssh", "mosh-client", "telnet"];
+
+/// Traffic of one remote client process
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConnection {
+    pub pid: u32,
+    pub program: String,
+    pub host: Option<String>,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Estimated remote traffic of a session, published on `pty://{id}/bandwidth`
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthStats {
+    /// Totals since the session started, including clients that have exited
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Clients running now
+    pub connections: Vec<RemoteConnection>,
+}
+
+/// A client being measured
+struct Client {
+    connection: RemoteConnection,
+    /// Terminal output and input while the client was in the foreground, which its counters
+    /// include but the network did not carry
+    terminal_out: u64,
+    terminal_in: u64,
+}
+
+/// Per-session accounting, sampled by the monitor
+#[derive(Default)]
+pub struct BandwidthMeter {
+    clients: HashMap<u32, Client>,
+    /// Totals of clients that have exited
+    finished_sent: u64,
+    finished_received: u64,
+    /// Session output and input counters at the previous sample
+    last_output: u64,
+    last_input: u64,
+    last_stats: BandwidthStats,
+}
+
+impl BandwidthMeter {
+    /// Take a sample, returning the new stats if they changed
+    ///
+    /// `processes` is the session's process tree. Terminal traffic since the previous sample is
+    /// attributed to the client in the foreground process group at the time of this one.
+    pub fn sample(
+        &mut self,
+        processes: &[ProcStat],
+        foreground_pgrp: Option<i32>,
+        output: u64,
+        input: u64,
+    ) -> Option<BandwidthStats> {
+        let output_delta = output.saturating_sub(self.last_output);
+        let input_delta = input.saturating_sub(self.last_input);
+        (self.last_output, self.last_input) = (output, input);
+
+        let is_client = |process: &ProcStat| CLIENTS.contains(&process.comm.as_str()) && process.state != 'Z';
+        // A client started by another, like the ssh of a ProxyJump, carries the same traffic
+        let outermost: Vec<&ProcStat> = processes
+            .iter()
+            .filter(|p| is_client(p))
+            .filter(|p| !processes.iter().any(|parent| parent.pid == p.ppid && is_client(parent)))
+            .collect();
+
+        let finished: Vec<u32> = self
+            .clients
+            .iter()
+            .filter(|(pid, client)| {
+                !outermost.iter().any(|p| p.pid == **pid && p.comm == client.connection.program)
+            })
+            .map(|(pid, _)| *pid)
+            .collect();
+        for pid in finished {
+            if let Some(client) = self.clients.remove(&pid) {
+                self.finished_sent += client.connection.bytes_sent;
+                self.finished_received += client.connection.bytes_received;
+            }
+        }
+
+        for process in outermost {
+            let client = self.clients.entry(process.pid).or_insert_with(|| Client {
+                connection: RemoteConnection {
+                    pid: process.pid,
+                    program: process.comm.clone(),
+                    host: title::remote_host(&procfs::read_cmdline(process.pid)),
+                    bytes_sent: 0,
+                    bytes_received: 0,
+                },
+                terminal_out: 0,
+                terminal_in: 0,
+            });
+            if Some(process.pgrp) == foreground_pgrp {
+                client.terminal_out += output_delta;
+                client.terminal_in += input_delta;
+            }
+            // Exited between listing and reading: keep the last counts
+            let Some(io) = procfs::read_io(process.pid) else {
+                continue;
+            };
+            // Writes go to the network and the terminal, reads come from both
+            client.connection.bytes_sent = io.wchar.saturating_sub(client.terminal_out);
+            client.connection.bytes_received = io.rchar.saturating_sub(client.terminal_in);
+        }
+
+        let stats = self.stats();
+        if stats == self.last_stats {
+            return None;
+        }
+        self.last_stats = stats.clone();
+        Some(stats)
+    }
+
+    pub fn stats(&self) -> BandwidthStats {
+        let mut connections: Vec<RemoteConnection> = self.clients.values().map(|c| c.connection.clone()).collect();
+        connections.sort_by_key(|c| c.pid);
+        BandwidthStats {
+            bytes_sent: self.finished_sent + connections.iter().map(|c| c.bytes_sent).sum::<u64>(),
+            bytes_received: self.finished_received + connections.iter().map(|c| c.bytes_received).sum::<u64>(),
+            connections,
+        }
+    }
+}
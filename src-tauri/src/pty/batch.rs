@@ -0,0 +1,92 @@
+// Output batching
+// A program printing as fast as it can fills a read every few microseconds, and an event per read
+// floods the webview during `cat largefile`. Reads that come close together are merged into one
+// chunk, up to a size, before it is processed and published. A read that did not fill the buffer
+// usually means the program is waiting, like a shell echoing a keystroke, so it goes out without
+// waiting for more.
+
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tokio::time::error::Elapsed;
+use tokio::time::Instant;
+
+/// Longest flush interval honored, so a bad setting cannot make the terminal feel sluggish
+const MAX_FLUSH_MS: u32 = 50;
+
+/// The `outputBatching` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchSettings {
+    /// Milliseconds to wait for more output after a full read; 0 merges only what already waits
+    pub flush_ms: u32,
+    /// Size at which a batch is sent without waiting, in bytes
+    pub max_bytes: usize,
+}
+
+impl Default for BatchSettings {
+    fn default() -> Self {
+        Self {
+            flush_ms: 6,
+            max_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Merges the reads of one session, owned by its reader task
+pub struct Batcher {
+    received: mpsc::Receiver<Vec<u8>>,
+    window: Duration,
+    max_bytes: usize,
+    /// Size of a read that filled the buffer
+    read_size: usize,
+}
+
+impl Batcher {
+    pub fn new(received: mpsc::Receiver<Vec<u8>>, settings: &BatchSettings, read_size: usize) -> Self {
+        Self {
+            received,
+            window: Duration::from_millis(u64::from(settings.flush_ms.min(MAX_FLUSH_MS))),
+            max_bytes: settings.max_bytes.max(read_size),
+            read_size,
+        }
+    }
+
+    /// The next batch of output, or None once the read thread is done and all was handed out
+    ///
+    /// A batch may pass the size limit by the last read merged into it.
+    pub async fn next(&mut self) -> Option<Vec<u8>> {
+        let first = self.received.recv().await?;
+        Some(self.fill(first).await)
+    }
+
+    /// Like `next`, but gives up if no output arrives within `wait`
+    pub async fn next_within(&mut self, wait: Duration) -> Result<Option<Vec<u8>>, Elapsed> {
+        // Only the first read is waited for with the timeout, so a batch is never dropped half-built
+        let Some(first) = tokio::time::timeout(wait, self.received.recv()).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.fill(first).await))
+    }
+
+    /// Merge the reads that follow `batch` into it
+    async fn fill(&mut self, mut batch: Vec<u8>) -> Vec<u8> {
+        let deadline = Instant::now() + self.window;
+        let mut full = batch.len() >= self.read_size;
+        while batch.len() < self.max_bytes {
+            let chunk = match self.received.try_recv() {
+                Ok(chunk) => chunk,
+                Err(TryRecvError::Empty) if full && !self.window.is_zero() => {
+                    match tokio::time::timeout_at(deadline, self.received.recv()).await {
+                        Ok(Some(chunk)) => chunk,
+                        _ => break,
+                    }
+                }
+                Err(_) => break,
+            };
+            full = chunk.len() >= self.read_size;
+            batch.extend_from_slice(&chunk);
+        }
+        batch
+    }
+}
@@ -0,0 +1,106 @@
+// Bell
+// Acts on BEL (0x07) in a session's output as its profile says: a `pty://{id}/bell` event for
+// the tab indicator and the visual bell, the desktop's bell sound, and the urgency hint that has
+// the window manager flag the window while it is in the background. Programs can ring hundreds
+// of bells in a row, so bells closer together than a moment count once.
+
+use crate::control::MAIN_WINDOW;
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::exec::{self, ExecOptions};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, UserAttentionType};
+
+/// Bells closer together than this ring once
+const MIN_INTERVAL: Duration = Duration::from_millis(200);
+
+/// What a profile's sessions do when a program rings the bell
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BellSettings {
+    /// Flash the terminal
+    pub flash: bool,
+    /// Play the desktop theme's bell sound
+    pub sound: bool,
+    /// Set the urgency hint while the window is not focused
+    pub urgent: bool,
+}
+
+impl Default for BellSettings {
+    fn default() -> Self {
+        Self {
+            flash: true,
+            sound: false,
+            urgent: true,
+        }
+    }
+}
+
+/// Per-session rate limit, owned by the output processor
+#[derive(Default)]
+pub struct BellLimiter {
+    last: Option<Instant>,
+}
+
+impl BellLimiter {
+    /// Whether a bell rung now should be acted on
+    pub fn ring(&mut self) -> bool {
+        let now = Instant::now();
+        if self.last.is_some_and(|last| now.duration_since(last) < MIN_INTERVAL) {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+/// Play sounds and set urgency hints for bells
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    events.subscribe(move |event: &SessionEvent| {
+        let EventKind::Bell(settings) = &event.kind else {
+            return;
+        };
+        let settings = *settings;
+        if !settings.sound && !settings.urgent {
+            return;
+        }
+        let app = app_handle.clone();
+        // Both wait on other processes, which the reader must not
+        tauri::async_runtime::spawn(async move {
+            if settings.urgent {
+                request_attention(&app);
+            }
+            if settings.sound {
+                play_sound().await;
+            }
+        });
+    });
+}
+
+fn request_attention(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    if window.is_focused().unwrap_or(false) {
+        return;
+    }
+    if let Err(e) = window.request_user_attention(Some(UserAttentionType::Informational)) {
+        log::debug!("Failed to set the urgency hint: {}", e);
+    }
+}
+
+async fn play_sound() {
+    // libcanberra plays the bell of the desktop's sound theme, honoring its mute setting
+    let result = exec::run(&ExecOptions {
+        program: "canberra-gtk-play".to_string(),
+        args: vec!["--id=bell".to_string(), "--description=Terminal bell".to_string()],
+        timeout_ms: Some(5000),
+        ..Default::default()
+    })
+    .await;
+    match result {
+        Ok(output) if !output.success() => log::debug!("Bell sound failed: {}", output.stderr.trim()),
+        Ok(_) => {}
+        Err(e) => log::debug!("Failed to play the bell sound: {}", e),
+    }
+}
@@ -0,0 +1,80 @@
+// Broadcast input filtering
+// Rules checked for every target of broadcast input, so typing into many sessions at once
+// cannot reach a pager, a full-screen program, or a root shell by accident. Sessions can also
+// join a named broadcast group, whose members all receive what is typed into any one of them.
+
+use serde::{Deserialize, Serialize};
+
+/// Filters applied to each broadcast target; all are on by default
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BroadcastRules {
+    /// Skip read-only sessions such as file viewers and followers
+    pub skip_read_only: bool,
+    /// Skip sessions showing the alternate screen (editors, pagers, top)
+    pub skip_alt_screen: bool,
+    /// Only send to root sessions the user confirmed
+    pub confirm_elevated: bool,
+}
+
+impl Default for BroadcastRules {
+    fn default() -> Self {
+        Self {
+            skip_read_only: true,
+            skip_alt_screen: true,
+            confirm_elevated: true,
+        }
+    }
+}
+
+/// Why a target did not receive the input
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    NotFound,
+    ReadOnly,
+    AltScreen,
+    /// Runs as root and was not in the confirmed list
+    NeedsConfirmation,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedTarget {
+    pub session_id: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedTarget {
+    pub session_id: String,
+    pub error: String,
+}
+
+/// Sessions that receive each other's input
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastGroup {
+    pub name: String,
+    pub session_ids: Vec<String>,
+}
+
+/// Outcome of a broadcast write
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastReport {
+    pub sent: Vec<String>,
+    pub skipped: Vec<SkippedTarget>,
+    pub failed: Vec<FailedTarget>,
+}
+
+impl BroadcastReport {
+    pub fn skip(&mut self, session_id: &str, reason: SkipReason) {
+        log::debug!("Broadcast skipped {}: {:?}", session_id, reason);
+        self.skipped.push(SkippedTarget {
+            session_id: session_id.to_string(),
+            reason,
+        });
+    }
+}
@@ -0,0 +1,124 @@
+// OSC 52 clipboard
+// Programs, including ones on the far side of ssh, can set the clipboard with OSC 52 and, where
+// their profile allows it, read it back. Requests are intercepted in the backend and served by
+// the clipboard manager plugin. Reading is denied unless a profile opts in: anything that can
+// print to the terminal, down to a `cat` of a downloaded file, could otherwise take whatever
+// was copied last.
+
+use super::PtyManager;
+use crate::events::{EventBus, EventKind, SessionEvent};
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, PAD};
+use base64::engine::DecodePaddingMode;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Programs differ on whether they pad their base64
+const LENIENT_BASE64: GeneralPurpose =
+    GeneralPurpose::new(&alphabet::STANDARD, PAD.with_decode_padding_mode(DecodePaddingMode::Indifferent));
+
+/// Whether programs may use one side of the clipboard
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClipboardAccess {
+    Allow,
+    Deny,
+}
+
+/// What a profile's programs may do with the clipboard through OSC 52
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClipboardPolicy {
+    /// Setting the clipboard, e.g. yanking in a remote editor
+    pub write: ClipboardAccess,
+    /// Reading the clipboard
+    pub read: ClipboardAccess,
+}
+
+impl Default for ClipboardPolicy {
+    fn default() -> Self {
+        Self {
+            write: ClipboardAccess::Allow,
+            read: ClipboardAccess::Deny,
+        }
+    }
+}
+
+impl ClipboardPolicy {
+    pub fn allows(&self, request: &ClipboardRequest) -> bool {
+        let access = match request {
+            ClipboardRequest::Write(_) => self.write,
+            ClipboardRequest::Read => self.read,
+        };
+        access == ClipboardAccess::Allow
+    }
+}
+
+/// A clipboard request from a program
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardRequest {
+    Write(String),
+    Read,
+}
+
+impl ClipboardRequest {
+    /// Parse an OSC 52 payload after `52;`: the selections, then base64 text or `?` to read
+    ///
+    /// The plugin reaches only the clipboard, so requests for just the primary selection or
+    /// cut buffers are ignored rather than clobbering it.
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        let split = payload.iter().position(|b| *b == b';')?;
+        let (selections, data) = (&payload[..split], &payload[split + 1..]);
+        // No selection means xterm's default, `s0`
+        if !selections.is_empty() && !selections.iter().any(|s| matches!(s, b'c' | b's')) {
+            return None;
+        }
+        if data == b"?" {
+            return Some(Self::Read);
+        }
+        let bytes = LENIENT_BASE64.decode(data).ok()?;
+        Some(Self::Write(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// Name of the access the request needs, as reported to the frontend
+    pub fn access(&self) -> &'static str {
+        match self {
+            Self::Write(_) => "write",
+            Self::Read => "read",
+        }
+    }
+}
+
+/// Serve the clipboard requests sessions were allowed to make
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    events.subscribe(move |event: &SessionEvent| {
+        let EventKind::Clipboard { request, allowed: true } = &event.kind else {
+            return;
+        };
+        let app = app_handle.clone();
+        let session_id = event.session_id.clone();
+        let request = request.clone();
+        // The clipboard can wait on another application, and subscribers run on the reader
+        tauri::async_runtime::spawn_blocking(move || serve(&app, &session_id, request));
+    });
+}
+
+fn serve(app: &AppHandle, session_id: &str, request: ClipboardRequest) {
+    match request {
+        ClipboardRequest::Write(text) => {
+            if let Err(e) = app.clipboard().write_text(text) {
+                log::warn!("Failed to set clipboard for {}: {}", session_id, e);
+            }
+        }
+        ClipboardRequest::Read => {
+            // Programs wait for an answer, so an empty or non-text clipboard reads as empty
+            let text = app.clipboard().read_text().unwrap_or_default();
+            let reply = format!("\x1b]52;c;{}\x1b\\", BASE64_STANDARD.encode(text));
+            if let Err(e) = app.state::<PtyManager>().respond(session_id, reply.as_bytes()) {
+                log::warn!("Failed to send clipboard to {}: {}", session_id, e);
+            }
+        }
+    }
+}
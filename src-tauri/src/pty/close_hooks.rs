@@ -0,0 +1,93 @@
+// Pre-close hooks
+// Runs the close hooks of a session's profile before it is closed: lines typed into the session,
+// such as `exit` to leave a remote shell cleanly, and commands run beside it, such as saving a
+// tmux layout. The session then has what is left of the timeout to exit on its own; closing
+// goes ahead either way, hanging up and killing whatever is still running.
+
+use crate::commands::scope::{self, SHORT_TIMEOUT};
+use crate::exec::{self, ExecOptions};
+use crate::profiles::{self, CloseHook};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// How often the session is checked for having exited after the steps
+const EXIT_POLL: Duration = Duration::from_millis(50);
+
+/// Run the close hooks of a session's profile, returning once they are done, the session has
+/// exited, or the hooks' timeout is up
+///
+/// Failures are logged rather than returned, as they must not keep a session from closing.
+pub async fn run(app: &AppHandle, session_id: &str) {
+    let id = session_id.to_string();
+    let Ok(info) = scope::run_for_app(app, SHORT_TIMEOUT, move |manager| manager.info(&id)).await else {
+        return;
+    };
+    let Some(hooks) = info.profile_id.as_deref().and_then(profiles::close_hooks) else {
+        return;
+    };
+    let deadline = Instant::now() + Duration::from_millis(hooks.timeout_ms);
+    log::info!("Running {} close hooks for {}", hooks.steps.len(), session_id);
+
+    let mut sent = false;
+    for step in hooks.steps {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            log::warn!("Close hooks of {} timed out", session_id);
+            return;
+        }
+        if exited(app, session_id).await {
+            return;
+        }
+        let result = match step {
+            CloseHook::Send { text } => {
+                sent = true;
+                let id = session_id.to_string();
+                scope::run_for_app(app, remaining, move |manager| manager.write(&id, &format!("{}\r", text))).await
+            }
+            CloseHook::Run { command } => run_command(app, session_id, command, remaining).await,
+        };
+        if let Err(e) = result {
+            log::warn!("Close hook of {} failed: {}", session_id, e);
+        }
+    }
+
+    // Only typed input can make the session exit; commands beside it are simply waited for
+    if !sent {
+        return;
+    }
+    while Instant::now() < deadline {
+        if exited(app, session_id).await {
+            return;
+        }
+        tokio::time::sleep(EXIT_POLL).await;
+    }
+    log::info!("Session {} did not exit after its close hooks", session_id);
+}
+
+async fn run_command(app: &AppHandle, session_id: &str, command: String, limit: Duration) -> Result<(), String> {
+    let id = session_id.to_string();
+    let cwd = scope::run_for_app(app, SHORT_TIMEOUT, move |manager| manager.cwd(&id)).await.ok();
+    let output = exec::run(&ExecOptions {
+        program: "sh".to_string(),
+        args: vec!["-c".to_string(), command.clone()],
+        cwd,
+        timeout_ms: Some(limit.as_millis() as u64),
+        ..Default::default()
+    })
+    .await?;
+    if output.timed_out {
+        return Err(format!("{} timed out", command));
+    }
+    if !output.success() {
+        return Err(format!("{} exited with {:?}: {}", command, output.exit_code, output.stderr.trim()));
+    }
+    Ok(())
+}
+
+async fn exited(app: &AppHandle, session_id: &str) -> bool {
+    let id = session_id.to_string();
+    // A session that cannot be found has nothing left to wait for
+    scope::run_for_app(app, SHORT_TIMEOUT, move |manager| manager.has_exited(&id))
+        .await
+        .unwrap_or(true)
+}
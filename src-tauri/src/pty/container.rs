@@ -0,0 +1,155 @@
+// Container detection
+// Works out whether a session runs somewhere other than the host: a toolbox or container it was
+// entered into, another namespace, or the Flatpak sandbox the app itself runs in
+
+use super::procfs;
+use crate::context;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Container clients whose foreground process means the user is working inside a container
+const ENTER_COMMANDS: &[&str] = &["toolbox", "distrobox", "distrobox-enter", "podman", "docker", "flatpak"];
+
+/// podman/docker options that consume the following argument
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "-e", "--env", "--env-file", "-u", "--user", "-w", "--workdir", "--detach-keys", "-v", "--volume", "-p",
+    "--publish", "--network", "--entrypoint",
+];
+
+/// Where a session's processes actually run, when that is not the host
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionContainer {
+    /// e.g. "toolbox", "distrobox", "podman", "docker", "flatpak", "lxc", or "namespace"
+    /// for an isolated process of unknown origin
+    pub runtime: String,
+    /// Container or application name, when known
+    pub name: Option<String>,
+}
+
+/// Detect the container of a session from its foreground process and shell
+pub fn detect(foreground: u32, shell: u32) -> Option<SessionContainer> {
+    // `flatpak-spawn --host` is how a sandboxed app reaches the host
+    if escapes_sandbox(shell) {
+        return None;
+    }
+    entered(foreground)
+        .or_else(|| isolated(foreground))
+        .or_else(|| isolated(shell))
+        .or_else(inherited)
+}
+
+/// A container client in the foreground, e.g. `toolbox enter dev` or `docker exec -it db sh`
+fn entered(pid: u32) -> Option<SessionContainer> {
+    let comm = procfs::read_stat(pid)?.comm;
+    if !ENTER_COMMANDS.contains(&comm.as_str()) {
+        return None;
+    }
+    let mut argv = procfs::read_cmdline(pid);
+    // Scripts such as distrobox run as `sh /usr/bin/distrobox ...`
+    if argv.get(1).is_some_and(|arg| Path::new(arg).file_name() == Some(comm.as_ref())) {
+        argv.remove(0);
+    }
+    let subcommand = argv.iter().skip(1).find(|arg| !arg.starts_with('-'))?.as_str();
+
+    let runtime = match (comm.as_str(), subcommand) {
+        ("toolbox", "enter" | "run") => "toolbox",
+        ("distrobox", "enter") | ("distrobox-enter", _) => "distrobox",
+        ("podman", "exec" | "run" | "attach" | "start") => "podman",
+        ("docker", "exec" | "run" | "attach" | "start") => "docker",
+        ("flatpak", "run" | "enter") => "flatpak",
+        _ => return None,
+    };
+
+    let name = match (comm.as_str(), subcommand) {
+        // `toolbox run` takes the command as its operand; unnamed means the default container
+        ("toolbox", "run") => named_container(&argv, Some(subcommand), &["-c", "--container"], false),
+        ("toolbox", _) => named_container(&argv, Some(subcommand), &["-c", "--container"], true),
+        // Called directly, the first argument that is not an option is already the container
+        ("distrobox-enter", _) => named_container(&argv, None, &["-n", "--name"], true),
+        ("distrobox", _) => named_container(&argv, Some(subcommand), &["-n", "--name"], true),
+        (_, "run") => named_container(&argv, Some(subcommand), &["--name"], true),
+        _ => named_container(&argv, Some(subcommand), &[], true),
+    };
+    Some(SessionContainer {
+        runtime: runtime.to_string(),
+        name,
+    })
+}
+
+/// A process in a different mount or PID namespace than the terminal
+fn isolated(pid: u32) -> Option<SessionContainer> {
+    let differs = ["mnt", "pid"].iter().any(|ns| {
+        let ours = fs::read_link(format!("/proc/self/ns/{}", ns));
+        let theirs = fs::read_link(format!("/proc/{}/ns/{}", pid, ns));
+        matches!((ours, theirs), (Ok(ours), Ok(theirs)) if ours != theirs)
+    });
+    if !differs {
+        return None;
+    }
+
+    let root = format!("/proc/{}/root", pid);
+    let root = Path::new(&root);
+    let runtime = context::container_at(root).unwrap_or_else(|| "namespace".to_string());
+    Some(SessionContainer {
+        name: container_name(root, &runtime),
+        runtime,
+    })
+}
+
+/// The app runs in a container and the session never left it
+fn inherited() -> Option<SessionContainer> {
+    let root = Path::new("/");
+    let runtime = context::container_at(root)?;
+    Some(SessionContainer {
+        name: container_name(root, &runtime),
+        runtime,
+    })
+}
+
+fn escapes_sandbox(pid: u32) -> bool {
+    let argv = procfs::read_cmdline(pid);
+    argv.first().is_some_and(|program| program.ends_with("flatpak-spawn")) && argv.iter().any(|arg| arg == "--host")
+}
+
+/// Name recorded by the runtime inside the container
+fn container_name(root: &Path, runtime: &str) -> Option<String> {
+    let (file, key) = match runtime {
+        "flatpak" => (".flatpak-info", "name="),
+        "toolbox" | "podman" => ("run/.containerenv", "name="),
+        _ => return None,
+    };
+    let contents = fs::read_to_string(root.join(file)).ok()?;
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Container named in the arguments after `subcommand` (or the program): the value of one of
+/// `name_options`, or else the first operand if `operand` is set
+///
+/// Parsing stops at the first operand, where the command run inside the container begins.
+fn named_container(argv: &[String], subcommand: Option<&str>, name_options: &[&str], operand: bool) -> Option<String> {
+    let mut args = argv.iter().skip(1);
+    if let Some(subcommand) = subcommand {
+        args.find(|arg| arg.as_str() == subcommand)?;
+    }
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            return operand.then(|| arg.clone());
+        }
+        if let Some((option, value)) = arg.split_once('=') {
+            if name_options.contains(&option) {
+                return Some(value.to_string());
+            }
+        } else if name_options.contains(&arg.as_str()) {
+            return args.next().cloned();
+        } else if OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            args.next();
+        }
+    }
+    None
+}
@@ -0,0 +1,205 @@
+// Child environment preparation
+// Strips variables injected by AppImage/Flatpak/Snap packaging so spawned shells see host values,
+// and applies the profile's policy for which of the app's variables the shell inherits
+
+use portable_pty::CommandBuilder;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::Path;
+
+/// Variables that only describe the bundle and are dropped outright
+const BUNDLE_MARKERS: &[&str] = &["APPDIR", "APPIMAGE", "ARGV0", "OWD", "SNAP_LIBRARY_PATH"];
+
+/// Colon-separated search paths whose bundle entries are filtered out
+const PATH_LISTS: &[&str] = &[
+    "PATH",
+    "LD_LIBRARY_PATH",
+    "XDG_DATA_DIRS",
+    "XDG_CONFIG_DIRS",
+    "GTK_PATH",
+    "GIO_EXTRA_MODULES",
+    "QT_PLUGIN_PATH",
+    "GST_PLUGIN_SYSTEM_PATH",
+    "PYTHONPATH",
+    "PERLLIB",
+];
+
+/// Single-valued variables removed when they point into the bundle
+const BUNDLE_VARS: &[&str] = &[
+    "GDK_PIXBUF_MODULE_FILE",
+    "GDK_PIXBUF_MODULEDIR",
+    "GSETTINGS_SCHEMA_DIR",
+    "GIO_MODULE_DIR",
+    "GTK_EXE_PREFIX",
+    "GTK_DATA_PREFIX",
+    "GTK_IM_MODULE_FILE",
+    "PYTHONHOME",
+    "LD_PRELOAD",
+];
+
+/// Variables kept by the clean baseline: identity, locale, and the desktop session
+const BASELINE_VARS: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "PATH",
+    "LANG",
+    "LANGUAGE",
+    "LC_*",
+    "TZ",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+    "XDG_SESSION_TYPE",
+    "XDG_CURRENT_DESKTOP",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "SSH_AUTH_SOCK",
+];
+
+/// PATH for a clean environment when the app has none
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Which of the app's environment variables a spawned shell inherits
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvInheritance {
+    /// Everything, minus packaging overrides
+    #[default]
+    Full,
+    /// A login-like baseline of identity, locale, and desktop session variables
+    Clean,
+    /// Only the listed variables
+    Allowlist,
+}
+
+/// How the app was packaged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Packaging {
+    Native,
+    AppImage,
+    Flatpak,
+    Snap,
+}
+
+impl Packaging {
+    /// Detect the packaging format from the app's own environment
+    pub fn detect() -> Self {
+        if env::var_os("APPIMAGE").is_some() || env::var_os("APPDIR").is_some() {
+            Packaging::AppImage
+        } else if env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists() {
+            Packaging::Flatpak
+        } else if env::var_os("SNAP").is_some() {
+            Packaging::Snap
+        } else {
+            Packaging::Native
+        }
+    }
+
+    /// Filesystem root of the bundle, used to recognize injected paths
+    fn bundle_root(self) -> Option<String> {
+        match self {
+            Packaging::Native => None,
+            Packaging::AppImage => env::var("APPDIR").ok(),
+            Packaging::Flatpak => Some("/app".to_string()),
+            Packaging::Snap => env::var("SNAP").ok(),
+        }
+    }
+}
+
+/// Restore host values for variables the packaging runtime overrode
+pub fn sanitize(cmd: &mut CommandBuilder) {
+    let packaging = Packaging::detect();
+    let Some(root) = packaging.bundle_root().filter(|root| !root.is_empty()) else {
+        return;
+    };
+
+    log::info!("Sanitizing child environment for {:?} bundle at {}", packaging, root);
+    // Whole components only: a bundle at /app must not claim /apple
+    let in_bundle = |value: &str| Path::new(value).starts_with(&root);
+
+    for name in PATH_LISTS.iter().chain(BUNDLE_VARS) {
+        // Launchers commonly stash the host value before overriding it
+        let original = [format!("{}_ORIG", name), format!("APPIMAGE_ORIGINAL_{}", name)]
+            .iter()
+            .find_map(|saved| env::var(saved).ok());
+        if let Some(original) = original {
+            set_or_remove(cmd, name, &original);
+            continue;
+        }
+
+        let Some(value) = cmd.get_env(name).and_then(|v| v.to_str()).map(str::to_string) else {
+            continue;
+        };
+
+        if PATH_LISTS.contains(name) {
+            let kept: Vec<&str> = value.split(':').filter(|entry| !in_bundle(entry)).collect();
+            set_or_remove(cmd, name, &kept.join(":"));
+        } else if in_bundle(&value) {
+            cmd.env_remove(name);
+        }
+    }
+
+    for name in BUNDLE_MARKERS {
+        cmd.env_remove(name);
+    }
+}
+
+fn set_or_remove(cmd: &mut CommandBuilder, name: &str, value: &str) {
+    if value.is_empty() {
+        cmd.env_remove(name);
+    } else {
+        cmd.env(name, value);
+    }
+}
+
+/// Reduce the inherited environment according to `mode`
+///
+/// `keep` lists extra variables to inherit in the clean and allowlist modes; a trailing `*`
+/// matches a prefix, e.g. `LC_*`. Run after `sanitize` and before profile variables are set.
+pub fn apply_inheritance(cmd: &mut CommandBuilder, mode: EnvInheritance, keep: &[String], shell: &str) {
+    let patterns: Vec<&str> = match mode {
+        EnvInheritance::Full => return,
+        EnvInheritance::Clean => BASELINE_VARS.iter().copied().chain(keep.iter().map(String::as_str)).collect(),
+        EnvInheritance::Allowlist => keep.iter().map(String::as_str).collect(),
+    };
+
+    let kept: Vec<(String, String)> = cmd
+        .iter_full_env_as_str()
+        .filter(|(name, _)| patterns.iter().any(|pattern| matches(pattern, name)))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    log::info!("Child environment limited to {} inherited variable(s) ({:?})", kept.len(), mode);
+    cmd.env_clear();
+    for (name, value) in kept {
+        cmd.env(name, value);
+    }
+
+    // Launches from systemd units or desktop portals may lack what a login would set
+    if mode == EnvInheritance::Clean {
+        let defaults = [
+            ("HOME", dirs::home_dir().map(|home| home.display().to_string())),
+            ("USER", Some(crate::context::username())),
+            ("LOGNAME", Some(crate::context::username())),
+            ("SHELL", Some(shell.to_string())),
+            ("PATH", Some(DEFAULT_PATH.to_string())),
+            ("LANG", Some("C.UTF-8".to_string())),
+        ];
+        for (name, value) in defaults {
+            if cmd.get_env(name).is_none() {
+                if let Some(value) = value.filter(|v| !v.is_empty()) {
+                    cmd.env(name, value);
+                }
+            }
+        }
+    }
+}
+
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
@@ -0,0 +1,276 @@
+// Escape sequence scanner
+// Incrementally scans PTY output for the control sequences the backend cares about.
+// State is kept across reads so sequences split between chunks are still recognized.
+
+/// Longest parameter string kept for a CSI sequence
+const MAX_CSI_PARAMS: usize = 64;
+
+/// Longest OSC payload looked at; longer ones are skipped
+const MAX_OSC_BYTES: usize = 4096;
+
+/// Longest OSC 52 payload, which carries clipboard contents in base64
+const MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
+
+/// Longest command line echo kept between prompt marks
+const MAX_ECHO_BYTES: usize = 64 * 1024;
+
+/// A control sequence recognized in the output stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum TermEvent {
+    /// DECSET/DECRST (`CSI ? Pm h` / `CSI ? Pm l`)
+    PrivateMode { mode: u16, enabled: bool },
+    /// ENQ (0x05), asking the terminal for its answerback string
+    Enquiry,
+    /// BEL (0x07) outside a string, where it would end an OSC instead
+    Bell,
+    /// OSC 0 or OSC 2, setting the window title; raw text, possibly with control characters
+    Title(String),
+    /// OSC 7, reporting the working directory as a URI
+    WorkingDirectory(String),
+    /// OSC 52, setting or querying the clipboard; the payload after `52;`
+    Clipboard(Vec<u8>),
+    /// OSC 133 (or VS Code's OSC 633) semantic prompt mark from shell integration
+    PromptMark(PromptMark),
+    /// ConEmu's OSC 9;4 progress report; the parameters after `9;4`
+    Progress(String),
+}
+
+/// Where a shell is in its prompt and command cycle
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptMark {
+    /// A: the prompt is about to be drawn
+    PromptStart,
+    /// B: the prompt is drawn and the user is typing
+    CommandStart,
+    /// C: the command runs; everything output since B, i.e. the echo of the command line
+    CommandExecuted(Option<Vec<u8>>),
+    /// D: the command finished, with its exit status when the shell reported one
+    CommandFinished(Option<i32>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    /// ESC followed by intermediate bytes (e.g. charset designation `ESC ( B`)
+    EscapeIntermediate,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, terminated by BEL or ST
+    String,
+    /// ESC seen inside a string, possibly the start of ST
+    StringEscape,
+}
+
+/// Stateful scanner over raw PTY output
+#[derive(Default)]
+pub struct EscapeScanner {
+    state: State,
+    params: Vec<u8>,
+    /// Payload of the string being scanned, when it is an OSC
+    osc: Option<Vec<u8>>,
+    /// Raw output since the last B mark, while waiting for its C mark
+    echo: Option<Vec<u8>>,
+}
+
+impl EscapeScanner {
+    /// Scan a chunk of output, appending recognized sequences to `events`
+    pub fn feed(&mut self, data: &[u8], events: &mut Vec<TermEvent>) {
+        for &byte in data {
+            self.advance(byte, events);
+        }
+    }
+
+    fn advance(&mut self, byte: u8, events: &mut Vec<TermEvent>) {
+        if let Some(echo) = &mut self.echo {
+            if echo.len() < MAX_ECHO_BYTES {
+                echo.push(byte);
+            }
+        }
+        match self.state {
+            State::Ground => match byte {
+                0x1b => self.state = State::Escape,
+                0x05 => events.push(TermEvent::Enquiry),
+                0x07 => events.push(TermEvent::Bell),
+                _ => {}
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.params.clear();
+                    self.state = State::Csi;
+                }
+                b']' | b'P' | b'_' | b'^' | b'X' => {
+                    self.params.clear();
+                    self.osc = (byte == b']').then(Vec::new);
+                    self.state = State::String;
+                }
+                0x1b => {}
+                0x20..=0x2f => self.state = State::EscapeIntermediate,
+                _ => self.state = State::Ground,
+            },
+            State::EscapeIntermediate => match byte {
+                0x20..=0x2f => {}
+                0x1b => self.state = State::Escape,
+                _ => self.state = State::Ground,
+            },
+            State::Csi => match byte {
+                0x1b => self.state = State::Escape,
+                // CAN and SUB abort the sequence
+                0x18 | 0x1a => self.state = State::Ground,
+                0x20..=0x3f if self.params.len() < MAX_CSI_PARAMS => self.params.push(byte),
+                0x20..=0x3f => {}
+                0x40..=0x7e => {
+                    self.dispatch_csi(byte, events);
+                    self.state = State::Ground;
+                }
+                // C0 controls are executed without interrupting the sequence
+                0x05 => events.push(TermEvent::Enquiry),
+                0x07 => events.push(TermEvent::Bell),
+                _ => {}
+            },
+            State::String => match byte {
+                0x07 => {
+                    self.dispatch_osc(events);
+                    self.state = State::Ground;
+                }
+                0x18 | 0x1a => self.state = State::Ground,
+                0x1b => self.state = State::StringEscape,
+                _ => match &mut self.osc {
+                    Some(osc) if osc.len() < osc_limit(osc) => osc.push(byte),
+                    // Too long to be anything the backend cares about
+                    Some(_) => self.osc = None,
+                    None => {}
+                },
+            },
+            State::StringEscape => match byte {
+                b'\\' => {
+                    self.dispatch_osc(events);
+                    self.state = State::Ground;
+                }
+                b'[' => {
+                    self.params.clear();
+                    self.state = State::Csi;
+                }
+                0x1b => {}
+                _ => self.state = State::String,
+            },
+        }
+    }
+
+    fn dispatch_osc(&mut self, events: &mut Vec<TermEvent>) {
+        let Some(osc) = self.osc.take() else {
+            return;
+        };
+        // OSC 1 sets only the icon name, which has no place to show
+        let title = osc.strip_prefix(b"0;").or_else(|| osc.strip_prefix(b"2;"));
+        if let Some(title) = title {
+            events.push(TermEvent::Title(String::from_utf8_lossy(title).into_owned()));
+        } else if let Some(uri) = osc.strip_prefix(b"7;") {
+            events.push(TermEvent::WorkingDirectory(String::from_utf8_lossy(uri).into_owned()));
+        } else if let Some(payload) = osc.strip_prefix(b"52;") {
+            events.push(TermEvent::Clipboard(payload.to_vec()));
+        } else if osc == b"9;4" || osc.starts_with(b"9;4;") {
+            events.push(TermEvent::Progress(String::from_utf8_lossy(&osc[3..]).into_owned()));
+        } else if let Some(mark) = osc.strip_prefix(b"133;").or_else(|| osc.strip_prefix(b"633;")) {
+            self.dispatch_prompt_mark(mark, events);
+        }
+    }
+
+    fn dispatch_prompt_mark(&mut self, mark: &[u8], events: &mut Vec<TermEvent>) {
+        let mut parts = mark.split(|b| *b == b';');
+        let mark = match parts.next() {
+            Some(b"A") => {
+                self.echo = None;
+                PromptMark::PromptStart
+            }
+            Some(b"B") => {
+                self.echo = Some(Vec::new());
+                PromptMark::CommandStart
+            }
+            Some(b"C") => PromptMark::CommandExecuted(self.echo.take()),
+            Some(b"D") => {
+                let exit_code = parts
+                    .next()
+                    .and_then(|code| std::str::from_utf8(code).ok())
+                    .and_then(|code| code.parse().ok());
+                PromptMark::CommandFinished(exit_code)
+            }
+            // Other marks, like VS Code's E carrying the command line, are not needed
+            _ => return,
+        };
+        events.push(TermEvent::PromptMark(mark));
+    }
+
+    fn dispatch_csi(&mut self, final_byte: u8, events: &mut Vec<TermEvent>) {
+        let Some(params) = self.params.strip_prefix(b"?") else {
+            return;
+        };
+
+        let enabled = match final_byte {
+            b'h' => true,
+            b'l' => false,
+            _ => return,
+        };
+
+        for param in params.split(|b| *b == b';') {
+            let mode = std::str::from_utf8(param).ok().and_then(|p| p.parse().ok());
+            if let Some(mode) = mode {
+                events.push(TermEvent::PrivateMode { mode, enabled });
+            }
+        }
+    }
+}
+
+/// Longest payload kept for the OSC being scanned
+fn osc_limit(osc: &[u8]) -> usize {
+    if osc.starts_with(b"52;") {
+        MAX_CLIPBOARD_BYTES
+    } else {
+        MAX_OSC_BYTES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> Vec<TermEvent> {
+        let mut scanner = EscapeScanner::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            scanner.feed(chunk, &mut events);
+        }
+        events
+    }
+
+    #[test]
+    fn osc_split_across_chunks_ends_with_bel() {
+        let events = scan(&[b"out\x1b]2;bu", b"ild lo", b"g\x07more"]);
+        assert_eq!(events, [TermEvent::Title("build log".to_string())]);
+    }
+
+    #[test]
+    fn osc_split_inside_its_string_terminator() {
+        let events = scan(&[b"\x1b]7;file://host/tmp\x1b", b"\\$ "]);
+        assert_eq!(events, [TermEvent::WorkingDirectory("file://host/tmp".to_string())]);
+    }
+
+    #[test]
+    fn bel_ends_an_osc_rather_than_ringing() {
+        let events = scan(&[b"\x1b]0;title\x07\x07"]);
+        assert_eq!(events, [TermEvent::Title("title".to_string()), TermEvent::Bell]);
+    }
+
+    #[test]
+    fn private_mode_with_several_parameters_reports_each() {
+        let events = scan(&[b"\x1b[?1000;10", b"06h\x1b[?2004l"]);
+        assert_eq!(
+            events,
+            [
+                TermEvent::PrivateMode { mode: 1000, enabled: true },
+                TermEvent::PrivateMode { mode: 1006, enabled: true },
+                TermEvent::PrivateMode { mode: 2004, enabled: false },
+            ]
+        );
+    }
+}
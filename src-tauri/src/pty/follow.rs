@@ -0,0 +1,270 @@
+// File-follow pseudo-sessions
+// Tails a local file with `tail -F` semantics and streams it through the normal session data
+// events, so log files can be watched in a tab without spawning a shell.
+
+use super::utf8::Utf8Decoder;
+use crate::events::{EventBus, EventKind};
+use crate::sync::MutexExt;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often the file is checked for new data
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Largest chunk read and emitted at once
+const MAX_CHUNK: usize = 64 * 1024;
+
+/// How far back to look when starting from the last N lines
+const TAIL_WINDOW: u64 = 256 * 1024;
+
+/// Snapshot of a follower for the frontend
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowInfo {
+    pub id: String,
+    pub path: String,
+    /// Byte offset of the next read
+    pub position: u64,
+    pub paused: bool,
+}
+
+/// State shared between a follower handle and its thread
+#[derive(Default)]
+struct FollowControl {
+    stop: AtomicBool,
+    paused: AtomicBool,
+    position: AtomicU64,
+    /// Pending seek: a byte offset, negative counting from the end
+    seek: Mutex<Option<i64>>,
+}
+
+/// A running file follower, stopped when dropped
+pub struct FileFollower {
+    id: String,
+    path: PathBuf,
+    control: Arc<FollowControl>,
+}
+
+impl FileFollower {
+    /// Start following `path`, beginning with its last `tail_lines` lines
+    pub fn start(
+        id: String,
+        path: PathBuf,
+        tail_lines: usize,
+        events: Arc<EventBus>,
+    ) -> Result<Self, String> {
+        let metadata = fs::metadata(&path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+        if !metadata.is_file() {
+            return Err(format!("Not a regular file: {:?}", path));
+        }
+
+        let control = Arc::new(FollowControl::default());
+        let start = tail_start(&path, tail_lines).unwrap_or(0);
+        control.position.store(start, Ordering::Relaxed);
+
+        let mut tail = Tail {
+            id: id.clone(),
+            path: path.clone(),
+            control: control.clone(),
+            events,
+            file: None,
+            inode: None,
+            decoder: Utf8Decoder::default(),
+        };
+        std::thread::spawn(move || tail.run());
+
+        log::info!("Following {:?} as session {}", path, id);
+        Ok(Self { id, path, control })
+    }
+
+    pub fn info(&self) -> FollowInfo {
+        FollowInfo {
+            id: self.id.clone(),
+            path: self.path.display().to_string(),
+            position: self.control.position.load(Ordering::Relaxed),
+            paused: self.control.paused.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stop or resume reading; the position is kept while paused
+    pub fn set_paused(&self, paused: bool) {
+        self.control.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Continue from a byte offset, counting from the end if negative
+    pub fn seek(&self, offset: i64) {
+        *self.control.seek.locked() = Some(offset);
+    }
+}
+
+impl Drop for FileFollower {
+    fn drop(&mut self) {
+        self.control.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The follower thread's state
+struct Tail {
+    id: String,
+    path: PathBuf,
+    control: Arc<FollowControl>,
+    events: Arc<EventBus>,
+    file: Option<File>,
+    inode: Option<u64>,
+    decoder: Utf8Decoder,
+}
+
+impl Tail {
+    fn run(&mut self) {
+        while !self.control.stop.load(Ordering::Relaxed) {
+            let seek = self.control.seek.locked().take();
+            if let Some(offset) = seek {
+                self.seek(offset);
+            }
+
+            if !self.control.paused.load(Ordering::Relaxed) {
+                // Drain everything available before sleeping again
+                while self.poll() && !self.control.stop.load(Ordering::Relaxed) {}
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        log::info!("Stopped following {:?}", self.path);
+    }
+
+    /// Read one chunk of new data, returning whether more may be available
+    fn poll(&mut self) -> bool {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            // Deleted or being rotated: wait for it to reappear
+            if self.file.take().is_some() {
+                self.publish(EventKind::FollowReset {
+                    reason: "missing".to_string(),
+                    position: None,
+                });
+            }
+            return false;
+        };
+
+        let position = self.control.position.load(Ordering::Relaxed);
+        if self.inode.is_some_and(|inode| inode != metadata.ino()) {
+            self.reset(0, "rotated");
+        } else if metadata.len() < position {
+            self.reset(0, "truncated");
+        }
+
+        if self.file.is_none() {
+            match File::open(&self.path) {
+                Ok(file) => {
+                    self.file = Some(file);
+                    self.inode = Some(metadata.ino());
+                }
+                Err(e) => {
+                    log::warn!("Failed to open {:?}: {}", self.path, e);
+                    return false;
+                }
+            }
+        }
+
+        let position = self.control.position.load(Ordering::Relaxed);
+        if metadata.len() <= position {
+            return false;
+        }
+
+        let mut chunk = vec![0u8; MAX_CHUNK.min((metadata.len() - position) as usize)];
+        let file = self.file.as_mut().unwrap();
+        let read = file
+            .seek(SeekFrom::Start(position))
+            .and_then(|_| file.read(&mut chunk));
+        let n = match read {
+            Ok(0) => return false,
+            Ok(n) => n,
+            Err(e) => {
+                log::warn!("Failed to read {:?}: {}", self.path, e);
+                self.file = None;
+                return false;
+            }
+        };
+
+        self.control.position.store(position + n as u64, Ordering::Relaxed);
+        let data = to_crlf(&self.decoder.decode(&chunk[..n]));
+        if !data.is_empty() {
+            self.publish(EventKind::Data(data));
+        }
+        n == chunk.len()
+    }
+
+    fn seek(&mut self, offset: i64) {
+        let len = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        let position = if offset < 0 {
+            len.saturating_sub(offset.unsigned_abs())
+        } else {
+            (offset as u64).min(len)
+        };
+        self.reset(position, "seek");
+    }
+
+    /// Continue from `position`, telling the frontend to clear what it shows
+    fn reset(&mut self, position: u64, reason: &str) {
+        self.control.position.store(position, Ordering::Relaxed);
+        self.decoder.reset();
+        if reason != "seek" {
+            self.file = None;
+            self.inode = None;
+        }
+        self.publish(EventKind::FollowReset {
+            reason: reason.to_string(),
+            position: Some(position),
+        });
+    }
+
+    fn publish(&self, kind: EventKind) {
+        self.events.publish(&self.id, kind);
+    }
+}
+
+/// Files use bare LF line endings, which a terminal needs as CRLF
+fn to_crlf(text: &str) -> String {
+    let mut converted = String::with_capacity(text.len());
+    let mut previous = '\0';
+    for c in text.chars() {
+        if c == '\n' && previous != '\r' {
+            converted.push('\r');
+        }
+        converted.push(c);
+        previous = c;
+    }
+    converted
+}
+
+/// Byte offset where the last `lines` lines of a file begin
+fn tail_start(path: &PathBuf, lines: usize) -> Option<u64> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    if lines == 0 {
+        return Some(len);
+    }
+
+    let window_start = len.saturating_sub(TAIL_WINDOW);
+    let mut window = Vec::new();
+    file.seek(SeekFrom::Start(window_start)).ok()?;
+    file.read_to_end(&mut window).ok()?;
+
+    // Ignore a trailing newline so it does not count as an empty last line
+    let search = window.strip_suffix(b"\n").unwrap_or(&window);
+    let start = search
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(lines - 1)
+        .map(|(i, _)| window_start + i as u64 + 1)
+        .unwrap_or(window_start);
+    Some(start)
+}
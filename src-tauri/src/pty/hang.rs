@@ -0,0 +1,115 @@
+// Hang detection
+// Flags sessions whose foreground program seems to have stopped responding: input was sent,
+// nothing came back, and the input still sits unread in the terminal after a while. The terminal
+// answers that last question itself, so detection never writes anything a program could take
+// for a keystroke. The frontend offers to interrupt or kill the program.
+
+use super::jobs::ForegroundProcess;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The `hangDetection` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HangSettings {
+    pub enabled: bool,
+    /// Seconds input may go unanswered and unread before the session is flagged
+    pub seconds: u32,
+}
+
+impl Default for HangSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            seconds: 10,
+        }
+    }
+}
+
+impl HangSettings {
+    /// How long input may go unanswered, or None when detection is off
+    pub fn threshold(&self) -> Option<Duration> {
+        self.enabled.then(|| Duration::from_secs(u64::from(self.seconds.max(1))))
+    }
+}
+
+/// Whether a session seems hung, published on `pty://{id}/unresponsive` when it is flagged and
+/// when output shows it recovered
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HangState {
+    pub unresponsive: bool,
+    /// Input the program has not read
+    pub unread_bytes: usize,
+    /// Seconds since the first unanswered input
+    pub seconds: u64,
+    /// The program to interrupt or kill, while unresponsive
+    pub process: Option<ForegroundProcess>,
+}
+
+impl HangState {
+    pub fn recovered() -> Self {
+        Self {
+            unresponsive: false,
+            unread_bytes: 0,
+            seconds: 0,
+            process: None,
+        }
+    }
+}
+
+/// What a check of the counters found
+#[derive(Debug, PartialEq)]
+pub enum HangCheck {
+    Unchanged,
+    /// Output arrived from a session that was flagged
+    Recovered,
+    /// Input has gone unanswered for this long; whether it was read decides
+    Suspect(Duration),
+}
+
+/// Input and output seen by the previous check, owned by the session
+#[derive(Debug, Default)]
+pub struct HangWatch {
+    bytes_read: u64,
+    bytes_written: u64,
+    /// When input was first sent after the last output
+    unanswered_since: Option<Instant>,
+    flagged: bool,
+}
+
+impl HangWatch {
+    /// Compare the session's byte counters with the previous check
+    pub fn check(&mut self, bytes_read: u64, bytes_written: u64, threshold: Option<Duration>) -> HangCheck {
+        if bytes_read != self.bytes_read {
+            self.bytes_read = bytes_read;
+            self.bytes_written = bytes_written;
+            self.unanswered_since = None;
+            return if std::mem::take(&mut self.flagged) {
+                HangCheck::Recovered
+            } else {
+                HangCheck::Unchanged
+            };
+        }
+        if bytes_written != self.bytes_written {
+            self.bytes_written = bytes_written;
+            self.unanswered_since.get_or_insert_with(Instant::now);
+        }
+        match (self.unanswered_since, threshold) {
+            (Some(since), Some(threshold)) if !self.flagged && since.elapsed() >= threshold => {
+                HangCheck::Suspect(since.elapsed())
+            }
+            _ => HangCheck::Unchanged,
+        }
+    }
+
+    /// The program read its input and just had nothing to say; wait for the next input
+    pub fn cleared(&mut self) {
+        self.unanswered_since = None;
+    }
+
+    /// The session was reported unresponsive; the next output reports it recovered
+    pub fn flagged(&mut self) {
+        self.flagged = true;
+    }
+}
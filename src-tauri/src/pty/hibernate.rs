@@ -0,0 +1,123 @@
+// Session hibernation
+// Sessions left idle give up the memory of their backend scrollback: it is compressed to a file
+// only the user can read and read back the moment anything needs it, be it a scrollback read,
+// new output, or the terminal gaining focus. Dozens of long-lived tabs then cost little more
+// than their processes. Incognito sessions never hibernate, since that would put their output
+// on disk.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+
+/// The `hibernation` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HibernationSettings {
+    pub enabled: bool,
+    /// Minutes without input or output before a session hibernates
+    pub idle_minutes: u32,
+    /// Sessions holding less scrollback than this are left alone
+    pub min_scrollback_kb: usize,
+}
+
+impl Default for HibernationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: 30,
+            min_scrollback_kb: 256,
+        }
+    }
+}
+
+/// Whether a session's scrollback is on disk, published on `pty://{id}/hibernation`
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HibernationState {
+    pub hibernating: bool,
+    /// Scrollback moved to disk
+    pub bytes: usize,
+    /// Size of the file it was compressed to
+    pub compressed_bytes: u64,
+}
+
+/// Scrollback written to disk, removed when dropped
+#[derive(Debug)]
+pub struct Spill {
+    path: PathBuf,
+    /// Uncompressed size
+    pub bytes: usize,
+    pub compressed_bytes: u64,
+}
+
+/// Directory holding the scrollback of hibernating sessions
+fn spill_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("xterminal").join("hibernation"))
+}
+
+/// Compress a session's scrollback to its spill file
+pub fn spill(session_id: &str, data: &[u8]) -> Result<Spill, String> {
+    let dir = spill_dir().ok_or_else(|| "Could not find cache directory".to_string())?;
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("Failed to create hibernation directory: {}", e))?;
+
+    let path = dir.join(format!("{}.gz", session_id));
+    let write = || -> std::io::Result<u64> {
+        let file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(&path)?;
+        let mut encoder = GzEncoder::new(file, Compression::fast());
+        encoder.write_all(data)?;
+        let file = encoder.finish()?;
+        Ok(file.metadata()?.len())
+    };
+    match write() {
+        Ok(compressed_bytes) => Ok(Spill {
+            path,
+            bytes: data.len(),
+            compressed_bytes,
+        }),
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            Err(format!("Failed to write hibernated scrollback: {}", e))
+        }
+    }
+}
+
+impl Spill {
+    /// Read the scrollback back, removing the file
+    pub fn restore(self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::with_capacity(self.bytes);
+        fs::File::open(&self.path)
+            .and_then(|file| GzDecoder::new(file).read_to_end(&mut data))
+            .map_err(|e| format!("Failed to read hibernated scrollback: {}", e))?;
+        Ok(data)
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("Failed to remove hibernated scrollback {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Remove scrollback left on disk by a previous run that did not shut down cleanly
+pub fn sweep_stale() {
+    let Some(dir) = spill_dir() else {
+        return;
+    };
+    // Only one instance runs per user, so nothing in here belongs to a live session
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// External input pipe
+// An opt-in per-session FIFO that lets scripts and editors send input to a session.
+// The path contains a random token so other local users cannot guess it.
+//
+// Data written to the pipe is forwarded verbatim, except lines starting with
+// `#!xterminal ` which are control commands:
+//   #!xterminal paste <text>   paste text (bracketed if enabled)
+//   #!xterminal key <name>     send a named key (enter, tab, esc, ctrl-c, up, ...)
+
+use super::session::SessionShared;
+use std::ffi::CString;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Prefix marking a control command line
+const CONTROL_PREFIX: &[u8] = b"#!xterminal ";
+
+/// A FIFO forwarding external input into a session
+pub struct InputPipe {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl InputPipe {
+    /// Create the FIFO and start forwarding its contents to the session
+    pub fn open(session_id: &str, shared: Arc<SessionShared>) -> Result<Self, String> {
+        let dir = dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("xterminal");
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&dir)
+            .map_err(|e| format!("Failed to create pipe directory: {}", e))?;
+
+        let token = Uuid::new_v4().simple().to_string();
+        let path = dir.join(format!("input-{}-{}", session_id, token));
+        make_fifo(&path)?;
+
+        // Opening read-write keeps the FIFO from reporting EOF between writers
+        let mut fifo = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(&path)
+            .map_err(|e| format!("Failed to open input pipe: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread_session = session_id.to_string();
+
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 4096];
+            let mut lines = PipeLines::default();
+
+            while !thread_stop.load(Ordering::Relaxed) {
+                let n = match fifo.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) => {
+                        log::error!("Input pipe for {} failed: {}", thread_session, e);
+                        break;
+                    }
+                };
+                if thread_stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                for piece in lines.feed(&buffer[..n]) {
+                    let result = match piece {
+                        Piece::Input(data) => shared.write_all(&data),
+                        Piece::Control(command) => run_control(&shared, &command),
+                    };
+                    if let Err(e) = result {
+                        log::warn!("Input pipe for {}: {}", thread_session, e);
+                    }
+                }
+            }
+
+            log::info!("Input pipe closed for session: {}", thread_session);
+        });
+
+        log::info!("Opened input pipe for {} at {:?}", session_id, path);
+        Ok(Self { path, stop })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InputPipe {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        // Wake the blocked reader so it sees the stop flag
+        if let Ok(mut fifo) = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.path)
+        {
+            let _ = fifo.write_all(b"\n");
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Part of the pipe's data, in the order it was written
+enum Piece {
+    /// Forwarded to the terminal as is
+    Input(Vec<u8>),
+    /// A control line without its prefix
+    Control(Vec<u8>),
+}
+
+/// Splits the pipe's data into input and control lines, which writers may split across reads
+#[derive(Default)]
+struct PipeLines {
+    /// Start of the current line, held back while it may still be a control line
+    held: Vec<u8>,
+    /// The current line is input, forwarded as it arrives so interactive writers see no delay
+    in_input: bool,
+}
+
+impl PipeLines {
+    fn feed(&mut self, data: &[u8]) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        for segment in data.split_inclusive(|b| *b == b'\n') {
+            let complete = segment.ends_with(b"\n");
+            if self.in_input {
+                pieces.push(Piece::Input(segment.to_vec()));
+                self.in_input = !complete;
+                continue;
+            }
+
+            self.held.extend_from_slice(segment);
+            if self.held.starts_with(CONTROL_PREFIX) {
+                // Run once the whole line is in
+                if complete {
+                    let line = std::mem::take(&mut self.held);
+                    pieces.push(Piece::Control(line[CONTROL_PREFIX.len()..].to_vec()));
+                }
+                continue;
+            }
+            if !complete && CONTROL_PREFIX.starts_with(&self.held) {
+                continue;
+            }
+            pieces.push(Piece::Input(std::mem::take(&mut self.held)));
+            self.in_input = !complete;
+        }
+        pieces
+    }
+}
+
+fn make_fifo(path: &Path) -> Result<(), String> {
+    let c_path = CString::new(path.as_os_str().as_encoded_bytes())
+        .map_err(|_| "Invalid pipe path".to_string())?;
+
+    // SAFETY: c_path is a valid NUL-terminated string for the duration of the call
+    let result = unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) };
+    if result != 0 {
+        return Err(format!("Failed to create input pipe: {}", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// Execute a `#!xterminal` control command
+fn run_control(shared: &SessionShared, command: &[u8]) -> Result<(), String> {
+    let command = String::from_utf8_lossy(command);
+    let command = command.trim_end_matches(['\r', '\n']);
+    let (verb, argument) = command.split_once(' ').unwrap_or((command, ""));
+
+    match verb {
+        "paste" => shared.paste(argument),
+        "key" => {
+            let sequence = named_key(argument).ok_or_else(|| format!("Unknown key: {}", argument))?;
+            shared.write_all(sequence.as_bytes())
+        }
+        _ => Err(format!("Unknown control command: {}", verb)),
+    }
+}
+
+/// Byte sequence sent for a named key
+fn named_key(name: &str) -> Option<&'static str> {
+    let sequence = match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => "\r",
+        "tab" => "\t",
+        "esc" | "escape" => "\x1b",
+        "backspace" => "\x7f",
+        "ctrl-c" => "\x03",
+        "ctrl-d" => "\x04",
+        "ctrl-z" => "\x1a",
+        "ctrl-l" => "\x0c",
+        "up" => "\x1b[A",
+        "down" => "\x1b[B",
+        "right" => "\x1b[C",
+        "left" => "\x1b[D",
+        _ => return None,
+    };
+    Some(sequence)
+}
@@ -0,0 +1,54 @@
+// Ordered input
+// Every keystroke reaches the backend as an IPC call of its own, and writing each from whichever
+// blocking thread picks it up first could swap two of them. A session's input is queued instead,
+// in the order the calls arrive, and written by one task; a write that waits on a program that
+// is not reading its input holds back only that session's later input.
+
+use super::memory::BufferKind;
+use super::session::SessionShared;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Input waiting to be written to a session's terminal, shared by the session and its reader
+#[derive(Clone)]
+pub struct InputQueue {
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    shared: Arc<SessionShared>,
+}
+
+impl InputQueue {
+    /// Start writing queued input to the terminal; the task ends once every clone is dropped
+    pub fn start(session_id: &str, shared: Arc<SessionShared>) -> Self {
+        let (sender, mut received) = mpsc::unbounded_channel::<Vec<u8>>();
+        let id = session_id.to_string();
+        let writer = shared.clone();
+        tokio::spawn(async move {
+            while let Some(data) = received.recv().await {
+                let shared = writer.clone();
+                // Writes block while the terminal's input buffer is full
+                let written = tokio::task::spawn_blocking(move || {
+                    let result = shared.write_all(&data);
+                    shared.memory.release(BufferKind::Pending, data.len());
+                    result
+                });
+                match written.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => log::warn!("Input to session {} was lost: {}", id, e),
+                    Err(e) => log::warn!("Input to session {} was lost: {}", id, e),
+                }
+            }
+        });
+        Self { sender, shared }
+    }
+
+    /// Queue input behind what was queued before it
+    pub fn push(&self, data: Vec<u8>) -> Result<(), String> {
+        // Counted before sending, so the writer never releases bytes not yet added
+        let len = data.len();
+        self.shared.memory.add(BufferKind::Pending, len);
+        self.sender.send(data).map_err(|_| {
+            self.shared.memory.release(BufferKind::Pending, len);
+            "Session no longer takes input".to_string()
+        })
+    }
+}
@@ -0,0 +1,84 @@
+// Job tracking
+// Detects shell jobs running outside the terminal's foreground process group, and what runs in it
+
+use super::procfs::{self, ProcessTree};
+use serde::Serialize;
+
+/// A background job belonging to a session's shell
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub pid: u32,
+    pub pgid: i32,
+    pub command: String,
+    pub stopped: bool,
+}
+
+/// List jobs spawned by the shell that are not in the foreground
+///
+/// With job control every job gets its own process group, so only group
+/// leaders are reported to yield one entry per job.
+pub fn list_jobs(processes: &ProcessTree, shell_pid: u32, foreground_pgrp: Option<i32>) -> Vec<JobInfo> {
+    if shell_pid == 0 {
+        return Vec::new();
+    }
+
+    processes
+        .descendants(shell_pid)
+        .into_iter()
+        .filter(|p| p.pid as i32 == p.pgrp)
+        .filter(|p| Some(p.pgrp) != foreground_pgrp)
+        .filter(|p| p.state != 'Z')
+        .map(|p| {
+            let argv = procfs::read_cmdline(p.pid);
+            let command = if argv.is_empty() { p.comm.clone() } else { argv.join(" ") };
+            JobInfo {
+                pid: p.pid,
+                pgid: p.pgrp,
+                command,
+                stopped: p.state == 'T',
+            }
+        })
+        .collect()
+}
+
+/// The process in the foreground of a session's terminal
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ForegroundProcess {
+    pub pid: u32,
+    pub pgid: i32,
+    /// Executable name from `/proc/<pid>/stat`, e.g. "vim"
+    pub name: String,
+    pub argv: Vec<String>,
+    /// The shell itself is in the foreground, i.e. nothing else is running
+    pub is_shell: bool,
+}
+
+/// Find the foreground process of a session
+///
+/// Prefers the leader of the foreground process group; if it already exited (e.g. the first
+/// command of a pipeline), any other member of the group is reported.
+pub fn foreground_process(shell_pid: u32, foreground_pgrp: Option<i32>) -> Option<ForegroundProcess> {
+    let stat = foreground_pgrp
+        .and_then(|pgrp| {
+            u32::try_from(pgrp)
+                .ok()
+                .and_then(procfs::read_stat)
+                .filter(|p| p.state != 'Z')
+                .or_else(|| {
+                    procfs::descendants(shell_pid)
+                        .into_iter()
+                        .find(|p| p.pgrp == pgrp && p.state != 'Z')
+                })
+        })
+        .or_else(|| procfs::read_stat(shell_pid))?;
+
+    Some(ForegroundProcess {
+        pid: stat.pid,
+        pgid: stat.pgrp,
+        argv: procfs::read_cmdline(stat.pid),
+        is_shell: stat.pid == shell_pid,
+        name: stat.comm,
+    })
+}
@@ -0,0 +1,105 @@
+// Input latency instrumentation
+// Opt-in per session: keystrokes written to the terminal are timestamped and matched to the next
+// output read back, giving keystroke-to-echo latency for diagnosing sluggish sessions
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Writes longer than this are pastes or scripted input, not keystrokes
+const MAX_KEYSTROKE_BYTES: usize = 16;
+
+/// Keystrokes with no output within this time are not echoed (passwords, `stty -echo`)
+const ECHO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Samples kept per session; older ones are dropped
+const MAX_SAMPLES: usize = 1000;
+
+/// Keystrokes waiting for their echo; a program that never echoes must not grow the queue
+const MAX_PENDING: usize = 64;
+
+/// Keystroke-to-echo latency of a session, in milliseconds
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyReport {
+    pub enabled: bool,
+    pub samples: usize,
+    pub p50_ms: Option<f64>,
+    pub p90_ms: Option<f64>,
+    pub p99_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+}
+
+/// Pending keystrokes and measured latencies of one session
+#[derive(Debug, Default)]
+pub struct LatencyProbe {
+    /// Keystrokes waiting for their echo and their length, oldest first
+    pending: VecDeque<(Instant, usize)>,
+    samples: VecDeque<Duration>,
+}
+
+impl LatencyProbe {
+    /// Record input about to be written to the terminal
+    pub fn on_input(&mut self, bytes: usize) {
+        if bytes == 0 || bytes > MAX_KEYSTROKE_BYTES {
+            return;
+        }
+        let now = Instant::now();
+        self.drop_stale(now);
+        if self.pending.len() == MAX_PENDING {
+            self.pending.pop_front();
+        }
+        self.pending.push_back((now, bytes));
+    }
+
+    /// Match `bytes` of output read from the terminal to the keystrokes it can echo, oldest first
+    ///
+    /// Keys typed faster than the program answers come back in one read.
+    pub fn on_output(&mut self, bytes: usize) {
+        let now = Instant::now();
+        self.drop_stale(now);
+        let mut echoed = 0;
+        while let Some(&(sent, length)) = self.pending.front() {
+            // Always match one, as an echo may be shorter than the key (e.g. a cursor move)
+            if echoed > 0 && echoed + length > bytes {
+                break;
+            }
+            self.pending.pop_front();
+            echoed += length;
+            if self.samples.len() == MAX_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(now.duration_since(sent));
+        }
+    }
+
+    /// Forget keystrokes that were not echoed in time
+    fn drop_stale(&mut self, now: Instant) {
+        while let Some(&(sent, _)) = self.pending.front() {
+            if now.duration_since(sent) <= ECHO_TIMEOUT {
+                break;
+            }
+            self.pending.pop_front();
+        }
+    }
+
+    /// Percentiles of the samples collected so far
+    pub fn report(&self) -> LatencyReport {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        let percentile = |p: f64| -> Option<f64> {
+            let last = sorted.len().checked_sub(1)?;
+            let index = ((last as f64) * p).round() as usize;
+            Some(sorted[index].as_secs_f64() * 1000.0)
+        };
+
+        LatencyReport {
+            enabled: true,
+            samples: sorted.len(),
+            p50_ms: percentile(0.5),
+            p90_ms: percentile(0.9),
+            p99_ms: percentile(0.99),
+            max_ms: percentile(1.0),
+        }
+    }
+}
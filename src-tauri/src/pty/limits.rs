@@ -0,0 +1,116 @@
+// Process resource limits
+// Profile umask, open-file limit, and core-dump size for reproducing CI-like constrained
+// environments. The umask is set in the child between fork and exec by portable-pty; the
+// resource limits are set by prlimit(1) run in front of the shell, which execs it once they are
+// in place, so the shell and everything it starts inherit them.
+
+use crate::exec;
+use serde::{Deserialize, Serialize};
+
+/// A resource limit value: a number or `"unlimited"`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Limit {
+    Value(u64),
+    Keyword(LimitKeyword),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LimitKeyword {
+    Unlimited,
+}
+
+impl Limit {
+    fn as_rlim(self, scale: u64) -> libc::rlim_t {
+        match self {
+            Limit::Value(value) => value.saturating_mul(scale) as libc::rlim_t,
+            Limit::Keyword(LimitKeyword::Unlimited) => libc::RLIM_INFINITY,
+        }
+    }
+}
+
+/// Resource limits applied to a session's shell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Maximum number of open file descriptors (`ulimit -n`)
+    pub open_files: Option<Limit>,
+    /// Maximum core dump size in KiB (`ulimit -c`); 0 disables core dumps
+    pub core_dump_kb: Option<Limit>,
+}
+
+impl ResourceLimits {
+    pub fn is_default(&self) -> bool {
+        self.open_files.is_none() && self.core_dump_kb.is_none()
+    }
+
+    /// Command line that sets the soft limits and then runs `argv`
+    ///
+    /// prlimit(1) sets them on itself and execs the command, so they are in place before the
+    /// shell reads its rc files. Soft limits above the hard limit, which the shell inherits from
+    /// this process, are left out and returned as errors rather than raising the hard limit,
+    /// which would need CAP_SYS_RESOURCE, or failing the spawn.
+    pub fn wrap(&self, argv: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let mut options = Vec::new();
+        let mut errors = Vec::new();
+        let requested = [
+            (self.open_files, libc::RLIMIT_NOFILE, 1, "nofile", "open-file limit"),
+            (self.core_dump_kb, libc::RLIMIT_CORE, 1024, "core", "core dump size"),
+        ];
+        for (limit, resource, scale, option, name) in requested {
+            let Some(limit) = limit else {
+                continue;
+            };
+            let soft = limit.as_rlim(scale);
+            match hard_limit(resource) {
+                Ok(hard) if soft > hard => {
+                    errors.push(format!("Failed to set {}: exceeds the hard limit of {}", name, hard));
+                }
+                Ok(_) => {
+                    let value = if soft == libc::RLIM_INFINITY {
+                        "unlimited".to_string()
+                    } else {
+                        soft.to_string()
+                    };
+                    // `soft:` leaves the hard limit as it is
+                    options.push(format!("--{}={}:", option, value));
+                }
+                Err(e) => errors.push(format!("Failed to set {}: {}", name, e)),
+            }
+        }
+        if options.is_empty() {
+            return (argv, errors);
+        }
+        let Some(prlimit) = exec::find_program("prlimit") else {
+            errors.push("Resource limits need prlimit from util-linux to be installed".to_string());
+            return (argv, errors);
+        };
+
+        let mut wrapped = vec![prlimit.display().to_string()];
+        wrapped.extend(options);
+        wrapped.push("--".to_string());
+        wrapped.extend(argv);
+        (wrapped, errors)
+    }
+}
+
+/// Parse an octal umask such as `"022"` or `"0077"`
+pub fn parse_umask(umask: &str) -> Result<libc::mode_t, String> {
+    libc::mode_t::from_str_radix(umask.trim(), 8)
+        .ok()
+        .filter(|mask| *mask <= 0o777)
+        .ok_or_else(|| format!("Invalid umask: {}", umask))
+}
+
+/// Hard limit of this process, which a child it spawns inherits
+fn hard_limit(resource: libc::__rlimit_resource_t) -> Result<libc::rlim_t, String> {
+    let mut current = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `current` is a valid rlimit for the kernel to fill in
+    if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(current.rlim_max)
+}
@@ -0,0 +1,516 @@
+// Output links
+// Finds links in a session's output as it is read: OSC 8 hyperlinks, plain URLs, and paths that
+// exist, and publishes the lines that have any with the ranges the frontend should make
+// clickable. Output is replayed on a line model so carriage returns and erases resolve to what
+// was finally shown; full-screen programs redraw rather than print lines and are left alone.
+// Looking paths up on the filesystem is left to a resolver thread, off the reader's path.
+
+use crate::events::{EventBus, EventKind};
+use crate::hints::{self, TokenKind};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest line scanned, in characters; the rest of a longer line is dropped
+const MAX_LINE_CHARS: usize = 4096;
+/// Longest OSC 8 payload kept
+const MAX_OSC_BYTES: usize = 4096;
+/// Most words of a line checked against the filesystem
+const MAX_PATHS_PER_LINE: usize = 8;
+/// Batches of lines waiting for the resolver; once full, further lines go unlinked
+const RESOLVE_QUEUE: usize = 256;
+/// How long a path lookup is reused, so a file created since is linked soon after
+const PATH_CACHE_TTL: Duration = Duration::from_secs(5);
+/// Lookups remembered before the cache starts over
+const MAX_CACHED_PATHS: usize = 4096;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkKind {
+    /// An OSC 8 hyperlink; the text need not look like its target
+    Hyperlink,
+    Url,
+    File,
+    Directory,
+}
+
+/// A clickable range of a line
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLink {
+    pub kind: LinkKind,
+    /// UTF-16 offsets into the line, as JavaScript indexes strings
+    pub start: usize,
+    pub end: usize,
+    /// URI to open, or the absolute path of a file or directory
+    pub target: String,
+    /// `id` parameter of an OSC 8 hyperlink, tying together its parts on different lines
+    pub id: Option<String>,
+}
+
+/// A line of output with links, published in batches on `pty://{id}/links`
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedLine {
+    /// Text as displayed, without trailing blanks
+    pub text: String,
+    pub links: Vec<OutputLink>,
+}
+
+/// A line as printed, before URLs and paths are looked for
+pub struct PrintedLine {
+    text: Vec<char>,
+    /// OSC 8 hyperlinks as character ranges
+    hyperlinks: Vec<(usize, usize, Hyperlink)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Hyperlink {
+    uri: String,
+    id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, terminated by BEL or ST
+    String,
+    StringEscape,
+}
+
+/// Per-session link scanner, owned by the output processor
+#[derive(Default)]
+pub struct LinkScanner {
+    state: State,
+    params: Vec<u8>,
+    osc: Option<Vec<u8>>,
+    /// Bytes of a character split across reads
+    partial: Vec<u8>,
+    line: Vec<char>,
+    cursor: usize,
+    hyperlinks: Vec<(usize, usize, Hyperlink)>,
+    /// The hyperlink being printed and the column it started at
+    open: Option<(usize, Hyperlink)>,
+}
+
+impl LinkScanner {
+    /// Scan a chunk of output, returning the lines it completed that may have links
+    pub fn feed(&mut self, data: &[u8]) -> Vec<PrintedLine> {
+        let mut lines = Vec::new();
+        for &byte in data {
+            self.advance(byte, &mut lines);
+        }
+        lines
+    }
+
+    fn advance(&mut self, byte: u8, lines: &mut Vec<PrintedLine>) {
+        match self.state {
+            State::Ground => match byte {
+                0x1b => self.state = State::Escape,
+                b'\n' | 0x0b | 0x0c => self.newline(lines),
+                b'\r' => self.cursor = 0,
+                0x08 => self.cursor = self.cursor.saturating_sub(1),
+                b'\t' => self.cursor = (self.cursor / 8 + 1) * 8,
+                0x00..=0x1f | 0x7f => {}
+                _ => self.byte(byte),
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.params.clear();
+                    self.state = State::Csi;
+                }
+                b']' | b'P' | b'_' | b'^' | b'X' => {
+                    self.osc = (byte == b']').then(Vec::new);
+                    self.state = State::String;
+                }
+                0x1b => {}
+                0x20..=0x2f => self.state = State::EscapeIntermediate,
+                _ => self.state = State::Ground,
+            },
+            State::EscapeIntermediate => match byte {
+                0x20..=0x2f => {}
+                0x1b => self.state = State::Escape,
+                _ => self.state = State::Ground,
+            },
+            State::Csi => match byte {
+                0x1b => self.state = State::Escape,
+                0x18 | 0x1a => self.state = State::Ground,
+                0x20..=0x3f if self.params.len() < 32 => self.params.push(byte),
+                0x20..=0x3f => {}
+                0x40..=0x7e => {
+                    self.csi(byte);
+                    self.state = State::Ground;
+                }
+                _ => {}
+            },
+            State::String => match byte {
+                0x07 => {
+                    self.dispatch_osc();
+                    self.state = State::Ground;
+                }
+                0x18 | 0x1a => self.state = State::Ground,
+                0x1b => self.state = State::StringEscape,
+                _ => match &mut self.osc {
+                    Some(osc) if osc.len() < MAX_OSC_BYTES => osc.push(byte),
+                    Some(_) => self.osc = None,
+                    None => {}
+                },
+            },
+            State::StringEscape => match byte {
+                b'\\' => {
+                    self.dispatch_osc();
+                    self.state = State::Ground;
+                }
+                b'[' => {
+                    self.params.clear();
+                    self.state = State::Csi;
+                }
+                0x1b => {}
+                _ => self.state = State::String,
+            },
+        }
+    }
+
+    /// A printable byte, collected until it completes a character
+    fn byte(&mut self, byte: u8) {
+        if byte < 0x80 {
+            self.partial.clear();
+            self.print(byte as char);
+            return;
+        }
+        if byte >= 0xc0 {
+            self.partial.clear();
+        }
+        self.partial.push(byte);
+        match std::str::from_utf8(&self.partial) {
+            Ok(text) => {
+                let c = text.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.partial.clear();
+                self.print(c);
+            }
+            Err(e) if e.error_len().is_some() || self.partial.len() >= 4 => {
+                self.partial.clear();
+                self.print(char::REPLACEMENT_CHARACTER);
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        if self.cursor >= MAX_LINE_CHARS {
+            return;
+        }
+        match self.line.get_mut(self.cursor) {
+            Some(cell) => *cell = c,
+            None => {
+                self.line.resize(self.cursor, ' ');
+                self.line.push(c);
+            }
+        }
+        self.cursor += 1;
+    }
+
+    fn csi(&mut self, final_byte: u8) {
+        let params = std::str::from_utf8(&self.params).unwrap_or_default();
+        let count = params.split(';').next().and_then(|p| p.parse::<usize>().ok()).unwrap_or(1).max(1);
+        match final_byte {
+            b'D' => self.cursor = self.cursor.saturating_sub(count),
+            b'C' => self.cursor += count,
+            b'G' => self.cursor = count - 1,
+            b'K' => match params {
+                "" | "0" => self.line.truncate(self.cursor),
+                "1" => self.line.iter_mut().take(self.cursor + 1).for_each(|cell| *cell = ' '),
+                _ => self.line.clear(),
+            },
+            // A cleared screen leaves nothing of the line to link
+            b'J' if params == "2" || params == "3" => {
+                self.line.clear();
+                self.hyperlinks.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn dispatch_osc(&mut self) {
+        let Some(osc) = self.osc.take() else {
+            return;
+        };
+        let Some(payload) = osc.strip_prefix(b"8;") else {
+            return;
+        };
+        // `OSC 8 ; params ; URI`, where params are `key=value` pairs separated by colons; an empty
+        // URI ends the link
+        let payload = String::from_utf8_lossy(payload);
+        let Some((params, uri)) = payload.split_once(';') else {
+            return;
+        };
+        self.close_hyperlink();
+        if uri.is_empty() {
+            return;
+        }
+        let id = params
+            .split(':')
+            .find_map(|param| param.strip_prefix("id="))
+            .filter(|id| !id.is_empty())
+            .map(str::to_string);
+        let hyperlink = Hyperlink {
+            uri: uri.to_string(),
+            id,
+        };
+        self.open = Some((self.cursor, hyperlink));
+    }
+
+    fn close_hyperlink(&mut self) {
+        if let Some((start, hyperlink)) = self.open.take() {
+            let end = self.cursor.min(self.line.len());
+            if start < end {
+                self.hyperlinks.push((start, end, hyperlink));
+            }
+        }
+    }
+
+    fn newline(&mut self, lines: &mut Vec<PrintedLine>) {
+        // A hyperlink still open continues on the next line
+        let open = self.open.as_ref().map(|(_, hyperlink)| hyperlink.clone());
+        self.close_hyperlink();
+        self.open = open.map(|hyperlink| (0, hyperlink));
+
+        let text = std::mem::take(&mut self.line);
+        let hyperlinks = std::mem::take(&mut self.hyperlinks);
+        self.cursor = 0;
+        // Lines without a slash can have neither a URL nor a path worth linking
+        if !hyperlinks.is_empty() || text.contains(&'/') {
+            lines.push(PrintedLine { text, hyperlinks });
+        }
+    }
+}
+
+/// Lines completed by one read of a session, with what their paths are relative to
+struct ResolveJob {
+    session_id: String,
+    lines: Vec<PrintedLine>,
+    local: bool,
+    cwd: Option<PathBuf>,
+}
+
+/// Annotates printed lines on a thread of its own and publishes the ones with links
+///
+/// One resolver serves every session; jobs are handled in the order they are sent, so a
+/// session's lines are published in the order they were printed.
+#[derive(Clone)]
+pub struct LinkResolver {
+    jobs: SyncSender<ResolveJob>,
+}
+
+impl LinkResolver {
+    /// Start the resolver thread; it ends once every clone is dropped
+    pub fn start(events: Arc<EventBus>) -> Self {
+        let (jobs, received) = mpsc::sync_channel(RESOLVE_QUEUE);
+        let started = std::thread::Builder::new()
+            .name("link-resolver".to_string())
+            .spawn(move || resolve_jobs(received, events));
+        if let Err(e) = started {
+            log::warn!("Failed to start the link resolver: {}", e);
+        }
+        Self { jobs }
+    }
+
+    /// Queue lines for annotation without waiting; lines are dropped while the resolver is behind
+    pub fn resolve(&self, session_id: &str, lines: Vec<PrintedLine>, local: bool, cwd: Option<PathBuf>) {
+        let job = ResolveJob {
+            session_id: session_id.to_string(),
+            lines,
+            local,
+            cwd,
+        };
+        match self.jobs.try_send(job) {
+            Ok(()) => {}
+            Err(TrySendError::Full(job)) => log::debug!("Link resolver busy, {} lines not linked", job.lines.len()),
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+fn resolve_jobs(received: Receiver<ResolveJob>, events: Arc<EventBus>) {
+    let mut paths = PathCache::default();
+    while let Ok(job) = received.recv() {
+        let lines: Vec<_> = job
+            .lines
+            .into_iter()
+            .filter_map(|line| annotate(line, job.local, job.cwd.as_deref(), &mut paths))
+            .collect();
+        if !lines.is_empty() {
+            events.publish(&job.session_id, EventKind::Links(lines));
+        }
+    }
+}
+
+/// A word found to name a path: the length to highlight, whether it is a directory, and where
+type FoundPath = (usize, LinkKind, String);
+
+/// Recent path lookups, as output often repeats the same paths (build errors, `ls`, `git status`)
+#[derive(Default)]
+pub struct PathCache {
+    entries: HashMap<(PathBuf, String), (Instant, Option<FoundPath>)>,
+}
+
+impl PathCache {
+    /// The path a word names relative to `base`, looked up unless done recently
+    fn lookup(&mut self, word: &str, base: &Path) -> Option<FoundPath> {
+        let key = (base.to_path_buf(), word.to_string());
+        if let Some((at, found)) = self.entries.get(&key) {
+            if at.elapsed() < PATH_CACHE_TTL {
+                return found.clone();
+            }
+        }
+        if self.entries.len() >= MAX_CACHED_PATHS {
+            self.entries.clear();
+        }
+        let found = hints::path_token(word, base).and_then(|(length, token)| {
+            let kind = match token.kind {
+                TokenKind::Directory => LinkKind::Directory,
+                _ => LinkKind::File,
+            };
+            Some((length, kind, token.target?))
+        });
+        self.entries.insert(key, (Instant::now(), found.clone()));
+        found
+    }
+}
+
+/// Find the URLs and existing paths of a printed line, alongside its hyperlinks
+///
+/// Paths are only looked for when they are `local`, and relative ones only with a `cwd` to
+/// resolve them against.
+pub fn annotate(line: PrintedLine, local: bool, cwd: Option<&Path>, paths: &mut PathCache) -> Option<LinkedLine> {
+    let text: String = line.text.iter().collect::<String>().trim_end().to_string();
+    // Character columns to byte offsets of the trimmed text
+    let mut offsets: Vec<usize> = text.char_indices().map(|(index, _)| index).collect();
+    offsets.push(text.len());
+    let byte_at = |column: usize| offsets[column.min(offsets.len() - 1)];
+
+    let mut found: Vec<(usize, usize, LinkKind, String, Option<String>)> = Vec::new();
+    for (start, end, hyperlink) in line.hyperlinks {
+        let (start, end) = (byte_at(start), byte_at(end));
+        if start < end {
+            found.push((start, end, LinkKind::Hyperlink, hyperlink.uri, hyperlink.id));
+        }
+    }
+    let free = |found: &[(usize, usize, LinkKind, String, Option<String>)], start: usize, end: usize| {
+        !found.iter().any(|(s, e, ..)| start < *e && *s < end)
+    };
+    for (start, end) in hints::urls(&text) {
+        if free(&found, start, end) {
+            found.push((start, end, LinkKind::Url, text[start..end].to_string(), None));
+        }
+    }
+    let mut checked = 0;
+    for (start, end) in hints::words(&text) {
+        let word = &text[start..end];
+        // Bare names are mostly prose; only words with a slash are worth a lookup
+        if !local || checked >= MAX_PATHS_PER_LINE || !word.contains('/') || !free(&found, start, end) {
+            continue;
+        }
+        let base = match cwd {
+            Some(cwd) => cwd,
+            None if word.starts_with('/') || word.starts_with("~/") => Path::new("/"),
+            None => continue,
+        };
+        checked += 1;
+        if let Some((length, kind, target)) = paths.lookup(word, base) {
+            found.push((start, start + length, kind, target, None));
+        }
+    }
+    if found.is_empty() {
+        return None;
+    }
+
+    found.sort_by_key(|(start, ..)| *start);
+    let links = found
+        .into_iter()
+        .map(|(start, end, kind, target, id)| {
+            let start_utf16 = hints::utf16_len(&text[..start]);
+            OutputLink {
+                kind,
+                start: start_utf16,
+                end: start_utf16 + hints::utf16_len(&text[start..end]),
+                target,
+                id,
+            }
+        })
+        .collect();
+    Some(LinkedLine { text, links })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printed(line: &PrintedLine) -> String {
+        line.text.iter().collect()
+    }
+
+    #[test]
+    fn carriage_return_and_erase_leave_the_final_text() {
+        let mut scanner = LinkScanner::default();
+        let lines = scanner.feed(b"progress 10%\rsaved /x/y\x1b[K\nno slash here\n");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(printed(&lines[0]), "saved /x/y");
+    }
+
+    #[test]
+    fn hyperlink_split_across_reads_keeps_its_range_and_id() {
+        let mut scanner = LinkScanner::default();
+        assert!(scanner.feed(b"see \x1b]8;id=doc;https://example.com/gu").is_empty());
+        let lines = scanner.feed(b"ide\x1b\\docs\x1b]8;;\x07 here\n");
+        assert_eq!(lines.len(), 1);
+
+        let linked = annotate(lines.into_iter().next().unwrap(), false, None, &mut PathCache::default());
+        let link = OutputLink {
+            kind: LinkKind::Hyperlink,
+            start: 4,
+            end: 8,
+            target: "https://example.com/guide".to_string(),
+            id: Some("doc".to_string()),
+        };
+        assert_eq!(linked, Some(LinkedLine { text: "see docs here".to_string(), links: vec![link] }));
+    }
+
+    #[test]
+    fn urls_are_offset_in_utf16_without_trailing_punctuation() {
+        let mut scanner = LinkScanner::default();
+        let line = scanner.feed("\u{1f600} https://example.com/a.\n".as_bytes()).pop().unwrap();
+        let linked = annotate(line, false, None, &mut PathCache::default()).unwrap();
+        assert_eq!(linked.links.len(), 1);
+        assert_eq!(linked.links[0].kind, LinkKind::Url);
+        assert_eq!((linked.links[0].start, linked.links[0].end), (3, 24));
+        assert_eq!(linked.links[0].target, "https://example.com/a");
+    }
+
+    #[test]
+    fn existing_paths_are_linked_only_for_local_sessions() {
+        let cwd = std::env::temp_dir().join(format!("xterminal-links-{}", std::process::id()));
+        std::fs::create_dir_all(cwd.join("src")).unwrap();
+        std::fs::write(cwd.join("src/main.rs"), "").unwrap();
+        let output = b"error: src/main.rs:12:5 and src/missing.rs in src/\n";
+
+        let mut paths = PathCache::default();
+        let line = LinkScanner::default().feed(output).pop().unwrap();
+        let linked = annotate(line, true, Some(&cwd), &mut paths).unwrap();
+        let found: Vec<_> = linked.links.iter().map(|link| (link.kind, link.start, link.end)).collect();
+        assert_eq!(found, [(LinkKind::File, 7, 23), (LinkKind::Directory, 46, 50)]);
+        assert_eq!(linked.links[0].target, cwd.join("src/main.rs").display().to_string());
+
+        let line = LinkScanner::default().feed(output).pop().unwrap();
+        assert_eq!(annotate(line, false, Some(&cwd), &mut paths), None);
+
+        std::fs::remove_dir_all(&cwd).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+// Man page viewer
+// Builds read-only pager sessions for manual pages, and the section outline used to jump
+// between NAME, SYNOPSIS, OPTIONS, and so on
+
+use super::session::SessionInfo;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Colors for the bold and underlined text groff emits, through less's termcap overrides
+const TERMCAP_COLORS: &[(&str, &str)] = &[
+    ("LESS_TERMCAP_md", "\x1b[1;36m"),
+    ("LESS_TERMCAP_me", "\x1b[0m"),
+    ("LESS_TERMCAP_us", "\x1b[4;32m"),
+    ("LESS_TERMCAP_ue", "\x1b[0m"),
+    ("LESS_TERMCAP_so", "\x1b[1;33;44m"),
+    ("LESS_TERMCAP_se", "\x1b[0m"),
+];
+
+/// A top-level heading of a man page
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManSection {
+    pub title: String,
+    /// 1-based line in the pager; typing `<line>g` in the session jumps there
+    pub line: usize,
+}
+
+/// A man page opened in a viewer session
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ManPage {
+    pub session: SessionInfo,
+    pub page: String,
+    pub section: Option<String>,
+    /// Headings in page order, for section navigation
+    pub sections: Vec<ManSection>,
+}
+
+/// Reject pages and sections that man would read as options or paths
+pub fn validate(page: &str, section: Option<&str>) -> Result<(), String> {
+    let valid_page = !page.is_empty()
+        && !page.starts_with('-')
+        && page.chars().all(|c| c.is_ascii_alphanumeric() || "._:+-@".contains(c));
+    if !valid_page {
+        return Err(format!("Invalid man page name: {:?}", page));
+    }
+    if let Some(section) = section {
+        let valid_section = (1..=4).contains(&section.len())
+            && section.starts_with(|c: char| c.is_ascii_digit() || c == 'n')
+            && section.chars().all(|c| c.is_ascii_alphanumeric());
+        if !valid_section {
+            return Err(format!("Invalid man section: {:?}", section));
+        }
+    }
+    Ok(())
+}
+
+/// Arguments to `man` for a page
+pub fn args(page: &str, section: Option<&str>) -> Vec<String> {
+    section.into_iter().chain([page]).map(str::to_string).collect()
+}
+
+/// Environment for the viewer session: secure less with colors
+pub fn env() -> HashMap<String, String> {
+    let mut env: HashMap<String, String> = TERMCAP_COLORS
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    env.insert("MANPAGER".into(), "less -R -K -+F".into());
+    env.insert("PAGER".into(), "less".into());
+    env.insert("LESSSECURE".into(), "1".into());
+    // Input preprocessors may run arbitrary programs
+    env.insert("LESSOPEN".into(), String::new());
+    env.insert("LESSCLOSE".into(), String::new());
+    env
+}
+
+/// Render the page as plain text at `cols` columns and list its headings
+///
+/// Fails the same way `man` does when the page does not exist.
+pub fn outline(page: &str, section: Option<&str>, cols: u16) -> Result<Vec<ManSection>, String> {
+    let output = Command::new("man")
+        .args(args(page, section))
+        .env("MANWIDTH", cols.to_string())
+        .env("MANPAGER", "cat")
+        .env("PAGER", "cat")
+        .env_remove("MAN_KEEP_FORMATTING")
+        .output()
+        .map_err(|e| format!("Failed to run man: {}", e))?;
+
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if message.is_empty() {
+            format!("No manual entry for {}", page)
+        } else {
+            message
+        });
+    }
+
+    let text = plain_text(&String::from_utf8_lossy(&output.stdout));
+    let lines: Vec<&str> = text.lines().collect();
+    let last = lines.iter().rposition(|line| !line.trim().is_empty()).unwrap_or(0);
+
+    // Headings start in the first column; the first and last lines are the page header and footer
+    Ok(lines
+        .iter()
+        .enumerate()
+        .filter(|(index, line)| *index != 0 && *index != last && line.starts_with(|c: char| !c.is_whitespace()))
+        .map(|(index, line)| ManSection {
+            title: line.trim().to_string(),
+            line: index + 1,
+        })
+        .collect())
+}
+
+/// Drop the backspace overstrikes groff uses for bold (`X\bX`) and underline (`_\bX`), and the
+/// SGR sequences it emits instead when configured to
+fn plain_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{8}' => {
+                result.pop();
+            }
+            '\u{1b}' if chars.peek() == Some(&'[') => {
+                // Skip parameters up to and including the final byte
+                chars.next();
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
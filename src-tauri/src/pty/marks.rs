@@ -0,0 +1,103 @@
+// Command lifecycle
+// Follows the semantic prompt marks of shell integration (OSC 133, or VS Code's OSC 633) as they
+// are read, turning them into command events: a command started, with the line the user typed,
+// and a command finished, with its exit status and how long it ran. The frontend builds prompt
+// navigation, duration badges, and re-running the last command on these.
+
+use super::escape::PromptMark;
+use super::script;
+use serde::Serialize;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A command began running, published on `pty://{id}/command-started`
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandStarted {
+    /// Counts up from 1 within a session; the finish event carries the same ID
+    pub id: u64,
+    /// Command line as echoed, when one was seen between the B and C marks
+    pub command: Option<String>,
+    /// Unix time in milliseconds
+    pub started_at: u64,
+}
+
+/// A command ended, published on `pty://{id}/command-finished`
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandFinished {
+    pub id: u64,
+    pub command: Option<String>,
+    /// None when the shell did not report it
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+}
+
+/// What a prompt mark meant for the session's commands
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lifecycle {
+    /// A prompt is shown and the shell waits for a command
+    Prompt,
+    Started(CommandStarted),
+    Finished(CommandFinished),
+}
+
+struct Running {
+    started: CommandStarted,
+    at: Instant,
+}
+
+/// Per-session command state, owned by the output processor
+#[derive(Default)]
+pub struct CommandTracker {
+    next_id: u64,
+    running: Option<Running>,
+}
+
+impl CommandTracker {
+    /// What a mark meant, in the order it should be published
+    pub fn on_mark(&mut self, mark: &PromptMark) -> Vec<Lifecycle> {
+        match mark {
+            // A new prompt without a D mark: the shell does not report ends, or the command was
+            // interrupted before it could
+            PromptMark::PromptStart => self
+                .finish(None)
+                .map(Lifecycle::Finished)
+                .into_iter()
+                .chain([Lifecycle::Prompt])
+                .collect(),
+            PromptMark::CommandStart => Vec::new(),
+            PromptMark::CommandExecuted(echo) => {
+                self.next_id += 1;
+                let started = CommandStarted {
+                    id: self.next_id,
+                    command: echo
+                        .as_deref()
+                        .and_then(script::echoed_command)
+                        .filter(|command| !command.is_empty()),
+                    started_at: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_millis() as u64),
+                };
+                self.running = Some(Running {
+                    started: started.clone(),
+                    at: Instant::now(),
+                });
+                vec![Lifecycle::Started(started)]
+            }
+            // Shells also send D after an empty command line, which never ran
+            PromptMark::CommandFinished(exit_code) => {
+                self.finish(*exit_code).map(Lifecycle::Finished).into_iter().collect()
+            }
+        }
+    }
+
+    fn finish(&mut self, exit_code: Option<i32>) -> Option<CommandFinished> {
+        let running = self.running.take()?;
+        Some(CommandFinished {
+            id: running.started.id,
+            command: running.started.command,
+            exit_code,
+            duration_ms: running.at.elapsed().as_millis() as u64,
+        })
+    }
+}
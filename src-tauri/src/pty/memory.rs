@@ -0,0 +1,80 @@
+// Session memory accounting
+// Buffers held by the backend and input waiting to be written report their size here so the
+// monitor can cap runaway sessions
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Default per-session buffer limit
+pub const DEFAULT_LIMIT_BYTES: usize = 64 * 1024 * 1024;
+
+/// Category of backend buffer
+#[derive(Debug, Clone, Copy)]
+pub enum BufferKind {
+    Scrollback,
+    /// Input queued for a terminal whose program has not read it yet
+    Pending,
+}
+
+/// Memory held by a session's backend buffers
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub scrollback_bytes: usize,
+    pub pending_bytes: usize,
+    pub total_bytes: usize,
+    pub limit_bytes: usize,
+}
+
+/// Per-session counters updated by buffer owners
+#[derive(Default)]
+pub struct MemoryAccount {
+    scrollback: AtomicUsize,
+    pending: AtomicUsize,
+    trim_requested: AtomicBool,
+}
+
+impl MemoryAccount {
+    /// Record bytes added to a buffer
+    pub fn add(&self, kind: BufferKind, bytes: usize) {
+        self.counter(kind).fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record bytes released from a buffer
+    pub fn release(&self, kind: BufferKind, bytes: usize) {
+        let counter = self.counter(kind);
+        let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+            Some(current.saturating_sub(bytes))
+        });
+    }
+
+    /// Whether the watchdog asked buffers to shrink, clearing the request
+    pub fn take_trim_request(&self) -> bool {
+        self.trim_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Current usage against a limit
+    pub fn usage(&self, limit_bytes: usize) -> MemoryUsage {
+        let scrollback_bytes = self.scrollback.load(Ordering::Relaxed);
+        let pending_bytes = self.pending.load(Ordering::Relaxed);
+        MemoryUsage {
+            scrollback_bytes,
+            pending_bytes,
+            total_bytes: scrollback_bytes + pending_bytes,
+            limit_bytes,
+        }
+    }
+
+    /// Ask buffer owners to trim down below the limit
+    pub fn request_trim(&self) {
+        self.trim_requested.store(true, Ordering::Relaxed);
+    }
+
+    fn counter(&self, kind: BufferKind) -> &AtomicUsize {
+        match kind {
+            BufferKind::Scrollback => &self.scrollback,
+            BufferKind::Pending => &self.pending,
+        }
+    }
+}
+
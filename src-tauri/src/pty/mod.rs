@@ -1,5 +1,51 @@
 // PTY module - PTY (pseudo-terminal) management
 
+pub mod activity;
+pub mod backend;
+pub mod bandwidth;
+pub mod batch;
+pub mod bell;
+pub mod broadcast;
+pub mod clipboard;
+pub mod close_hooks;
+pub mod container;
+pub mod environment;
+pub mod escape;
+pub mod follow;
+pub mod hang;
+pub mod hibernate;
+pub mod input_pipe;
+pub mod input_queue;
+pub mod jobs;
+pub mod latency;
+pub mod limits;
+pub mod links;
+pub mod man;
+pub mod marks;
+pub mod memory;
+pub mod modes;
+pub mod monitor;
+pub mod output;
+pub mod priority;
+pub mod procfs;
+pub mod progress;
+pub mod recording;
+pub mod redact;
+pub mod resize;
+pub mod sandbox;
+pub mod scratch;
+pub mod script;
+pub mod scrollback;
 pub mod session;
+pub mod signal;
+pub mod terminfo;
+pub mod title;
+pub mod utf8;
 
-pub use session::{PtyManager, SessionInfo, SpawnOptions};
+#[cfg(test)]
+mod tests;
+
+pub use follow::FollowInfo;
+pub use jobs::JobInfo;
+pub use memory::MemoryUsage;
+pub use session::{PtyManager, SessionAppearance, SessionHeartbeat, SessionInfo, SpawnOptions};
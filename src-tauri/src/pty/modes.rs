@@ -0,0 +1,153 @@
+// Terminal mode tracking
+// Mirrors the DEC private modes applications toggle, so the backend knows the terminal's state
+
+use serde::{Deserialize, Serialize};
+
+/// Which mouse events the application asked to receive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MouseTracking {
+    #[default]
+    None,
+    /// Button presses only (mode 9)
+    X10,
+    /// Presses and releases (mode 1000)
+    Normal,
+    /// Motion while a button is held (mode 1002)
+    ButtonEvent,
+    /// All motion (mode 1003)
+    AnyEvent,
+}
+
+/// How mouse reports are encoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MouseEncoding {
+    #[default]
+    Default,
+    /// Mode 1005
+    Utf8,
+    /// Mode 1006
+    Sgr,
+    /// Mode 1015
+    Urxvt,
+}
+
+/// DEC private modes tracked per session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalModes {
+    /// Alternate screen buffer active (modes 47/1047/1049)
+    pub alt_screen: bool,
+    pub mouse_tracking: MouseTracking,
+    pub mouse_encoding: MouseEncoding,
+    /// Bracketed paste requested by the application (mode 2004)
+    pub bracketed_paste: bool,
+}
+
+/// A tracked mode that changed value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeChange {
+    AltScreen(bool),
+    Mouse(MouseTracking, MouseEncoding),
+    BracketedPaste(bool),
+}
+
+impl TerminalModes {
+    /// Apply a DECSET/DECRST and report the change, if any
+    pub fn apply(&mut self, mode: u16, enabled: bool) -> Option<ModeChange> {
+        match mode {
+            47 | 1047 | 1049 if self.alt_screen != enabled => {
+                self.alt_screen = enabled;
+                Some(ModeChange::AltScreen(enabled))
+            }
+            9 => self.set_mouse_tracking(MouseTracking::X10, enabled),
+            1000 => self.set_mouse_tracking(MouseTracking::Normal, enabled),
+            1002 => self.set_mouse_tracking(MouseTracking::ButtonEvent, enabled),
+            1003 => self.set_mouse_tracking(MouseTracking::AnyEvent, enabled),
+            1005 => self.set_mouse_encoding(MouseEncoding::Utf8, enabled),
+            1006 => self.set_mouse_encoding(MouseEncoding::Sgr, enabled),
+            1015 => self.set_mouse_encoding(MouseEncoding::Urxvt, enabled),
+            2004 if self.bracketed_paste != enabled => {
+                self.bracketed_paste = enabled;
+                Some(ModeChange::BracketedPaste(enabled))
+            }
+            _ => None,
+        }
+    }
+
+    /// Enabling a tracking mode replaces the current one; disabling only clears it if active
+    fn set_mouse_tracking(&mut self, tracking: MouseTracking, enabled: bool) -> Option<ModeChange> {
+        let next = match (enabled, self.mouse_tracking == tracking) {
+            (true, _) => tracking,
+            (false, true) => MouseTracking::None,
+            (false, false) => return None,
+        };
+        self.update_mouse(next, self.mouse_encoding)
+    }
+
+    fn set_mouse_encoding(&mut self, encoding: MouseEncoding, enabled: bool) -> Option<ModeChange> {
+        let next = match (enabled, self.mouse_encoding == encoding) {
+            (true, _) => encoding,
+            (false, true) => MouseEncoding::Default,
+            (false, false) => return None,
+        };
+        self.update_mouse(self.mouse_tracking, next)
+    }
+
+    fn update_mouse(&mut self, tracking: MouseTracking, encoding: MouseEncoding) -> Option<ModeChange> {
+        if tracking == self.mouse_tracking && encoding == self.mouse_encoding {
+            return None;
+        }
+        self.mouse_tracking = tracking;
+        self.mouse_encoding = encoding;
+        Some(ModeChange::Mouse(tracking, encoding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pty::escape::{EscapeScanner, TermEvent};
+
+    /// Apply the modes a chunk of output sets, as the output processor does
+    fn apply(modes: &mut TerminalModes, output: &[u8]) -> Vec<ModeChange> {
+        let mut events = Vec::new();
+        EscapeScanner::default().feed(output, &mut events);
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                TermEvent::PrivateMode { mode, enabled } => modes.apply(mode, enabled),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mouse_tracking_and_encoding_set_in_one_sequence() {
+        let mut modes = TerminalModes::default();
+        let changes = apply(&mut modes, b"\x1b[?1000;1006h");
+        assert_eq!(
+            changes,
+            [
+                ModeChange::Mouse(MouseTracking::Normal, MouseEncoding::Default),
+                ModeChange::Mouse(MouseTracking::Normal, MouseEncoding::Sgr),
+            ]
+        );
+        assert_eq!(modes.mouse_tracking, MouseTracking::Normal);
+        assert_eq!(modes.mouse_encoding, MouseEncoding::Sgr);
+
+        let changes = apply(&mut modes, b"\x1b[?1000;1006l");
+        assert_eq!(changes.last(), Some(&ModeChange::Mouse(MouseTracking::None, MouseEncoding::Default)));
+    }
+
+    #[test]
+    fn only_changes_are_reported() {
+        let mut modes = TerminalModes::default();
+        assert_eq!(apply(&mut modes, b"\x1b[?1049h\x1b[?1049h"), [ModeChange::AltScreen(true)]);
+        // Disabling a tracking mode that is not the current one leaves tracking alone
+        let changes = apply(&mut modes, b"\x1b[?1002h\x1b[?1000l");
+        assert_eq!(changes, [ModeChange::Mouse(MouseTracking::ButtonEvent, MouseEncoding::Default)]);
+        assert!(apply(&mut modes, b"\x1b[?2004l").is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+// Session monitor
+// Periodic background checks across all sessions (jobs, titles, privileges, containers, memory,
+// remote traffic, liveness, hibernation, unresponsive programs, activity and silence)
+
+use super::activity::MonitorAlert;
+use super::bandwidth::BandwidthMeter;
+use super::container::{self, SessionContainer};
+use super::hang::HangSettings;
+use super::hibernate::HibernationSettings;
+use super::jobs::{self, JobInfo};
+use super::procfs::{self, ProcessTree};
+use super::session::SessionMap;
+use crate::commands::settings;
+use crate::events::{EventBus, EventKind};
+use crate::sync::MutexExt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often the monitor polls session state
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Ticks between liveness heartbeats
+const HEARTBEAT_TICKS: u64 = 5;
+
+/// Ticks between remote bandwidth samples
+const BANDWIDTH_TICKS: u64 = 5;
+
+/// Ticks between looks for idle sessions to hibernate
+const HIBERNATE_TICKS: u64 = 60;
+
+/// Ticks between reloads of the hang detection settings
+const HANG_SETTINGS_TICKS: u64 = 30;
+
+/// Start the monitor loop for the lifetime of the app
+pub fn start(sessions: SessionMap, events: Arc<EventBus>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        let mut ticks: u64 = 0;
+        let mut hang_threshold = None;
+
+        loop {
+            interval.tick().await;
+            if ticks % HANG_SETTINGS_TICKS == 0 {
+                hang_threshold = settings::read_section::<HangSettings>("hangDetection").threshold();
+            }
+            ticks += 1;
+
+            // Collect events while holding the lock, publish after releasing it
+            let (heartbeat, bandwidth) = (ticks % HEARTBEAT_TICKS == 0, ticks % BANDWIDTH_TICKS == 0);
+            // A check that panics on one session skips the round rather than ending monitoring
+            let found = panic::catch_unwind(AssertUnwindSafe(|| tick(&sessions, heartbeat, bandwidth, hang_threshold)))
+                .unwrap_or_else(|_| {
+                    log::error!("Session monitor check panicked");
+                    Vec::new()
+                });
+            for (session_id, kind) in found {
+                events.publish(&session_id, kind);
+            }
+
+            if ticks % HIBERNATE_TICKS == 0 {
+                hibernate_idle(&sessions).await;
+            }
+        }
+    });
+}
+
+/// Move the scrollback of sessions idle past the configured time to disk
+///
+/// The next tick reports the sessions that went into hibernation.
+async fn hibernate_idle(sessions: &SessionMap) {
+    let settings: HibernationSettings = settings::read_section("hibernation");
+    if !settings.enabled {
+        return;
+    }
+    let idle = Duration::from_secs(u64::from(settings.idle_minutes) * 60);
+    let candidates: Vec<_> = sessions
+        .locked()
+        .iter()
+        .filter_map(|(id, session)| Some((id.clone(), session.hibernation_candidate(idle)?)))
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+
+    // Compressing takes a moment, so it happens without holding the session map
+    let min_bytes = settings.min_scrollback_kb * 1024;
+    let _ = tokio::task::spawn_blocking(move || {
+        for (id, shared) in candidates {
+            match shared.hibernate(&id, min_bytes) {
+                Ok(true) => log::info!("Session {} is idle, moved its scrollback to disk", id),
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to hibernate session {}: {}", id, e),
+            }
+        }
+    })
+    .await;
+}
+
+/// What a round needs of a session to inspect its processes without holding the session map
+struct Probe {
+    id: String,
+    pid: u32,
+    foreground: Option<i32>,
+    /// Meter and output and input counters, on rounds that sample remote traffic
+    bandwidth: Option<(Arc<Mutex<BandwidthMeter>>, u64, u64)>,
+}
+
+/// What was found of a session's processes, applied once the session map is held again
+struct Scanned {
+    id: String,
+    foreground: Option<i32>,
+    elevated: bool,
+    container: Option<SessionContainer>,
+    jobs: Vec<JobInfo>,
+}
+
+/// Run one round of checks and return the events to publish
+fn tick(
+    sessions: &SessionMap,
+    heartbeat: bool,
+    bandwidth: bool,
+    hang_threshold: Option<Duration>,
+) -> Vec<(String, EventKind)> {
+    let mut events = Vec::new();
+    let mut probes = Vec::new();
+
+    for (id, session) in sessions.locked().iter_mut() {
+        if heartbeat {
+            events.push((id.clone(), EventKind::Heartbeat(session.heartbeat())));
+        }
+
+        if let Some(state) = session.refresh_hibernation() {
+            events.push((id.clone(), EventKind::Hibernation(state)));
+        }
+
+        if let Some(usage) = session.check_memory() {
+            log::warn!(
+                "Session {} buffers use {} bytes (limit {}), trimming",
+                id,
+                usage.total_bytes,
+                usage.limit_bytes
+            );
+            events.push((id.clone(), EventKind::MemoryWarning(usage)));
+        }
+
+        match session.refresh_activity() {
+            Some(MonitorAlert::Activity { quiet }) => events.push((
+                id.clone(),
+                EventKind::Activity {
+                    quiet_seconds: quiet.as_secs(),
+                },
+            )),
+            Some(MonitorAlert::Silence { quiet }) => {
+                events.push((id.clone(), EventKind::Silence { seconds: quiet.as_secs() }))
+            }
+            None => {}
+        }
+
+        // Serial sessions have no process to inspect
+        if session.is_serial() {
+            continue;
+        }
+
+        if let Some(title) = session.refresh_title() {
+            events.push((id.clone(), EventKind::ComputedTitle { title }));
+        }
+
+        if let Some(location) = session.refresh_location() {
+            events.push((id.clone(), EventKind::Location(location)));
+        }
+
+        if let Some(state) = session.refresh_hang(hang_threshold) {
+            if state.unresponsive {
+                log::warn!("Session {} has not read its input for {} s", id, state.seconds);
+            }
+            events.push((id.clone(), EventKind::Unresponsive(state)));
+        }
+
+        probes.push(Probe {
+            id: id.clone(),
+            pid: session.pid,
+            foreground: session.process.foreground_pgrp(),
+            bandwidth: bandwidth.then(|| session.bandwidth_meter()),
+        });
+    }
+
+    // Walking /proc takes a while with many sessions, so it happens once per round and without
+    // holding the map
+    let processes = ProcessTree::scan();
+    let scanned: Vec<_> = probes
+        .into_iter()
+        .map(|probe| {
+            // The foreground process group leader, unless it already exited
+            let foreground_pid = probe
+                .foreground
+                .and_then(|pgrp| u32::try_from(pgrp).ok())
+                .filter(|pid| processes.get(*pid).is_some())
+                .unwrap_or(probe.pid);
+            if let Some((meter, output, input)) = probe.bandwidth {
+                // The session's own process counts too, for sessions that run ssh instead of a shell
+                let mut tree: Vec<_> = processes.get(probe.pid).cloned().into_iter().collect();
+                tree.extend(processes.descendants(probe.pid));
+                if let Some(stats) = meter.locked().sample(&tree, probe.foreground, output, input) {
+                    events.push((probe.id.clone(), EventKind::Bandwidth(stats)));
+                }
+            }
+            Scanned {
+                elevated: procfs::is_elevated(probe.pid, foreground_pid),
+                container: container::detect(foreground_pid, probe.pid),
+                jobs: jobs::list_jobs(&processes, probe.pid, probe.foreground),
+                foreground: probe.foreground,
+                id: probe.id,
+            }
+        })
+        .collect();
+
+    let mut sessions = sessions.locked();
+    for Scanned {
+        id,
+        foreground,
+        elevated,
+        container,
+        jobs: current,
+    } in scanned
+    {
+        // Closed while its processes were inspected
+        let Some(session) = sessions.get_mut(&id) else {
+            continue;
+        };
+        if let Some(elevated) = session.update_elevated(elevated) {
+            events.push((id.clone(), EventKind::Elevated { elevated }));
+        }
+        if let Some(container) = session.update_container(container) {
+            events.push((id.clone(), EventKind::Container(container)));
+        }
+        if current == session.jobs {
+            continue;
+        }
+
+        // A job brought back with `fg` is still running, just no longer in the background
+        let finished = session
+            .jobs
+            .iter()
+            .filter(|job| Some(job.pgid) != foreground && !current.iter().any(|j| j.pgid == job.pgid));
+        for finished in finished {
+            events.push((
+                id.clone(),
+                EventKind::JobExit {
+                    pid: finished.pid,
+                    command: finished.command.clone(),
+                },
+            ));
+        }
+
+        session.jobs = current;
+        events.push((id.clone(), EventKind::Jobs(session.jobs.clone())));
+    }
+
+    events
+}
@@ -0,0 +1,208 @@
+// PTY output processing
+// Scans a session's output for control sequences, updates tracked state, and publishes events
+
+use super::bell::BellLimiter;
+use super::clipboard::ClipboardRequest;
+use super::escape::{EscapeScanner, TermEvent};
+use super::input_queue::InputQueue;
+use super::links::{LinkResolver, LinkScanner};
+use super::marks::{CommandTracker, Lifecycle};
+use super::modes::ModeChange;
+use super::progress::TerminalProgress;
+use super::session::{SessionConfig, SessionShared};
+use super::title::ReportedCwd;
+use crate::events::{EventBus, EventKind};
+use crate::sync::MutexExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Longest window title passed on, in characters
+const MAX_TITLE_CHARS: usize = 256;
+
+/// Per-session output processor owned by the reader task
+pub struct OutputProcessor {
+    session_id: String,
+    events: Arc<EventBus>,
+    shared: Arc<SessionShared>,
+    /// The session's input, which replies to the program are queued on
+    input: InputQueue,
+    /// Looks up the paths of printed lines and publishes their links
+    resolver: LinkResolver,
+    config: SessionConfig,
+    scanner: EscapeScanner,
+    commands: CommandTracker,
+    links: LinkScanner,
+    bell: BellLimiter,
+    pending: Vec<TermEvent>,
+}
+
+impl OutputProcessor {
+    pub fn new(
+        session_id: String,
+        events: Arc<EventBus>,
+        shared: Arc<SessionShared>,
+        input: InputQueue,
+        resolver: LinkResolver,
+        config: SessionConfig,
+    ) -> Self {
+        Self {
+            session_id,
+            events,
+            shared,
+            input,
+            resolver,
+            config,
+            scanner: EscapeScanner::default(),
+            commands: CommandTracker::default(),
+            links: LinkScanner::default(),
+            bell: BellLimiter::default(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Handle a chunk of raw output before it is forwarded to the frontend
+    pub fn process(&mut self, data: &[u8]) {
+        self.scanner.feed(data, &mut self.pending);
+
+        let pending = std::mem::take(&mut self.pending);
+        for event in &pending {
+            self.handle(event);
+        }
+
+        // Hand the allocation back to avoid reallocating on every read
+        self.pending = pending;
+        self.pending.clear();
+
+        self.find_links(data);
+    }
+
+    fn find_links(&mut self, data: &[u8]) {
+        let printed = self.links.feed(data);
+        if printed.is_empty() || self.shared.modes.locked().alt_screen {
+            return;
+        }
+        let (local, cwd) = self.link_paths();
+        self.resolver.resolve(&self.session_id, printed, local, cwd);
+    }
+
+    /// Whether paths in the output are on this machine, and the directory relative ones are in
+    ///
+    /// Only the working directory the shell reports is used, as looking up the process's on every
+    /// read would cost too much. Without a report, ssh sessions are assumed to print remote paths.
+    fn link_paths(&self) -> (bool, Option<PathBuf>) {
+        match &*self.shared.reported_cwd.locked() {
+            Some(cwd) => (!cwd.remote, Some(PathBuf::from(&cwd.path))),
+            None => (Path::new(&self.config.shell).file_name().map_or(true, |name| name != "ssh"), None),
+        }
+    }
+
+    fn handle(&mut self, event: &TermEvent) {
+        match event {
+            TermEvent::PrivateMode { mode, enabled } => {
+                let change = self.shared.modes.locked().apply(*mode, *enabled);
+                let kind = match change {
+                    Some(ModeChange::AltScreen(active)) => EventKind::AltScreen { active },
+                    Some(ModeChange::Mouse(tracking, encoding)) => EventKind::MouseMode { tracking, encoding },
+                    Some(ModeChange::BracketedPaste(enabled)) => EventKind::BracketedPaste { enabled },
+                    None => return,
+                };
+                self.events.publish(&self.session_id, kind);
+            }
+            TermEvent::Enquiry => {
+                if self.config.answerback.is_empty() {
+                    return;
+                }
+                // Queued rather than written here, where a full input buffer would stall the reader
+                if let Err(e) = self.input.push(self.config.answerback.as_bytes().to_vec()) {
+                    log::warn!("Failed to send answerback for {}: {}", self.session_id, e);
+                }
+            }
+            TermEvent::Bell => {
+                if self.bell.ring() {
+                    self.events.publish(&self.session_id, EventKind::Bell(self.config.bell));
+                }
+            }
+            TermEvent::Title(raw) => {
+                // Control characters would garble the tab, and prompts resend the same title often
+                let title: String = raw
+                    .chars()
+                    .filter(|c| !c.is_control())
+                    .take(MAX_TITLE_CHARS)
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+                let mut current = self.shared.program_title.locked();
+                if current.as_deref() == Some(title.as_str()) {
+                    return;
+                }
+                *current = Some(title.clone());
+                drop(current);
+                self.events.publish(&self.session_id, EventKind::Title { title });
+            }
+            TermEvent::WorkingDirectory(uri) => {
+                let Some(cwd) = ReportedCwd::parse(uri) else {
+                    log::debug!("Ignoring malformed OSC 7 from {}: {:?}", self.session_id, uri);
+                    return;
+                };
+                let mut current = self.shared.reported_cwd.locked();
+                if current.as_ref() == Some(&cwd) {
+                    return;
+                }
+                *current = Some(cwd.clone());
+                drop(current);
+                self.events.publish(&self.session_id, EventKind::Cwd(cwd));
+            }
+            TermEvent::Clipboard(payload) => {
+                let Some(request) = ClipboardRequest::parse(payload) else {
+                    log::debug!("Ignoring unsupported OSC 52 from {}", self.session_id);
+                    return;
+                };
+                let allowed = self.config.clipboard.allows(&request);
+                if !allowed {
+                    log::info!("Denied clipboard {} for {}", request.access(), self.session_id);
+                }
+                // Denials are published too, so the frontend can tell the user why nothing happened
+                self.events.publish(&self.session_id, EventKind::Clipboard { request, allowed });
+            }
+            TermEvent::Progress(params) => {
+                let mut current = self.shared.progress.locked();
+                let Some(progress) = TerminalProgress::parse(params, current.as_ref()) else {
+                    log::debug!("Ignoring malformed OSC 9;4 from {}: {:?}", self.session_id, params);
+                    return;
+                };
+                if *current == progress {
+                    return;
+                }
+                *current = progress;
+                drop(current);
+                self.events.publish(&self.session_id, EventKind::Progress(progress));
+            }
+            TermEvent::PromptMark(mark) => {
+                for lifecycle in self.commands.on_mark(mark) {
+                    let kind = match lifecycle {
+                        Lifecycle::Prompt => {
+                            // Progress left behind by a program that was interrupted or crashed
+                            if self.shared.progress.locked().take().is_some() {
+                                self.events.publish(&self.session_id, EventKind::Progress(None));
+                            }
+                            EventKind::Prompt
+                        }
+                        Lifecycle::Started(started) => {
+                            if started.command.is_some() {
+                                *self.shared.last_command.locked() = started.command.clone();
+                            }
+                            self.shared.log_command(true);
+                            EventKind::CommandStarted(started)
+                        }
+                        Lifecycle::Finished(finished) => {
+                            *self.shared.last_exit_code.locked() = finished.exit_code;
+                            self.shared.log_command(false);
+                            EventKind::CommandFinished(finished)
+                        }
+                    };
+                    self.events.publish(&self.session_id, kind);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Process scheduling priority
+// CPU niceness, I/O priority, and OOM score for a session's shell, so heavy build tabs can be
+// deprioritized relative to interactive ones and are killed before the terminal under memory
+// pressure. Children started by the shell inherit all three.
+
+use serde::{Deserialize, Serialize};
+
+/// Lowest and highest niceness accepted by setpriority(2)
+const NICE_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+
+/// Highest level within the realtime and best-effort I/O classes
+const MAX_IO_LEVEL: u8 = 7;
+
+/// `which` argument of ioprio_set(2) selecting a single process
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+
+/// Bit offset of the class in an I/O priority value
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+
+/// Range accepted by `/proc/<pid>/oom_score_adj`
+const OOM_SCORE_ADJ_RANGE: std::ops::RangeInclusive<i32> = -1000..=1000;
+
+/// I/O scheduling class, as in ionice(1)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IoClass {
+    Realtime,
+    BestEffort,
+    /// Only gets disk time when no other process needs it
+    Idle,
+}
+
+impl IoClass {
+    fn value(self) -> libc::c_int {
+        match self {
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        }
+    }
+}
+
+/// I/O priority of a session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IoPriority {
+    pub class: IoClass,
+    /// 0 (highest) to 7 (lowest); ignored by the idle class (default 4)
+    pub level: Option<u8>,
+}
+
+/// Scheduling priority applied to a session's shell at spawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Priority {
+    /// Niceness from -20 (highest) to 19 (lowest)
+    pub nice: Option<i32>,
+    pub io: Option<IoPriority>,
+    /// Added to the kernel's OOM badness score, -1000 (never kill) to 1000 (kill first)
+    pub oom_score_adj: Option<i32>,
+}
+
+impl Priority {
+    pub fn is_default(&self) -> bool {
+        self.nice.is_none() && self.io.is_none() && self.oom_score_adj.is_none()
+    }
+
+    /// Check the levels before anything is spawned
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(nice) = self.nice {
+            if !NICE_RANGE.contains(&nice) {
+                return Err(format!("Invalid nice level {}: expected -20 to 19", nice));
+            }
+        }
+        if let Some(level) = self.io.and_then(|io| io.level) {
+            if level > MAX_IO_LEVEL {
+                return Err(format!("Invalid ionice level {}: expected 0 to 7", level));
+            }
+        }
+        if let Some(adj) = self.oom_score_adj {
+            if !OOM_SCORE_ADJ_RANGE.contains(&adj) {
+                return Err(format!("Invalid OOM score adjustment {}: expected -1000 to 1000", adj));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply to a running process
+    ///
+    /// Each setting is attempted even if an earlier one fails. Raising priority above the
+    /// default (negative nice, realtime I/O, an OOM score below the terminal's) needs
+    /// CAP_SYS_NICE, CAP_SYS_ADMIN, or CAP_SYS_RESOURCE.
+    pub fn apply(&self, pid: u32) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if let Some(nice) = self.nice {
+            // SAFETY: plain syscall on a pid; no memory is passed
+            let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+            if result != 0 {
+                errors.push(format!("Failed to set nice level: {}", std::io::Error::last_os_error()));
+            }
+        }
+
+        if let Some(io) = self.io {
+            let level = match io.class {
+                IoClass::Idle => 0,
+                _ => io.level.unwrap_or(4) as libc::c_int,
+            };
+            let value = (io.class.value() << IOPRIO_CLASS_SHIFT) | level;
+            // SAFETY: plain syscall on a pid; no memory is passed
+            let result = unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid, value) };
+            if result != 0 {
+                errors.push(format!("Failed to set I/O priority: {}", std::io::Error::last_os_error()));
+            }
+        }
+
+        if let Some(adj) = self.oom_score_adj {
+            if let Err(e) = std::fs::write(format!("/proc/{}/oom_score_adj", pid), adj.to_string()) {
+                errors.push(format!("Failed to set OOM score adjustment: {}", e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// /proc helpers
+// Lightweight process inspection used to reason about a session's process tree
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Fields parsed from `/proc/<pid>/stat`
+#[derive(Debug, Clone)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgrp: i32,
+    pub state: char,
+    pub comm: String,
+}
+
+/// Byte counters from `/proc/<pid>/io`
+#[derive(Debug, Clone, Copy)]
+pub struct ProcIo {
+    /// Bytes passed to read-like syscalls, from files, pipes, terminals, and sockets alike
+    pub rchar: u64,
+    pub wchar: u64,
+}
+
+/// Read and parse `/proc/<pid>/stat`
+pub fn read_stat(pid: u32) -> Option<ProcStat> {
+    let contents = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // comm is wrapped in parentheses and may itself contain spaces or ')'
+    let open = contents.find('(')?;
+    let close = contents.rfind(')')?;
+    let comm = contents[open + 1..close].to_string();
+
+    let mut fields = contents[close + 1..].split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let pgrp = fields.next()?.parse().ok()?;
+
+    Some(ProcStat {
+        pid,
+        ppid,
+        pgrp,
+        state,
+        comm,
+    })
+}
+
+/// Read the argument vector of a process
+pub fn read_cmdline(pid: u32) -> Vec<String> {
+    fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|bytes| {
+            bytes
+                .split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// List all processes currently visible in /proc
+pub fn all_processes() -> Vec<ProcStat> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_stat)
+        .collect()
+}
+
+/// Every process visible in /proc at one moment, indexed by parent
+///
+/// Built once and shared by all the checks that need a session's process tree, so a round of
+/// checks walks /proc once rather than once per session and check.
+#[derive(Default)]
+pub struct ProcessTree {
+    processes: HashMap<u32, ProcStat>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl ProcessTree {
+    pub fn scan() -> Self {
+        let mut tree = Self::default();
+        for process in all_processes() {
+            tree.children.entry(process.ppid).or_default().push(process.pid);
+            tree.processes.insert(process.pid, process);
+        }
+        tree
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&ProcStat> {
+        self.processes.get(&pid)
+    }
+
+    /// List every descendant of `root` (excluding `root` itself)
+    pub fn descendants(&self, root: u32) -> Vec<ProcStat> {
+        let mut result = Vec::new();
+        let mut frontier = vec![root];
+
+        while let Some(parent) = frontier.pop() {
+            for pid in self.children.get(&parent).into_iter().flatten() {
+                if let Some(process) = self.processes.get(pid) {
+                    frontier.push(*pid);
+                    result.push(process.clone());
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// List every descendant of `root` (excluding `root` itself)
+pub fn descendants(root: u32) -> Vec<ProcStat> {
+    ProcessTree::scan().descendants(root)
+}
+
+/// Read a variable from the environment a process was started with
+pub fn read_environ_var(pid: u32, key: &str) -> Option<String> {
+    let environ = fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    environ.split(|b| *b == 0).find_map(|entry| {
+        let value = entry.strip_prefix(key.as_bytes())?.strip_prefix(b"=")?;
+        Some(String::from_utf8_lossy(value).to_string())
+    })
+}
+
+/// Read the current working directory of a process
+pub fn read_cwd(pid: u32) -> Option<PathBuf> {
+    fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+/// Whether the shell or the foreground process runs as root
+pub fn is_elevated(shell: u32, foreground: u32) -> bool {
+    read_euid(shell) == Some(0) || read_euid(foreground) == Some(0)
+}
+
+/// Read the effective user ID of a process from `/proc/<pid>/status`
+pub fn read_euid(pid: u32) -> Option<u32> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let uids = status.lines().find_map(|line| line.strip_prefix("Uid:"))?;
+    uids.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Read the I/O counters of a process; only readable for the user's own processes
+pub fn read_io(pid: u32) -> Option<ProcIo> {
+    let contents = fs::read_to_string(format!("/proc/{}/io", pid)).ok()?;
+    let field = |name: &str| {
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.trim().parse().ok())
+    };
+    Some(ProcIo {
+        rchar: field("rchar")?,
+        wchar: field("wchar")?,
+    })
+}
@@ -0,0 +1,140 @@
+// Progress reports
+// Programs report progress with ConEmu's OSC 9;4 (`ESC ] 9 ; 4 ; state ; percent ST`), as
+// winget, systemd, and some build tools do. Each session's latest report is published for its
+// tab, and the sessions' reports are combined into one bar on the window's taskbar entry.
+
+use crate::control::MAIN_WINDOW;
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ProgressState {
+    Normal,
+    Error,
+    /// Busy without knowing how far along
+    Indeterminate,
+    /// Paused, or a warning
+    Paused,
+}
+
+/// Progress a program reported, published on `pty://{id}/progress`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalProgress {
+    pub state: ProgressState,
+    /// 0 to 100; None for indeterminate progress
+    pub percent: Option<u8>,
+}
+
+impl TerminalProgress {
+    /// Parse the parameters after `9;4`, keeping the percentage of `previous` when an error or
+    /// pause report leaves it out; Some(None) removes the progress
+    pub fn parse(params: &str, previous: Option<&Self>) -> Option<Option<Self>> {
+        let mut parts = params.strip_prefix(';').unwrap_or(params).split(';');
+        let state = parts.next().filter(|state| !state.is_empty()).unwrap_or("0");
+        let percent = parts
+            .next()
+            .filter(|percent| !percent.is_empty())
+            .map(|percent| percent.parse::<u32>().map(|percent| percent.min(100) as u8))
+            .transpose()
+            .ok()?;
+        let kept = || percent.or_else(|| previous.and_then(|previous| previous.percent));
+        let progress = match state {
+            "0" => return Some(None),
+            "1" => Self {
+                state: ProgressState::Normal,
+                percent: Some(percent.unwrap_or(0)),
+            },
+            "2" => Self {
+                state: ProgressState::Error,
+                percent: kept(),
+            },
+            "3" => Self {
+                state: ProgressState::Indeterminate,
+                percent: None,
+            },
+            "4" => Self {
+                state: ProgressState::Paused,
+                percent: kept(),
+            },
+            _ => return None,
+        };
+        Some(Some(progress))
+    }
+}
+
+/// Latest progress of each session, managed by Tauri
+#[derive(Default)]
+pub struct TaskbarProgress {
+    sessions: Mutex<HashMap<String, TerminalProgress>>,
+    /// What the taskbar shows, to skip updates that change nothing
+    shown: Mutex<Option<TerminalProgress>>,
+}
+
+impl TaskbarProgress {
+    /// One bar for all sessions: errors first, then the average of those with a percentage
+    fn combined(&self) -> Option<TerminalProgress> {
+        let sessions = self.sessions.locked();
+        let state = [ProgressState::Error, ProgressState::Normal, ProgressState::Paused]
+            .into_iter()
+            .find(|state| sessions.values().any(|progress| progress.state == *state))
+            .or_else(|| (!sessions.is_empty()).then_some(ProgressState::Indeterminate))?;
+        let percents: Vec<u32> = sessions
+            .values()
+            .filter_map(|progress| progress.percent)
+            .map(u32::from)
+            .collect();
+        let percent = (!percents.is_empty()).then(|| (percents.iter().sum::<u32>() / percents.len() as u32) as u8);
+        Some(TerminalProgress { state, percent })
+    }
+}
+
+/// Keep the taskbar progress in step with the sessions' reports
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    events.subscribe(move |event: &SessionEvent| {
+        let taskbar = app_handle.state::<TaskbarProgress>();
+        {
+            let mut sessions = taskbar.sessions.locked();
+            match &event.kind {
+                EventKind::Progress(Some(progress)) => {
+                    sessions.insert(event.session_id.clone(), *progress);
+                }
+                EventKind::Progress(None) | EventKind::Exit { .. } => {
+                    if sessions.remove(&event.session_id).is_none() {
+                        return;
+                    }
+                }
+                _ => return,
+            }
+        }
+
+        let combined = taskbar.combined();
+        let mut shown = taskbar.shown.locked();
+        if *shown == combined {
+            return;
+        }
+        *shown = combined;
+        let Some(window) = app_handle.get_webview_window(MAIN_WINDOW) else {
+            return;
+        };
+        let state = ProgressBarState {
+            status: Some(match combined.map(|progress| progress.state) {
+                None => ProgressBarStatus::None,
+                Some(ProgressState::Normal) => ProgressBarStatus::Normal,
+                Some(ProgressState::Error) => ProgressBarStatus::Error,
+                Some(ProgressState::Indeterminate) => ProgressBarStatus::Indeterminate,
+                Some(ProgressState::Paused) => ProgressBarStatus::Paused,
+            }),
+            progress: combined.and_then(|progress| progress.percent).map(u64::from),
+        };
+        if let Err(e) = window.set_progress_bar(state) {
+            log::debug!("Failed to set taskbar progress: {}", e);
+        }
+    });
+}
@@ -0,0 +1,169 @@
+// Session recordings
+// Captures a session's output with its timing and terminal size as asciicast v2, the format
+// asciinema players replay. Files are protected like session logs.
+//
+//   {"version": 2, "width": 80, "height": 24, "timestamp": 1700000000, "env": {...}}
+//   [0.248848, "o", "output"]
+//   [1.001, "r", "100x30"]
+
+use super::redact::Redactor;
+use super::utf8::Utf8Decoder;
+use crate::logs;
+use crate::logs::writer::{LogWriter, LoggingSettings};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A recording being written, or the summary of one that was stopped
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub path: String,
+    /// Unix time the recording started
+    pub started_at: u64,
+    pub duration_secs: f64,
+    /// Output and resize events recorded
+    pub events: u64,
+    pub encrypted: bool,
+    pub chained: bool,
+    pub bytes_written: u64,
+}
+
+#[derive(Serialize)]
+struct Header<'a> {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    env: HeaderEnv<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct HeaderEnv<'a> {
+    term: &'a str,
+    shell: &'a str,
+}
+
+/// Asciicast recorder of one session, fed by its reader
+pub struct Recorder {
+    info: RecordingInfo,
+    writer: LogWriter,
+    started: Instant,
+    decoder: Utf8Decoder,
+    redactor: Option<Arc<Redactor>>,
+}
+
+/// Resolve a recording path; without one, a file named after the session is created in the
+/// recordings directory, and relative paths are placed there too
+pub fn resolve_path(path: Option<&str>, session_id: &str) -> Result<PathBuf, String> {
+    let dir = || logs::recordings_dir().ok_or_else(|| "Could not find data directory".to_string());
+    match path.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(path) if Path::new(path).is_absolute() => Ok(PathBuf::from(path)),
+        Some(path) => Ok(dir()?.join(path)),
+        None => Ok(dir()?.join(format!("{}-{}.cast", session_id, unix_time()))),
+    }
+}
+
+impl Recorder {
+    /// Create the recording and write its header
+    ///
+    /// Loading the encryption key may talk to the keyring, so call this from a blocking task.
+    pub fn create(
+        path: &Path,
+        cols: u16,
+        rows: u16,
+        term: &str,
+        shell: &str,
+        settings: &LoggingSettings,
+        redactor: Option<Arc<Redactor>>,
+    ) -> Result<Self, String> {
+        let header = Header {
+            version: 2,
+            width: cols,
+            height: rows,
+            timestamp: unix_time(),
+            env: HeaderEnv { term, shell },
+        };
+        let mut line = serde_json::to_string(&header).map_err(|e| format!("Failed to write recording: {}", e))?;
+        line.push('\n');
+
+        let mut writer = LogWriter::create(path, settings)?;
+        writer.write(line.as_bytes())?;
+        Ok(Self {
+            info: RecordingInfo {
+                path: path.display().to_string(),
+                started_at: header.timestamp,
+                duration_secs: 0.0,
+                events: 0,
+                encrypted: writer.encrypted(),
+                chained: writer.chained(),
+                bytes_written: writer.bytes_written(),
+            },
+            writer,
+            started: Instant::now(),
+            decoder: Utf8Decoder::default(),
+            redactor,
+        })
+    }
+
+    pub fn info(&self) -> RecordingInfo {
+        RecordingInfo {
+            duration_secs: self.elapsed(),
+            ..self.info.clone()
+        }
+    }
+
+    /// Record a chunk of output as read from the terminal
+    ///
+    /// Secrets split across two reads are not caught by redaction.
+    pub fn output(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let text = self.decoder.decode(bytes);
+        self.output_text(text)
+    }
+
+    /// Record a change of the terminal size
+    pub fn resize(&mut self, cols: u16, rows: u16) -> Result<(), String> {
+        self.event("r", &format!("{}x{}", cols, rows))
+    }
+
+    /// Record what is still held back and close the recording
+    pub async fn finish(mut self) -> Result<RecordingInfo, String> {
+        let tail = self.decoder.finish();
+        self.output_text(tail)?;
+        let info = self.info();
+        self.writer.finish().await?;
+        Ok(info)
+    }
+
+    fn output_text(&mut self, text: String) -> Result<(), String> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let text = match &self.redactor {
+            Some(redactor) => redactor.redact(&text).into_owned(),
+            None => text,
+        };
+        self.event("o", &text)
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> Result<(), String> {
+        let mut line = serde_json::to_string(&(self.elapsed(), code, data))
+            .map_err(|e| format!("Failed to write recording: {}", e))?;
+        line.push('\n');
+        self.writer.write(line.as_bytes())?;
+        self.info.events += 1;
+        self.info.bytes_written = self.writer.bytes_written();
+        Ok(())
+    }
+
+    /// Seconds since the recording started, to the microsecond
+    fn elapsed(&self) -> f64 {
+        self.started.elapsed().as_micros() as f64 / 1_000_000.0
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
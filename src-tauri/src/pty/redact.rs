@@ -0,0 +1,192 @@
+// Output redaction
+// Masks secrets (cloud keys, tokens, passwords) in session output before it is persisted,
+// and optionally before it is shown.
+//
+// Rules are regular expressions; when a rule has a `secret` capture group only that group is
+// masked, so `password=hunter2` becomes `password=[REDACTED]`.
+
+use regex::Regex;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Text substituted for a masked secret
+pub const MASK: &str = "[REDACTED]";
+
+/// Longest secret the live stream is sure to mask when a read splits it; output this close to
+/// the end of what was read is held back until the rest of its line arrives
+const MAX_LIVE_MATCH: usize = 1024;
+
+/// Rules applied unless redaction is disabled
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    ("aws-access-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "aws-secret-key",
+        r#"(?i)aws_secret_access_key["']?\s*[=:]\s*["']?(?P<secret>[A-Za-z0-9/+=]{40,})"#,
+    ),
+    ("bearer-token", r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9\-._~+/]+=*)"),
+    (
+        "password-assignment",
+        r#"(?i)\b(?:password|passwd|pwd|secret|api[_-]?key|access[_-]?token)["']?\s*[=:]\s*["']?(?P<secret>[^\s"']+)"#,
+    ),
+    ("private-key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+];
+
+/// A user-defined rule from settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// The `redaction` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RedactionSettings {
+    pub enabled: bool,
+    /// Also mask secrets in the live stream sent to the frontend
+    pub mask_live_output: bool,
+    pub rules: Vec<RedactionRule>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mask_live_output: false,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Compiled redaction rules
+#[derive(Debug)]
+pub struct Redactor {
+    rules: Vec<(String, Regex)>,
+    mask_live_output: bool,
+}
+
+impl Redactor {
+    /// Compile the built-in and user rules; returns `None` when redaction is disabled
+    ///
+    /// Invalid user patterns are logged and skipped rather than failing the session.
+    pub fn from_settings(settings: &RedactionSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+
+        let builtin = BUILTIN_RULES
+            .iter()
+            .map(|(name, pattern)| (name.to_string(), pattern.to_string()));
+        let custom = settings
+            .rules
+            .iter()
+            .map(|rule| (rule.name.clone(), rule.pattern.clone()));
+
+        let rules = builtin
+            .chain(custom)
+            .filter_map(|(name, pattern)| match Regex::new(&pattern) {
+                Ok(regex) => Some((name, regex)),
+                Err(e) => {
+                    log::warn!("Skipping invalid redaction rule {}: {}", name, e);
+                    None
+                }
+            })
+            .collect();
+
+        Some(Self {
+            rules,
+            mask_live_output: settings.mask_live_output,
+        })
+    }
+
+    /// Whether the live stream should be masked too
+    pub fn masks_live_output(&self) -> bool {
+        self.mask_live_output
+    }
+
+    /// Where the rules match, before any masking
+    fn matches<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+        self.rules
+            .iter()
+            .flat_map(move |(_, regex)| regex.find_iter(text).map(|m| m.range()))
+    }
+
+    /// Mask every secret matched by the rules
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed(text);
+
+        for (_, regex) in &self.rules {
+            if !regex.is_match(&result) {
+                continue;
+            }
+
+            let mut redacted = String::with_capacity(result.len());
+            let mut last = 0;
+            for captures in regex.captures_iter(&result) {
+                let range = captures
+                    .name("secret")
+                    .or_else(|| captures.get(0))
+                    .map(|m| m.range())
+                    .unwrap_or_default();
+                redacted.push_str(&result[last..range.start]);
+                redacted.push_str(MASK);
+                last = range.end;
+            }
+            redacted.push_str(&result[last..]);
+            result = Cow::Owned(redacted);
+        }
+
+        result
+    }
+}
+
+/// Masks the live stream of a session, whose reads may end in the middle of a secret
+///
+/// As the session log does for partial lines, the end of the output is held back until the rest
+/// of its line arrives, or `flush` is called because the output went quiet.
+pub struct LiveRedaction {
+    redactor: Arc<Redactor>,
+    held: String,
+}
+
+impl LiveRedaction {
+    pub fn new(redactor: Arc<Redactor>) -> Self {
+        Self {
+            redactor,
+            held: String::new(),
+        }
+    }
+
+    /// Whether output is held back, waiting for more or a flush
+    pub fn is_holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// Add decoded output and return what can be shown, masked
+    pub fn push(&mut self, data: &str) -> String {
+        self.held.push_str(data);
+
+        let line_start = self.held.rfind('\n').map_or(0, |i| i + 1);
+        let mut cut = line_start.max(self.held.len().saturating_sub(MAX_LIVE_MATCH));
+        while !self.held.is_char_boundary(cut) {
+            cut += 1;
+        }
+        // A match that may still go on, or that spans lines, stays whole on the held side
+        while let Some(m) = self.redactor.matches(&self.held).find(|m| m.start < cut && cut < m.end) {
+            cut = m.start;
+        }
+
+        let rest = self.held.split_off(cut);
+        let ready = std::mem::replace(&mut self.held, rest);
+        self.redactor.redact(&ready).into_owned()
+    }
+
+    /// Everything held back, masked
+    pub fn flush(&mut self) -> String {
+        let held = std::mem::take(&mut self.held);
+        self.redactor.redact(&held).into_owned()
+    }
+}
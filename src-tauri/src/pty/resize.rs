@@ -0,0 +1,95 @@
+// Resize coalescing
+// Dragging a window edge produces a stream of resize requests, and each one applied sends
+// SIGWINCH and makes full-screen programs repaint. Requests are coalesced: the latest size is
+// applied once they pause, and at most every MAX_DELAY while they keep coming.
+
+use portable_pty::PtySize;
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last request before its size is applied
+pub const DEBOUNCE: Duration = Duration::from_millis(40);
+
+/// Longest a request waits while new ones keep arriving
+const MAX_DELAY: Duration = Duration::from_millis(200);
+
+/// Resize requests of one session
+#[derive(Debug)]
+pub struct ResizeState {
+    /// Size the terminal has now
+    applied: PtySize,
+    /// Latest requested size not applied yet
+    pending: Option<PtySize>,
+    /// When the oldest unapplied request arrived
+    first_request: Instant,
+    last_request: Instant,
+    /// A flush task is waiting to apply `pending`
+    scheduled: bool,
+}
+
+/// What a flush task should do next
+pub enum Flush {
+    /// Apply this size now
+    Apply(PtySize),
+    /// Requests are still arriving; check again after this long
+    Wait(Duration),
+    /// Nothing is left to apply
+    Done,
+}
+
+impl ResizeState {
+    pub fn new(size: PtySize) -> Self {
+        let now = Instant::now();
+        Self {
+            applied: size,
+            pending: None,
+            first_request: now,
+            last_request: now,
+            scheduled: false,
+        }
+    }
+
+    /// Latest requested size, applied or not
+    pub fn size(&self) -> PtySize {
+        self.pending.unwrap_or(self.applied)
+    }
+
+    /// Record a request; returns true if the caller must start a flush task
+    pub fn request(&mut self, size: PtySize) -> bool {
+        let now = Instant::now();
+        if self.pending.is_none() {
+            if size == self.applied {
+                return false;
+            }
+            self.first_request = now;
+        }
+        self.pending = Some(size);
+        self.last_request = now;
+
+        let start = !self.scheduled;
+        self.scheduled = true;
+        start
+    }
+
+    /// Decide whether the pending size is due; the task stops once this returns `Done`
+    pub fn flush(&mut self) -> Flush {
+        let Some(size) = self.pending else {
+            self.scheduled = false;
+            return Flush::Done;
+        };
+
+        let now = Instant::now();
+        let quiet_until = self.last_request + DEBOUNCE;
+        let deadline = self.first_request + MAX_DELAY;
+        if now < quiet_until && now < deadline {
+            return Flush::Wait(quiet_until.min(deadline) - now);
+        }
+
+        self.pending = None;
+        self.scheduled = false;
+        if size == self.applied {
+            return Flush::Done;
+        }
+        self.applied = size;
+        Flush::Apply(size)
+    }
+}
@@ -0,0 +1,92 @@
+// Sandboxed sessions
+// Runs a session's shell inside bubblewrap, for trying untrusted scripts or generated commands
+// in a throwaway tab. The system directories are mounted read-only, home and /tmp are empty
+// tmpfs mounts, and only the paths chosen in the profile are visible from the host.
+//
+// The sandbox keeps the terminal's session and controlling TTY (no `--new-session`) so job
+// control works; the PTY is private to the sandboxed shell, so that exposes nothing else.
+
+use crate::exec;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// System directories mounted read-only when they exist
+const SYSTEM_DIRS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt"];
+
+/// Filesystem and network access granted to a sandboxed session
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxOptions {
+    /// Host paths visible read-write; the first is the starting directory
+    #[serde(default)]
+    pub writable: Vec<String>,
+    /// Host paths visible read-only
+    #[serde(default)]
+    pub read_only: Vec<String>,
+    /// Keep network access (default false)
+    #[serde(default)]
+    pub network: bool,
+}
+
+impl SandboxOptions {
+    /// Command line running `program` with `args` inside the sandbox
+    pub fn wrap(&self, program: &str, args: &[String]) -> Result<Vec<String>, String> {
+        let bwrap = exec::find_program("bwrap")
+            .ok_or_else(|| "Sandboxed sessions need bubblewrap (bwrap) to be installed".to_string())?;
+        let home = dirs::home_dir().ok_or_else(|| "Failed to find home directory".to_string())?;
+
+        let mut argv = vec![
+            bwrap.display().to_string(),
+            "--die-with-parent".to_string(),
+            "--unshare-all".to_string(),
+        ];
+        if self.network {
+            argv.push("--share-net".to_string());
+        }
+
+        for dir in SYSTEM_DIRS {
+            push(&mut argv, &["--ro-bind-try", dir, dir]);
+        }
+        push(&mut argv, &["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp", "--tmpfs", "/run"]);
+
+        // An empty home so the user's dotfiles and credentials stay outside
+        let home = home.display().to_string();
+        push(&mut argv, &["--tmpfs", "/home", "--dir", &home]);
+
+        // Later mounts cover earlier ones, so read-only paths go last: one inside a writable
+        // directory stays read-only
+        let mut start = home.clone();
+        for (i, path) in self.writable.iter().enumerate() {
+            let path = resolve(path)?;
+            push(&mut argv, &["--bind", &path, &path]);
+            if i == 0 {
+                start = path;
+            }
+        }
+        for path in &self.read_only {
+            let path = resolve(path)?;
+            push(&mut argv, &["--ro-bind", &path, &path]);
+        }
+
+        push(&mut argv, &["--setenv", "HOME", &home, "--chdir", &start, "--", program]);
+        argv.extend(args.iter().cloned());
+        Ok(argv)
+    }
+}
+
+fn push(argv: &mut Vec<String>, items: &[&str]) {
+    argv.extend(items.iter().map(|item| item.to_string()));
+}
+
+/// Expand `~` and make sure a shared path exists, returning it in absolute form
+fn resolve(path: &str) -> Result<String, String> {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| PathBuf::from(path)),
+        None if path == "~" => dirs::home_dir().unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    };
+    let canonical = expanded
+        .canonicalize()
+        .map_err(|e| format!("Failed to share {} with the sandbox: {}", path, e))?;
+    Ok(canonical.display().to_string())
+}
@@ -0,0 +1,127 @@
+// Session scratch directories
+// A private TMPDIR and optionally a throwaway home per session, created at spawn and removed
+// when the session is closed, so experiments don't litter /tmp or the real home directory.
+
+use portable_pty::CommandBuilder;
+use std::fs;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+
+/// Prefix of scratch directories inside the system temp directory
+const DIR_PREFIX: &str = "xterminal-session-";
+
+/// Shell startup files copied into a throwaway home so the shell still feels familiar
+const SEED_FILES: &[&str] = &[
+    ".profile",
+    ".bashrc",
+    ".bash_profile",
+    ".zshenv",
+    ".zprofile",
+    ".zshrc",
+    ".inputrc",
+    ".config/fish/config.fish",
+];
+
+/// Scratch directories owned by one session, removed when dropped
+pub struct SessionScratch {
+    root: PathBuf,
+}
+
+impl SessionScratch {
+    /// Create the directories and point the command's environment at them
+    pub fn create(
+        session_id: &str,
+        private_tmp: bool,
+        throwaway_home: bool,
+        cmd: &mut CommandBuilder,
+    ) -> Result<Self, String> {
+        let root = std::env::temp_dir().join(format!("{}{}", DIR_PREFIX, session_id));
+        create_private_dir(&root)?;
+        let scratch = Self { root };
+
+        if private_tmp {
+            let tmp = scratch.root.join("tmp");
+            create_private_dir(&tmp)?;
+            for key in ["TMPDIR", "TMP", "TEMP"] {
+                cmd.env(key, &tmp);
+            }
+        }
+
+        if throwaway_home {
+            let home = scratch.root.join("home");
+            create_private_dir(&home)?;
+            seed_home(&home);
+            cmd.env("HOME", &home);
+            // Tools honoring explicit XDG paths would otherwise still write to the real home
+            for (key, dir) in [
+                ("XDG_CONFIG_HOME", ".config"),
+                ("XDG_CACHE_HOME", ".cache"),
+                ("XDG_DATA_HOME", ".local/share"),
+                ("XDG_STATE_HOME", ".local/state"),
+            ] {
+                cmd.env(key, home.join(dir));
+            }
+        }
+
+        log::info!("Created scratch directory {:?}", scratch.root);
+        Ok(scratch)
+    }
+}
+
+impl Drop for SessionScratch {
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.root) {
+            Ok(()) => log::info!("Removed scratch directory {:?}", self.root),
+            Err(e) => log::warn!("Failed to remove scratch directory {:?}: {}", self.root, e),
+        }
+    }
+}
+
+/// Remove scratch directories left behind by a previous run that did not shut down cleanly
+pub fn sweep_stale() {
+    let Ok(entries) = fs::read_dir(std::env::temp_dir()) else {
+        return;
+    };
+
+    // Only one instance runs per user, so every scratch directory this user owns is stale
+    // SAFETY: getuid has no preconditions
+    let uid = unsafe { libc::getuid() };
+    for entry in entries.flatten() {
+        let owned = entry
+            .metadata()
+            .map(|m| std::os::unix::fs::MetadataExt::uid(&m) == uid && m.is_dir())
+            .unwrap_or(false);
+        if owned && entry.file_name().to_string_lossy().starts_with(DIR_PREFIX) {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+fn create_private_dir(path: &Path) -> Result<(), String> {
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(path)
+        .map_err(|e| format!("Failed to create scratch directory {:?}: {}", path, e))
+}
+
+/// Copy the user's shell startup files into a fresh home
+fn seed_home(home: &Path) {
+    let Some(real_home) = dirs::home_dir() else {
+        return;
+    };
+
+    for file in SEED_FILES {
+        let source = real_home.join(file);
+        if !source.is_file() {
+            continue;
+        }
+        let target = home.join(file);
+        if let Some(parent) = target.parent() {
+            let _ = create_private_dir(parent);
+        }
+        if let Err(e) = fs::copy(&source, &target) {
+            log::warn!("Failed to copy {:?} into throwaway home: {}", source, e);
+        }
+    }
+}
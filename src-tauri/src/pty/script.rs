@@ -0,0 +1,317 @@
+// Scripts from scrollback
+// Recovers the commands run in a range of a session's output from the semantic prompt marks of
+// shell integration (OSC 133, or VS Code's OSC 633): the text between the end of a prompt (B) and
+// the start of the command's output (C) is the command line as the shell echoed it, and the text
+// from there to the command's end (D) is its output. Both are replayed on a line model so
+// readline's cursor movement and redraws, or a progress bar's carriage returns, resolve to what
+// was finally shown.
+
+use serde::Serialize;
+
+/// Longest line kept; a command line this long is most likely not an echoed command
+const MAX_COMMAND_BYTES: usize = 64 * 1024;
+
+/// Commands of a scrollback range turned into a shell script
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedScript {
+    pub script: String,
+    /// Commands in the script
+    pub commands: usize,
+    /// Commands left out because they exited with a non-zero status
+    pub skipped: usize,
+    /// Stream offsets the commands were taken from
+    pub offset: u64,
+    pub end: u64,
+}
+
+/// A command of a session's scrollback whose output was compared
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffedCommand {
+    /// Position among the commands in the scrollback, oldest first
+    pub index: usize,
+    pub line: String,
+    pub exit_code: Option<i32>,
+}
+
+/// Difference between the outputs of two commands
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOutputDiff {
+    /// Unified diff from the first output to the second; empty when they are the same
+    pub diff: String,
+    pub identical: bool,
+    pub a: DiffedCommand,
+    pub b: DiffedCommand,
+}
+
+/// A command found between prompt marks
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkedCommand {
+    pub line: String,
+    /// Exit status from the D mark, when the shell reported one
+    pub exit_code: Option<i32>,
+    /// What the command printed, as plain text
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS payload, terminated by BEL or ST
+    String,
+    StringEscape,
+}
+
+/// Text being printed, with the terminal's cursor on its last line
+#[derive(Default)]
+struct Lines {
+    lines: Vec<String>,
+    line: Vec<char>,
+    cursor: usize,
+}
+
+impl Lines {
+    fn print(&mut self, c: char) {
+        if self.line.len() >= MAX_COMMAND_BYTES {
+            return;
+        }
+        // Terminals overwrite in place; readline inserts by redrawing the rest of the line
+        match self.line.get_mut(self.cursor) {
+            Some(cell) => *cell = c,
+            None => {
+                self.line.resize(self.cursor, ' ');
+                self.line.push(c);
+            }
+        }
+        self.cursor += 1;
+    }
+
+    fn newline(&mut self) {
+        self.lines.push(self.line.iter().collect::<String>().trim_end().to_string());
+        self.line.clear();
+        self.cursor = 0;
+    }
+
+    fn csi(&mut self, params: &str, final_byte: char) {
+        let count = params.split(';').next().and_then(|p| p.parse::<usize>().ok()).unwrap_or(1).max(1);
+        match final_byte {
+            'D' => self.cursor = self.cursor.saturating_sub(count),
+            'C' => self.cursor += count,
+            'G' => self.cursor = count - 1,
+            'K' => match params {
+                "" | "0" => self.line.truncate(self.cursor),
+                "1" => self.line.iter_mut().take(self.cursor + 1).for_each(|cell| *cell = ' '),
+                _ => self.line.clear(),
+            },
+            'P' => {
+                let end = (self.cursor + count).min(self.line.len());
+                if self.cursor < end {
+                    self.line.drain(self.cursor..end);
+                }
+            }
+            '@' if self.cursor <= self.line.len() => {
+                for _ in 0..count {
+                    self.line.insert(self.cursor, ' ');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The text as a command line
+    fn command(mut self) -> String {
+        self.newline();
+        let mut lines = self.lines.into_iter();
+        let first = lines.next().unwrap_or_default();
+        // Continuation lines are echoed after the shell's secondary prompt, `> ` by default
+        let rest = lines.map(|line| line.strip_prefix("> ").map(str::to_string).unwrap_or(line));
+        std::iter::once(first).chain(rest).collect::<Vec<_>>().join("\n").trim().to_string()
+    }
+
+    /// The text as command output, without the empty line the cursor was left on
+    fn output(mut self) -> String {
+        if !self.line.is_empty() {
+            self.newline();
+        }
+        self.lines.join("\n")
+    }
+}
+
+/// Commands between the prompt marks in a chunk of output, in the order they ran
+///
+/// Commands abandoned at the prompt, with Ctrl+C for example, have no C mark and are left out.
+pub fn extract_commands(output: &[u8]) -> Vec<MarkedCommand> {
+    let text = String::from_utf8_lossy(output);
+    let mut marks = Marks::default();
+    let mut state = State::Ground;
+    let mut params = String::new();
+
+    for c in text.chars() {
+        state = match (state, c) {
+            (State::Ground, '\u{1b}') => State::Escape,
+            (State::Ground, c) => {
+                if let Some(lines) = marks.printing() {
+                    match c {
+                        '\r' => lines.cursor = 0,
+                        '\n' => lines.newline(),
+                        '\u{8}' => lines.cursor = lines.cursor.saturating_sub(1),
+                        '\t' => lines.print(c),
+                        c if c.is_control() => {}
+                        c => lines.print(c),
+                    }
+                }
+                State::Ground
+            }
+            (State::Escape, '[' | ']' | 'P' | '_' | '^' | 'X') => {
+                params.clear();
+                if c == '[' {
+                    State::Csi
+                } else {
+                    // Only OSC payloads are looked at; keep a marker for the others
+                    params.push(if c == ']' { ']' } else { '\0' });
+                    State::String
+                }
+            }
+            (State::Escape, '\u{1b}') => State::Escape,
+            (State::Escape, '\u{20}'..='\u{2f}') => State::EscapeIntermediate,
+            (State::Escape, _) => State::Ground,
+            (State::EscapeIntermediate, '\u{20}'..='\u{2f}') => State::EscapeIntermediate,
+            (State::EscapeIntermediate, '\u{1b}') => State::Escape,
+            (State::EscapeIntermediate, _) => State::Ground,
+            (State::Csi, '\u{1b}') => State::Escape,
+            (State::Csi, '\u{18}' | '\u{1a}') => State::Ground,
+            (State::Csi, '\u{40}'..='\u{7e}') => {
+                if let Some(lines) = marks.printing() {
+                    lines.csi(&params, c);
+                }
+                State::Ground
+            }
+            (State::Csi, c) => {
+                if params.len() < 64 {
+                    params.push(c);
+                }
+                State::Csi
+            }
+            (State::String, '\u{7}') => {
+                marks.mark(&params);
+                State::Ground
+            }
+            (State::String, '\u{18}' | '\u{1a}') => State::Ground,
+            (State::String, '\u{1b}') => State::StringEscape,
+            (State::String, c) => {
+                if params.len() < 256 {
+                    params.push(c);
+                }
+                State::String
+            }
+            (State::StringEscape, '\\') => {
+                marks.mark(&params);
+                State::Ground
+            }
+            (State::StringEscape, '[') => {
+                params.clear();
+                State::Csi
+            }
+            (State::StringEscape, '\u{1b}') => State::StringEscape,
+            (State::StringEscape, _) => State::String,
+        };
+    }
+    marks.finish()
+}
+
+/// Commands found so far and the text being collected for the latest
+#[derive(Default)]
+struct Marks {
+    commands: Vec<MarkedCommand>,
+    /// Command line being echoed, after a B mark
+    echo: Option<Lines>,
+    /// Output of the last command, after its C mark and until its D mark
+    output: Option<Lines>,
+}
+
+impl Marks {
+    /// Where printed text goes, if anywhere
+    fn printing(&mut self) -> Option<&mut Lines> {
+        self.echo.as_mut().or(self.output.as_mut())
+    }
+
+    /// Act on a finished OSC payload, `]` followed by its parameters
+    fn mark(&mut self, payload: &str) {
+        let Some(mark) = payload.strip_prefix("]133;").or_else(|| payload.strip_prefix("]633;")) else {
+            return;
+        };
+        let mut parts = mark.split(';');
+        match parts.next() {
+            // A new prompt; a command still being echoed was abandoned
+            Some("A") => {
+                self.echo = None;
+                self.end_output();
+            }
+            Some("B") => {
+                self.end_output();
+                self.echo = Some(Lines::default());
+            }
+            Some("C") => {
+                if let Some(line) = self.echo.take().map(Lines::command).filter(|line| !line.is_empty()) {
+                    self.commands.push(MarkedCommand {
+                        line,
+                        exit_code: None,
+                        output: String::new(),
+                    });
+                    self.output = Some(Lines::default());
+                }
+            }
+            Some("D") if self.output.is_some() => {
+                if let Some(command) = self.commands.last_mut() {
+                    command.exit_code = parts.next().and_then(|code| code.parse().ok());
+                }
+                self.end_output();
+            }
+            _ => {}
+        }
+    }
+
+    /// Give the output collected so far to the command that printed it
+    fn end_output(&mut self) {
+        if let (Some(output), Some(command)) = (self.output.take(), self.commands.last_mut()) {
+            command.output = output.output();
+        }
+    }
+
+    /// The commands, the last one with its output so far if it is still running
+    fn finish(mut self) -> Vec<MarkedCommand> {
+        self.end_output();
+        self.commands
+    }
+}
+
+/// The command line in what a shell printed between a B mark and the C mark ending it
+pub fn echoed_command(echo: &[u8]) -> Option<String> {
+    let mut marked = b"\x1b]133;B\x07".to_vec();
+    marked.extend_from_slice(echo);
+    // The echo ends with its C mark, unless it was cut short
+    marked.extend_from_slice(b"\x1b]133;C\x07");
+    extract_commands(&marked).into_iter().next().map(|command| command.line)
+}
+
+/// A script running the commands with the session's shell
+pub fn script(commands: &[&MarkedCommand], shell: &str) -> String {
+    let name = shell.rsplit('/').next().unwrap_or(shell);
+    let interpreter = match name {
+        "bash" | "zsh" | "fish" | "ksh" | "mksh" | "dash" | "sh" => name,
+        // Commands typed at other shells are most likely still POSIX
+        _ => "sh",
+    };
+    let mut script = format!("#!/usr/bin/env {}\n\n", interpreter);
+    for command in commands {
+        script.push_str(&command.line);
+        script.push('\n');
+    }
+    script
+}
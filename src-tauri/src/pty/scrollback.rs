@@ -0,0 +1,165 @@
+// Backend scrollback
+// Keeps the most recent output of a session, as it was delivered to the frontend, so a terminal
+// can be re-rendered after a webview reload or when a tab moves to another window
+
+use super::hibernate::{self, HibernationState, Spill};
+use super::session::OutputEncoding;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Scrollback kept per session unless the profile says otherwise
+pub const DEFAULT_CAPACITY_BYTES: usize = 4 * 1024 * 1024;
+
+/// A range of a session's output
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollbackChunk {
+    /// Stream offset of the first byte returned; later than requested if that output was dropped
+    pub offset: u64,
+    /// Stream offset just past the last byte returned
+    pub end: u64,
+    /// Oldest stream offset still held
+    pub first: u64,
+    /// Total output so far, the offset the next output will start at
+    pub total: u64,
+    /// How `data` is encoded, the session's output encoding
+    pub encoding: OutputEncoding,
+    /// Text, or base64 for sessions using the base64 output encoding
+    pub data: String,
+}
+
+/// Ring buffer of the latest output, addressed by offset into the session's whole output stream
+///
+/// Reads and pushes expect a hibernating buffer to be woken first.
+#[derive(Debug)]
+pub struct Scrollback {
+    data: VecDeque<u8>,
+    capacity: usize,
+    /// Stream offset of `data[0]`
+    first: u64,
+    /// Output moved to disk while the session hibernates; `data` is empty meanwhile
+    spill: Option<Spill>,
+}
+
+impl Scrollback {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            data: VecDeque::new(),
+            capacity,
+            first: 0,
+            spill: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Append output, dropping the oldest beyond capacity
+    pub fn push(&mut self, bytes: &[u8]) {
+        if bytes.len() >= self.capacity {
+            self.first += (self.data.len() + bytes.len() - self.capacity) as u64;
+            self.data.clear();
+            self.data.extend(&bytes[bytes.len() - self.capacity..]);
+            return;
+        }
+        let overflow = (self.data.len() + bytes.len()).saturating_sub(self.capacity);
+        self.drop_oldest(overflow);
+        self.data.extend(bytes);
+    }
+
+    /// Drop the oldest output until at most `len` bytes are held
+    pub fn trim_to(&mut self, len: usize) {
+        self.drop_oldest(self.data.len().saturating_sub(len));
+    }
+
+    /// Copy out up to `len` bytes starting at stream offset `offset`
+    ///
+    /// With `text`, the range is narrowed to whole UTF-8 characters.
+    pub fn read(&self, offset: Option<u64>, len: Option<usize>, text: bool) -> (u64, Vec<u8>) {
+        let total = self.first + self.data.len() as u64;
+        let start = offset.unwrap_or(self.first).clamp(self.first, total);
+        let mut from = (start - self.first) as usize;
+        let mut to = len.map_or(self.data.len(), |len| from.saturating_add(len).min(self.data.len()));
+
+        if text {
+            // Continuation bytes are never the first byte of a character
+            while from < to && is_continuation(self.data[from]) {
+                from += 1;
+            }
+            if to < self.data.len() {
+                while to > from && is_continuation(self.data[to]) {
+                    to -= 1;
+                }
+            }
+        }
+
+        let bytes = self.data.range(from..to).copied().collect();
+        (self.first + from as u64, bytes)
+    }
+
+    /// Oldest stream offset still held
+    pub fn first(&self) -> u64 {
+        self.first
+    }
+
+    /// Total output pushed so far
+    pub fn total(&self) -> u64 {
+        self.first + self.held() as u64
+    }
+
+    pub fn is_hibernating(&self) -> bool {
+        self.spill.is_some()
+    }
+
+    pub fn hibernation(&self) -> HibernationState {
+        HibernationState {
+            hibernating: self.spill.is_some(),
+            bytes: self.spill.as_ref().map_or(0, |spill| spill.bytes),
+            compressed_bytes: self.spill.as_ref().map_or(0, |spill| spill.compressed_bytes),
+        }
+    }
+
+    /// Move the output to disk and free its memory
+    pub fn hibernate(&mut self, session_id: &str) -> Result<(), String> {
+        if self.spill.is_some() || self.data.is_empty() {
+            return Ok(());
+        }
+        self.spill = Some(hibernate::spill(session_id, self.data.make_contiguous())?);
+        self.data = VecDeque::new();
+        Ok(())
+    }
+
+    /// Read hibernated output back into memory; if it cannot be read, it is dropped
+    pub fn wake(&mut self) -> Result<(), String> {
+        let Some(spill) = self.spill.take() else {
+            return Ok(());
+        };
+        let bytes = spill.bytes;
+        match spill.restore() {
+            Ok(data) if data.len() == bytes => {
+                self.data = data.into();
+                Ok(())
+            }
+            result => {
+                self.first += bytes as u64;
+                Err(result.err().unwrap_or_else(|| "Hibernated scrollback was truncated".to_string()))
+            }
+        }
+    }
+
+    /// Bytes of output kept, in memory or on disk
+    fn held(&self) -> usize {
+        self.data.len() + self.spill.as_ref().map_or(0, |spill| spill.bytes)
+    }
+
+    fn drop_oldest(&mut self, count: usize) {
+        let count = count.min(self.data.len());
+        self.data.drain(..count);
+        self.first += count as u64;
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
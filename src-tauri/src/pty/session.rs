@@ -1,89 +1,805 @@
 // PTY Session Management
 // Handles PTY spawning, reading, and lifecycle
 
-use portable_pty::{native_pty_system, CommandBuilder, Child, MasterPty, PtySize};
+use super::activity::{ActivityWatch, MonitorAlert, MonitorSettings};
+use super::backend::{ExitStatus, NativeBackend, PtyBackend, PtyProcess, SpawnedPty};
+use super::bandwidth::{BandwidthMeter, BandwidthStats};
+use super::batch::{BatchSettings, Batcher};
+use super::bell::BellSettings;
+use super::broadcast::{BroadcastGroup, BroadcastReport, BroadcastRules, FailedTarget, SkipReason};
+use super::clipboard::ClipboardPolicy;
+use super::container::{self, SessionContainer};
+use super::environment::{self, EnvInheritance};
+use super::follow::{FileFollower, FollowInfo};
+use super::hang::{HangCheck, HangState, HangWatch};
+use super::hibernate::{self, HibernationState};
+use super::input_pipe::InputPipe;
+use super::input_queue::InputQueue;
+use super::jobs::{self, ForegroundProcess, JobInfo};
+use super::latency::{LatencyProbe, LatencyReport};
+use super::limits::{self, Limit, ResourceLimits};
+use super::links::LinkResolver;
+use super::man::{self, ManPage};
+use super::memory::{self, BufferKind, MemoryAccount, MemoryUsage};
+use super::modes::TerminalModes;
+use super::monitor;
+use super::output::OutputProcessor;
+use super::priority::{IoPriority, Priority};
+use super::procfs::{self, ProcessTree};
+use super::progress::TerminalProgress;
+use super::recording::{self, Recorder, RecordingInfo};
+use super::redact::{LiveRedaction, RedactionSettings, Redactor};
+use super::resize::{self, Flush, ResizeState};
+use super::sandbox::SandboxOptions;
+use super::scratch::{self, SessionScratch};
+use super::script::{self, CommandOutputDiff, DiffedCommand, ExportedScript};
+use super::scrollback::{self, Scrollback, ScrollbackChunk};
+use super::signal::Signal;
+use super::title::{self, ReportedCwd, SessionLocation, TitleContext};
+use super::utf8::Utf8Decoder;
+use crate::commands::settings;
+use crate::diff;
+use crate::events::{EventBus, EventKind};
+use crate::logs::session::{self as session_log, LogFormat, SessionLog, SessionLogInfo};
+use crate::logs::writer::LoggingSettings;
+use crate::profiles::{self, SpawnContext};
+use crate::serial::port::{self as serial_port, SerialOptions};
+use crate::session_state::SessionSnapshot;
+use crate::ssh::RemoteHostInfo;
+use crate::sync::MutexExt;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use portable_pty::{CommandBuilder, PtySize};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 /// Session information returned to frontend
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct SessionInfo {
     pub id: String,
     pub pid: u32,
     pub shell: String,
+    pub modes: TerminalModes,
+    /// User-assigned name, e.g. "prod db"
+    pub name: Option<String>,
+    pub labels: Vec<String>,
+    pub appearance: SessionAppearance,
+    /// Title from the profile's title template
+    pub title: Option<String>,
+    /// Window title set by the program itself (OSC 0/2), e.g. "vim ~/file.rs"
+    pub program_title: Option<String>,
+    /// Working directory reported by the shell (OSC 7)
+    pub reported_cwd: Option<ReportedCwd>,
+    /// Command line of the last command run, from shell integration's prompt marks
+    pub last_command: Option<String>,
+    /// Whether the shell or foreground process runs as root
+    pub elevated: bool,
+    /// Container the session runs in, when that is not the host
+    pub container: Option<SessionContainer>,
+    /// Nothing from this session is written to disk
+    pub incognito: bool,
+    /// The shell runs inside a filesystem sandbox
+    pub sandboxed: bool,
+    /// The session only displays output, e.g. a file viewer
+    pub read_only: bool,
+    pub output_encoding: OutputEncoding,
+    /// Line settings when the session is a serial port rather than a process
+    pub serial: Option<SerialOptions>,
+    /// Profile the session was opened with, chosen by a rule if the spawn named none
+    pub profile_id: Option<String>,
+    /// System an SSH session is connected to, once probed
+    pub remote_host: Option<RemoteHostInfo>,
+    /// Progress the running program reported
+    pub progress: Option<TerminalProgress>,
+    /// Whether activity and silence are reported
+    pub monitor: MonitorSettings,
+    /// Broadcast group whose members receive the session's input
+    pub broadcast_group: Option<String>,
+}
+
+/// How a session's output reaches the frontend
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputEncoding {
+    /// Decoded UTF-8 text on `pty://{id}/data`
+    #[default]
+    Text,
+    /// Raw bytes as base64 on `pty://{id}/binary`, for programs that emit binary data
+    /// (zmodem, sixel); live secret masking does not apply
+    Base64,
+}
+
+/// Liveness of a session, published periodically on `pty://{id}/heartbeat`
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHeartbeat {
+    /// The shell has not exited
+    pub child_alive: bool,
+    /// The reader task is still forwarding output
+    pub reader_alive: bool,
+    /// Output read since the previous heartbeat
+    pub bytes_since_last: u64,
+}
+
+/// TERM advertised to spawned shells
+pub const TERM: &str = "xterm-256color";
+
+/// Markers wrapped around pasted text when bracketed paste is enabled
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
+/// How long to wait for the exit status once a session's output has ended
+const EXIT_WAIT: Duration = Duration::from_secs(2);
+
+/// How long a closed session's processes get to exit after SIGHUP before they are killed
+const CLOSE_GRACE: Duration = Duration::from_secs(3);
+
+/// Bytes asked for by each read of a terminal
+const READ_SIZE: usize = 8192;
+
+/// How long masked output held back for the rest of its line waits before it is shown anyway,
+/// e.g. a prompt, which has no line end
+const LIVE_REDACTION_WAIT: Duration = Duration::from_millis(30);
+
+/// Reads waiting to be processed; once full, the reader waits and the program's writes block
+/// on the full terminal, rather than output piling up in memory
+const READ_QUEUE: usize = 32;
+
+/// Presentation metadata for a session's tab
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SessionAppearance {
+    /// Tab color as `#rgb` or `#rrggbb`
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub emoji: Option<String>,
+}
+
+impl SessionAppearance {
+    /// Check that the color, if any, is a hex color
+    fn validate(&self) -> Result<(), String> {
+        if let Some(color) = &self.color {
+            let hex = color.strip_prefix('#').unwrap_or("");
+            let valid = matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(format!("Invalid tab color: {}", color));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Options for spawning a PTY
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct SpawnOptions {
     pub shell: Option<String>,
+    /// Arguments passed to the shell program
+    pub args: Option<Vec<String>>,
+    /// Working directory of the shell; defaults to the home directory
+    pub cwd: Option<PathBuf>,
     pub cols: u16,
     pub rows: u16,
     pub env: Option<HashMap<String, String>>,
+    /// Profile title template, see `title::render` for placeholders
+    pub title_template: Option<String>,
+    /// Reply sent when the application sends ENQ (empty by default)
+    pub answerback: Option<String>,
+    /// Strip AppImage/Flatpak/Snap variables from the child environment (default true)
+    pub sanitize_env: Option<bool>,
+    /// Which of the app's environment variables the shell inherits (default full)
+    pub env_inheritance: Option<EnvInheritance>,
+    /// Variables inherited in addition to the clean baseline, or the whole allowlist;
+    /// a trailing `*` matches a prefix
+    pub inherit_vars: Option<Vec<String>>,
+    /// Cap on backend buffer memory for this session, in megabytes
+    pub memory_limit_mb: Option<usize>,
+    /// Keep this session out of history, logs, recordings, clipboard history, and restore
+    pub incognito: Option<bool>,
+    /// CPU niceness of the shell, -20 to 19
+    pub nice: Option<i32>,
+    /// I/O scheduling class and level of the shell
+    pub ionice: Option<IoPriority>,
+    /// OOM score adjustment of the shell, -1000 to 1000; positive values make the kernel
+    /// kill this session's processes before the terminal
+    pub oom_score_adj: Option<i32>,
+    /// File mode creation mask of the shell in octal, e.g. "022"
+    pub umask: Option<String>,
+    /// Soft limit on open file descriptors
+    pub open_files_limit: Option<Limit>,
+    /// Soft limit on core dump size in KiB; 0 disables core dumps
+    pub core_dump_size_kb: Option<Limit>,
+    /// Run the shell in a bubblewrap sandbox limited to the given paths
+    pub sandbox: Option<SandboxOptions>,
+    /// Give the session its own TMPDIR, removed on close
+    pub private_tmp: Option<bool>,
+    /// Use an empty home seeded with the shell startup files, removed on close
+    pub throwaway_home: Option<bool>,
+    /// The session only displays output; broadcast input skips it
+    pub read_only: Option<bool>,
+    /// Deliver output as text (default) or as raw base64-encoded bytes
+    pub output_encoding: Option<OutputEncoding>,
+    /// Output kept in the backend for replay, in megabytes; 0 keeps none (default 4)
+    pub scrollback_mb: Option<usize>,
+    /// Profile to open the session with; when unset, profile rules may pick one
+    pub profile_id: Option<String>,
+    /// Where the spawn was asked for, whose default profile is used when nothing else picks one
+    pub context: Option<SpawnContext>,
+    /// What programs may do with the clipboard through OSC 52; defaults to the profile's
+    pub clipboard: Option<ClipboardPolicy>,
+    /// What ringing the bell does; defaults to the profile's
+    pub bell: Option<BellSettings>,
+}
+
+/// Per-session configuration resolved at spawn time
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub shell: String,
+    pub title_template: String,
+    pub answerback: String,
+    pub memory_limit: usize,
+    /// Fixed at spawn so the frontend cannot turn persistence back on
+    pub incognito: bool,
+    pub sandboxed: bool,
+    pub read_only: bool,
+    pub output_encoding: OutputEncoding,
+    /// Secret masking from the redaction settings, if enabled
+    pub redactor: Option<Arc<Redactor>>,
+    pub clipboard: ClipboardPolicy,
+    pub bell: BellSettings,
+}
+
+impl SessionConfig {
+    /// Whether history, logs, recordings, clipboard history, and restore may store this session
+    pub fn allows_persistence(&self) -> bool {
+        !self.incognito
+    }
+
+    /// Output as it may be written to history, logs, or recordings
+    pub fn redact_for_storage<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match &self.redactor {
+            Some(redactor) => redactor.redact(text),
+            None => Cow::Borrowed(text),
+        }
+    }
+}
+
+/// State shared between a session and its reader task
+pub struct SessionShared {
+    writer: Mutex<Box<dyn Write + Send>>,
+    pub modes: Mutex<TerminalModes>,
+    /// Exit code of the last command, when reported by shell integration
+    pub last_exit_code: Mutex<Option<i32>>,
+    /// Command line of the last command run, when shell integration echoed it between marks
+    pub last_command: Mutex<Option<String>>,
+    /// Window title last set by the program with OSC 0 or 2
+    pub program_title: Mutex<Option<String>>,
+    /// Working directory last reported by the shell with OSC 7
+    pub reported_cwd: Mutex<Option<ReportedCwd>>,
+    /// Progress last reported with OSC 9;4, until removed or the next prompt
+    pub progress: Mutex<Option<TerminalProgress>>,
+    /// Size of backend buffers held for this session
+    pub memory: MemoryAccount,
+    /// Total output read from the terminal
+    pub bytes_read: AtomicU64,
+    /// Total input written to the terminal
+    pub bytes_written: AtomicU64,
+    /// Set by the waiter once the shell has been reaped
+    pub child_exited: AtomicBool,
+    /// Keystroke-to-echo measurement, when enabled
+    pub latency: Mutex<Option<LatencyProbe>>,
+    /// Latest output as delivered, for replay
+    scrollback: Mutex<Scrollback>,
+    /// Output log, while one is being written
+    log: Mutex<Option<SessionLog>>,
+    /// Asciicast recording, while one is being made
+    recording: Mutex<Option<Recorder>>,
+}
+
+impl SessionShared {
+    pub fn new(writer: Box<dyn Write + Send>, scrollback_bytes: usize) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            modes: Mutex::new(TerminalModes::default()),
+            last_exit_code: Mutex::new(None),
+            last_command: Mutex::new(None),
+            program_title: Mutex::new(None),
+            reported_cwd: Mutex::new(None),
+            progress: Mutex::new(None),
+            memory: MemoryAccount::default(),
+            bytes_read: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            child_exited: AtomicBool::new(false),
+            latency: Mutex::new(None),
+            scrollback: Mutex::new(Scrollback::new(scrollback_bytes)),
+            log: Mutex::new(None),
+            recording: Mutex::new(None),
+        }
+    }
+
+    /// Tee output to the log, handing the log back if writing to it failed
+    fn write_log(&self, bytes: &[u8]) -> Option<(SessionLog, String)> {
+        let mut slot = self.log.locked();
+        let error = slot.as_mut()?.write(bytes).err()?;
+        slot.take().map(|log| (log, error))
+    }
+
+    /// Tell the log where a command's output starts or ends, for its size limit
+    pub fn log_command(&self, running: bool) {
+        if let Some(log) = self.log.locked().as_mut() {
+            if running {
+                log.command_started();
+            } else {
+                log.command_finished();
+            }
+        }
+    }
+
+    fn take_log(&self) -> Option<SessionLog> {
+        self.log.locked().take()
+    }
+
+    /// Add output to the recording, handing the recorder back if writing to it failed
+    fn write_recording(&self, bytes: &[u8]) -> Option<(Recorder, String)> {
+        let mut slot = self.recording.locked();
+        let error = slot.as_mut()?.output(bytes).err()?;
+        slot.take().map(|recorder| (recorder, error))
+    }
+
+    /// Add a size change to the recording; failures surface with the next output
+    fn record_resize(&self, size: PtySize) {
+        if let Some(recorder) = self.recording.locked().as_mut() {
+            if let Err(e) = recorder.resize(size.cols, size.rows) {
+                log::warn!("Failed to record resize: {}", e);
+            }
+        }
+    }
+
+    fn take_recording(&self) -> Option<Recorder> {
+        self.recording.locked().take()
+    }
+
+    /// Lock the scrollback, reading it back from disk first if the session hibernates
+    fn awake_scrollback(&self) -> MutexGuard<'_, Scrollback> {
+        let mut scrollback = self.scrollback.locked();
+        if scrollback.is_hibernating() {
+            match scrollback.wake() {
+                Ok(()) => self.memory.add(BufferKind::Scrollback, scrollback.len()),
+                Err(e) => log::warn!("{}; the session's scrollback is lost", e),
+            }
+        }
+        scrollback
+    }
+
+    /// Move the scrollback to disk if it holds at least `min_bytes`, returning whether it did
+    pub fn hibernate(&self, session_id: &str, min_bytes: usize) -> Result<bool, String> {
+        let mut scrollback = self.scrollback.locked();
+        let len = scrollback.len();
+        if scrollback.is_hibernating() || len < min_bytes {
+            return Ok(false);
+        }
+        scrollback.hibernate(session_id)?;
+        self.memory.release(BufferKind::Scrollback, len);
+        Ok(true)
+    }
+
+    /// Keep output in the scrollback, halving it first if the memory watchdog asked
+    fn keep_output(&self, bytes: &[u8]) {
+        let mut scrollback = self.awake_scrollback();
+        let before = scrollback.len();
+        if self.memory.take_trim_request() {
+            scrollback.trim_to(before / 2);
+        }
+        scrollback.push(bytes);
+
+        let after = scrollback.len();
+        if after > before {
+            self.memory.add(BufferKind::Scrollback, after - before);
+        } else {
+            self.memory.release(BufferKind::Scrollback, before - after);
+        }
+    }
+
+    /// Write bytes to the PTY and flush them
+    pub fn write_all(&self, data: &[u8]) -> Result<(), String> {
+        let mut writer = self.writer.locked();
+        writer
+            .write_all(data)
+            .map_err(|e| format!("Failed to write to PTY: {}", e))?;
+        self.bytes_written.fetch_add(data.len() as u64, Ordering::Relaxed);
+
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to flush PTY: {}", e))
+    }
+
+    /// Paste text, bracketing it if the application enabled bracketed paste
+    pub fn paste(&self, text: &str) -> Result<(), String> {
+        self.write_all(self.paste_data(text).as_bytes())
+    }
+
+    /// Pasted text as it is written to the terminal
+    fn paste_data(&self, text: &str) -> String {
+        // Terminals send CR for newlines; strip end markers so pasted text can't escape the bracket
+        let text = text.replace("\r\n", "\r").replace('\n', "\r").replace(PASTE_END, "");
+        let bracketed = self.modes.locked().bracketed_paste;
+
+        if bracketed {
+            format!("{}{}{}", PASTE_START, text, PASTE_END)
+        } else {
+            text
+        }
+    }
 }
 
+/// Shared map of active sessions keyed by session ID
+pub type SessionMap = Arc<Mutex<HashMap<String, PtySession>>>;
+
 /// Internal PTY session
 pub struct PtySession {
-    #[allow(dead_code)] // Kept for debugging/logging purposes
     id: String,
-    #[allow(dead_code)] // Kept to maintain child process lifecycle
-    child: Box<dyn Child + Send>,
-    pub master: Box<dyn MasterPty + Send>,
-    writer: Mutex<Box<dyn Write + Send>>,
+    pub pid: u32,
+    config: SessionConfig,
+    name: Option<String>,
+    labels: Vec<String>,
+    appearance: SessionAppearance,
+    computed_title: Option<String>,
+    elevated: bool,
+    container: Option<SessionContainer>,
+    over_memory_limit: bool,
+    /// The running shell and its terminal
+    pub process: Box<dyn PtyProcess>,
     reader_handle: JoinHandle<()>,
+    /// Background jobs seen on the last monitor tick
+    pub jobs: Vec<JobInfo>,
+    /// State updated by the reader task
+    shared: Arc<SessionShared>,
+    /// Typed and pasted input, written in order
+    input: InputQueue,
+    /// External input FIFO, removed when the session is dropped
+    input_pipe: Option<InputPipe>,
+    /// Private TMPDIR and throwaway home, removed when the session is dropped
+    scratch: Option<SessionScratch>,
+    /// `bytes_read` at the last heartbeat
+    heartbeat_bytes: u64,
+    /// Coalesces resize requests into as few SIGWINCHs as possible
+    resize: ResizeState,
+    /// What the session was started with, for session restore
+    spawn_options: SpawnOptions,
+    /// Foreground location seen on the last monitor tick
+    location: SessionLocation,
+    /// Traffic of remote clients running in the session, sampled by the monitor without holding
+    /// the session map
+    bandwidth: Arc<Mutex<BandwidthMeter>>,
+    /// Input plus output at the last monitor tick, and when it last changed
+    activity_bytes: u64,
+    active_at: Instant,
+    /// Hibernation state last reported to the frontend
+    hibernating: bool,
+    /// Set for serial port sessions, which have no process to inspect or restore
+    serial: Option<SerialOptions>,
+    remote_host: Option<RemoteHostInfo>,
+    /// Input left unanswered, for hang detection
+    hang: HangWatch,
+    /// Output resuming or stopping, when monitored
+    activity: ActivityWatch,
+    broadcast_group: Option<String>,
 }
 
 impl PtySession {
     pub fn new(
         id: String,
-        child: Box<dyn Child + Send>,
-        master: Box<dyn MasterPty + Send>,
-        writer: Box<dyn Write + Send>,
+        config: SessionConfig,
+        process: Box<dyn PtyProcess>,
+        size: PtySize,
         reader_handle: JoinHandle<()>,
+        shared: Arc<SessionShared>,
+        input: InputQueue,
     ) -> Self {
         Self {
+            input,
             id,
-            child,
-            master,
-            writer: Mutex::new(writer),
+            pid: process.pid().unwrap_or(0),
+            config,
+            name: None,
+            labels: Vec::new(),
+            appearance: SessionAppearance::default(),
+            computed_title: None,
+            elevated: false,
+            container: None,
+            over_memory_limit: false,
+            process,
             reader_handle,
+            jobs: Vec::new(),
+            shared,
+            input_pipe: None,
+            scratch: None,
+            heartbeat_bytes: 0,
+            resize: ResizeState::new(size),
+            spawn_options: SpawnOptions::default(),
+            location: SessionLocation::default(),
+            bandwidth: Arc::default(),
+            activity_bytes: 0,
+            active_at: Instant::now(),
+            hibernating: false,
+            serial: None,
+            remote_host: None,
+            hang: HangWatch::default(),
+            activity: ActivityWatch::new(0),
+            broadcast_group: None,
+        }
+    }
+
+    /// Whether the session is a serial port rather than a process
+    pub fn is_serial(&self) -> bool {
+        self.serial.is_some()
+    }
+
+    /// Current liveness, counting output since the previous call
+    pub fn heartbeat(&mut self) -> SessionHeartbeat {
+        let bytes_read = self.shared.bytes_read.load(Ordering::Relaxed);
+        let bytes_since_last = bytes_read - self.heartbeat_bytes;
+        self.heartbeat_bytes = bytes_read;
+
+        SessionHeartbeat {
+            child_alive: !self.shared.child_exited.load(Ordering::Relaxed),
+            reader_alive: !self.reader_handle.is_finished(),
+            bytes_since_last,
+        }
+    }
+
+    /// Snapshot of the session for the frontend
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            id: self.id.clone(),
+            pid: self.pid,
+            shell: self.config.shell.clone(),
+            modes: self.shared.modes.locked().clone(),
+            name: self.name.clone(),
+            labels: self.labels.clone(),
+            appearance: self.appearance.clone(),
+            title: self.computed_title.clone(),
+            program_title: self.shared.program_title.locked().clone(),
+            reported_cwd: self.shared.reported_cwd.locked().clone(),
+            last_command: self.shared.last_command.locked().clone(),
+            elevated: self.elevated,
+            container: self.container.clone(),
+            incognito: self.config.incognito,
+            sandboxed: self.config.sandboxed,
+            read_only: self.config.read_only,
+            output_encoding: self.config.output_encoding,
+            serial: self.serial.clone(),
+            profile_id: self.spawn_options.profile_id.clone(),
+            remote_host: self.remote_host.clone(),
+            progress: *self.shared.progress.locked(),
+            monitor: self.activity.settings(),
+            broadcast_group: self.broadcast_group.clone(),
+        }
+    }
+
+    /// PID of the foreground process group leader, falling back to the shell
+    pub fn foreground_pid(&self) -> u32 {
+        // The foreground process group leader's pid equals the group id
+        self.process
+            .foreground_pgrp()
+            .and_then(|pgrp| u32::try_from(pgrp).ok())
+            .filter(|pid| procfs::read_stat(*pid).is_some())
+            .unwrap_or(self.pid)
+    }
+
+    /// Re-check whether the session runs as root, returning the new state if it changed
+    pub fn refresh_elevated(&mut self) -> Option<bool> {
+        self.update_elevated(procfs::is_elevated(self.pid, self.foreground_pid()))
+    }
+
+    /// Record whether the session runs as root, returning the new state if it changed
+    pub fn update_elevated(&mut self, elevated: bool) -> Option<bool> {
+        if elevated == self.elevated {
+            return None;
         }
+        self.elevated = elevated;
+        Some(elevated)
+    }
+
+    /// Re-check which container the session runs in, returning the new one if it changed
+    pub fn refresh_container(&mut self) -> Option<Option<SessionContainer>> {
+        self.update_container(container::detect(self.foreground_pid(), self.pid))
+    }
+
+    /// Record which container the session runs in, returning the new one if it changed
+    pub fn update_container(&mut self, container: Option<SessionContainer>) -> Option<Option<SessionContainer>> {
+        if container == self.container {
+            return None;
+        }
+        self.container = container.clone();
+        Some(container)
+    }
+
+    /// Current backend buffer usage of the session
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.shared.memory.usage(self.config.memory_limit)
+    }
+
+    /// Request trimming when over the memory limit, returning usage when the limit is first crossed
+    pub fn check_memory(&mut self) -> Option<MemoryUsage> {
+        let usage = self.memory_usage();
+        let over = usage.total_bytes > usage.limit_bytes;
+        if over {
+            self.shared.memory.request_trim();
+        }
+
+        let newly_over = over && !self.over_memory_limit;
+        self.over_memory_limit = over;
+        newly_over.then_some(usage)
+    }
+
+    /// Recompute the title from current state, returning it if it changed
+    pub fn refresh_title(&mut self) -> Option<String> {
+        let mut context = TitleContext::gather(self.foreground_pid(), &self.config.shell);
+        context.status = *self.shared.last_exit_code.locked();
+        context.name = self.name.clone();
+
+        let computed = title::render(&self.config.title_template, &context);
+        if self.computed_title.as_deref() == Some(computed.as_str()) {
+            return None;
+        }
+        self.computed_title = Some(computed.clone());
+        Some(computed)
+    }
+
+    /// Look up the foreground process's directory and remote host, returning them if they changed
+    pub fn refresh_location(&mut self) -> Option<SessionLocation> {
+        let location = SessionLocation::gather(self.foreground_pid());
+        if location == self.location {
+            return None;
+        }
+        self.location = location.clone();
+        Some(location)
+    }
+
+    /// Sample the traffic of remote clients, returning the new stats if they changed
+    pub fn refresh_bandwidth(&mut self) -> Option<BandwidthStats> {
+        // The session's own process counts too, for sessions that run ssh instead of a shell
+        let mut processes: Vec<_> = procfs::read_stat(self.pid).into_iter().collect();
+        processes.extend(procfs::descendants(self.pid));
+        let (meter, output, input) = self.bandwidth_meter();
+        let mut meter = meter.locked();
+        meter.sample(&processes, self.process.foreground_pgrp(), output, input)
+    }
+
+    /// The remote traffic meter, with the session's output and input counters to sample it at
+    pub fn bandwidth_meter(&self) -> (Arc<Mutex<BandwidthMeter>>, u64, u64) {
+        (
+            self.bandwidth.clone(),
+            self.shared.bytes_read.load(Ordering::Relaxed),
+            self.shared.bytes_written.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Note input and output since the last tick, returning the hibernation state if it changed
+    pub fn refresh_hibernation(&mut self) -> Option<HibernationState> {
+        let bytes = self.shared.bytes_read.load(Ordering::Relaxed) + self.shared.bytes_written.load(Ordering::Relaxed);
+        if bytes != self.activity_bytes {
+            self.activity_bytes = bytes;
+            self.active_at = Instant::now();
+        }
+
+        let state = self.shared.scrollback.locked().hibernation();
+        if state.hibernating == self.hibernating {
+            return None;
+        }
+        // Woken by a focus or read rather than output; the idle time starts over all the same
+        if !state.hibernating {
+            self.active_at = Instant::now();
+        }
+        self.hibernating = state.hibernating;
+        Some(state)
+    }
+
+    /// Check whether the foreground program stopped responding to input, returning the state if
+    /// it changed; `threshold` is how long input may go unanswered, None when detection is off
+    pub fn refresh_hang(&mut self, threshold: Option<Duration>) -> Option<HangState> {
+        let bytes_read = self.shared.bytes_read.load(Ordering::Relaxed);
+        let bytes_written = self.shared.bytes_written.load(Ordering::Relaxed);
+        match self.hang.check(bytes_read, bytes_written, threshold) {
+            HangCheck::Unchanged => None,
+            HangCheck::Recovered => Some(HangState::recovered()),
+            HangCheck::Suspect(waited) => {
+                // Input that was read went to a program with nothing to say, not a hung one
+                let unread_bytes = self.process.unread_input().unwrap_or(0);
+                if unread_bytes == 0 {
+                    self.hang.cleared();
+                    return None;
+                }
+                self.hang.flagged();
+                Some(HangState {
+                    unresponsive: true,
+                    unread_bytes,
+                    seconds: waited.as_secs(),
+                    process: jobs::foreground_process(self.pid, self.process.foreground_pgrp()),
+                })
+            }
+        }
+    }
+
+    /// Check whether output resumed or stopped, when the session is monitored for it
+    pub fn refresh_activity(&mut self) -> Option<MonitorAlert> {
+        self.activity.check(self.shared.bytes_read.load(Ordering::Relaxed))
+    }
+
+    /// The session's shared state if it has been idle for `idle` and may hibernate
+    pub fn hibernation_candidate(&self, idle: Duration) -> Option<Arc<SessionShared>> {
+        let eligible = !self.config.incognito && !self.hibernating && self.active_at.elapsed() >= idle;
+        eligible.then(|| self.shared.clone())
     }
 }
 
 /// PTY Manager - Manages all active PTY sessions
 pub struct PtyManager {
-    sessions: Arc<Mutex<HashMap<String, PtySession>>>,
-    app_handle: AppHandle,
+    backend: Arc<dyn PtyBackend>,
+    sessions: SessionMap,
+    /// File-follow pseudo-sessions, which share the session ID space and events
+    followers: Mutex<HashMap<String, FileFollower>>,
+    events: Arc<EventBus>,
+    /// Finds the paths in every session's output, off the readers
+    links: LinkResolver,
 }
 
 impl PtyManager {
-    /// Create a new PTY manager
-    pub fn new(app_handle: AppHandle) -> Self {
+    /// Create a new PTY manager using the system PTY, publishing session events to `events`
+    pub fn new(events: Arc<EventBus>) -> Self {
+        // Scratch directories and hibernated scrollback of a previous run that crashed are no
+        // longer in use
+        scratch::sweep_stale();
+        hibernate::sweep_stale();
+        Self::with_backend(events, Arc::new(NativeBackend))
+    }
+
+    /// Create a PTY manager on a specific backend, e.g. the in-memory mock
+    pub fn with_backend(events: Arc<EventBus>, backend: Arc<dyn PtyBackend>) -> Self {
+        let sessions: SessionMap = Arc::new(Mutex::new(HashMap::new()));
+        monitor::start(sessions.clone(), events.clone());
+
         Self {
-            sessions: Arc::new(Mutex::new(HashMap::new())),
-            app_handle,
+            backend,
+            sessions,
+            followers: Mutex::new(HashMap::new()),
+            links: LinkResolver::start(events.clone()),
+            events,
         }
     }
 
     /// Spawn a new PTY session
-    pub fn spawn(&self, options: SpawnOptions) -> Result<SessionInfo, String> {
+    pub fn spawn(&self, mut options: SpawnOptions) -> Result<SessionInfo, String> {
         let id = Uuid::new_v4().to_string();
 
-        // Detect default shell if not specified
-        let shell = options.shell.unwrap_or_else(|| {
-            std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
-        });
+        // Fill in the shell, arguments, directory, and variables from the profile
+        profiles::resolve(&mut options);
+        let spawn_options = options.clone();
+        let shell = options.shell.unwrap_or_default();
 
         log::info!("Spawning PTY with shell: {}", shell);
 
-        // Create PTY
-        let pty_system = native_pty_system();
+        let priority = Priority {
+            nice: options.nice,
+            io: options.ionice,
+            oom_score_adj: options.oom_score_adj,
+        };
+        priority.validate()?;
+        let umask = options.umask.as_deref().map(limits::parse_umask).transpose()?;
+        let resource_limits = ResourceLimits {
+            open_files: options.open_files_limit,
+            core_dump_kb: options.core_dump_size_kb,
+        };
+
         let pty_size = PtySize {
             rows: options.rows,
             cols: options.cols,
@@ -91,12 +807,32 @@ impl PtyManager {
             pixel_height: 0,
         };
 
-        let pty_pair = pty_system
-            .openpty(pty_size)
-            .map_err(|e| format!("Failed to open PTY: {}", e))?;
-
         // Build command
-        let mut cmd = CommandBuilder::new(&shell);
+        let args = options.args.clone().unwrap_or_default();
+        let mut argv = match &options.sandbox {
+            Some(sandbox) => sandbox.wrap(&shell, &args)?,
+            None => std::iter::once(shell.clone()).chain(args).collect(),
+        };
+        // Not fatal: the shell starts with the inherited limits instead
+        if !resource_limits.is_default() {
+            let errors;
+            (argv, errors) = resource_limits.wrap(argv);
+            for e in errors {
+                log::warn!("Session {} resource limits not fully applied: {}", id, e);
+            }
+        }
+        let mut cmd = CommandBuilder::from_argv(argv.into_iter().map(Into::into).collect());
+
+        // Undo packaging overrides before applying profile variables
+        if options.sanitize_env.unwrap_or(true) {
+            environment::sanitize(&mut cmd);
+        }
+        environment::apply_inheritance(
+            &mut cmd,
+            options.env_inheritance.unwrap_or_default(),
+            options.inherit_vars.as_deref().unwrap_or_default(),
+            &shell,
+        );
 
         // Set environment variables if provided
         if let Some(env) = options.env {
@@ -106,68 +842,363 @@ impl PtyManager {
         }
 
         // Set default environment for terminal
-        cmd.env("TERM", "xterm-256color");
+        cmd.env("TERM", TERM);
         cmd.env("COLORTERM", "truecolor");
 
-        // Spawn child process
-        let child = pty_pair
-            .slave
-            .spawn_command(cmd)
-            .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        let private_tmp = options.private_tmp.unwrap_or(false);
+        let throwaway_home = options.throwaway_home.unwrap_or(false);
+        let scratch = if options.sandbox.is_some() {
+            // The sandbox already mounts an empty /tmp and home
+            None
+        } else if private_tmp || throwaway_home {
+            Some(SessionScratch::create(&id, private_tmp, throwaway_home, &mut cmd)?)
+        } else {
+            None
+        };
 
-        let pid = child.process_id().unwrap_or(0);
+        // A directory that has gone away since it was picked is not worth failing the spawn over
+        if let Some(cwd) = options.cwd {
+            if cwd.is_dir() {
+                cmd.cwd(cwd);
+            } else {
+                log::warn!("Working directory {:?} does not exist, using the default", cwd);
+            }
+        }
 
-        log::info!("Spawned shell with PID: {}", pid);
+        // Applied in the child between fork and exec
+        cmd.umask(umask);
 
-        // Get the writer upfront - take_writer can only be called once
-        let writer = pty_pair
-            .master
-            .take_writer()
-            .map_err(|e| format!("Failed to get writer: {}", e))?;
+        // Spawn child process on a new terminal
+        let spawned = self.backend.spawn(cmd, pty_size)?;
 
-        // Start reader task
-        let reader_handle = self.start_reader(&id, pty_pair.master.try_clone_reader().unwrap());
+        log::info!("Spawned shell with PID: {}", spawned.process.pid().unwrap_or(0));
 
-        // Store session with writer
-        let session = PtySession::new(id.clone(), child, pty_pair.master, writer, reader_handle);
-        self.sessions.lock().unwrap().insert(id.clone(), session);
+        // Not fatal: raising priority needs privileges the user may not have
+        if !priority.is_default() {
+            if let Err(e) = spawned.process.set_priority(&priority) {
+                log::warn!("Session {} priority not fully applied: {}", id, e);
+            }
+        }
 
-        Ok(SessionInfo {
-            id,
-            pid,
+        let config = SessionConfig {
             shell,
-        })
+            title_template: options
+                .title_template
+                .filter(|template| !template.trim().is_empty())
+                .unwrap_or_else(|| title::DEFAULT_TEMPLATE.to_string()),
+            answerback: options.answerback.unwrap_or_default(),
+            memory_limit: options
+                .memory_limit_mb
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(memory::DEFAULT_LIMIT_BYTES),
+            incognito: options.incognito.unwrap_or(false),
+            sandboxed: options.sandbox.is_some(),
+            read_only: options.read_only.unwrap_or(false),
+            output_encoding: options.output_encoding.unwrap_or_default(),
+            clipboard: options.clipboard.unwrap_or_default(),
+            bell: options.bell.unwrap_or_default(),
+            redactor: Redactor::from_settings(&settings::read_section::<RedactionSettings>("redaction"))
+                .map(Arc::new),
+        };
+
+        let scrollback_bytes = options
+            .scrollback_mb
+            .map(|mb| mb * 1024 * 1024)
+            .unwrap_or(scrollback::DEFAULT_CAPACITY_BYTES);
+
+        Ok(self.start_session(id, spawned, config, pty_size, scrollback_bytes, |session| {
+            session.scratch = scratch;
+            session.spawn_options = spawn_options;
+            session.refresh_title();
+            session.refresh_elevated();
+            session.refresh_container();
+        }))
     }
 
-    /// Write data to a PTY session
-    pub fn write(&self, session_id: &str, data: &str) -> Result<(), String> {
-        let sessions = self.sessions.lock().unwrap();
-        let session = sessions
+    /// Open a serial port as a session
+    ///
+    /// Output, input, and exit go through the same events as PTY sessions; the session ends
+    /// when it is closed or the device goes away.
+    pub fn spawn_serial(&self, options: SerialOptions, cols: u16, rows: u16) -> Result<SessionInfo, String> {
+        let id = Uuid::new_v4().to_string();
+        log::info!("Opening serial port {} at {} baud", options.port, options.baud);
+        let spawned = serial_port::open(&options)?;
+
+        let config = SessionConfig {
+            shell: options.port.clone(),
+            title_template: title::DEFAULT_TEMPLATE.to_string(),
+            answerback: String::new(),
+            memory_limit: memory::DEFAULT_LIMIT_BYTES,
+            incognito: false,
+            sandboxed: false,
+            read_only: false,
+            output_encoding: OutputEncoding::default(),
+            clipboard: ClipboardPolicy::default(),
+            bell: BellSettings::default(),
+            redactor: Redactor::from_settings(&settings::read_section::<RedactionSettings>("redaction"))
+                .map(Arc::new),
+        };
+        let size = PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+
+        Ok(self.start_session(id, spawned, config, size, scrollback::DEFAULT_CAPACITY_BYTES, |session| {
+            session.computed_title = Some(format!("{} ({} baud)", options.port, options.baud));
+            session.serial = Some(options);
+        }))
+    }
+
+    /// Start reading a spawned terminal and add it to the sessions; `setup` fills in the
+    /// session before it becomes visible
+    fn start_session(
+        &self,
+        id: String,
+        spawned: SpawnedPty,
+        config: SessionConfig,
+        pty_size: PtySize,
+        scrollback_bytes: usize,
+        setup: impl FnOnce(&mut PtySession),
+    ) -> SessionInfo {
+        let shared = Arc::new(SessionShared::new(spawned.writer, scrollback_bytes));
+
+        // Reap the child on its own thread; the reader reports the status once output ends
+        let (exit_sender, exit_receiver) = oneshot::channel();
+        let waiter = spawned.waiter;
+        let waiter_shared = shared.clone();
+        std::thread::spawn(move || {
+            let status = waiter();
+            waiter_shared.child_exited.store(true, Ordering::Relaxed);
+            let _ = exit_sender.send(status);
+        });
+
+        // Start reader task; terminal replies it sends queue behind typed input
+        let input = InputQueue::start(&id, shared.clone());
+        let reader_handle =
+            self.start_reader(&id, spawned.reader, exit_receiver, shared.clone(), input.clone(), config.clone());
+
+        // Store session with writer
+        let mut session = PtySession::new(id.clone(), config, spawned.process, pty_size, reader_handle, shared, input);
+        setup(&mut session);
+        let info = session.info();
+        self.sessions.locked().insert(id, session);
+
+        info
+    }
+
+    /// Get current information about a PTY session
+    pub fn info(&self, session_id: &str) -> Result<SessionInfo, String> {
+        let sessions = self.sessions.locked();
+        sessions
             .get(session_id)
-            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+            .map(PtySession::info)
+            .ok_or_else(|| format!("Session not found: {}", session_id))
+    }
 
-        // Lock the writer and write data
-        let mut writer = session
-            .writer
-            .lock()
-            .map_err(|e| format!("Failed to lock writer: {}", e))?;
+    /// Text as history may store it for a session: redacted, or None for incognito sessions
+    pub fn storable(&self, session_id: &str, text: &str) -> Option<String> {
+        let sessions = self.sessions.locked();
+        let config = &sessions.get(session_id)?.config;
+        config
+            .allows_persistence()
+            .then(|| config.redact_for_storage(text).into_owned())
+    }
 
-        writer
-            .write_all(data.as_bytes())
-            .map_err(|e| format!("Failed to write to PTY: {}", e))?;
+    /// Queue data to be written to a PTY session, after the input queued before it
+    pub fn write(&self, session_id: &str, data: &str) -> Result<(), String> {
+        self.with_session(session_id, |session| {
+            // Timed before queueing, so the wait for earlier input counts towards the latency
+            if let Some(probe) = session.shared.latency.locked().as_mut() {
+                probe.on_input(data.len());
+            }
+            session.input.push(data.as_bytes().to_vec())
+        })
+    }
 
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush PTY: {}", e))?;
+    /// Answer a query from the program; unlike typed input, it is not measured for latency
+    pub fn respond(&self, session_id: &str, data: &[u8]) -> Result<(), String> {
+        self.shared(session_id)?.write_all(data)
+    }
 
+    /// Start or stop measuring keystroke-to-echo latency; stopping discards the samples
+    pub fn set_latency_tracking(&self, session_id: &str, enabled: bool) -> Result<(), String> {
+        let shared = self.shared(session_id)?;
+        let mut latency = shared.latency.locked();
+        match (enabled, latency.is_some()) {
+            (true, false) => *latency = Some(LatencyProbe::default()),
+            (false, true) => *latency = None,
+            _ => {}
+        }
         Ok(())
     }
 
+    /// Keystroke-to-echo latency measured so far
+    pub fn latency(&self, session_id: &str) -> Result<LatencyReport, String> {
+        let shared = self.shared(session_id)?;
+        let latency = shared.latency.locked();
+        Ok(latency.as_ref().map(LatencyProbe::report).unwrap_or_default())
+    }
+
+    /// Paste text into a PTY session, bracketing it if the application asked for it
+    pub fn paste(&self, session_id: &str, text: &str) -> Result<(), String> {
+        self.with_session(session_id, |session| {
+            let data = session.shared.paste_data(text);
+            session.input.push(data.into_bytes())
+        })
+    }
+
+    /// Send the same input to several sessions, skipping targets the rules exclude
+    ///
+    /// Root sessions are only written when their ID is in `confirmed`. Checks run under the
+    /// session map lock; the input is queued on each target, behind what was typed there, and
+    /// privilege change events are published after the lock is released.
+    pub fn write_many(
+        &self,
+        session_ids: &[String],
+        data: &str,
+        rules: &BroadcastRules,
+        confirmed: &[String],
+    ) -> BroadcastReport {
+        let mut report = BroadcastReport::default();
+        let mut targets = Vec::new();
+        let mut changes = Vec::new();
+
+        {
+            let mut sessions = self.sessions.locked();
+            let followers = self.followers.locked();
+            for id in session_ids {
+                let Some(session) = sessions.get_mut(id) else {
+                    let reason = if followers.contains_key(id) {
+                        SkipReason::ReadOnly
+                    } else {
+                        SkipReason::NotFound
+                    };
+                    report.skip(id, reason);
+                    continue;
+                };
+
+                if rules.skip_read_only && session.config.read_only {
+                    report.skip(id, SkipReason::ReadOnly);
+                    continue;
+                }
+                if rules.skip_alt_screen && session.shared.modes.locked().alt_screen {
+                    report.skip(id, SkipReason::AltScreen);
+                    continue;
+                }
+                if rules.confirm_elevated {
+                    // The monitor may not have seen a `sudo -i` typed a moment ago
+                    if let Some(elevated) = session.refresh_elevated() {
+                        changes.push((id.clone(), elevated));
+                    }
+                    if session.elevated && !confirmed.contains(id) {
+                        report.skip(id, SkipReason::NeedsConfirmation);
+                        continue;
+                    }
+                }
+                targets.push((id.clone(), session.input.clone()));
+            }
+        }
+        for (id, elevated) in changes {
+            self.events.publish(&id, EventKind::Elevated { elevated });
+        }
+
+        for (id, input) in targets {
+            match input.push(data.as_bytes().to_vec()) {
+                Ok(()) => report.sent.push(id),
+                Err(error) => report.failed.push(FailedTarget { session_id: id, error }),
+            }
+        }
+        report
+    }
+
+    /// Write input typed into `session_ids` to them, and to the other members of their broadcast
+    /// groups as filtered by `rules`
+    pub fn write_broadcast(
+        &self,
+        session_ids: &[String],
+        data: &str,
+        rules: &BroadcastRules,
+        confirmed: &[String],
+    ) -> BroadcastReport {
+        let members: Vec<String> = {
+            let sessions = self.sessions.locked();
+            let groups: Vec<&String> = session_ids
+                .iter()
+                .filter_map(|id| sessions.get(id)?.broadcast_group.as_ref())
+                .collect();
+            sessions
+                .values()
+                .filter(|session| session.broadcast_group.as_ref().is_some_and(|group| groups.contains(&group)))
+                .map(|session| session.id.clone())
+                .filter(|id| !session_ids.contains(id))
+                .collect()
+        };
+
+        // Where the input was typed it goes in as it is, like any keystroke
+        let mut report = self.write_many(&members, data, rules, confirmed);
+        for id in session_ids {
+            match self.write(id, data) {
+                Ok(()) => report.sent.push(id.clone()),
+                Err(_) if !self.sessions.locked().contains_key(id) => report.skip(id, SkipReason::NotFound),
+                Err(error) => report.failed.push(FailedTarget {
+                    session_id: id.clone(),
+                    error,
+                }),
+            }
+        }
+        report
+    }
+
+    /// Put a session in a broadcast group, or take it out of its group with None or an empty name
+    pub fn set_broadcast_group(&self, session_id: &str, group: Option<&str>) -> Result<SessionInfo, String> {
+        self.with_session(session_id, |session| {
+            session.broadcast_group = group.map(str::trim).filter(|group| !group.is_empty()).map(str::to_string);
+            Ok(session.info())
+        })
+    }
+
+    /// Broadcast groups with at least one session, by name
+    pub fn broadcast_groups(&self) -> Vec<BroadcastGroup> {
+        let sessions = self.sessions.locked();
+        let mut groups: Vec<BroadcastGroup> = Vec::new();
+        for session in sessions.values() {
+            let Some(name) = &session.broadcast_group else {
+                continue;
+            };
+            match groups.iter_mut().find(|group| group.name == *name) {
+                Some(group) => group.session_ids.push(session.id.clone()),
+                None => groups.push(BroadcastGroup {
+                    name: name.clone(),
+                    session_ids: vec![session.id.clone()],
+                }),
+            }
+        }
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        groups
+    }
+
+    /// State shared with a session's reader, for I/O that must not hold the session map lock
+    ///
+    /// A write blocks while the shell is not reading, which would otherwise stall every
+    /// other session operation with it.
+    fn shared(&self, session_id: &str) -> Result<Arc<SessionShared>, String> {
+        let sessions = self.sessions.locked();
+        sessions
+            .get(session_id)
+            .map(|session| session.shared.clone())
+            .ok_or_else(|| format!("Session not found: {}", session_id))
+    }
+
     /// Resize a PTY session
+    ///
+    /// The size is applied shortly after, once resize requests stop arriving, so a window drag
+    /// does not send the program a SIGWINCH for every intermediate size.
     pub fn resize(&self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
-        let sessions = self.sessions.lock().unwrap();
+        let mut sessions = self.sessions.locked();
         let session = sessions
-            .get(session_id)
+            .get_mut(session_id)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
         let size = PtySize {
@@ -177,75 +1208,761 @@ impl PtyManager {
             pixel_height: 0,
         };
 
-        session
-            .master
-            .resize(size)
-            .map_err(|e| format!("Failed to resize PTY: {}", e))
+        if session.resize.request(size) {
+            let sessions = self.sessions.clone();
+            let session_id = session_id.to_string();
+            tauri::async_runtime::spawn(async move {
+                let mut wait = resize::DEBOUNCE;
+                loop {
+                    tokio::time::sleep(wait).await;
+
+                    let mut sessions = sessions.locked();
+                    let Some(session) = sessions.get_mut(&session_id) else {
+                        break;
+                    };
+                    match session.resize.flush() {
+                        Flush::Apply(size) => {
+                            if let Err(e) = session.process.resize(size) {
+                                log::warn!("Failed to resize session {}: {}", session_id, e);
+                            }
+                            session.shared.record_resize(size);
+                            break;
+                        }
+                        Flush::Wait(remaining) => wait = remaining,
+                        Flush::Done => break,
+                    }
+                }
+            });
+        }
+        Ok(())
     }
 
-    /// Close a PTY session
+    /// Close a PTY session or file follower
     pub fn close(&self, session_id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock().unwrap();
+        if self.followers.locked().remove(session_id).is_some() {
+            log::info!("Closing file follower: {}", session_id);
+            return Ok(());
+        }
+
+        let mut sessions = self.sessions.locked();
         let session = sessions
             .remove(session_id)
             .ok_or_else(|| format!("Session not found: {}", session_id))?;
 
+        drop(sessions);
+
         log::info!("Closing session: {}", session_id);
 
         // Abort the reader task
         session.reader_handle.abort();
 
-        // Note: MasterPty is automatically closed when dropped
+        if let Some(log) = session.shared.take_log() {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = log.finish().await {
+                    log::warn!("Failed to finish session log: {}", e);
+                }
+            });
+        }
+        if let Some(recorder) = session.shared.take_recording() {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = recorder.finish().await {
+                    log::warn!("Failed to finish recording: {}", e);
+                }
+            });
+        }
+
+        // Hang up like a closed terminal would, then kill whatever ignores it. The waiter thread
+        // reaps the shell once it exits, so it does not linger as a zombie.
+        if let Err(e) = session.process.signal(Signal::Sighup) {
+            log::debug!("Failed to hang up session {}: {}", session_id, e);
+        }
+        let session_id = session_id.to_string();
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + CLOSE_GRACE;
+            while !session.shared.child_exited.load(Ordering::Relaxed) {
+                if Instant::now() >= deadline {
+                    log::info!("Session {} ignored SIGHUP, killing it", session_id);
+                    if let Err(e) = session.process.signal(Signal::Sigkill) {
+                        log::warn!("Failed to kill session {}: {}", session_id, e);
+                    }
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            // The terminal is closed when the process handle is dropped
+        });
+
+        Ok(())
+    }
+
+    /// Whether a session's process has exited, leaving the session open to be read
+    pub fn has_exited(&self, session_id: &str) -> Result<bool, String> {
+        Ok(self.shared(session_id)?.child_exited.load(Ordering::Relaxed))
+    }
+
+    /// Send a signal to a session's shell and its foreground job
+    pub fn signal(&self, session_id: &str, signal: Signal) -> Result<(), String> {
+        self.with_session(session_id, |session| session.process.signal(signal))
+    }
+
+    /// Send a signal to the foreground job of a session, leaving its shell alone
+    pub fn signal_foreground(&self, session_id: &str, signal: Signal) -> Result<(), String> {
+        self.with_session(session_id, |session| session.process.signal_foreground(signal))
+    }
+
+    /// Start teeing a session's output to a log file
+    ///
+    /// Relative paths are placed in the logs directory. Secrets are masked as configured in
+    /// the redaction settings, and incognito sessions cannot be logged.
+    pub fn start_logging(&self, session_id: &str, path: &str, format: LogFormat) -> Result<SessionLogInfo, String> {
+        let (config, shared) = self.with_session(session_id, |session| Ok((session.config.clone(), session.shared.clone())))?;
+        if !config.allows_persistence() {
+            return Err("Incognito sessions cannot be logged".to_string());
+        }
+        if let Some(log) = shared.log.locked().as_ref() {
+            return Err(format!("Session is already logged to {}", log.info().path));
+        }
+
+        // Created without holding the lock, since the keyring may be slow to hand out the key
+        let path = session_log::resolve_path(path)?;
+        let settings = settings::read_section::<LoggingSettings>("logging");
+        let log = SessionLog::create(&path, format, &settings, config.redactor.clone())?;
+        let info = log.info();
+
+        let mut slot = shared.log.locked();
+        if let Some(existing) = slot.as_ref() {
+            return Err(format!("Session is already logged to {}", existing.info().path));
+        }
+        *slot = Some(log);
+        log::info!("Logging session {} to {}", session_id, info.path);
+        Ok(info)
+    }
+
+    /// Stop logging a session, returning the log so it can be finished off the blocking pool
+    pub fn stop_logging(&self, session_id: &str) -> Result<SessionLog, String> {
+        let shared = self.with_session(session_id, |session| Ok(session.shared.clone()))?;
+        let log = shared
+            .take_log()
+            .ok_or_else(|| format!("Session {} is not being logged", session_id))?;
+        log::info!("Stopped logging session {}", session_id);
+        Ok(log)
+    }
+
+    /// Start recording a session as asciicast v2
+    ///
+    /// Without a path, the recording is named after the session in the recordings directory.
+    /// Secrets are masked as configured in the redaction settings, and incognito sessions
+    /// cannot be recorded.
+    pub fn start_recording(&self, session_id: &str, path: Option<&str>) -> Result<RecordingInfo, String> {
+        let (config, shared, size) = self.with_session(session_id, |session| {
+            Ok((session.config.clone(), session.shared.clone(), session.resize.size()))
+        })?;
+        if !config.allows_persistence() {
+            return Err("Incognito sessions cannot be recorded".to_string());
+        }
+        if let Some(recorder) = shared.recording.locked().as_ref() {
+            return Err(format!("Session is already recorded to {}", recorder.info().path));
+        }
 
+        let path = recording::resolve_path(path, session_id)?;
+        let settings = settings::read_section::<LoggingSettings>("logging");
+        let recorder = Recorder::create(
+            &path,
+            size.cols,
+            size.rows,
+            TERM,
+            &config.shell,
+            &settings,
+            config.redactor.clone(),
+        )?;
+        let info = recorder.info();
+
+        let mut slot = shared.recording.locked();
+        if let Some(existing) = slot.as_ref() {
+            return Err(format!("Session is already recorded to {}", existing.info().path));
+        }
+        *slot = Some(recorder);
+        log::info!("Recording session {} to {}", session_id, info.path);
+        Ok(info)
+    }
+
+    /// Stop recording a session, returning the recorder so it can be finished off the blocking pool
+    pub fn stop_recording(&self, session_id: &str) -> Result<Recorder, String> {
+        let shared = self.with_session(session_id, |session| Ok(session.shared.clone()))?;
+        let recorder = shared
+            .take_recording()
+            .ok_or_else(|| format!("Session {} is not being recorded", session_id))?;
+        log::info!("Stopped recording session {}", session_id);
+        Ok(recorder)
+    }
+
+    /// Describe the sessions that may be restored after a restart
+    ///
+    /// Incognito, read-only, and serial sessions are left out. Each snapshot respawns with the original
+    /// options in the shell's current directory and the terminal's current size.
+    pub fn snapshots(&self) -> Vec<SessionSnapshot> {
+        let sessions = self.sessions.locked();
+        sessions
+            .values()
+            .filter(|session| {
+                session.config.allows_persistence() && !session.config.read_only && !session.is_serial()
+            })
+            .map(|session| {
+                let size = session.resize.size();
+                let mut options = session.spawn_options.clone();
+                // Variables can hold secrets; respawning with the recorded profile_id fills the
+                // profile's back in, and only ones the caller added are lost
+                options.env = None;
+                options.cwd = procfs::read_cwd(session.pid).or(options.cwd);
+                options.cols = size.cols;
+                options.rows = size.rows;
+
+                SessionSnapshot {
+                    id: session.id.clone(),
+                    options,
+                    name: session.name.clone(),
+                    labels: session.labels.clone(),
+                    appearance: session.appearance.clone(),
+                    title: session.computed_title.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// List all active PTY sessions
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let sessions = self.sessions.locked();
+        sessions.values().map(PtySession::info).collect()
+    }
+
+    /// Set or clear (with an empty name) the user-assigned name of a session
+    pub fn rename(&self, session_id: &str, name: &str) -> Result<SessionInfo, String> {
+        self.with_session(session_id, |session| {
+            let name = name.trim();
+            session.name = (!name.is_empty()).then(|| name.to_string());
+            Ok(session.info())
+        })
+    }
+
+    /// Replace the labels of a session, dropping blanks and duplicates
+    pub fn set_labels(&self, session_id: &str, labels: Vec<String>) -> Result<SessionInfo, String> {
+        self.with_session(session_id, |session| {
+            session.labels.clear();
+            for label in labels {
+                let label = label.trim().to_string();
+                if !label.is_empty() && !session.labels.contains(&label) {
+                    session.labels.push(label);
+                }
+            }
+            Ok(session.info())
+        })
+    }
+
+    /// Choose whether output resuming and stopping are reported for a session
+    pub fn set_monitor(&self, session_id: &str, monitor: MonitorSettings) -> Result<SessionInfo, String> {
+        self.with_session(session_id, |session| {
+            let bytes_read = session.shared.bytes_read.load(Ordering::Relaxed);
+            session.activity.set(monitor, bytes_read);
+            Ok(session.info())
+        })
+    }
+
+    /// Get the tab presentation metadata of a session
+    pub fn appearance(&self, session_id: &str) -> Result<SessionAppearance, String> {
+        self.with_session(session_id, |session| Ok(session.appearance.clone()))
+    }
+
+    /// Replace the tab presentation metadata of a session and notify the frontend
+    pub fn set_appearance(&self, session_id: &str, appearance: SessionAppearance) -> Result<(), String> {
+        appearance.validate()?;
+        self.with_session(session_id, |session| {
+            session.appearance = appearance.clone();
+            Ok(())
+        })?;
+
+        self.events.publish(session_id, EventKind::Appearance(appearance));
+        Ok(())
+    }
+
+    /// Attach what a probe found out about the system a session is connected to
+    pub fn set_remote_host(&self, session_id: &str, info: RemoteHostInfo) -> Result<(), String> {
+        self.with_session(session_id, |session| {
+            session.remote_host = Some(info.clone());
+            Ok(())
+        })?;
+
+        self.events.publish(session_id, EventKind::RemoteHost(info));
+        Ok(())
+    }
+
+    /// Get the backend buffer memory used by a session
+    pub fn memory_usage(&self, session_id: &str) -> Result<MemoryUsage, String> {
+        self.with_session(session_id, |session| Ok(session.memory_usage()))
+    }
+
+    /// Get the estimated remote traffic of a session, sampled now
+    pub fn bandwidth(&self, session_id: &str) -> Result<BandwidthStats, String> {
+        self.with_session(session_id, |session| {
+            if !session.is_serial() {
+                session.refresh_bandwidth();
+            }
+            Ok(session.bandwidth.locked().stats())
+        })
+    }
+
+    /// Read a hibernating session's scrollback back into memory, e.g. when its terminal is focused
+    pub fn wake(&self, session_id: &str) -> Result<(), String> {
+        let shared = self.with_session(session_id, |session| Ok(session.shared.clone()))?;
+        drop(shared.awake_scrollback());
         Ok(())
     }
 
-    /// Start the reader task for a PTY session
-    fn start_reader(&self, session_id: &str, mut reader: Box<dyn Read + Send>) -> JoinHandle<()> {
-        let app_handle = self.app_handle.clone();
+    /// Read back output kept in a session's scrollback
+    ///
+    /// `offset` is a position in the session's whole output stream (default: the oldest kept),
+    /// `len` a byte count (default: everything after `offset`).
+    pub fn scrollback(&self, session_id: &str, offset: Option<u64>, len: Option<usize>) -> Result<ScrollbackChunk, String> {
+        let (shared, encoding) =
+            self.with_session(session_id, |session| Ok((session.shared.clone(), session.config.output_encoding)))?;
+        let binary = encoding == OutputEncoding::Base64;
+
+        let scrollback = shared.awake_scrollback();
+        let (start, bytes) = scrollback.read(offset, len, !binary);
+        let data = if binary {
+            BASE64_STANDARD.encode(&bytes)
+        } else {
+            String::from_utf8_lossy(&bytes).into_owned()
+        };
+
+        Ok(ScrollbackChunk {
+            offset: start,
+            end: start + bytes.len() as u64,
+            first: scrollback.first(),
+            total: scrollback.total(),
+            encoding,
+            data,
+        })
+    }
+
+    /// Turn the commands run in a range of the scrollback into a shell script
+    ///
+    /// Needs shell integration that emits prompt marks; `skip_failed` leaves out commands that
+    /// exited with a non-zero status.
+    pub fn export_script(
+        &self,
+        session_id: &str,
+        offset: Option<u64>,
+        end: Option<u64>,
+        skip_failed: bool,
+    ) -> Result<ExportedScript, String> {
+        let (shared, shell) =
+            self.with_session(session_id, |session| Ok((session.shared.clone(), session.config.shell.clone())))?;
+        let (start, bytes) = {
+            let scrollback = shared.awake_scrollback();
+            let len = end.map(|end| end.saturating_sub(offset.unwrap_or(scrollback.first())) as usize);
+            scrollback.read(offset, len, false)
+        };
+
+        let commands = script::extract_commands(&bytes);
+        if commands.is_empty() {
+            return Err("No commands found; exporting needs shell integration with prompt marks".to_string());
+        }
+        let kept: Vec<_> = commands
+            .iter()
+            .filter(|command| !skip_failed || command.exit_code.map_or(true, |code| code == 0))
+            .collect();
+        Ok(ExportedScript {
+            script: script::script(&kept, &shell),
+            commands: kept.len(),
+            skipped: commands.len() - kept.len(),
+            offset: start,
+            end: start + bytes.len() as u64,
+        })
+    }
+
+    /// Diff the outputs of two commands in a session's scrollback
+    ///
+    /// Commands are found through prompt marks and picked by index, oldest first; negative
+    /// indexes count back from the latest, so -2 and -1 compare the last two runs.
+    pub fn diff_command_outputs(&self, session_id: &str, a: i64, b: i64) -> Result<CommandOutputDiff, String> {
+        let shared = self.shared(session_id)?;
+        let (_, bytes) = shared.awake_scrollback().read(None, None, false);
+        let commands = script::extract_commands(&bytes);
+        if commands.is_empty() {
+            return Err("No commands found; comparing outputs needs shell integration with prompt marks".to_string());
+        }
+
+        let pick = |index: i64| {
+            let resolved = if index < 0 {
+                usize::try_from(commands.len() as i64 + index).ok()
+            } else {
+                usize::try_from(index).ok()
+            };
+            resolved
+                .filter(|i| *i < commands.len())
+                .map(|i| (i, &commands[i]))
+                .ok_or_else(|| format!("No command {}; the scrollback holds {}", index, commands.len()))
+        };
+        let ((a_index, a), (b_index, b)) = (pick(a)?, pick(b)?);
+        let label = |index: usize, line: &str| format!("#{} {}", index, line.lines().next().unwrap_or_default());
+        let diff = diff::unified(&a.output, &b.output, &label(a_index, &a.line), &label(b_index, &b.line));
+        let described = |index: usize, command: &script::MarkedCommand| DiffedCommand {
+            index,
+            line: command.line.clone(),
+            exit_code: command.exit_code,
+        };
+        Ok(CommandOutputDiff {
+            identical: diff.is_empty(),
+            diff,
+            a: described(a_index, a),
+            b: described(b_index, b),
+        })
+    }
+
+    /// Open the external input FIFO of a session, returning its path
+    ///
+    /// Opening an already open pipe returns the existing path.
+    pub fn open_input_pipe(&self, session_id: &str) -> Result<String, String> {
+        self.with_session(session_id, |session| {
+            if session.input_pipe.is_none() {
+                session.input_pipe = Some(InputPipe::open(&session.id, session.shared.clone())?);
+            }
+            let pipe = session.input_pipe.as_ref().unwrap();
+            Ok(pipe.path().display().to_string())
+        })
+    }
+
+    /// Close and remove the external input FIFO of a session
+    pub fn close_input_pipe(&self, session_id: &str) -> Result<(), String> {
+        self.with_session(session_id, |session| {
+            session.input_pipe = None;
+            Ok(())
+        })
+    }
+
+    /// Open a file read-only in a pager session (`less -R` in secure mode, so ANSI colors pass
+    /// through and the pager cannot run commands or edit files)
+    pub fn view_file(&self, path: &str, cols: u16, rows: u16) -> Result<SessionInfo, String> {
+        let path = std::fs::canonicalize(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        if !path.is_file() {
+            return Err(format!("Not a regular file: {:?}", path));
+        }
+
+        let env = HashMap::from([
+            ("LESSSECURE".to_string(), "1".to_string()),
+            // Input preprocessors may run arbitrary programs
+            ("LESSOPEN".to_string(), String::new()),
+            ("LESSCLOSE".to_string(), String::new()),
+        ]);
+
+        self.spawn(SpawnOptions {
+            shell: Some("less".to_string()),
+            args: Some(vec![
+                "-R".into(),
+                "-K".into(),
+                "-+F".into(),
+                "--".into(),
+                path.display().to_string(),
+            ]),
+            cols,
+            rows,
+            env: Some(env),
+            read_only: Some(true),
+            ..Default::default()
+        })
+    }
+
+    /// Open a man page read-only in a pager session, with its headings for navigation
+    pub fn view_man(&self, page: &str, section: Option<&str>, cols: u16, rows: u16) -> Result<ManPage, String> {
+        man::validate(page, section)?;
+        // Rendered at the session's width so heading lines match what the pager shows
+        let sections = man::outline(page, section, cols)?;
+
+        let session = self.spawn(SpawnOptions {
+            shell: Some("man".to_string()),
+            args: Some(man::args(page, section)),
+            cols,
+            rows,
+            env: Some(man::env()),
+            read_only: Some(true),
+            ..Default::default()
+        })?;
+
+        Ok(ManPage {
+            session,
+            page: page.to_string(),
+            section: section.map(str::to_string),
+            sections,
+        })
+    }
+
+    /// Start a file-follow pseudo-session streaming `path` as session output
+    pub fn follow_file(&self, path: &str, tail_lines: usize) -> Result<FollowInfo, String> {
+        let id = Uuid::new_v4().to_string();
+        let follower = FileFollower::start(id.clone(), path.into(), tail_lines, self.events.clone())?;
+        let info = follower.info();
+        self.followers.locked().insert(id, follower);
+        Ok(info)
+    }
+
+    /// Pause or resume a file follower
+    pub fn set_follow_paused(&self, session_id: &str, paused: bool) -> Result<FollowInfo, String> {
+        self.with_follower(session_id, |follower| {
+            follower.set_paused(paused);
+            follower.info()
+        })
+    }
+
+    /// Move a file follower to a byte offset, counting from the end if negative
+    pub fn follow_seek(&self, session_id: &str, offset: i64) -> Result<(), String> {
+        self.with_follower(session_id, |follower| follower.seek(offset))
+    }
+
+    fn with_follower<T>(&self, session_id: &str, f: impl FnOnce(&FileFollower) -> T) -> Result<T, String> {
+        let followers = self.followers.locked();
+        let follower = followers
+            .get(session_id)
+            .ok_or_else(|| format!("File follower not found: {}", session_id))?;
+        Ok(f(follower))
+    }
+
+    /// List background jobs of a PTY session's shell
+    pub fn list_jobs(&self, session_id: &str) -> Result<Vec<JobInfo>, String> {
+        let (pid, foreground) = {
+            let sessions = self.sessions.locked();
+            let session = sessions
+                .get(session_id)
+                .ok_or_else(|| format!("Session not found: {}", session_id))?;
+            (session.pid, session.process.foreground_pgrp())
+        };
+
+        Ok(jobs::list_jobs(&ProcessTree::scan(), pid, foreground))
+    }
+
+    /// Process in the foreground of a session's terminal, e.g. to warn before closing vim or ssh
+    pub fn foreground_process(&self, session_id: &str) -> Result<ForegroundProcess, String> {
+        let sessions = self.sessions.locked();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        jobs::foreground_process(session.pid, session.process.foreground_pgrp())
+            .ok_or_else(|| format!("Session {} has no running process", session_id))
+    }
+
+    /// Current working directory of a session's foreground process, falling back to the shell
+    pub fn cwd(&self, session_id: &str) -> Result<String, String> {
+        let sessions = self.sessions.locked();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+
+        // The shell's own report covers processes /proc does not show, e.g. in another namespace
+        let reported = || {
+            let reported = session.shared.reported_cwd.locked();
+            reported.as_ref().filter(|cwd| !cwd.remote).map(|cwd| cwd.path.clone())
+        };
+        procfs::read_cwd(session.foreground_pid())
+            .or_else(|| procfs::read_cwd(session.pid))
+            .map(|cwd| cwd.display().to_string())
+            .or_else(reported)
+            .ok_or_else(|| format!("Failed to read working directory of session {}", session_id))
+    }
+
+    /// Where a session's foreground process is working right now
+    pub fn location(&self, session_id: &str) -> Result<SessionLocation, String> {
+        self.with_session(session_id, |session| Ok(SessionLocation::gather(session.foreground_pid())))
+    }
+
+    /// Run a closure against a session while holding the session map lock
+    fn with_session<T>(
+        &self,
+        session_id: &str,
+        f: impl FnOnce(&mut PtySession) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut sessions = self.sessions.locked();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        f(session)
+    }
+
+    /// Start reading a PTY session's output
+    ///
+    /// Reads block, so they happen on a thread of their own rather than on the async runtime,
+    /// where every open session would hold a worker. The task returned processes what is read,
+    /// merging reads that come close together.
+    fn start_reader(
+        &self,
+        session_id: &str,
+        reader: Box<dyn Read + Send>,
+        exit: oneshot::Receiver<ExitStatus>,
+        shared: Arc<SessionShared>,
+        input: InputQueue,
+        config: SessionConfig,
+    ) -> JoinHandle<()> {
+        let events = self.events.clone();
+        let links = self.links.clone();
         let session_id = session_id.to_string();
+        let mut live_redaction = config
+            .redactor
+            .clone()
+            .filter(|r| r.masks_live_output())
+            .map(LiveRedaction::new);
+        let binary = config.output_encoding == OutputEncoding::Base64;
+        let (chunks, received) = mpsc::channel(READ_QUEUE);
+        spawn_read_thread(&session_id, reader, chunks);
+        let mut batches = Batcher::new(received, &settings::read_section::<BatchSettings>("outputBatching"), READ_SIZE);
 
         tokio::spawn(async move {
-            let mut buffer = [0u8; 8192];
+            let mut processor = OutputProcessor::new(session_id.clone(), events.clone(), shared.clone(), input, links, config);
+            let mut decoder = Utf8Decoder::default();
 
             log::info!("Starting reader for session: {}", session_id);
 
+            loop {
+                let next = match live_redaction.as_mut().filter(|live| live.is_holding()) {
+                    Some(live) => match batches.next_within(LIVE_REDACTION_WAIT).await {
+                        Ok(next) => next,
+                        // Quiet for now, so what was held back is all there is
+                        Err(_) => {
+                            let data = live.flush();
+                            shared.keep_output(data.as_bytes());
+                            events.publish(&session_id, EventKind::Data(data));
+                            continue;
+                        }
+                    },
+                    None => batches.next().await,
+                };
+                let Some(chunk) = next else {
+                    break;
+                };
+                shared.bytes_read.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                if let Some(probe) = shared.latency.locked().as_mut() {
+                    probe.on_output(chunk.len());
+                }
+
+                // Track terminal state before forwarding the output
+                processor.process(&chunk);
+
+                if let Some((log, error)) = shared.write_log(&chunk) {
+                    log::warn!("Session {} log failed: {}", session_id, error);
+                    end_log(&events, &session_id, log, Some(error)).await;
+                }
+                if let Some((recorder, error)) = shared.write_recording(&chunk) {
+                    log::warn!("Session {} recording failed: {}", session_id, error);
+                    end_recording(&events, &session_id, recorder, Some(error)).await;
+                }
+
+                if binary {
+                    shared.keep_output(&chunk);
+                    events.publish(&session_id, EventKind::Binary(chunk));
+                    continue;
+                }
+
+                // Characters split across reads are completed by the next one
+                let mut data = decoder.decode(&chunk);
+                if let Some(live) = live_redaction.as_mut() {
+                    data = live.push(&data);
+                }
+                if data.is_empty() {
+                    continue;
+                }
+
+                // Publish data for the frontend and other consumers
+                shared.keep_output(data.as_bytes());
+                events.publish(&session_id, EventKind::Data(data));
+            }
+
+            // The read thread is done: the shell exited or the terminal was closed
+            let mut tail = decoder.finish();
+            if let Some(live) = live_redaction.as_mut() {
+                tail = live.push(&tail) + &live.flush();
+            }
+            if !tail.is_empty() {
+                shared.keep_output(tail.as_bytes());
+                events.publish(&session_id, EventKind::Data(tail));
+            }
+
+            if let Some(log) = shared.take_log() {
+                end_log(&events, &session_id, log, None).await;
+            }
+            if let Some(recorder) = shared.take_recording() {
+                end_recording(&events, &session_id, recorder, None).await;
+            }
+
+            // Output ends when the command closes the terminal, usually just before it exits
+            let status = match tokio::time::timeout(EXIT_WAIT, exit).await {
+                Ok(Ok(status)) => status,
+                _ => {
+                    log::warn!("Session {} output ended but the process has not exited", session_id);
+                    ExitStatus::default()
+                }
+            };
+            log::info!("Session {} exited: {:?}", session_id, status);
+            events.publish(
+                &session_id,
+                EventKind::Exit {
+                    exit_code: status.code,
+                    signal: status.signal,
+                },
+            );
+
+            log::info!("Reader task ended for session: {}", session_id);
+        })
+    }
+}
+
+/// Read a terminal on a thread of its own until it ends, or until nothing takes the chunks
+/// anymore because the session was closed
+fn spawn_read_thread(session_id: &str, mut reader: Box<dyn Read + Send>, chunks: mpsc::Sender<Vec<u8>>) {
+    let id = session_id.to_string();
+    let spawned = std::thread::Builder::new()
+        .name(format!("pty-read-{}", id.get(..8).unwrap_or(&id)))
+        .spawn(move || {
+            let mut buffer = vec![0u8; READ_SIZE];
             loop {
                 match reader.read(&mut buffer) {
                     Ok(0) => {
-                        // EOF - shell exited normally
-                        log::info!("Session {} EOF - shell exited", session_id);
-                        let event_name = format!("pty://{}/exit", session_id);
-                        let _ = app_handle.emit(
-                            event_name.as_str(),
-                            serde_json::json!({ "exitCode": 0 }),
-                        );
+                        log::info!("Session {} EOF - shell exited", id);
                         break;
                     }
                     Ok(n) => {
-                        // Convert bytes to string (lossy conversion for invalid UTF-8)
-                        let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-
-                        // Emit data event to frontend
-                        let event_name = format!("pty://{}/data", session_id);
-                        let _ = app_handle.emit(
-                            event_name.as_str(),
-                            data,
-                        );
+                        if chunks.blocking_send(buffer[..n].to_vec()).is_err() {
+                            break;
+                        }
                     }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
                     Err(e) => {
-                        log::error!("Error reading from PTY {}: {}", session_id, e);
-                        let event_name = format!("pty://{}/exit", session_id);
-                        let _ = app_handle.emit(
-                            event_name.as_str(),
-                            serde_json::json!({ "exitCode": 1 }),
-                        );
+                        // Linux reports the end of a terminal as EIO once the last writer closes it
+                        log::info!("Session {} read ended: {}", id, e);
                         break;
                     }
                 }
             }
-
-            log::info!("Reader task ended for session: {}", session_id);
-        })
+        });
+    // Without the thread the sender is dropped, and the session ends as if the shell had exited
+    if let Err(e) = spawned {
+        log::error!("Failed to start the reader thread of session {}: {}", session_id, e);
     }
 }
+
+/// Close a log that ended without pty_stop_logging and tell the frontend why
+async fn end_log(events: &EventBus, session_id: &str, log: SessionLog, error: Option<String>) {
+    let path = log.info().path;
+    let error = match log.finish().await {
+        Ok(_) => error,
+        Err(e) => error.or(Some(e)),
+    };
+    events.publish(session_id, EventKind::LogStopped { path, error });
+}
+
+/// Close a recording that ended without stop_recording and tell the frontend why
+async fn end_recording(events: &EventBus, session_id: &str, recorder: Recorder, error: Option<String>) {
+    let path = recorder.info().path;
+    let error = match recorder.finish().await {
+        Ok(_) => error,
+        Err(e) => error.or(Some(e)),
+    };
+    events.publish(session_id, EventKind::RecordingStopped { path, error });
+}
@@ -0,0 +1,78 @@
+// Session signals
+// Signals the frontend can send to a session's processes, delivered to the shell's process group
+// and the terminal's foreground process group, which differ while a job runs
+
+use serde::{Deserialize, Serialize};
+use std::ffi::CStr;
+use std::io;
+
+/// A signal that can be sent to a session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Signal {
+    Sigint,
+    Sigterm,
+    Sigkill,
+    Sighup,
+}
+
+impl Signal {
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Sigint => libc::SIGINT,
+            Signal::Sigterm => libc::SIGTERM,
+            Signal::Sigkill => libc::SIGKILL,
+            Signal::Sighup => libc::SIGHUP,
+        }
+    }
+}
+
+/// Send `signal` to the process group `group` only
+pub fn send_group(group: i32, signal: Signal) -> Result<(), String> {
+    if group <= 0 {
+        return Err(format!("Invalid process group: {}", group));
+    }
+    // SAFETY: killpg has no memory safety preconditions
+    if unsafe { libc::killpg(group, signal.number()) } != 0 {
+        return Err(format!("Failed to send {:?}: {}", signal, io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// Send `signal` to the process group led by `pid` and to `foreground`, if that is another group
+pub fn send(pid: u32, foreground: Option<i32>, signal: Signal) -> Result<(), String> {
+    let shell_group = i32::try_from(pid).map_err(|_| format!("Invalid PID: {}", pid))?;
+    if shell_group <= 0 {
+        return Err("Process has no PID".to_string());
+    }
+    let mut groups = vec![shell_group];
+    if let Some(foreground) = foreground.filter(|&group| group > 0 && group != shell_group) {
+        groups.push(foreground);
+    }
+
+    let mut delivered = false;
+    let mut error = None;
+    for group in groups {
+        // SAFETY: killpg has no memory safety preconditions
+        if unsafe { libc::killpg(group, signal.number()) } == 0 {
+            delivered = true;
+        } else {
+            error = Some(io::Error::last_os_error());
+        }
+    }
+    match (delivered, error) {
+        (false, Some(e)) => Err(format!("Failed to send {:?}: {}", signal, e)),
+        _ => Ok(()),
+    }
+}
+
+/// Description of a signal number, e.g. "Killed" for SIGKILL
+pub fn describe(number: libc::c_int) -> String {
+    // SAFETY: strsignal has no memory safety preconditions
+    let text = unsafe { libc::strsignal(number) };
+    if text.is_null() {
+        return format!("Signal {}", number);
+    }
+    // SAFETY: a non-null result is NUL-terminated; it is copied before another call can reuse it
+    unsafe { CStr::from_ptr(text) }.to_string_lossy().into_owned()
+}
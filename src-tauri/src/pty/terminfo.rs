@@ -0,0 +1,143 @@
+// Terminfo self-check
+// Verifies the TERM we advertise resolves on the host and matches what the renderer supports
+
+use crate::exec::{self, ExecOptions};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Result of comparing one capability with the renderer
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityCheck {
+    pub name: String,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub ok: bool,
+}
+
+/// Terminfo report for the advertised TERM
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminfoReport {
+    pub term: String,
+    pub found: bool,
+    /// Compiled entry on disk, if located
+    pub path: Option<String>,
+    pub capabilities: Vec<CapabilityCheck>,
+    pub mismatches: Vec<String>,
+}
+
+impl TerminfoReport {
+    fn push_check(&mut self, name: &str, expected: &str, ok: bool, actual: Option<String>) {
+        if !ok {
+            self.mismatches.push(format!(
+                "{}: renderer expects {}, terminfo has {}",
+                name,
+                expected,
+                actual.as_deref().unwrap_or("nothing")
+            ));
+        }
+        self.capabilities.push(CapabilityCheck {
+            name: name.to_string(),
+            expected: expected.to_string(),
+            actual,
+            ok,
+        });
+    }
+}
+
+/// Check the terminfo entry for `term`
+pub async fn check(term: &str) -> Result<TerminfoReport, String> {
+    let path = find_entry(term);
+    let output = exec::run(&ExecOptions {
+        program: "infocmp".to_string(),
+        args: vec!["-1".into(), "-x".into(), term.to_string()],
+        ..Default::default()
+    })
+    .await;
+
+    let mut report = TerminfoReport {
+        term: term.to_string(),
+        found: path.is_some(),
+        path: path.map(|p| p.display().to_string()),
+        capabilities: Vec::new(),
+        mismatches: Vec::new(),
+    };
+
+    let caps = match output {
+        Ok(output) if output.success() => output.stdout,
+        _ => {
+            let message = if report.found {
+                "infocmp is unavailable, capabilities were not compared".to_string()
+            } else {
+                format!("No terminfo entry for {}; programs will fall back to dumb output", term)
+            };
+            report.mismatches.push(message);
+            return Ok(report);
+        }
+    };
+    report.found = true;
+
+    let colors = capability(&caps, "colors").map(|value| normalize_number(&value));
+    let has_256 = colors
+        .as_deref()
+        .and_then(|value| value.parse::<u32>().ok())
+        .is_some_and(|n| n >= 256);
+    let truecolor = ["RGB", "Tc"].iter().find_map(|name| capability(&caps, name));
+    let bce = capability(&caps, "bce");
+
+    report.push_check("colors", "256", has_256, colors);
+    report.push_check("truecolor (RGB/Tc)", "present", truecolor.is_some(), truecolor);
+    report.push_check("bce", "present", bce.is_some(), bce);
+
+    Ok(report)
+}
+
+/// Find the value of a capability in `infocmp -1` output
+///
+/// Boolean capabilities yield `"true"`, numeric and string ones their value.
+fn capability(caps: &str, name: &str) -> Option<String> {
+    caps.lines().map(|line| line.trim().trim_end_matches(',')).find_map(|cap| {
+        if cap == name {
+            return Some("true".to_string());
+        }
+        cap.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('#').or_else(|| rest.strip_prefix('=')))
+            .map(|value| value.to_string())
+    })
+}
+
+/// infocmp may print numbers in hex (`0x100`)
+fn normalize_number(value: &str) -> String {
+    value
+        .strip_prefix("0x")
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Locate the compiled terminfo entry in the standard search path
+fn find_entry(term: &str) -> Option<PathBuf> {
+    let first = term.chars().next()?;
+    let mut search_path: Vec<PathBuf> = Vec::new();
+
+    if let Ok(dir) = std::env::var("TERMINFO") {
+        search_path.push(dir.into());
+    }
+    if let Some(home) = dirs::home_dir() {
+        search_path.push(home.join(".terminfo"));
+    }
+    if let Ok(list) = std::env::var("TERMINFO_DIRS") {
+        search_path.extend(list.split(':').filter(|d| !d.is_empty()).map(PathBuf::from));
+    }
+    search_path.extend(["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"].map(PathBuf::from));
+
+    search_path.into_iter().find_map(|dir| {
+        [
+            dir.join(first.to_string()).join(term),
+            dir.join(format!("{:x}", first as u32)).join(term),
+        ]
+        .into_iter()
+        .find(|path| path.exists())
+    })
+}
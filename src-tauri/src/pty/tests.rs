@@ -0,0 +1,189 @@
+// Headless session tests
+// Drive PtyManager end to end on the in-memory mock backend: no terminal, shell, or webview.
+
+use super::backend::{MockBackend, MockHandle};
+use super::broadcast::BroadcastRules;
+use super::redact::{LiveRedaction, RedactionSettings, Redactor, MASK};
+use super::signal::Signal;
+use super::{PtyManager, SpawnOptions};
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::sync::MutexExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Longest a test waits for an event before failing
+const EVENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sessions open at once in the stress test
+const STRESS_SESSIONS: usize = 50;
+
+/// A manager on the mock backend, with every event it publishes
+fn mock_manager() -> (PtyManager, Arc<MockBackend>, UnboundedReceiver<SessionEvent>) {
+    let events = Arc::new(EventBus::default());
+    let (sender, received) = mpsc::unbounded_channel();
+    events.subscribe(move |event: &SessionEvent| {
+        let _ = sender.send(event.clone());
+    });
+    let backend = Arc::new(MockBackend::default());
+    (PtyManager::with_backend(events, backend.clone()), backend, received)
+}
+
+fn spawn(manager: &PtyManager, backend: &MockBackend) -> (String, MockHandle) {
+    let info = manager
+        .spawn(SpawnOptions {
+            shell: Some("/bin/sh".to_string()),
+            cols: 80,
+            rows: 24,
+            ..Default::default()
+        })
+        .expect("spawn on the mock backend");
+    let handle = backend
+        .spawned()
+        .into_iter()
+        .find(|handle| handle.pid == info.pid)
+        .expect("mock process for the session");
+    (info.id, handle)
+}
+
+/// Output published for a session until `until` has been seen in it
+async fn read_until(received: &mut UnboundedReceiver<SessionEvent>, session_id: &str, until: &str) -> String {
+    let mut output = String::new();
+    tokio::time::timeout(EVENT_TIMEOUT, async {
+        while !output.contains(until) {
+            let event = received.recv().await.expect("event bus open");
+            if let (true, EventKind::Data(data)) = (event.session_id == session_id, event.kind) {
+                output.push_str(&data);
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no {:?} in the output, got {:?}", until, output));
+    output
+}
+
+/// Exit code and signal the session's exit event reports
+async fn wait_exit(received: &mut UnboundedReceiver<SessionEvent>, session_id: &str) -> (Option<i32>, Option<String>) {
+    tokio::time::timeout(EVENT_TIMEOUT, async {
+        loop {
+            let event = received.recv().await.expect("event bus open");
+            if let (true, EventKind::Exit { exit_code, signal }) = (event.session_id == session_id, event.kind) {
+                return (exit_code, signal);
+            }
+        }
+    })
+    .await
+    .expect("session exit event")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mock_session_writes_reads_exits_and_closes() {
+    let (manager, backend, mut received) = mock_manager();
+    let (id, handle) = spawn(&manager, &backend);
+    assert_eq!(handle.command.get_argv()[0], "/bin/sh");
+
+    manager.write(&id, "echo hello\r").unwrap();
+    read_until(&mut received, &id, "echo hello\r").await;
+    assert_eq!(handle.input.locked().as_slice(), b"echo hello\r");
+
+    handle.emit(b"hello\r\n");
+    read_until(&mut received, &id, "hello\r\n").await;
+
+    handle.exit();
+    assert_eq!(wait_exit(&mut received, &id).await, (Some(0), None));
+    assert!(manager.has_exited(&id).unwrap());
+
+    manager.close(&id).unwrap();
+    assert!(manager.info(&id).is_err());
+    assert!(manager.write(&id, "x").is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closing_a_running_mock_session_hangs_it_up() {
+    let (manager, backend, _received) = mock_manager();
+    let (id, handle) = spawn(&manager, &backend);
+
+    manager.close(&id).unwrap();
+    assert_eq!(handle.signals.locked().as_slice(), [Signal::Sighup]);
+    assert!(manager.info(&id).is_err());
+}
+
+/// Input broadcast to a group member goes through its input queue, behind what was typed there
+#[tokio::test(flavor = "multi_thread")]
+async fn broadcast_input_queues_behind_typed_input() {
+    let (manager, backend, mut received) = mock_manager();
+    let (typed_in, _) = spawn(&manager, &backend);
+    let (member, handle) = spawn(&manager, &backend);
+    manager.set_broadcast_group(&typed_in, Some("build")).unwrap();
+    manager.set_broadcast_group(&member, Some("build")).unwrap();
+
+    manager.write(&member, "make").unwrap();
+    let report = manager.write_broadcast(std::slice::from_ref(&typed_in), " all\r", &BroadcastRules::default(), &[]);
+    assert_eq!(report.sent, [member.clone(), typed_in.clone()]);
+
+    read_until(&mut received, &member, "make all\r").await;
+    assert_eq!(handle.input.locked().as_slice(), b"make all\r");
+}
+
+/// Every reader blocks on its own thread, so many idle sessions leave the runtime's few workers
+/// free for other tasks, and each session's output still reaches its own processor
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fifty_sessions_block_in_readers_without_starving_the_runtime() {
+    let (manager, backend, mut received) = mock_manager();
+    let sessions: Vec<_> = (0..STRESS_SESSIONS).map(|_| spawn(&manager, &backend)).collect();
+
+    // All 50 readers are now blocked waiting for output
+    let answered = tokio::time::timeout(Duration::from_millis(500), tokio::spawn(async { 42 })).await;
+    assert_eq!(answered.expect("runtime starved by blocked readers").unwrap(), 42);
+
+    for (n, (_, handle)) in sessions.iter().enumerate() {
+        handle.emit(format!("\x1b]2;session {}\x07line {}\r\n", n, n).as_bytes());
+    }
+    let mut output: HashMap<String, String> = HashMap::new();
+    let done = |output: &HashMap<String, String>| {
+        sessions.iter().enumerate().all(|(n, (id, _))| {
+            output
+                .get(id)
+                .is_some_and(|data| data.contains(&format!("line {}\r\n", n)))
+        })
+    };
+    tokio::time::timeout(EVENT_TIMEOUT, async {
+        while !done(&output) {
+            let event = received.recv().await.expect("event bus open");
+            if let EventKind::Data(data) = event.kind {
+                output.entry(event.session_id).or_default().push_str(&data);
+            }
+        }
+    })
+    .await
+    .expect("output of every session");
+
+    for (n, (id, _)) in sessions.iter().enumerate() {
+        let info = manager.info(id).unwrap();
+        assert_eq!(info.program_title, Some(format!("session {}", n)), "title of session {}", n);
+    }
+    for (id, _) in &sessions {
+        manager.close(id).unwrap();
+    }
+}
+
+#[test]
+fn live_redaction_masks_a_secret_split_across_reads() {
+    let settings = RedactionSettings {
+        enabled: true,
+        mask_live_output: true,
+        rules: Vec::new(),
+    };
+    let redactor = Redactor::from_settings(&settings).expect("redaction is enabled");
+    let mut live = LiveRedaction::new(Arc::new(redactor));
+
+    let mut shown = live.push("$ export PASSWORD=hun");
+    assert_eq!(shown, "", "the start of the secret is held back");
+    shown += &live.push("ter2\r\n$ ");
+    assert!(live.is_holding(), "the prompt waits for the rest of its line");
+    shown += &live.flush();
+
+    assert!(!shown.contains("hun"), "secret shown: {:?}", shown);
+    assert_eq!(shown, format!("$ export PASSWORD={}\r\n$ ", MASK));
+}
@@ -0,0 +1,193 @@
+// Title composer
+// Builds a session's tab title from tracked state using a per-profile template
+
+use super::procfs;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Template used when the profile does not define one
+pub const DEFAULT_TEMPLATE: &str = "{process}: {location}";
+
+/// ssh options that consume the following argument
+const SSH_OPTIONS_WITH_VALUE: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+/// Data available to title templates
+#[derive(Debug, Default)]
+pub struct TitleContext {
+    /// Name of the foreground process (falls back to the shell)
+    pub process: String,
+    /// Working directory of the foreground process, `~`-abbreviated
+    pub cwd: String,
+    /// Remote host when the foreground process is ssh/mosh/telnet
+    pub host: Option<String>,
+    /// Exit code of the last command, when reported by shell integration
+    pub status: Option<i32>,
+    pub shell: String,
+    pub name: Option<String>,
+}
+
+impl TitleContext {
+    /// Inspect the session's foreground process to fill in the context
+    pub fn gather(foreground: u32, shell: &str) -> Self {
+        let process = procfs::read_stat(foreground)
+            .map(|stat| stat.comm)
+            .unwrap_or_else(|| file_name(shell));
+        let cwd = procfs::read_cwd(foreground)
+            .map(|path| abbreviate_home(&path))
+            .unwrap_or_default();
+        let host = match process.as_str() {
+            "ssh" | "mosh" | "mosh-client" | "telnet" => remote_host(&procfs::read_cmdline(foreground)),
+            _ => None,
+        };
+
+        Self {
+            process,
+            cwd,
+            host,
+            status: None,
+            shell: file_name(shell),
+            name: None,
+        }
+    }
+}
+
+/// Where a session's foreground process is working
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionLocation {
+    pub cwd: Option<PathBuf>,
+    /// Remote host when the foreground process is ssh/mosh/telnet
+    pub host: Option<String>,
+}
+
+impl SessionLocation {
+    pub fn gather(foreground: u32) -> Self {
+        let host = procfs::read_stat(foreground).and_then(|stat| match stat.comm.as_str() {
+            "ssh" | "mosh" | "mosh-client" | "telnet" => remote_host(&procfs::read_cmdline(foreground)),
+            _ => None,
+        });
+        Self {
+            cwd: procfs::read_cwd(foreground),
+            host,
+        }
+    }
+}
+
+/// Working directory reported by the shell with OSC 7 (`ESC ] 7 ; file://host/path BEL`)
+///
+/// Shells send it at every prompt, from wherever they run, so it also covers SSH sessions whose
+/// remote end is set up to send it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedCwd {
+    pub path: String,
+    /// Host named in the report, if any
+    pub host: Option<String>,
+    /// The host is not this machine, so the path is not a local one
+    pub remote: bool,
+}
+
+impl ReportedCwd {
+    /// Parse the payload of an OSC 7 sequence
+    pub fn parse(uri: &str) -> Option<Self> {
+        // kitty's shell integration uses its own scheme for the same thing
+        let rest = uri.strip_prefix("file://").or_else(|| uri.strip_prefix("kitty-shell-cwd://"))?;
+        let (host, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => return None,
+        };
+        let path = percent_decode(path)?;
+        let host = Some(host).filter(|host| !host.is_empty() && *host != "localhost");
+        let remote = host.is_some_and(|host| {
+            let local = hostname::get().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            // Shells send either the short or the fully qualified name
+            !host.eq_ignore_ascii_case(&local) && host.split('.').next() != local.split('.').next()
+        });
+        Some(Self {
+            path,
+            host: host.map(str::to_string),
+            remote,
+        })
+    }
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' && tail.len() >= 2 {
+            let hex = std::str::from_utf8(&tail[..2]).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Substitute `{process}`, `{cwd}`, `{host}`, `{location}`, `{status}`, `{shell}` and `{name}`
+pub fn render(template: &str, ctx: &TitleContext) -> String {
+    let host = ctx.host.clone().unwrap_or_default();
+    let location = ctx.host.clone().unwrap_or_else(|| ctx.cwd.clone());
+    let status = ctx.status.map(|code| code.to_string()).unwrap_or_default();
+
+    let title = template
+        .replace("{process}", &ctx.process)
+        .replace("{cwd}", &ctx.cwd)
+        .replace("{host}", &host)
+        .replace("{location}", &location)
+        .replace("{status}", &status)
+        .replace("{shell}", &ctx.shell)
+        .replace("{name}", ctx.name.as_deref().unwrap_or_default());
+
+    // Empty placeholders can leave dangling separators behind
+    title
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '-' | '|' | '@'))
+        .to_string()
+}
+
+/// Replace the home directory prefix with `~`
+pub fn abbreviate_home(path: &Path) -> String {
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            return if rest.as_os_str().is_empty() {
+                "~".to_string()
+            } else {
+                format!("~/{}", rest.display())
+            };
+        }
+    }
+    path.display().to_string()
+}
+
+/// Extract the destination host from an ssh-style argument vector
+pub fn remote_host(argv: &[String]) -> Option<String> {
+    let mut args = argv.iter().skip(1);
+
+    while let Some(arg) = args.next() {
+        if let Some(flags) = arg.strip_prefix('-') {
+            // A value-taking flag consumes the next argument unless attached (-p22)
+            if let Some(pos) = flags.find(|c| SSH_OPTIONS_WITH_VALUE.contains(c)) {
+                if pos == flags.len() - 1 {
+                    args.next();
+                }
+            }
+            continue;
+        }
+
+        let destination = arg.strip_prefix("ssh://").unwrap_or(arg);
+        let host = destination.rsplit('@').next().unwrap_or(destination);
+        let host = host.split(':').next().unwrap_or(host);
+        return Some(host.to_string());
+    }
+
+    None
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
@@ -0,0 +1,92 @@
+// Incremental UTF-8 decoding
+// Terminal output arrives in arbitrary chunks, so a multi-byte character can straddle two reads.
+// The decoder holds back an incomplete trailing sequence until the rest of it arrives.
+
+/// Stateful UTF-8 decoder for a byte stream
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    /// Start of a character whose remaining bytes have not arrived yet (at most 3 bytes)
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode the next chunk, returning all complete characters
+    ///
+    /// Invalid bytes become U+FFFD; only a sequence cut off at the end of the chunk is kept
+    /// for the next call.
+    pub fn decode(&mut self, chunk: &[u8]) -> String {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+
+        let mut text = String::with_capacity(bytes.len());
+        let mut rest = bytes.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // Already validated by from_utf8 above
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            self.pending = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// Flush a sequence left incomplete when the stream ends
+    pub fn finish(&mut self) -> String {
+        let pending = std::mem::take(&mut self.pending);
+        String::from_utf8_lossy(&pending).into_owned()
+    }
+
+    /// Drop any incomplete sequence, e.g. after seeking in the stream
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn character_split_across_chunks_is_held_back() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"price: \xe2\x82"), "price: ");
+        assert_eq!(decoder.decode(b"\xac5"), "\u{20ac}5");
+        assert_eq!(decoder.decode(b"\xf0"), "");
+        assert_eq!(decoder.decode(b"\x9f"), "");
+        assert_eq!(decoder.decode(b"\x98\x80!"), "\u{1f600}!");
+    }
+
+    #[test]
+    fn invalid_bytes_become_replacement_characters() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"a\xffb\x80c"), "a\u{fffd}b\u{fffd}c");
+        // A lead byte followed by something that cannot continue it
+        assert_eq!(decoder.decode(b"\xc3("), "\u{fffd}(");
+        assert_eq!(decoder.finish(), "");
+    }
+
+    #[test]
+    fn finish_flushes_an_incomplete_tail() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"end\xe2\x82"), "end");
+        assert_eq!(decoder.finish(), "\u{fffd}");
+        assert_eq!(decoder.finish(), "");
+        assert_eq!(decoder.decode(b"ok"), "ok");
+    }
+}
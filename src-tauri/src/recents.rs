@@ -0,0 +1,305 @@
+// Recents
+// Remembers where sessions went (directories, project roots, SSH hosts) and which profiles were
+// opened, and ranks them by frecency to suggest entries for the new tab dropdown
+
+use crate::commands::settings;
+use crate::events::{EventBus, EventKind, SessionEvent};
+use crate::pty::title::{self, SessionLocation};
+use crate::pty::PtyManager;
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// How often recorded usage is written to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Entries kept per kind; the lowest ranked are dropped
+const MAX_ENTRIES: usize = 200;
+
+/// Suggestions returned per kind when the caller does not say
+const DEFAULT_LIMIT: usize = 5;
+
+/// Files or directories marking the root of a project
+const PROJECT_MARKERS: &[&str] = &[".git", ".hg", ".jj"];
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// What a recorded entry refers to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum RecentKind {
+    Directory,
+    SshHost,
+    /// Root of a project (a version-controlled directory) sessions worked in
+    Workspace,
+    /// ID of a settings profile
+    Profile,
+}
+
+/// Usage of one directory, host, workspace, or profile
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RecentEntry {
+    kind: RecentKind,
+    value: String,
+    count: u32,
+    /// Unix time in seconds
+    last_used: u64,
+}
+
+impl RecentEntry {
+    /// Use count weighted by how recently the entry was used
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_used);
+        let weight = match age {
+            a if a < HOUR => 4.0,
+            a if a < DAY => 2.0,
+            a if a < WEEK => 0.5,
+            _ => 0.25,
+        };
+        f64::from(self.count) * weight
+    }
+}
+
+/// A ranked suggestion
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    /// Path, host, or profile ID
+    pub value: String,
+    /// Short display form, e.g. a `~`-abbreviated path
+    pub label: String,
+    pub count: u32,
+    /// Unix time in milliseconds
+    pub last_used: u64,
+    pub score: f64,
+}
+
+/// Entries for the new tab dropdown, best first within each kind
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTabSuggestions {
+    pub directories: Vec<Suggestion>,
+    pub ssh_hosts: Vec<Suggestion>,
+    pub workspaces: Vec<Suggestion>,
+    pub profiles: Vec<Suggestion>,
+}
+
+/// Only the profile IDs are needed to drop deleted profiles
+#[derive(Debug, Deserialize)]
+struct ProfileRef {
+    id: String,
+}
+
+/// Recorded usage, managed by Tauri
+#[derive(Default)]
+pub struct Recents {
+    entries: Mutex<Vec<RecentEntry>>,
+    /// Last location seen per session, so moving around inside one project counts it once
+    locations: Mutex<HashMap<String, SessionLocation>>,
+    dirty: AtomicBool,
+}
+
+impl Recents {
+    /// Load recorded usage from disk
+    pub fn load() -> Self {
+        let entries = recents_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable recents file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            entries: Mutex::new(entries),
+            ..Self::default()
+        }
+    }
+
+    /// Count one use of a directory, host, workspace, or profile
+    pub fn record(&self, kind: RecentKind, value: &str) {
+        let now = unix_now();
+        let mut entries = self.entries.locked();
+        match entries.iter_mut().find(|e| e.kind == kind && e.value == value) {
+            Some(entry) => {
+                entry.count = entry.count.saturating_add(1);
+                entry.last_used = now;
+            }
+            None => {
+                entries.push(RecentEntry {
+                    kind,
+                    value: value.to_string(),
+                    count: 1,
+                    last_used: now,
+                });
+                prune(&mut entries, kind, now);
+            }
+        }
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    /// Record a session moving to a new directory or host
+    fn on_location(&self, session_id: &str, location: SessionLocation) {
+        let previous = self
+            .locations
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), location.clone())
+            .unwrap_or_default();
+
+        if let Some(host) = location.host.as_deref().filter(|h| previous.host.as_deref() != Some(*h)) {
+            self.record(RecentKind::SshHost, host);
+        }
+        // While connected elsewhere the local directory is not where the user is working
+        if location.host.is_some() {
+            return;
+        }
+        let Some(cwd) = location.cwd.filter(|cwd| previous.cwd.as_ref() != Some(cwd)) else {
+            return;
+        };
+
+        // The home directory is where new tabs open anyway
+        if dirs::home_dir().as_deref() != Some(cwd.as_path()) {
+            self.record(RecentKind::Directory, &cwd.to_string_lossy());
+        }
+        if let Some(root) = project_root(&cwd) {
+            let previous_root = previous.cwd.as_deref().and_then(project_root);
+            if previous_root.as_ref() != Some(&root) {
+                self.record(RecentKind::Workspace, &root.to_string_lossy());
+            }
+        }
+    }
+
+    /// Ranked suggestions per kind, optionally only those containing `query`
+    pub fn suggestions(&self, query: Option<&str>, limit: Option<usize>) -> NewTabSuggestions {
+        let now = unix_now();
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        let query = query.map(str::to_lowercase).filter(|q| !q.is_empty());
+        let profiles: Option<Vec<String>> = settings::read_section::<Option<Vec<ProfileRef>>>("profiles")
+            .map(|profiles| profiles.into_iter().map(|p| p.id).collect());
+
+        let entries = self.entries.locked();
+        let rank = |kind: RecentKind| -> Vec<Suggestion> {
+            let mut ranked: Vec<Suggestion> = entries
+                .iter()
+                .filter(|e| e.kind == kind)
+                .filter(|e| match kind {
+                    // Directories that were removed and profiles that were deleted cannot be opened
+                    RecentKind::Directory | RecentKind::Workspace => Path::new(&e.value).is_dir(),
+                    RecentKind::Profile => profiles.as_ref().map_or(true, |ids| ids.contains(&e.value)),
+                    RecentKind::SshHost => true,
+                })
+                .filter(|e| query.as_ref().map_or(true, |q| e.value.to_lowercase().contains(q)))
+                .map(|e| Suggestion {
+                    value: e.value.clone(),
+                    label: match kind {
+                        RecentKind::Directory | RecentKind::Workspace => title::abbreviate_home(Path::new(&e.value)),
+                        _ => e.value.clone(),
+                    },
+                    count: e.count,
+                    last_used: e.last_used * 1000,
+                    score: e.score(now),
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.last_used.cmp(&a.last_used)));
+            ranked.truncate(limit);
+            ranked
+        };
+
+        NewTabSuggestions {
+            directories: rank(RecentKind::Directory),
+            ssh_hosts: rank(RecentKind::SshHost),
+            workspaces: rank(RecentKind::Workspace),
+            profiles: rank(RecentKind::Profile),
+        }
+    }
+
+    /// Write recorded usage to disk if it changed since the last flush
+    pub fn flush(&self) -> Result<(), String> {
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let path = recents_path().ok_or_else(|| "Could not find data directory".to_string())?;
+        let contents = serde_json::to_string(&*self.entries.locked())
+            .map_err(|e| format!("Failed to serialize recents: {}", e))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, contents).map_err(|e| format!("Failed to write recents: {}", e))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("Failed to write recents: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Record session locations from the event bus and flush them periodically
+pub fn start(app_handle: AppHandle, events: &EventBus) {
+    let handle = app_handle.clone();
+    events.subscribe(move |event: &SessionEvent| match &event.kind {
+        EventKind::Location(location) => {
+            // Incognito sessions leave no trace
+            let persist = handle
+                .state::<PtyManager>()
+                .info(&event.session_id)
+                .is_ok_and(|info| !info.incognito);
+            if persist {
+                handle.state::<Recents>().on_location(&event.session_id, location.clone());
+            }
+        }
+        EventKind::Exit { .. } => {
+            handle.state::<Recents>().locations.locked().remove(&event.session_id);
+        }
+        _ => {}
+    });
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = app_handle.state::<Recents>().flush() {
+                log::warn!("Failed to save recents: {}", e);
+            }
+        }
+    });
+}
+
+fn recents_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xterminal").join("recents.json"))
+}
+
+/// Nearest ancestor of `dir` (or `dir` itself) containing a project marker, below the home directory
+fn project_root(dir: &Path) -> Option<PathBuf> {
+    let home = dirs::home_dir();
+    dir.ancestors()
+        .take_while(|ancestor| Some(*ancestor) != home.as_deref())
+        .find(|ancestor| PROJECT_MARKERS.iter().any(|marker| ancestor.join(marker).exists()))
+        .map(Path::to_path_buf)
+}
+
+/// Keep at most MAX_ENTRIES of one kind, dropping the lowest ranked
+fn prune(entries: &mut Vec<RecentEntry>, kind: RecentKind, now: u64) {
+    let (mut of_kind, rest): (Vec<RecentEntry>, Vec<RecentEntry>) = entries.drain(..).partition(|e| e.kind == kind);
+    if of_kind.len() > MAX_ENTRIES {
+        of_kind.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        of_kind.truncate(MAX_ENTRIES);
+    }
+    *entries = rest;
+    entries.extend(of_kind);
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
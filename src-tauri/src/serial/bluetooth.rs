@@ -0,0 +1,85 @@
+// Bluetooth serial (RFCOMM) devices
+// Bound RFCOMM channels show up as /dev/rfcommN tty devices once paired and bound with `rfcomm bind`
+
+use crate::exec::{self, ExecOptions};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+
+/// A bound RFCOMM tty
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BluetoothSerialPort {
+    /// Device node, e.g. /dev/rfcomm0
+    pub path: String,
+    /// Remote device address, when `rfcomm` reports it
+    pub address: Option<String>,
+    pub channel: Option<u8>,
+    /// Connection state as reported by `rfcomm`, e.g. "clean" or "connected"
+    pub state: Option<String>,
+}
+
+/// List bound RFCOMM devices
+pub async fn list_ports() -> Vec<BluetoothSerialPort> {
+    let mut details = rfcomm_details().await;
+
+    let mut ports: Vec<BluetoothSerialPort> = fs::read_dir("/sys/class/tty")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("rfcomm").then_some(name)
+        })
+        .map(|name| {
+            details.remove(&name).unwrap_or_else(|| BluetoothSerialPort {
+                path: format!("/dev/{}", name),
+                address: None,
+                channel: None,
+                state: None,
+            })
+        })
+        .collect();
+
+    ports.sort_by(|a, b| a.path.cmp(&b.path));
+    ports
+}
+
+/// Bindings reported by `rfcomm`, keyed by device name:
+///
+/// `rfcomm0: 00:1A:7D:DA:71:13 channel 1 clean`
+async fn rfcomm_details() -> HashMap<String, BluetoothSerialPort> {
+    let output = exec::run(&ExecOptions {
+        program: "rfcomm".to_string(),
+        ..Default::default()
+    })
+    .await;
+
+    let Ok(output) = output else {
+        return HashMap::new();
+    };
+
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, rest) = line.split_once(':')?;
+            let name = name.trim();
+            let mut fields = rest.split_whitespace();
+            let address = fields.next()?.to_string();
+            let channel = match (fields.next(), fields.next()) {
+                (Some("channel"), Some(n)) => n.parse().ok(),
+                _ => None,
+            };
+            let state = fields.collect::<Vec<_>>().join(" ");
+
+            let port = BluetoothSerialPort {
+                path: format!("/dev/{}", name),
+                address: Some(address),
+                channel,
+                state: (!state.is_empty()).then_some(state),
+            };
+            Some((name.to_string(), port))
+        })
+        .collect()
+}
@@ -0,0 +1,6 @@
+// Serial module - serial device discovery and sessions
+
+pub mod bluetooth;
+pub mod port;
+
+pub use bluetooth::BluetoothSerialPort;
@@ -0,0 +1,293 @@
+// Serial port sessions
+// USB and UART serial devices opened as terminal sessions. The port stands in for the PTY, so
+// output, input, scrollback, and exit go through the same session machinery and events.
+
+use crate::pty::backend::{ExitStatus, PtyProcess, SpawnedPty};
+use crate::pty::priority::Priority;
+use crate::pty::signal::Signal;
+use portable_pty::PtySize;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a read waits for data before checking whether the session was closed
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How a serial device is attached
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SerialPortKind {
+    Usb,
+    Pci,
+    Bluetooth,
+    Unknown,
+}
+
+/// A serial device found on the system
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortInfo {
+    /// Device node, e.g. /dev/ttyUSB0
+    pub path: String,
+    pub kind: SerialPortKind,
+    /// USB vendor and product IDs
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum FlowControl {
+    #[default]
+    None,
+    /// XON/XOFF
+    Software,
+    /// RTS/CTS
+    Hardware,
+}
+
+/// Line settings of a serial session; unset fields default to 8N1 without flow control
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialOptions {
+    /// Device node, e.g. /dev/ttyUSB0
+    pub port: String,
+    pub baud: u32,
+    #[serde(default)]
+    pub parity: Parity,
+    /// 1 or 2
+    pub stop_bits: Option<u8>,
+    /// 5 to 8
+    pub data_bits: Option<u8>,
+    #[serde(default)]
+    pub flow_control: FlowControl,
+}
+
+/// Serial devices on the system, sorted by path
+pub fn list_ports() -> Vec<SerialPortInfo> {
+    // The enumeration panics rather than failing when sysfs is not mounted
+    if !Path::new("/sys/class/tty").is_dir() {
+        return Vec::new();
+    }
+    let ports = match serialport::available_ports() {
+        Ok(ports) => ports,
+        Err(e) => {
+            log::warn!("Failed to list serial ports: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut ports: Vec<SerialPortInfo> = ports
+        .into_iter()
+        .map(|port| {
+            let mut info = SerialPortInfo {
+                path: port.port_name,
+                kind: SerialPortKind::Unknown,
+                vid: None,
+                pid: None,
+                manufacturer: None,
+                product: None,
+                serial_number: None,
+            };
+            match port.port_type {
+                serialport::SerialPortType::UsbPort(usb) => {
+                    info.kind = SerialPortKind::Usb;
+                    info.vid = Some(usb.vid);
+                    info.pid = Some(usb.pid);
+                    info.manufacturer = usb.manufacturer;
+                    info.product = usb.product;
+                    info.serial_number = usb.serial_number;
+                }
+                serialport::SerialPortType::PciPort => info.kind = SerialPortKind::Pci,
+                serialport::SerialPortType::BluetoothPort => info.kind = SerialPortKind::Bluetooth,
+                serialport::SerialPortType::Unknown => {}
+            }
+            info
+        })
+        .collect();
+    ports.sort_by(|a, b| a.path.cmp(&b.path));
+    ports
+}
+
+/// Open a serial port as the terminal of a session
+pub fn open(options: &SerialOptions) -> Result<SpawnedPty, String> {
+    if !options.port.starts_with("/dev/") {
+        return Err(format!("Not a device path: {}", options.port));
+    }
+    if options.baud == 0 {
+        return Err("Baud rate must not be zero".to_string());
+    }
+    let stop_bits = match options.stop_bits.unwrap_or(1) {
+        1 => serialport::StopBits::One,
+        2 => serialport::StopBits::Two,
+        bits => return Err(format!("Invalid stop bits: {}", bits)),
+    };
+    let data_bits = match options.data_bits.unwrap_or(8) {
+        5 => serialport::DataBits::Five,
+        6 => serialport::DataBits::Six,
+        7 => serialport::DataBits::Seven,
+        8 => serialport::DataBits::Eight,
+        bits => return Err(format!("Invalid data bits: {}", bits)),
+    };
+    let parity = match options.parity {
+        Parity::None => serialport::Parity::None,
+        Parity::Odd => serialport::Parity::Odd,
+        Parity::Even => serialport::Parity::Even,
+    };
+    let flow_control = match options.flow_control {
+        FlowControl::None => serialport::FlowControl::None,
+        FlowControl::Software => serialport::FlowControl::Software,
+        FlowControl::Hardware => serialport::FlowControl::Hardware,
+    };
+
+    let port = serialport::new(options.port.as_str(), options.baud)
+        .data_bits(data_bits)
+        .parity(parity)
+        .stop_bits(stop_bits)
+        .flow_control(flow_control)
+        .timeout(READ_TIMEOUT)
+        .open()
+        .map_err(|e| format!("Failed to open {}: {}", options.port, e))?;
+    let writer = port
+        .try_clone()
+        .map_err(|e| format!("Failed to open {} for writing: {}", options.port, e))?;
+
+    let closed = Arc::new(AtomicBool::new(false));
+    let (exit_sender, exit_receiver) = mpsc::channel();
+
+    Ok(SpawnedPty {
+        process: Box::new(SerialProcess {
+            closed: closed.clone(),
+            exit: exit_sender.clone(),
+        }),
+        reader: Box::new(SerialReader {
+            port,
+            closed,
+            exit: Some(exit_sender),
+        }),
+        writer: Box::new(writer),
+        // Ends when the session is closed or the device goes away
+        waiter: Box::new(move || exit_receiver.recv().unwrap_or_default()),
+    })
+}
+
+/// Stands in for the child process of a PTY session
+struct SerialProcess {
+    closed: Arc<AtomicBool>,
+    exit: Sender<ExitStatus>,
+}
+
+impl PtyProcess for SerialProcess {
+    fn pid(&self) -> Option<u32> {
+        None
+    }
+
+    /// The line has no window size
+    fn resize(&self, _size: PtySize) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn foreground_pgrp(&self) -> Option<i32> {
+        None
+    }
+
+    fn set_priority(&self, _priority: &Priority) -> Result<(), String> {
+        Err("Serial sessions have no process".to_string())
+    }
+
+    /// Anything but SIGINT closes the port, as the signal would end a process
+    fn signal(&self, signal: Signal) -> Result<(), String> {
+        if signal == Signal::Sigint {
+            return Err("Serial sessions have no process to interrupt".to_string());
+        }
+        self.close(signal);
+        Ok(())
+    }
+
+    fn signal_foreground(&self, _signal: Signal) -> Result<(), String> {
+        Err("Serial sessions have no process".to_string())
+    }
+
+    /// What is written goes straight to the line
+    fn unread_input(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl SerialProcess {
+    fn close(&self, signal: Signal) {
+        self.closed.store(true, Ordering::Relaxed);
+        let name = match signal {
+            Signal::Sighup => "Hangup",
+            Signal::Sigterm => "Terminated",
+            _ => "Killed",
+        };
+        let _ = self.exit.send(ExitStatus {
+            code: None,
+            signal: Some(name.to_string()),
+        });
+    }
+}
+
+impl Drop for SerialProcess {
+    /// Closing the session ends the reader, which closes the port
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Reads the port, turning read timeouts into the wait a PTY read would do
+struct SerialReader {
+    port: Box<dyn serialport::SerialPort>,
+    closed: Arc<AtomicBool>,
+    exit: Option<Sender<ExitStatus>>,
+}
+
+impl SerialReader {
+    /// Report the end of the session to the waiter
+    fn finish(&mut self) {
+        if let Some(exit) = self.exit.take() {
+            let _ = exit.send(ExitStatus::default());
+        }
+    }
+}
+
+impl Read for SerialReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.closed.load(Ordering::Relaxed) {
+                self.finish();
+                return Ok(0);
+            }
+            match self.port.read(buf) {
+                Ok(0) => {
+                    self.finish();
+                    return Ok(0);
+                }
+                Ok(n) => return Ok(n),
+                Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) => continue,
+                // e.g. the USB adapter was unplugged
+                Err(e) => {
+                    self.finish();
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Session restore
+// Snapshots open sessions and the frontend's tab layout to disk, periodically and on exit,
+// so the next start can respawn them where they left off
+
+use crate::commands::settings;
+use crate::pty::{PtyManager, SessionAppearance, SessionInfo, SpawnOptions};
+use crate::sync::MutexExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often open sessions are snapshotted while the app runs
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Bumped when the file format changes incompatibly; other versions are ignored
+const STATE_VERSION: u32 = 1;
+
+/// The `sessionRestore` section of settings
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SessionRestoreSettings {
+    /// Snapshot sessions and offer them for restore on the next start
+    pub enabled: bool,
+}
+
+/// One session as it can be respawned
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    /// ID the session had, referenced by the saved layout
+    pub id: String,
+    /// Original spawn options with the current directory and size; without variables, which
+    /// may hold secrets and come back from the profile on restore
+    pub options: SpawnOptions,
+    pub name: Option<String>,
+    pub labels: Vec<String>,
+    pub appearance: SessionAppearance,
+    pub title: Option<String>,
+}
+
+/// Contents of the state file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SavedState {
+    version: u32,
+    sessions: Vec<SessionSnapshot>,
+    /// Tab and pane layout as reported by the frontend, opaque to the backend
+    layout: Option<Value>,
+}
+
+/// A session respawned from a snapshot
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoredSession {
+    pub previous_id: String,
+    pub info: SessionInfo,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedRestore {
+    pub previous_id: String,
+    pub error: String,
+}
+
+/// Outcome of restoring the previous run's sessions
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub restored: Vec<RestoredSession>,
+    pub failed: Vec<FailedRestore>,
+    /// Layout saved with the sessions; pane session IDs refer to `previous_id`
+    pub layout: Option<Value>,
+}
+
+/// Restore state shared with the commands, managed by Tauri
+#[derive(Default)]
+pub struct SessionRestore {
+    /// Latest layout reported by the frontend
+    layout: Mutex<Option<Value>>,
+    /// Restore runs once per app start, so reloading the frontend does not duplicate sessions
+    restored: AtomicBool,
+}
+
+impl SessionRestore {
+    pub fn set_layout(&self, layout: Value) {
+        *self.layout.locked() = Some(layout);
+    }
+}
+
+fn state_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xterminal").join("session-state.json"))
+}
+
+/// Snapshot sessions for the lifetime of the app
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        // The first tick fires immediately; skip it so a fresh start does not overwrite the
+        // previous run's state before the frontend had a chance to restore it
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = save(&app_handle) {
+                log::warn!("Failed to save session state: {}", e);
+            }
+        }
+    });
+}
+
+/// Write the current sessions and layout to disk, or remove the file if restore is disabled
+pub fn save(app_handle: &AppHandle) -> Result<(), String> {
+    let path = state_path().ok_or_else(|| "Could not find data directory".to_string())?;
+    let restore = app_handle.state::<SessionRestore>();
+
+    if !settings::read_section::<SessionRestoreSettings>("sessionRestore").enabled {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove session state: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    // Until the previous run was restored (or declined), its state is still worth keeping
+    if !restore.restored.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let state = SavedState {
+        version: STATE_VERSION,
+        sessions: app_handle.state::<PtyManager>().snapshots(),
+        layout: restore.layout.locked().clone(),
+    };
+    let contents = serde_json::to_string_pretty(&state)
+        .map_err(|e| format!("Failed to serialize session state: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    // Commands, directories, and titles are the user's business alone; the mode only applies to
+    // a new file, so a leftover from an interrupted save is removed first
+    let tmp = path.with_extension("json.tmp");
+    let _ = fs::remove_file(&tmp);
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Failed to write session state: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to write session state: {}", e))?;
+
+    log::debug!("Saved {} session(s) to {:?}", state.sessions.len(), path);
+    Ok(())
+}
+
+/// Respawn the sessions saved by the previous run
+///
+/// Only the first call per app start restores anything; later calls return an empty report.
+/// With `discard` the saved state is dropped instead.
+pub fn restore(app_handle: &AppHandle, discard: bool) -> RestoreReport {
+    let restore = app_handle.state::<SessionRestore>();
+    if restore.restored.swap(true, Ordering::SeqCst) {
+        return RestoreReport::default();
+    }
+
+    let enabled = settings::read_section::<SessionRestoreSettings>("sessionRestore").enabled;
+    let Some(state) = state_path().filter(|_| enabled && !discard).and_then(|path| read_state(&path)) else {
+        return RestoreReport::default();
+    };
+
+    let manager = app_handle.state::<PtyManager>();
+    let mut report = RestoreReport {
+        layout: state.layout,
+        ..RestoreReport::default()
+    };
+
+    for snapshot in state.sessions {
+        match respawn(&manager, &snapshot) {
+            Ok(info) => report.restored.push(RestoredSession {
+                previous_id: snapshot.id,
+                info,
+            }),
+            Err(error) => {
+                log::warn!("Failed to restore session {}: {}", snapshot.id, error);
+                report.failed.push(FailedRestore {
+                    previous_id: snapshot.id,
+                    error,
+                });
+            }
+        }
+    }
+
+    log::info!(
+        "Restored {} session(s), {} failed",
+        report.restored.len(),
+        report.failed.len()
+    );
+    report
+}
+
+fn read_state(path: &PathBuf) -> Option<SavedState> {
+    let contents = fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<SavedState>(&contents) {
+        Ok(state) if state.version == STATE_VERSION => Some(state),
+        Ok(state) => {
+            log::warn!("Ignoring session state of version {}", state.version);
+            None
+        }
+        Err(e) => {
+            log::warn!("Ignoring unreadable session state: {}", e);
+            None
+        }
+    }
+}
+
+fn respawn(manager: &PtyManager, snapshot: &SessionSnapshot) -> Result<SessionInfo, String> {
+    let info = manager.spawn(snapshot.options.clone())?;
+    if let Some(name) = &snapshot.name {
+        manager.rename(&info.id, name)?;
+    }
+    if !snapshot.labels.is_empty() {
+        manager.set_labels(&info.id, snapshot.labels.clone())?;
+    }
+    manager.set_appearance(&info.id, snapshot.appearance.clone())?;
+    manager.info(&info.id)
+}
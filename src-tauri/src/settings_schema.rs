@@ -0,0 +1,581 @@
+// Settings schema
+// The typed shape of settings.json. Saves are checked against it so a bad value from the UI or a
+// hand edit is reported instead of written, and loads drop invalid values so their defaults
+// apply. The file itself stays JSON as the frontend wrote it: keys the schema does not know,
+// from newer versions or other tools, are kept as they are.
+
+use crate::control::socket::ControlSocketSettings;
+use crate::hints::IssueLinkRule;
+use crate::logs::writer::LoggingSettings;
+use crate::maintenance::RetentionSettings;
+use crate::notifications::CommandNotificationSettings;
+use crate::profiles::ContextDefaults;
+use crate::history::HistorySettings;
+use crate::pty::batch::BatchSettings;
+use crate::pty::hang::HangSettings;
+use crate::pty::hibernate::HibernationSettings;
+use crate::pty::broadcast::BroadcastRules;
+use crate::pty::redact::RedactionSettings;
+use crate::session_state::SessionRestoreSettings;
+use crate::status::StatusBarSettings;
+use crate::themes;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_path_to_error::Segment;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+/// Largest scrollback the terminal is allowed to keep, in lines
+const MAX_SCROLLBACK: u32 = 1_000_000;
+
+/// A setting that does not fit the schema
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsValidationError {
+    /// JSON pointer to the setting, e.g. "/terminal/bellStyle"
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    System,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CursorStyle {
+    Bar,
+    Block,
+    Underline,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RightClickAction {
+    Paste,
+    ContextMenu,
+    SelectWord,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MiddleClickAction {
+    Paste,
+    None,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BellStyle {
+    None,
+    Audio,
+    Visual,
+    Both,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkHandler {
+    Auto,
+    Click,
+    CtrlClick,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RendererType {
+    Canvas,
+    Webgl,
+    Dom,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+// Named as they are in settings.json
+#[allow(clippy::enum_variant_names)]
+pub enum MouseAction {
+    RightClick,
+    MiddleClick,
+    DoubleClick,
+    TripleClick,
+    CtrlClick,
+    ShiftClick,
+    AltClick,
+}
+
+/// The settings the frontend owns; sections read by backend modules are checked with their types
+///
+/// Lists left out of the file use the frontend's built-in profiles, schemes, and bindings.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub profiles: Option<Vec<Profile>>,
+    pub color_schemes: Option<Vec<ColorScheme>>,
+    pub key_bindings: Option<Vec<KeyBinding>>,
+    pub active_profile_id: Option<String>,
+    pub active_color_scheme_id: Option<String>,
+
+    // Appearance
+    pub theme: ThemeMode,
+    pub font_size: f64,
+    pub font_family: String,
+    pub font_weight: u16,
+    pub font_weight_bold: u16,
+    pub line_height: f64,
+    pub letter_spacing: f64,
+    pub cursor_style: CursorStyle,
+    pub cursor_blink: bool,
+    pub cursor_width: f64,
+
+    pub terminal: TerminalSettings,
+    pub scrollback_size: u32,
+    pub tab_width: u8,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            profiles: None,
+            color_schemes: None,
+            key_bindings: None,
+            active_profile_id: None,
+            active_color_scheme_id: None,
+            theme: ThemeMode::Dark,
+            font_size: 14.0,
+            font_family: "monospace".to_string(),
+            font_weight: 400,
+            font_weight_bold: 700,
+            line_height: 1.2,
+            letter_spacing: 0.0,
+            cursor_style: CursorStyle::Block,
+            cursor_blink: true,
+            cursor_width: 2.0,
+            terminal: TerminalSettings::default(),
+            scrollback_size: 10_000,
+            tab_width: 4,
+        }
+    }
+}
+
+/// The `terminal` section
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TerminalSettings {
+    pub copy_on_select: bool,
+    pub right_click_action: RightClickAction,
+    pub middle_click_action: MiddleClickAction,
+    pub scroll_sensitivity: f64,
+    pub bell_style: BellStyle,
+    pub bell_sound: Option<String>,
+    pub word_separators: String,
+    pub triple_click_selects_line: bool,
+    pub link_handler: LinkHandler,
+    pub renderer_type: RendererType,
+    pub gpu_acceleration: bool,
+    pub sanitize_child_environment: bool,
+}
+
+impl Default for TerminalSettings {
+    fn default() -> Self {
+        Self {
+            copy_on_select: false,
+            right_click_action: RightClickAction::Paste,
+            middle_click_action: MiddleClickAction::Paste,
+            scroll_sensitivity: 1.0,
+            bell_style: BellStyle::None,
+            bell_sound: None,
+            word_separators: " ()[]{}'\",;:".to_string(),
+            triple_click_selects_line: true,
+            link_handler: LinkHandler::CtrlClick,
+            renderer_type: RendererType::Webgl,
+            gpu_acceleration: true,
+            sanitize_child_environment: true,
+        }
+    }
+}
+
+/// A frontend profile; process options such as limits and sandboxing are checked when spawning
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub shell: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub starting_directory: Option<String>,
+    #[serde(default)]
+    pub selection: SelectionSettings,
+}
+
+/// How a profile's terminals select and copy text
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SelectionSettings {
+    /// Characters that end a word on double-click; unset uses `terminal.wordSeparators`
+    pub word_separators: Option<String>,
+    /// Double-clicking a path or URL selects all of it, separators included
+    pub select_paths: bool,
+    /// More regexes for what counts as a path, e.g. `[\w./-]+:\d+` for file:line. They run in
+    /// the frontend, so only syntax both regex dialects share is portable.
+    pub path_patterns: Vec<String>,
+    /// Remove spaces the terminal pads lines with from the ends of copied lines
+    pub trim_trailing_whitespace: bool,
+}
+
+impl Default for SelectionSettings {
+    fn default() -> Self {
+        Self {
+            word_separators: None,
+            select_paths: true,
+            path_patterns: Vec::new(),
+            trim_trailing_whitespace: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorScheme {
+    pub id: String,
+    pub name: String,
+    pub black: String,
+    pub red: String,
+    pub green: String,
+    pub yellow: String,
+    pub blue: String,
+    pub magenta: String,
+    pub cyan: String,
+    pub white: String,
+    pub bright_black: String,
+    pub bright_red: String,
+    pub bright_green: String,
+    pub bright_yellow: String,
+    pub bright_blue: String,
+    pub bright_magenta: String,
+    pub bright_cyan: String,
+    pub bright_white: String,
+    pub foreground: String,
+    pub background: String,
+    pub cursor_color: String,
+    pub selection_background: String,
+}
+
+impl ColorScheme {
+    fn colors(&self) -> [(&'static str, &str); 20] {
+        [
+            ("black", &self.black),
+            ("red", &self.red),
+            ("green", &self.green),
+            ("yellow", &self.yellow),
+            ("blue", &self.blue),
+            ("magenta", &self.magenta),
+            ("cyan", &self.cyan),
+            ("white", &self.white),
+            ("brightBlack", &self.bright_black),
+            ("brightRed", &self.bright_red),
+            ("brightGreen", &self.bright_green),
+            ("brightYellow", &self.bright_yellow),
+            ("brightBlue", &self.bright_blue),
+            ("brightMagenta", &self.bright_magenta),
+            ("brightCyan", &self.bright_cyan),
+            ("brightWhite", &self.bright_white),
+            ("foreground", &self.foreground),
+            ("background", &self.background),
+            ("cursorColor", &self.cursor_color),
+            ("selectionBackground", &self.selection_background),
+        ]
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyBinding {
+    pub id: String,
+    pub name: String,
+    pub command: String,
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub mouse_actions: Vec<MouseAction>,
+}
+
+/// Check settings before they are saved; an empty list means they are valid
+pub fn validate(value: &Value) -> Vec<SettingsValidationError> {
+    let Value::Object(object) = value else {
+        return vec![error("", "Settings must be a JSON object")];
+    };
+    let (settings, mut errors) = parse(value, object);
+    if let Some(settings) = settings {
+        settings.check(&mut errors);
+    }
+    check_patterns(object, &mut errors);
+    errors
+}
+
+/// Prepare the settings file for the frontend
+///
+/// Invalid values are removed so the frontend's defaults replace them. Otherwise every save
+/// would fail until the file is fixed.
+pub fn sanitize(mut value: Value) -> Value {
+    if !value.is_object() {
+        log::warn!("Settings file is not a JSON object, using defaults");
+        return Value::Object(Map::new());
+    }
+    // One at a time, since removing a list item moves the items after it
+    while let Some(error) = validate(&value).into_iter().next() {
+        let segments: Vec<String> = error
+            .path
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+        if !remove_at(&mut value, &segments) {
+            log::warn!("Invalid setting at {}: {}", error.path, error.message);
+            break;
+        }
+        log::warn!("Invalid setting at {}, using the default: {}", error.path, error.message);
+    }
+    value
+}
+
+/// Remove the value at a path; a bad list item is removed whole, since its fields have no defaults
+fn remove_at(value: &mut Value, segments: &[String]) -> bool {
+    match (value, segments) {
+        (Value::Array(items), [index, ..]) => match index.parse::<usize>() {
+            Ok(index) if index < items.len() => {
+                items.remove(index);
+                true
+            }
+            _ => false,
+        },
+        (Value::Object(object), [key]) => object.remove(key).is_some(),
+        (Value::Object(object), [key, rest @ ..]) => match object.get_mut(key) {
+            Some(child) => remove_at(child, rest),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Deserialize the schema and the backend sections, reporting type errors with their paths
+fn parse(value: &Value, object: &Map<String, Value>) -> (Option<Settings>, Vec<SettingsValidationError>) {
+    let mut errors = Vec::new();
+    section::<RetentionSettings>(object, "retention", &mut errors);
+    section::<LoggingSettings>(object, "logging", &mut errors);
+    section::<RedactionSettings>(object, "redaction", &mut errors);
+    section::<BroadcastRules>(object, "broadcast", &mut errors);
+    section::<SessionRestoreSettings>(object, "sessionRestore", &mut errors);
+    section::<ControlSocketSettings>(object, "controlSocket", &mut errors);
+    section::<HibernationSettings>(object, "hibernation", &mut errors);
+    section::<HangSettings>(object, "hangDetection", &mut errors);
+    section::<BatchSettings>(object, "outputBatching", &mut errors);
+    section::<StatusBarSettings>(object, "statusBar", &mut errors);
+    section::<HistorySettings>(object, "history", &mut errors);
+    section::<CommandNotificationSettings>(object, "commandNotifications", &mut errors);
+    section::<ContextDefaults>(object, "defaultProfiles", &mut errors);
+    section::<Vec<IssueLinkRule>>(object, "issueLinks", &mut errors);
+
+    let settings = match serde_path_to_error::deserialize::<_, Settings>(value) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            errors.push(error(&pointer(e.path()), e.inner()));
+            None
+        }
+    };
+    (settings, errors)
+}
+
+fn section<T: DeserializeOwned>(object: &Map<String, Value>, key: &str, errors: &mut Vec<SettingsValidationError>) {
+    let Some(value) = object.get(key) else {
+        return;
+    };
+    if let Err(e) = serde_path_to_error::deserialize::<_, T>(value) {
+        errors.push(error(&format!("/{}{}", pointer_key(key), pointer(e.path())), e.inner()));
+    }
+}
+
+impl Settings {
+    /// Check what the types alone cannot
+    ///
+    /// References between settings, like a profile's color scheme, are not checked: the frontend
+    /// falls back to defaults for them, and deleting a scheme must not block saving.
+    fn check(&self, errors: &mut Vec<SettingsValidationError>) {
+        range(errors, "/fontSize", self.font_size, 6.0, 100.0);
+        if self.font_family.trim().is_empty() {
+            errors.push(error("/fontFamily", "Must not be empty"));
+        }
+        range(errors, "/fontWeight", self.font_weight, 100, 900);
+        range(errors, "/fontWeightBold", self.font_weight_bold, 100, 900);
+        range(errors, "/lineHeight", self.line_height, 0.5, 5.0);
+        range(errors, "/letterSpacing", self.letter_spacing, -10.0, 50.0);
+        range(errors, "/cursorWidth", self.cursor_width, 1.0, 10.0);
+        range(errors, "/scrollbackSize", self.scrollback_size, 0, MAX_SCROLLBACK);
+        range(errors, "/tabWidth", self.tab_width, 1, 32);
+        if !(self.terminal.scroll_sensitivity > 0.0 && self.terminal.scroll_sensitivity <= 100.0) {
+            errors.push(error("/terminal/scrollSensitivity", "Must be greater than 0 and at most 100"));
+        }
+        check_word_separators(errors, "/terminal/wordSeparators", &self.terminal.word_separators);
+
+        if let Some(schemes) = &self.color_schemes {
+            let mut ids = HashSet::new();
+            for (index, scheme) in schemes.iter().enumerate() {
+                let path = format!("/colorSchemes/{}", index);
+                check_id(errors, &path, &scheme.id, &mut ids, "color scheme");
+                if scheme.name.trim().is_empty() {
+                    errors.push(error(&format!("{}/name", path), "Must not be empty"));
+                }
+                for (name, color) in scheme.colors() {
+                    if !themes::is_css_color(color.trim()) {
+                        errors.push(error(&format!("{}/{}", path, name), format!("Invalid color: {}", color)));
+                    }
+                }
+            }
+        }
+
+        if let Some(profiles) = &self.profiles {
+            let mut ids = HashSet::new();
+            for (index, profile) in profiles.iter().enumerate() {
+                let path = format!("/profiles/{}", index);
+                check_id(errors, &path, &profile.id, &mut ids, "profile");
+                if profile.name.trim().is_empty() {
+                    errors.push(error(&format!("{}/name", path), "Must not be empty"));
+                }
+                if profile.shell.trim().is_empty() {
+                    errors.push(error(&format!("{}/shell", path), "Must not be empty"));
+                }
+                // Passed to exec and chdir, which cannot take them
+                for (arg_index, arg) in profile.args.iter().enumerate() {
+                    if arg.contains('\0') {
+                        errors.push(error(&format!("{}/args/{}", path, arg_index), "Must not contain NUL characters"));
+                    }
+                }
+                if profile.starting_directory.as_deref().is_some_and(|dir| dir.contains('\0')) {
+                    errors.push(error(&format!("{}/startingDirectory", path), "Must not contain NUL characters"));
+                }
+                for name in profile.env.keys() {
+                    if name.is_empty() || name.contains(['=', '\0']) {
+                        errors.push(error(&format!("{}/env", path), format!("Invalid variable name: {:?}", name)));
+                    }
+                }
+                let selection = &profile.selection;
+                if let Some(separators) = &selection.word_separators {
+                    check_word_separators(errors, &format!("{}/selection/wordSeparators", path), separators);
+                }
+                for (pattern_index, pattern) in selection.path_patterns.iter().enumerate() {
+                    let pattern_path = format!("{}/selection/pathPatterns/{}", path, pattern_index);
+                    match Regex::new(pattern) {
+                        // Would match between any two characters and select nothing useful
+                        Ok(regex) if regex.is_match("") => errors.push(error(&pattern_path, "Must not match empty text")),
+                        Ok(_) => {}
+                        Err(e) => errors.push(error(&pattern_path, format!("Invalid regex: {}", e))),
+                    }
+                }
+            }
+        }
+
+        if let Some(bindings) = &self.key_bindings {
+            let mut ids = HashSet::new();
+            for (index, binding) in bindings.iter().enumerate() {
+                let path = format!("/keyBindings/{}", index);
+                check_id(errors, &path, &binding.id, &mut ids, "key binding");
+                if binding.name.trim().is_empty() {
+                    errors.push(error(&format!("{}/name", path), "Must not be empty"));
+                }
+                if binding.command.trim().is_empty() {
+                    errors.push(error(&format!("{}/command", path), "Must not be empty"));
+                }
+                for (key_index, key) in binding.keys.iter().enumerate() {
+                    // `+` itself is written as `plus`, so every part must be non-empty
+                    if key.split('+').any(|part| part.trim().is_empty()) {
+                        errors.push(error(&format!("{}/keys/{}", path, key_index), format!("Invalid key: {:?}", key)));
+                    }
+                }
+                for (action_index, action) in binding.mouse_actions.iter().enumerate() {
+                    if binding.mouse_actions[..action_index].contains(action) {
+                        let action_path = format!("{}/mouseActions/{}", path, action_index);
+                        errors.push(error(&action_path, "Listed more than once"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Regexes in backend sections, which would otherwise only fail when a session starts
+fn check_patterns(object: &Map<String, Value>, errors: &mut Vec<SettingsValidationError>) {
+    let patterns = [("redaction", "/redaction/rules"), ("issueLinks", "/issueLinks")];
+    for (key, path) in patterns {
+        let rules = match (key, object.get(key)) {
+            ("redaction", Some(section)) => section.get("rules").and_then(Value::as_array),
+            (_, Some(section)) => section.as_array(),
+            _ => None,
+        };
+        for (index, rule) in rules.into_iter().flatten().enumerate() {
+            let Some(pattern) = rule.get("pattern").and_then(Value::as_str) else {
+                continue;
+            };
+            if let Err(e) = Regex::new(pattern) {
+                errors.push(error(&format!("{}/{}/pattern", path, index), format!("Invalid regex: {}", e)));
+            }
+        }
+    }
+}
+
+fn check_word_separators(errors: &mut Vec<SettingsValidationError>, path: &str, separators: &str) {
+    if separators.chars().any(char::is_control) {
+        errors.push(error(path, "Must not contain control characters"));
+    } else if separators.chars().any(char::is_alphanumeric) {
+        errors.push(error(path, "Must not contain letters or digits"));
+    }
+}
+
+fn check_id(
+    errors: &mut Vec<SettingsValidationError>,
+    path: &str,
+    id: &str,
+    seen: &mut HashSet<String>,
+    kind: &str,
+) {
+    if id.trim().is_empty() {
+        errors.push(error(&format!("{}/id", path), "Must not be empty"));
+    } else if !seen.insert(id.to_string()) {
+        errors.push(error(&format!("{}/id", path), format!("Duplicate {} ID {:?}", kind, id)));
+    }
+}
+
+fn range<T: PartialOrd + Display>(errors: &mut Vec<SettingsValidationError>, path: &str, value: T, min: T, max: T) {
+    if !(value >= min && value <= max) {
+        errors.push(error(path, format!("Must be between {} and {}", min, max)));
+    }
+}
+
+fn error(path: &str, message: impl Display) -> SettingsValidationError {
+    SettingsValidationError {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// JSON pointer for a deserialization path
+fn pointer(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } => Some(pointer_key(key)),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        })
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+fn pointer_key(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,458 @@
+// Settings sync
+// Notices when another program (a dotfile manager, a sync tool) rewrites settings.json and
+// merges its changes with the app's instead of letting the last writer win.
+// The app remembers the file as it last loaded or saved it (the base); a save over a changed
+// file is a three-way merge of base, ours, and theirs, and clashing edits wait for resolution.
+// Writes are atomic and durable, and the previous good version is kept as settings.json.bak to
+// recover from a file corrupted by a crash or a bad edit.
+
+use crate::commands::settings;
+use crate::settings_schema::SettingsValidationError;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the settings file is checked for outside changes when it cannot be watched
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often a watched file is checked anyway, for changes the watcher misses (network mounts)
+const WATCHED_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Quiet time after a change before the file is read, so multi-step writes are seen once
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// A setting both sides changed differently
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsConflict {
+    /// JSON pointer to the setting, e.g. "/terminal/fontSize"
+    pub path: String,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// Which version of a conflicting setting to keep
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+/// Outcome of a save
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SaveStatus {
+    /// Written as given
+    Saved,
+    /// The file had changed on disk; both sides' changes were combined and written
+    Merged,
+    /// The file had changed on disk in the same places; nothing was written
+    Conflict,
+    /// The settings failed validation; nothing was written
+    Invalid,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsSaveResult {
+    pub status: SaveStatus,
+    /// Settings now on disk, when they differ from what was passed in
+    pub settings: Option<Value>,
+    pub conflicts: Vec<SettingsConflict>,
+    pub errors: Vec<SettingsValidationError>,
+}
+
+/// Payload of `settings://recovered`, sent when load_settings found the file corrupt
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsRecoveredEvent {
+    /// Why the settings file could not be read
+    pub error: String,
+    /// Backup the settings were loaded from
+    pub backup_path: String,
+    /// Copy of the corrupt file, kept for salvaging edits the backup lacks
+    pub corrupt_path: Option<String>,
+}
+
+/// Settings read by `SettingsSync::load`
+#[derive(Debug, Clone)]
+pub struct LoadedSettings {
+    pub settings: Option<Value>,
+    /// Set when the file was corrupt and the backup was used instead
+    pub recovered: Option<SettingsRecoveredEvent>,
+}
+
+/// Payload of `settings://changed`
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChangedEvent {
+    /// New contents as load_settings returns them, or None if the file was removed or is not
+    /// valid JSON
+    pub settings: Option<Value>,
+}
+
+/// The file contents the app last agreed with
+#[derive(Debug, Clone)]
+struct Base {
+    value: Option<Value>,
+    hash: Option<String>,
+}
+
+/// A merge waiting for the user
+#[derive(Debug, Clone)]
+struct Pending {
+    /// Merge result with our side at every conflict
+    merged: Value,
+    /// Hash of the file the merge was made against
+    theirs_hash: Option<String>,
+    conflicts: Vec<SettingsConflict>,
+}
+
+#[derive(Debug, Default)]
+struct SyncState {
+    /// None until the app has loaded or saved settings once
+    base: Option<Base>,
+    /// Hash of the file when it was last looked at, to report each outside change once
+    seen_hash: Option<String>,
+    pending: Option<Pending>,
+}
+
+/// Shared sync state, managed by Tauri
+#[derive(Debug, Default)]
+pub struct SettingsSync {
+    state: Mutex<SyncState>,
+}
+
+impl SettingsSync {
+    /// Read the settings file and make it the base for later saves
+    ///
+    /// A corrupt file is replaced by its backup as far as the app is concerned; it stays on disk
+    /// until the next save overwrites it.
+    pub fn load(&self, path: &Path) -> Result<LoadedSettings, String> {
+        let mut state = self.lock();
+        let (contents, hash) = read_file(path)?;
+        let (value, recovered) = match contents.map(|c| parse(&c)).transpose() {
+            Ok(value) => (value, None),
+            Err(error) => {
+                let (value, recovered) = recover(path, error)?;
+                (Some(value), Some(recovered))
+            }
+        };
+
+        // The corrupt file's hash, so the next save replaces it without merging
+        state.base = Some(Base { value: value.clone(), hash: hash.clone() });
+        state.seen_hash = hash;
+        state.pending = None;
+        Ok(LoadedSettings { settings: value, recovered })
+    }
+
+    /// Save settings, merging with changes made on disk since the last load or save
+    pub fn save(&self, path: &Path, ours: Value) -> Result<SettingsSaveResult, String> {
+        let mut state = self.lock();
+        let (contents, hash) = read_file(path)?;
+
+        let base = match &state.base {
+            Some(base) if base.hash != hash => base.clone(),
+            // Unchanged on disk, or never loaded: nothing to merge with
+            _ => {
+                write(&mut state, path, &ours)?;
+                return Ok(SettingsSaveResult {
+                    status: SaveStatus::Saved,
+                    settings: None,
+                    conflicts: Vec::new(),
+                    errors: Vec::new(),
+                });
+            }
+        };
+
+        let theirs = contents.map(|c| parse(&c)).transpose()?;
+        let mut conflicts = Vec::new();
+        let merged = merge(base.value.as_ref(), Some(&ours), theirs.as_ref(), "", &mut conflicts)
+            .unwrap_or_else(|| Value::Object(Map::new()));
+
+        if conflicts.is_empty() {
+            log::info!("Settings changed on disk; merged with unsaved changes");
+            write(&mut state, path, &merged)?;
+            return Ok(SettingsSaveResult {
+                status: SaveStatus::Merged,
+                settings: Some(merged),
+                conflicts,
+                errors: Vec::new(),
+            });
+        }
+
+        log::warn!("Settings changed on disk; {} conflicting setting(s)", conflicts.len());
+        state.seen_hash = hash.clone();
+        state.pending = Some(Pending { merged, theirs_hash: hash, conflicts: conflicts.clone() });
+        Ok(SettingsSaveResult {
+            status: SaveStatus::Conflict,
+            settings: None,
+            conflicts,
+            errors: Vec::new(),
+        })
+    }
+
+    /// Conflicts from the last save, if it could not be written
+    pub fn conflicts(&self) -> Vec<SettingsConflict> {
+        self.lock().pending.as_ref().map(|p| p.conflicts.clone()).unwrap_or_default()
+    }
+
+    /// Finish a conflicted save, picking a side for each conflicting setting
+    ///
+    /// Settings missing from `choices` take `prefer`. Returns the settings written.
+    pub fn resolve(
+        &self,
+        path: &Path,
+        prefer: ConflictSide,
+        choices: &HashMap<String, ConflictSide>,
+    ) -> Result<Value, String> {
+        let mut state = self.lock();
+        let pending = state.pending.clone().ok_or_else(|| "No settings conflict to resolve".to_string())?;
+
+        let (_, hash) = read_file(path)?;
+        if hash != pending.theirs_hash {
+            return Err("Settings changed on disk again; save again to merge the new changes".to_string());
+        }
+
+        let mut resolved = pending.merged;
+        for conflict in &pending.conflicts {
+            let side = choices.get(&conflict.path).copied().unwrap_or(prefer);
+            if side == ConflictSide::Theirs {
+                set_pointer(&mut resolved, &conflict.path, conflict.theirs.clone());
+            }
+        }
+
+        write(&mut state, path, &resolved)?;
+        log::info!("Resolved {} settings conflict(s)", pending.conflicts.len());
+        Ok(resolved)
+    }
+
+    /// If the file changed since it was last looked at, return its new contents
+    fn poll(&self, path: &Path) -> Option<SettingsChangedEvent> {
+        let mut state = self.lock();
+        let (contents, hash) = read_file(path).ok()?;
+        if hash == state.seen_hash {
+            return None;
+        }
+        state.seen_hash = hash;
+        Some(SettingsChangedEvent {
+            settings: contents.and_then(|c| parse(&c).ok()).map(settings::upgrade),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SyncState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Check the settings file for outside changes for the lifetime of the app,
+/// emitting `settings://changed` for each
+///
+/// inotify reports changes as they happen; without it, e.g. when the user's watch limit is
+/// used up, the file is polled. The app's own saves are recognized by their hash and skipped.
+pub fn watch(app_handle: AppHandle, path: PathBuf) {
+    std::thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        // Kept alive for the loop below; dropping it stops the notifications
+        let watcher = watch_file(&path, tx)
+            .map_err(|e| log::warn!("Cannot watch {:?}, polling instead: {}", path, e))
+            .ok();
+        let interval = if watcher.is_some() { WATCHED_POLL_INTERVAL } else { POLL_INTERVAL };
+
+        loop {
+            match rx.recv_timeout(interval) {
+                Ok(()) => {
+                    std::thread::sleep(SETTLE_DELAY);
+                    while rx.try_recv().is_ok() {}
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(interval),
+            }
+            let sync = app_handle.state::<SettingsSync>();
+            if let Some(change) = sync.poll(&path) {
+                log::info!("Settings file changed on disk");
+                let _ = app_handle.emit("settings://changed", change);
+            }
+        }
+    });
+}
+
+/// Send a message whenever the file is written, replaced, or removed
+fn watch_file(path: &Path, tx: mpsc::Sender<()>) -> notify::Result<RecommendedWatcher> {
+    let target = path.to_path_buf();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if event.paths.iter().any(|p| p == &target) && !event.kind.is_access() {
+                let _ = tx.send(());
+            }
+        }
+    })?;
+    // Editors and sync tools often replace the file rather than write it, so its directory
+    // is watched
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+/// Three-way merge of one value; None means the setting is absent
+///
+/// Objects merge key by key; any other value (including arrays) is replaced as a whole.
+/// Where both sides changed a value differently, ours is kept and a conflict recorded.
+pub fn merge(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &str,
+    conflicts: &mut Vec<SettingsConflict>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+
+    if let (Some(Value::Object(ours)), Some(Value::Object(theirs))) = (ours, theirs) {
+        let empty = Map::new();
+        let base = match base {
+            Some(Value::Object(base)) => base,
+            _ => &empty,
+        };
+        let mut keys: Vec<&String> = ours.keys().chain(theirs.keys()).chain(base.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut merged = Map::new();
+        for key in keys {
+            let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+            if let Some(value) = merge(base.get(key), ours.get(key), theirs.get(key), &child, conflicts) {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    conflicts.push(SettingsConflict {
+        path: path.to_string(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.cloned()
+}
+
+/// Set or remove the value at a JSON pointer whose parent exists
+fn set_pointer(root: &mut Value, pointer: &str, value: Option<Value>) {
+    if pointer.is_empty() {
+        *root = value.unwrap_or_else(|| Value::Object(Map::new()));
+        return;
+    }
+    let Some((parent, key)) = pointer.rsplit_once('/') else {
+        return;
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    if let Some(Value::Object(parent)) = root.pointer_mut(parent) {
+        match value {
+            Some(value) => parent.insert(key, value),
+            None => parent.remove(&key),
+        };
+    }
+}
+
+/// Contents and hash of the file; both None if it does not exist
+fn read_file(path: &Path) -> Result<(Option<String>, Option<String>), String> {
+    if !path.exists() {
+        return Ok((None, None));
+    }
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    let hash = hash(&contents);
+    Ok((Some(contents), Some(hash)))
+}
+
+/// Settings from the backup of a corrupt file, keeping a copy of the corrupt one
+fn recover(path: &Path, error: String) -> Result<(Value, SettingsRecoveredEvent), String> {
+    let corrupt = path.with_extension("json.corrupt");
+    let corrupt_path = match fs::copy(path, &corrupt) {
+        Ok(_) => Some(corrupt.display().to_string()),
+        Err(e) => {
+            log::warn!("Failed to keep a copy of the corrupt settings file: {}", e);
+            None
+        }
+    };
+
+    let backup = backup_path(path);
+    let value = fs::read_to_string(&backup)
+        .ok()
+        .and_then(|contents| parse(&contents).ok())
+        .ok_or_else(|| format!("{}; no usable backup at {:?}", error, backup))?;
+    log::warn!("Settings file is corrupt ({}); loaded {:?} instead", error, backup);
+    Ok((
+        value,
+        SettingsRecoveredEvent {
+            error,
+            backup_path: backup.display().to_string(),
+            corrupt_path,
+        },
+    ))
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+fn parse(contents: &str) -> Result<Value, String> {
+    serde_json::from_str(contents).map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+fn hash(contents: &str) -> String {
+    hex::encode(Sha256::digest(contents.as_bytes()))
+}
+
+/// Write settings atomically and make them the new base, keeping the previous version as backup
+fn write(state: &mut SyncState, path: &Path, settings: &Value) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    // Only a file that parses is worth keeping; a corrupt one must not replace a good backup
+    if let Ok(previous) = fs::read_to_string(path) {
+        if parse(&previous).is_ok() {
+            if let Err(e) = write_durably(&backup_path(path), &previous) {
+                log::warn!("Failed to back up settings: {}", e);
+            }
+        }
+    }
+    write_durably(path, &contents).map_err(|e| format!("Failed to write settings: {}", e))?;
+
+    let hash = Some(hash(&contents));
+    state.base = Some(Base { value: Some(settings.clone()), hash: hash.clone() });
+    state.seen_hash = hash;
+    state.pending = None;
+    log::info!("Saved settings to {:?}", path);
+    Ok(())
+}
+
+/// Replace a file so that a crash leaves either the old or the new contents, never a mix
+fn write_durably(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    // Without this, a power cut after the rename can leave an empty file behind
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
+    // Persist the rename itself
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+// Shell framework detection
+// Finds prompt frameworks and themes in the user's shell configuration, and whether something
+// already emits semantic prompt marks (OSC 133), so shell integration can be tailored to them
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Markers of third-party shell integrations that already emit OSC 133 prompt marks
+const PROMPT_MARK_MARKERS: &[&str] = &[
+    "133;A",
+    "133;B",
+    "vscode-shell-integration",
+    "shellIntegration-",
+    "iterm2_shell_integration",
+    "wezterm.sh",
+    "kitty/shell-integration",
+    "POWERLEVEL9K_TERM_SHELL_INTEGRATION=true",
+];
+
+/// A framework found in the user's configuration
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellFramework {
+    /// Framework ID: "ohMyZsh", "powerlevel10k", "prezto", "starship", "ohMyBash", "bashIt",
+    /// "ohMyFish", "tide"
+    pub id: String,
+    /// Shell the configuration belongs to: "zsh", "bash", or "fish"
+    pub shell: String,
+    /// Selected theme, where the framework has one
+    pub theme: Option<String>,
+    /// Configuration file it was found in
+    pub source: String,
+}
+
+/// Frameworks and prompt marking found in the user's shell configuration
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellFrameworks {
+    pub frameworks: Vec<ShellFramework>,
+    /// Configuration files that already emit OSC 133 prompt marks
+    pub prompt_marks: Vec<String>,
+}
+
+impl ShellFrameworks {
+    fn add(&mut self, id: &str, shell: &str, theme: Option<String>, source: &Path) {
+        if self.frameworks.iter().any(|f| f.id == id && f.shell == shell) {
+            return;
+        }
+        self.frameworks.push(ShellFramework {
+            id: id.to_string(),
+            shell: shell.to_string(),
+            theme,
+            source: source.display().to_string(),
+        });
+    }
+
+    /// Record frameworks and prompt marks from one configuration file
+    fn scan(&mut self, shell: &str, path: &Path, detectors: &[Detector]) {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return;
+        };
+        // Commented-out setup does not count
+        let contents: String = contents
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        for detector in detectors {
+            if detector.markers.iter().any(|marker| contents.contains(marker)) {
+                let theme = detector.theme_variable.and_then(|var| assignment(&contents, var));
+                self.add(detector.id, shell, theme, path);
+            }
+        }
+
+        if PROMPT_MARK_MARKERS.iter().any(|marker| contents.contains(marker)) {
+            let source = path.display().to_string();
+            if !self.prompt_marks.contains(&source) {
+                self.prompt_marks.push(source);
+            }
+        }
+    }
+}
+
+/// How to recognize a framework in a configuration file
+struct Detector {
+    id: &'static str,
+    markers: &'static [&'static str],
+    /// Variable holding the theme name, e.g. `ZSH_THEME`
+    theme_variable: Option<&'static str>,
+}
+
+const ZSH_DETECTORS: &[Detector] = &[
+    Detector { id: "ohMyZsh", markers: &["oh-my-zsh.sh"], theme_variable: Some("ZSH_THEME") },
+    Detector { id: "powerlevel10k", markers: &["powerlevel10k", ".p10k.zsh"], theme_variable: None },
+    Detector { id: "prezto", markers: &[".zprezto", "zprezto/init.zsh"], theme_variable: None },
+    Detector { id: "starship", markers: &["starship init zsh"], theme_variable: None },
+];
+
+const BASH_DETECTORS: &[Detector] = &[
+    Detector { id: "ohMyBash", markers: &["oh-my-bash.sh"], theme_variable: Some("OSH_THEME") },
+    Detector { id: "bashIt", markers: &["bash_it.sh"], theme_variable: Some("BASH_IT_THEME") },
+    Detector { id: "starship", markers: &["starship init bash"], theme_variable: None },
+];
+
+const FISH_DETECTORS: &[Detector] = &[
+    Detector { id: "starship", markers: &["starship init fish"], theme_variable: None },
+    Detector { id: "ohMyFish", markers: &["omf/init.fish", "OMF_PATH"], theme_variable: None },
+    Detector { id: "tide", markers: &["_tide_", "tide configure"], theme_variable: None },
+];
+
+/// Inspect the user's zsh, bash, and fish configuration
+pub fn detect() -> ShellFrameworks {
+    let mut found = ShellFrameworks::default();
+    let Some(home) = dirs::home_dir() else {
+        return found;
+    };
+    let config = dirs::config_dir().unwrap_or_else(|| home.join(".config"));
+
+    let zdotdir = std::env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or_else(|| home.clone());
+    for file in [".zshenv", ".zprofile", ".zshrc", ".p10k.zsh"] {
+        found.scan("zsh", &zdotdir.join(file), ZSH_DETECTORS);
+    }
+
+    for file in [".bashrc", ".bash_profile", ".profile"] {
+        found.scan("bash", &home.join(file), BASH_DETECTORS);
+    }
+
+    let fish = config.join("fish");
+    found.scan("fish", &fish.join("config.fish"), FISH_DETECTORS);
+    for dir in ["conf.d", "functions"] {
+        for path in fish_files(&fish.join(dir)) {
+            found.scan("fish", &path, FISH_DETECTORS);
+        }
+    }
+
+    // Oh My Fish keeps its theme in its own config rather than in a variable
+    if let Some(framework) = found.frameworks.iter_mut().find(|f| f.id == "ohMyFish") {
+        framework.theme = fs::read_to_string(config.join("omf/theme"))
+            .ok()
+            .map(|theme| theme.trim().to_string())
+            .filter(|theme| !theme.is_empty());
+    }
+
+    found
+}
+
+fn fish_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "fish"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Value of the last `NAME=value` assignment in a shell script
+fn assignment(contents: &str, name: &str) -> Option<String> {
+    contents
+        .lines()
+        .rev()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            line.strip_prefix(name)?.strip_prefix('=')
+        })
+        .map(|value| match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or("").to_string(),
+            _ => value.split_whitespace().next().unwrap_or("").to_string(),
+        })
+        .find(|value| !value.is_empty())
+}
@@ -0,0 +1,9 @@
+// Shell module - Inspection of the user's shells and their configuration
+
+pub mod frameworks;
+pub mod prompt;
+pub mod startup;
+
+pub use frameworks::ShellFrameworks;
+pub use prompt::{PromptBootstrapOptions, PromptBootstrapReport};
+pub use startup::StartupProfile;
@@ -0,0 +1,225 @@
+// Prompt bootstrap
+// Sets up a minimal, fast starship prompt whose palette matches the active color scheme.
+// Every file that is changed is first copied to a timestamped backup next to it.
+
+use crate::exec;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Comment marking lines added to shell startup files
+const ADDED_MARKER: &str = "# Added by Xterminal prompt setup";
+
+/// Scheme colors used by the prompt; other color scheme fields are ignored
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptColors {
+    pub foreground: String,
+    pub red: String,
+    pub green: String,
+    pub yellow: String,
+    pub blue: String,
+    pub magenta: String,
+    pub cyan: String,
+    pub bright_black: String,
+}
+
+/// What to set up
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptBootstrapOptions {
+    pub colors: PromptColors,
+    /// Color scheme name, noted in the generated file
+    pub scheme_name: Option<String>,
+    /// Shells whose startup file gets the starship init line: "zsh", "bash", "fish"
+    #[serde(default)]
+    pub shells: Vec<String>,
+}
+
+/// What was written
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptBootstrapReport {
+    pub config_path: String,
+    /// Files changed, including the config
+    pub modified: Vec<String>,
+    /// Backups of files that existed before
+    pub backups: Vec<String>,
+    /// Whether the starship binary was found; the init lines are skipped by the shell until it is
+    pub starship_installed: bool,
+}
+
+/// Write the starship config and hook it into the requested shells
+pub fn bootstrap(options: &PromptBootstrapOptions) -> Result<PromptBootstrapReport, String> {
+    let colors = &options.colors;
+    for color in [
+        &colors.foreground,
+        &colors.red,
+        &colors.green,
+        &colors.yellow,
+        &colors.blue,
+        &colors.magenta,
+        &colors.cyan,
+        &colors.bright_black,
+    ] {
+        validate_color(color)?;
+    }
+
+    let home = dirs::home_dir().ok_or_else(|| "Failed to find home directory".to_string())?;
+    let config_path = std::env::var_os("STARSHIP_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| dirs::config_dir().unwrap_or_else(|| home.join(".config")).join("starship.toml"));
+
+    let mut report = PromptBootstrapReport {
+        config_path: config_path.display().to_string(),
+        modified: Vec::new(),
+        backups: Vec::new(),
+        starship_installed: exec::find_program("starship").is_some(),
+    };
+
+    let config = render_config(colors, options.scheme_name.as_deref());
+    write_with_backup(&config_path, &config, &mut report)?;
+
+    for shell in &options.shells {
+        let (rc, line) = init_line(shell, &home)?;
+        let existing = fs::read_to_string(&rc).unwrap_or_default();
+        if existing.contains(&format!("starship init {}", shell)) {
+            continue;
+        }
+
+        let mut contents = existing;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&format!("\n{}\n{}\n", ADDED_MARKER, line));
+        write_with_backup(&rc, &contents, &mut report)?;
+    }
+
+    log::info!("Prompt set up in {:?}", report.modified);
+    Ok(report)
+}
+
+/// Startup file and guarded init line for a shell
+fn init_line(shell: &str, home: &Path) -> Result<(PathBuf, &'static str), String> {
+    match shell {
+        "zsh" => {
+            let zdotdir = std::env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or_else(|| home.to_path_buf());
+            Ok((
+                zdotdir.join(".zshrc"),
+                r#"command -v starship >/dev/null 2>&1 && eval "$(starship init zsh)""#,
+            ))
+        }
+        "bash" => Ok((
+            home.join(".bashrc"),
+            r#"command -v starship >/dev/null 2>&1 && eval "$(starship init bash)""#,
+        )),
+        "fish" => {
+            let config = dirs::config_dir().unwrap_or_else(|| home.join(".config"));
+            Ok((
+                config.join("fish/config.fish"),
+                "type -q starship; and starship init fish | source",
+            ))
+        }
+        _ => Err(format!("Unsupported shell for prompt setup: {}", shell)),
+    }
+}
+
+fn render_config(colors: &PromptColors, scheme_name: Option<&str>) -> String {
+    // Keep the name on its comment line
+    let scheme = scheme_name
+        .map(|name| name.replace(['\r', '\n'], " "))
+        .unwrap_or_else(|| "current".to_string());
+
+    format!(
+        r#"# Generated by Xterminal from the {scheme} color scheme
+"$schema" = "https://starship.rs/config-schema.json"
+
+add_newline = false
+command_timeout = 200
+scan_timeout = 10
+format = "$username$hostname$directory$git_branch$git_status$cmd_duration$line_break$character"
+palette = "xterminal"
+
+[palettes.xterminal]
+foreground = "{foreground}"
+red = "{red}"
+green = "{green}"
+yellow = "{yellow}"
+blue = "{blue}"
+magenta = "{magenta}"
+cyan = "{cyan}"
+muted = "{muted}"
+
+[username]
+style_user = "cyan"
+style_root = "bold red"
+format = "[$user]($style)@"
+
+[hostname]
+ssh_only = true
+style = "cyan"
+format = "[$hostname]($style) "
+
+[directory]
+style = "bold blue"
+truncation_length = 3
+
+[git_branch]
+style = "magenta"
+format = "[$branch]($style) "
+
+[git_status]
+style = "yellow"
+
+[cmd_duration]
+min_time = 2000
+style = "muted"
+
+[character]
+success_symbol = "[❯](green)"
+error_symbol = "[❯](red)"
+"#,
+        scheme = scheme,
+        foreground = colors.foreground,
+        red = colors.red,
+        green = colors.green,
+        yellow = colors.yellow,
+        blue = colors.blue,
+        magenta = colors.magenta,
+        cyan = colors.cyan,
+        muted = colors.bright_black,
+    )
+}
+
+/// Write a file, keeping a timestamped copy of what was there before
+fn write_with_backup(path: &Path, contents: &str, report: &mut PromptBootstrapReport) -> Result<(), String> {
+    if path.exists() {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".xterminal-backup-{}", stamp));
+        let backup = PathBuf::from(backup);
+        fs::copy(path, &backup).map_err(|e| format!("Failed to back up {:?}: {}", path, e))?;
+        report.backups.push(backup.display().to_string());
+    } else if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+
+    fs::write(path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    report.modified.push(path.display().to_string());
+    Ok(())
+}
+
+/// Colors end up inside TOML strings, so only plain hex colors are accepted
+fn validate_color(color: &str) -> Result<(), String> {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    if matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(format!("Invalid color: {}", color))
+    }
+}
+
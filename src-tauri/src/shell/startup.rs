@@ -0,0 +1,136 @@
+// Shell startup profiling
+// Measures how long an interactive shell takes to start and where the time goes
+
+use crate::exec::{self, ExecOptions};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Number of timed runs used for the total startup figure
+const TIMED_RUNS: usize = 3;
+
+/// Timeout for a single shell startup
+const RUN_TIMEOUT_MS: u64 = 30_000;
+
+/// Maximum number of breakdown entries returned
+const MAX_SEGMENTS: usize = 20;
+
+/// Time spent in a single startup file
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupSegment {
+    pub source: String,
+    pub duration_ms: f64,
+    pub lines: usize,
+}
+
+/// Startup timing report for a shell
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProfile {
+    pub shell: String,
+    pub runs_ms: Vec<u64>,
+    pub average_ms: u64,
+    /// Per-file breakdown from an xtrace run (bash and zsh only)
+    pub breakdown: Vec<StartupSegment>,
+}
+
+/// Profile the startup of an interactive shell
+pub async fn profile(shell: &str) -> Result<StartupProfile, String> {
+    let mut runs_ms = Vec::with_capacity(TIMED_RUNS);
+
+    for _ in 0..TIMED_RUNS {
+        let output = exec::run(&ExecOptions {
+            program: shell.to_string(),
+            args: vec!["-i".into(), "-c".into(), "exit".into()],
+            timeout_ms: Some(RUN_TIMEOUT_MS),
+            ..Default::default()
+        })
+        .await?;
+
+        if output.timed_out {
+            return Err(format!("{} did not finish starting within {}ms", shell, RUN_TIMEOUT_MS));
+        }
+        runs_ms.push(output.duration_ms);
+    }
+
+    let average_ms = runs_ms.iter().sum::<u64>() / runs_ms.len() as u64;
+    let breakdown = match trace_options(shell) {
+        Some(options) => {
+            let output = exec::run(&options).await?;
+            parse_trace(&output.stderr)
+        }
+        None => Vec::new(),
+    };
+
+    Ok(StartupProfile {
+        shell: shell.to_string(),
+        runs_ms,
+        average_ms,
+        breakdown,
+    })
+}
+
+/// Build an xtrace run whose PS4 prefixes every line with a timestamp and source location
+fn trace_options(shell: &str) -> Option<ExecOptions> {
+    let name = Path::new(shell).file_name()?.to_str()?;
+    let ps4 = match name {
+        "zsh" => "+%D{%s.%6.} %x:%I> ",
+        "bash" => "+${EPOCHREALTIME} ${BASH_SOURCE}:${LINENO}> ",
+        _ => return None,
+    };
+
+    Some(ExecOptions {
+        program: shell.to_string(),
+        args: vec!["-i".into(), "-x".into(), "-c".into(), "exit".into()],
+        env: Some(HashMap::from([("PS4".to_string(), ps4.to_string())])),
+        timeout_ms: Some(RUN_TIMEOUT_MS),
+        ..Default::default()
+    })
+}
+
+/// Attribute the time between consecutive trace lines to the earlier line's source file
+fn parse_trace(stderr: &str) -> Vec<StartupSegment> {
+    let mut previous: Option<(f64, String)> = None;
+    let mut totals: HashMap<String, (f64, usize)> = HashMap::new();
+
+    for line in stderr.lines() {
+        let Some((timestamp, source)) = parse_trace_line(line) else {
+            continue;
+        };
+
+        if let Some((prev_time, prev_source)) = previous.take() {
+            let entry = totals.entry(prev_source).or_insert((0.0, 0));
+            entry.0 += (timestamp - prev_time).max(0.0) * 1000.0;
+            entry.1 += 1;
+        }
+        previous = Some((timestamp, source));
+    }
+
+    let mut segments: Vec<StartupSegment> = totals
+        .into_iter()
+        .map(|(source, (duration_ms, lines))| StartupSegment {
+            source,
+            duration_ms,
+            lines,
+        })
+        .collect();
+
+    segments.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+    segments.truncate(MAX_SEGMENTS);
+    segments
+}
+
+/// Parse `+<timestamp> <source>:<line>> ...` into its timestamp and source
+fn parse_trace_line(line: &str) -> Option<(f64, String)> {
+    let rest = line.trim_start_matches('+');
+    let (timestamp, rest) = rest.split_once(' ')?;
+    let timestamp = timestamp.parse::<f64>().ok()?;
+    let (location, _) = rest.split_once("> ").unwrap_or((rest, ""));
+    let source = location
+        .rsplit_once(':')
+        .map(|(source, _)| source)
+        .unwrap_or(location);
+
+    Some((timestamp, source.to_string()))
+}
@@ -0,0 +1,409 @@
+// SSH sessions
+// Connections run the system ssh client on a PTY, so keys, agents, known_hosts, and
+// ~/.ssh/config behave exactly as in a shell. The hosts named in the config are listed for the
+// new tab dropdown. Once connected, a session can look up the remote system over a second,
+// non-interactive connection, so its tab shows where it actually is.
+
+use crate::exec::{self, ExecOptions};
+use crate::glob;
+use crate::pty::{PtyManager, SpawnOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// ssh stops following Include directives this deep
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Seconds after spawning at which the remote system is probed, until a probe gets through;
+/// authentication in the interactive session usually takes a moment
+const PROBE_SCHEDULE: &[u64] = &[2, 5, 15, 30, 60];
+
+/// Longest a probe may take, connecting included
+const PROBE_TIMEOUT_MS: u64 = 10_000;
+
+/// Prints `key=value` lines about the remote system; each lookup is optional, so a minimal or
+/// non-Linux system answers what it can
+const PROBE_SCRIPT: &str = r#"
+echo "hostname=$(uname -n)"
+echo "kernel=$(uname -sr)"
+echo "arch=$(uname -m)"
+if [ -r /etc/os-release ]; then
+    . /etc/os-release
+    echo "os=${PRETTY_NAME:-$NAME}"
+elif command -v sw_vers >/dev/null 2>&1; then
+    echo "os=$(sw_vers -productName) $(sw_vers -productVersion)"
+fi
+if [ -r /proc/uptime ]; then
+    echo "uptime=$(cut -d' ' -f1 /proc/uptime)"
+fi
+if [ -r /proc/loadavg ]; then
+    echo "load=$(cut -d' ' -f1-3 /proc/loadavg)"
+else
+    echo "load=$(sysctl -n vm.loadavg 2>/dev/null | tr -d '{}')"
+fi
+df -Pk / 2>/dev/null | awk 'NR == 2 { print "disk=" $2 " " $3 " " $4 }'
+"#;
+
+/// What to connect to
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTarget {
+    /// Host name, address, or a `Host` alias from ~/.ssh/config
+    pub host: String,
+    /// Overrides the user from the config
+    pub user: Option<String>,
+    /// Overrides the port from the config
+    pub port: Option<u16>,
+    /// Private key to authenticate with, instead of the agent's and the config's keys
+    pub identity_file: Option<PathBuf>,
+    /// Look up the remote system once connected and attach it to the session info
+    #[serde(default)]
+    pub probe: bool,
+}
+
+/// What a probe found out about the system a session is connected to
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteHostInfo {
+    pub hostname: String,
+    /// Distribution or product name, e.g. "Ubuntu 24.04.1 LTS"
+    pub os: Option<String>,
+    /// Kernel name and release, e.g. "Linux 6.8.0-45-generic"
+    pub kernel: Option<String>,
+    pub arch: Option<String>,
+    pub uptime_secs: Option<u64>,
+    /// Load average over 1, 5, and 15 minutes
+    pub load: Option<[f64; 3]>,
+    /// Space on the root filesystem
+    pub disk: Option<DiskUsage>,
+    /// Unix time in milliseconds
+    pub probed_at: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// A host alias defined in ~/.ssh/config
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SshHost {
+    /// Name to connect with, as written after `Host`
+    pub alias: String,
+    /// Real host name, when the alias is not one
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+}
+
+impl SshTarget {
+    /// Reject values ssh would read as options
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_host = !self.host.is_empty()
+            && !self.host.starts_with('-')
+            && self.host.chars().all(|c| c.is_ascii_alphanumeric() || "._-:[]%".contains(c));
+        if !valid_host {
+            return Err(format!("Invalid ssh host: {}", self.host));
+        }
+        if let Some(user) = &self.user {
+            let valid_user = !user.is_empty()
+                && !user.starts_with('-')
+                && user.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+            if !valid_user {
+                return Err(format!("Invalid ssh user: {}", user));
+            }
+        }
+        if let Some(path) = &self.identity_file {
+            if !expand_home(path).is_file() {
+                return Err(format!("Identity file not found: {}", path.display()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawn options running ssh for this target
+    pub fn spawn_options(&self, cols: u16, rows: u16) -> Result<SpawnOptions, String> {
+        self.validate()?;
+        Ok(SpawnOptions {
+            shell: Some("ssh".to_string()),
+            args: Some(self.args()),
+            cols,
+            rows,
+            ..Default::default()
+        })
+    }
+
+    /// Options running the probe script on this target without a terminal
+    ///
+    /// The probe never prompts, and never becomes the master of a shared connection, so with
+    /// ControlMaster set up it rides on the interactive session's connection.
+    fn probe_options(&self) -> ExecOptions {
+        let mut args: Vec<String> = ["-T", "-o", "BatchMode=yes", "-o", "ControlMaster=no", "-o", "ConnectTimeout=5"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        args.extend(self.args());
+        args.extend(["sh".to_string(), "-s".to_string()]);
+        ExecOptions {
+            program: "ssh".to_string(),
+            args,
+            timeout_ms: Some(PROBE_TIMEOUT_MS),
+            stdin: Some(PROBE_SCRIPT.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Connection options followed by the host
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(path) = &self.identity_file {
+            args.extend(["-i".to_string(), expand_home(path).to_string_lossy().to_string()]);
+            // Offering every agent key first can exhaust the server's MaxAuthTries
+            args.extend(["-o".to_string(), "IdentitiesOnly=yes".to_string()]);
+        }
+        if let Some(user) = &self.user {
+            args.extend(["-l".to_string(), user.clone()]);
+        }
+        args.push("--".to_string());
+        args.push(self.host.trim_start_matches('[').trim_end_matches(']').to_string());
+        args
+    }
+}
+
+/// Probe the system an SSH session connected to, attaching what was found to the session
+///
+/// Probes run on their own connection and cannot answer prompts, so they are retried while the
+/// user authenticates; they stop when one succeeds, the session closes, or the schedule ends.
+pub async fn probe(app: AppHandle, target: SshTarget, session_id: String) {
+    let options = target.probe_options();
+    let mut elapsed = 0;
+    for &at in PROBE_SCHEDULE {
+        tokio::time::sleep(Duration::from_secs(at - elapsed)).await;
+        elapsed = at;
+        if app.state::<PtyManager>().info(&session_id).is_err() {
+            return;
+        }
+
+        let output = match exec::run(&options).await {
+            Ok(output) => output,
+            Err(e) => {
+                log::warn!("Cannot probe {}: {}", target.host, e);
+                return;
+            }
+        };
+        let info = output.success().then(|| parse_probe(&output.stdout)).flatten();
+        if let Some(info) = info {
+            if let Err(e) = app.state::<PtyManager>().set_remote_host(&session_id, info) {
+                log::debug!("Probe of {} finished after its session: {}", target.host, e);
+            }
+            return;
+        }
+        log::debug!("Probe of {} failed: {}", target.host, output.stderr.trim());
+    }
+    log::info!("Gave up probing {}", target.host);
+}
+
+/// Read the probe script's output; login scripts may print before it, so only known keys count
+fn parse_probe(stdout: &str) -> Option<RemoteHostInfo> {
+    let mut info = RemoteHostInfo {
+        probed_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64),
+        ..Default::default()
+    };
+    let mut hostname = None;
+    for line in stdout.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key {
+            "hostname" => hostname = Some(value.to_string()),
+            "os" => info.os = Some(value.to_string()),
+            "kernel" => info.kernel = Some(value.to_string()),
+            "arch" => info.arch = Some(value.to_string()),
+            "uptime" => info.uptime_secs = value.parse::<f64>().ok().map(|secs| secs as u64),
+            "load" => {
+                let loads: Vec<f64> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+                info.load = <[f64; 3]>::try_from(loads).ok();
+            }
+            "disk" => {
+                // 1024-byte blocks from df -P
+                let kb: Vec<u64> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+                if let [total, used, available] = kb[..] {
+                    info.disk = Some(DiskUsage {
+                        total_bytes: total * 1024,
+                        used_bytes: used * 1024,
+                        available_bytes: available * 1024,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    info.hostname = hostname?;
+    Some(info)
+}
+
+/// Hosts defined in the user's ~/.ssh/config, in the order they appear
+///
+/// Wildcard patterns such as `Host *` and `Match` blocks configure other hosts rather than
+/// naming one, so they are not listed.
+pub fn config_hosts() -> Vec<SshHost> {
+    let Some(path) = dirs::home_dir().map(|home| home.join(".ssh").join("config")) else {
+        return Vec::new();
+    };
+    let mut parser = ConfigParser::default();
+    parser.read(&path, 0);
+    parser.hosts
+}
+
+/// Collects hosts across a config file and the files it includes
+#[derive(Default)]
+struct ConfigParser {
+    hosts: Vec<SshHost>,
+    /// Indices in `hosts` the current `Host` line applies to
+    current: Vec<usize>,
+}
+
+impl ConfigParser {
+    fn read(&mut self, path: &Path, depth: usize) {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return;
+        };
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // Keywords are separated from their arguments by whitespace or one `=`
+            let (keyword, rest) = line
+                .split_once(|c: char| c.is_whitespace() || c == '=')
+                .unwrap_or((line, ""));
+            let rest = rest.trim_start().strip_prefix('=').unwrap_or(rest).trim();
+            let value = first_argument(rest);
+
+            match keyword.to_ascii_lowercase().as_str() {
+                "host" => self.start_block(rest),
+                "match" => self.current.clear(),
+                "include" if depth < MAX_INCLUDE_DEPTH => {
+                    for pattern in rest.split_whitespace() {
+                        for included in include_paths(pattern) {
+                            self.read(&included, depth + 1);
+                        }
+                    }
+                }
+                "hostname" => self.set(|host| &mut host.host_name, value.map(str::to_string)),
+                "user" => self.set(|host| &mut host.user, value.map(str::to_string)),
+                "port" => self.set(|host| &mut host.port, value.and_then(|v| v.parse().ok())),
+                "identityfile" => self.set(|host| &mut host.identity_file, value.map(|v| expand_home(Path::new(v)))),
+                _ => {}
+            }
+        }
+    }
+
+    /// Begin a `Host` block, registering its literal aliases
+    fn start_block(&mut self, patterns: &str) {
+        self.current.clear();
+        for alias in patterns.split_whitespace().map(|p| p.trim_matches('"')) {
+            if alias.is_empty() || alias.contains(['*', '?', '!']) {
+                continue;
+            }
+            let index = match self.hosts.iter().position(|host| host.alias == alias) {
+                Some(index) => index,
+                None => {
+                    self.hosts.push(SshHost {
+                        alias: alias.to_string(),
+                        ..SshHost::default()
+                    });
+                    self.hosts.len() - 1
+                }
+            };
+            self.current.push(index);
+        }
+    }
+
+    /// Apply a setting to the hosts of the current block; as in ssh, the first value wins
+    fn set<T: Clone>(&mut self, field: impl Fn(&mut SshHost) -> &mut Option<T>, value: Option<T>) {
+        let Some(value) = value else {
+            return;
+        };
+        for &index in &self.current {
+            field(&mut self.hosts[index]).get_or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// First argument of a directive, without surrounding quotes
+fn first_argument(rest: &str) -> Option<&str> {
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split_whitespace().next()?,
+    };
+    Some(value).filter(|v| !v.is_empty())
+}
+
+/// Files matched by an Include pattern; relative patterns are relative to ~/.ssh
+///
+/// Wildcards are supported in the file name, which covers the usual `Include config.d/*`.
+fn include_paths(pattern: &str) -> Vec<PathBuf> {
+    let mut path = expand_home(Path::new(pattern));
+    if path.is_relative() {
+        let Some(home) = dirs::home_dir() else {
+            return Vec::new();
+        };
+        path = home.join(".ssh").join(path);
+    }
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Vec::new();
+    };
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let mut matches: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.file_name().is_some_and(|n| wildcard_match(&name, &n.to_string_lossy())))
+                .filter(|p| p.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    // ssh reads glob matches in sorted order
+    matches.sort();
+    matches
+}
+
+/// Like shell globs, wildcards do not match a leading `.`
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    glob::matches(pattern, name)
+}
+
+/// Expand a leading `~/` to the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,199 @@
+// Built-in status segments
+// Git branch, Python environment, and input latency of each session, and the system's load.
+// A new segment is an entry in `BUILTINS` and a function computing its value.
+
+use super::{SegmentInfo, SegmentLevel, SegmentScope, SegmentValue};
+use crate::profiles;
+use crate::pty::{procfs, PtyManager};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Echo this slow is noticeable while typing
+const SLOW_ECHO_MS: f64 = 100.0;
+
+/// Memory use from which the system segment warns, in percent
+const MEMORY_WARNING_PERCENT: u64 = 90;
+
+/// Directory names virtual environments are usually created with
+const VENV_DIRS: &[&str] = &[".venv", "venv"];
+
+/// A segment refreshed by the backend
+pub struct Builtin {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub scope: SegmentScope,
+    pub interval: Duration,
+    /// Compute the value for a session, or for the app when the session is unset; None hides
+    /// the segment
+    pub refresh: fn(&PtyManager, Option<&str>) -> Option<SegmentValue>,
+}
+
+impl Builtin {
+    pub fn info(&self) -> SegmentInfo {
+        SegmentInfo {
+            id: self.id.to_string(),
+            title: self.title.to_string(),
+            scope: self.scope,
+            interval_ms: Some(self.interval.as_millis() as u64),
+            plugin: None,
+        }
+    }
+}
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin {
+        id: "git",
+        title: "Git Branch",
+        scope: SegmentScope::Session,
+        interval: Duration::from_secs(3),
+        refresh: git,
+    },
+    Builtin {
+        id: "python",
+        title: "Python Environment",
+        scope: SegmentScope::Session,
+        interval: Duration::from_secs(5),
+        refresh: python,
+    },
+    Builtin {
+        id: "latency",
+        title: "Input Latency",
+        scope: SegmentScope::Session,
+        interval: Duration::from_secs(2),
+        refresh: latency,
+    },
+    Builtin {
+        id: "system",
+        title: "System Load",
+        scope: SegmentScope::Global,
+        interval: Duration::from_secs(2),
+        refresh: system,
+    },
+];
+
+fn value(text: String, tooltip: String, level: SegmentLevel) -> Option<SegmentValue> {
+    Some(SegmentValue {
+        text,
+        tooltip: Some(tooltip),
+        level,
+    })
+}
+
+/// Branch checked out in the repository the session is in, or the commit when detached
+fn git(manager: &PtyManager, session_id: Option<&str>) -> Option<SegmentValue> {
+    let cwd = PathBuf::from(manager.cwd(session_id?).ok()?);
+    let (root, git_dir) = profiles::git_dir(&cwd)?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let text = match head.strip_prefix("ref:") {
+        Some(reference) => {
+            let reference = reference.trim();
+            reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string()
+        }
+        None => head.get(..7)?.to_string(),
+    };
+    value(text, root.display().to_string(), SegmentLevel::Normal)
+}
+
+/// Virtual environment or conda environment of the session
+///
+/// A shell that sourced an activate script keeps the environment it started with, so besides
+/// what the foreground program was started in, the nearest project environment is shown.
+fn python(manager: &PtyManager, session_id: Option<&str>) -> Option<SegmentValue> {
+    let session_id = session_id?;
+    if let Ok(process) = manager.foreground_process(session_id) {
+        if let Some(venv) = procfs::read_environ_var(process.pid, "VIRTUAL_ENV") {
+            return venv_value(Path::new(&venv), "Active virtual environment");
+        }
+        if let Some(name) = procfs::read_environ_var(process.pid, "CONDA_DEFAULT_ENV") {
+            let prefix = procfs::read_environ_var(process.pid, "CONDA_PREFIX").unwrap_or_else(|| name.clone());
+            return value(name, format!("Active conda environment {}", prefix), SegmentLevel::Normal);
+        }
+    }
+
+    let cwd = PathBuf::from(manager.cwd(session_id).ok()?);
+    let home = dirs::home_dir();
+    let venv = cwd
+        .ancestors()
+        .take_while(|dir| Some(*dir) != home.as_deref())
+        .flat_map(|dir| VENV_DIRS.iter().map(move |name| dir.join(name)))
+        .find(|venv| venv.join("pyvenv.cfg").is_file())?;
+    venv_value(&venv, "Project virtual environment")
+}
+
+/// Name and Python version of a virtual environment, from its `pyvenv.cfg`
+fn venv_value(venv: &Path, description: &str) -> Option<SegmentValue> {
+    let config = fs::read_to_string(venv.join("pyvenv.cfg")).unwrap_or_default();
+    let setting = |key: &str| {
+        config.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        })
+    };
+    let dir_name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().to_string());
+    // `.venv` says nothing; the project it belongs to does
+    let name = setting("prompt")
+        .or_else(|| match dir_name(venv) {
+            Some(name) if VENV_DIRS.contains(&name.as_str()) => venv.parent().and_then(dir_name),
+            name => name,
+        })
+        .unwrap_or_default();
+    let text = match setting("version").or_else(|| setting("version_info")) {
+        Some(version) => format!("{} {}", name, version),
+        None => name,
+    };
+    value(text, format!("{} {}", description, venv.display()), SegmentLevel::Normal)
+}
+
+/// Median keystroke-to-echo time, while the session measures it
+fn latency(manager: &PtyManager, session_id: Option<&str>) -> Option<SegmentValue> {
+    let report = manager.latency(session_id?).ok()?;
+    let p50 = report.p50_ms?;
+    let ms = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.0} ms", v));
+    let tooltip = format!(
+        "Echo latency over {} keystrokes: p90 {}, p99 {}, max {}",
+        report.samples,
+        ms(report.p90_ms),
+        ms(report.p99_ms),
+        ms(report.max_ms)
+    );
+    let level = if p50 >= SLOW_ECHO_MS {
+        SegmentLevel::Warning
+    } else {
+        SegmentLevel::Normal
+    };
+    value(ms(Some(p50)), tooltip, level)
+}
+
+/// Load average and memory in use
+fn system(_: &PtyManager, _: Option<&str>) -> Option<SegmentValue> {
+    let loadavg = fs::read_to_string("/proc/loadavg").ok()?;
+    let loads: Vec<&str> = loadavg.split_whitespace().take(3).collect();
+    let load: f64 = loads.first()?.parse().ok()?;
+
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let kb = |key: &str| -> Option<u64> {
+        let line = meminfo.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))?;
+        line.split_whitespace().next()?.parse().ok()
+    };
+    let total = kb("MemTotal").filter(|total| *total > 0)?;
+    let used = total.saturating_sub(kb("MemAvailable")?);
+    let percent = used * 100 / total;
+
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let level = if percent >= MEMORY_WARNING_PERCENT || load > cpus as f64 {
+        SegmentLevel::Warning
+    } else {
+        SegmentLevel::Normal
+    };
+    let gib = |kb: u64| kb as f64 / (1024.0 * 1024.0);
+    let tooltip = format!(
+        "Load average {} on {} CPUs, {:.1} of {:.1} GiB memory in use",
+        loads.join(" "),
+        cpus,
+        gib(used),
+        gib(total)
+    );
+    value(format!("load {:.2}  mem {}%", load, percent), tooltip, level)
+}
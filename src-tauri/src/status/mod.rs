@@ -0,0 +1,284 @@
+// Status module - status bar segments
+// Everything the status bar shows is a segment with an ID, a refresh interval, and either one
+// value for the whole app or one per session. Built-in segments are refreshed here on their
+// intervals; plugins register their own and push values. Changed values of both go out on the
+// single `status://update` stream, so the frontend renders any segment the same way.
+
+pub mod builtin;
+
+use crate::commands::settings;
+use crate::pty::PtyManager;
+use crate::sync::MutexExt;
+use builtin::{Builtin, BUILTINS};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often segments are checked for being due
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether a segment has one value for the app or one per session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SegmentScope {
+    Global,
+    Session,
+}
+
+/// How prominently a value is shown
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SegmentLevel {
+    #[default]
+    Normal,
+    Warning,
+    Error,
+}
+
+/// What a segment shows
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentValue {
+    pub text: String,
+    #[serde(default)]
+    pub tooltip: Option<String>,
+    #[serde(default)]
+    pub level: SegmentLevel,
+}
+
+/// A segment the status bar can show
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentInfo {
+    pub id: String,
+    pub title: String,
+    pub scope: SegmentScope,
+    /// How often the backend refreshes the value; unset for plugin segments, which push theirs
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// Plugin that registered the segment; unset for built-in segments
+    #[serde(default)]
+    pub plugin: Option<String>,
+}
+
+/// A segment's value changed, published on `status://update`
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusUpdate {
+    pub segment: String,
+    /// Session the value belongs to; unset for global segments
+    pub session_id: Option<String>,
+    /// None hides the segment
+    pub value: Option<SegmentValue>,
+}
+
+/// The `statusBar` section of settings
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatusBarSettings {
+    /// Built-in segments to refresh; plugin segments show once registered
+    pub segments: Vec<String>,
+}
+
+impl Default for StatusBarSettings {
+    fn default() -> Self {
+        Self {
+            segments: BUILTINS.iter().map(|builtin| builtin.id.to_string()).collect(),
+        }
+    }
+}
+
+/// Value of a segment for one session, or for the app when the session is unset
+type ValueKey = (String, Option<String>);
+
+/// Registered plugin segments and the last published values, managed by Tauri
+#[derive(Default)]
+pub struct StatusSegments {
+    plugin_segments: Mutex<Vec<SegmentInfo>>,
+    values: Mutex<HashMap<ValueKey, SegmentValue>>,
+}
+
+impl StatusSegments {
+    /// Built-in segments followed by plugin segments
+    pub fn list(&self) -> Vec<SegmentInfo> {
+        let plugin_segments = self.plugin_segments.locked().clone();
+        BUILTINS.iter().map(Builtin::info).chain(plugin_segments).collect()
+    }
+
+    /// Every value currently shown, for a frontend that starts listening late
+    pub fn values(&self) -> Vec<StatusUpdate> {
+        let values = self.values.locked();
+        values
+            .iter()
+            .map(|((segment, session_id), value)| StatusUpdate {
+                segment: segment.clone(),
+                session_id: session_id.clone(),
+                value: Some(value.clone()),
+            })
+            .collect()
+    }
+
+    /// Add a plugin segment, or replace the one with the same ID
+    pub fn register(&self, mut segment: SegmentInfo) -> Result<(), String> {
+        if segment.plugin.is_none() {
+            return Err(format!("Status segment {} does not name its plugin", segment.id));
+        }
+        if segment.id.trim().is_empty() || segment.title.trim().is_empty() {
+            return Err("Status segment ID and title must not be empty".to_string());
+        }
+        if BUILTINS.iter().any(|builtin| builtin.id == segment.id) {
+            return Err(format!("Status segment {} is built in", segment.id));
+        }
+        // Plugins push their values whenever they change
+        segment.interval_ms = None;
+        let mut segments = self.plugin_segments.locked();
+        segments.retain(|existing| existing.id != segment.id);
+        segments.push(segment);
+        Ok(())
+    }
+
+    /// Remove a plugin segment, hiding its values
+    pub fn unregister(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        let mut segments = self.plugin_segments.locked();
+        let before = segments.len();
+        segments.retain(|segment| segment.id != id);
+        if segments.len() == before {
+            return Err(format!("Status segment not found: {}", id));
+        }
+        drop(segments);
+        self.clear(app, id);
+        Ok(())
+    }
+
+    /// Set or hide the value of a plugin segment
+    pub fn set(
+        &self,
+        app: &AppHandle,
+        id: &str,
+        session_id: Option<String>,
+        value: Option<SegmentValue>,
+    ) -> Result<(), String> {
+        let scope = self
+            .plugin_segments
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|segment| segment.id == id)
+            .map(|segment| segment.scope)
+            .ok_or_else(|| format!("Status segment not found: {}", id))?;
+        match (scope, &session_id) {
+            (SegmentScope::Session, None) => return Err(format!("Status segment {} needs a session", id)),
+            (SegmentScope::Global, Some(_)) => return Err(format!("Status segment {} is global", id)),
+            _ => {}
+        }
+        self.update(app, id, session_id, value);
+        Ok(())
+    }
+
+    /// Store a value, publishing it if it changed
+    fn update(&self, app: &AppHandle, id: &str, session_id: Option<String>, value: Option<SegmentValue>) {
+        let key = (id.to_string(), session_id);
+        let mut values = self.values.locked();
+        let changed = match &value {
+            Some(value) => values.insert(key.clone(), value.clone()).as_ref() != Some(value),
+            None => values.remove(&key).is_some(),
+        };
+        drop(values);
+        if changed {
+            publish(app, key, value);
+        }
+    }
+
+    /// Hide every value of a segment
+    fn clear(&self, app: &AppHandle, id: &str) {
+        let mut values = self.values.locked();
+        let keys: Vec<ValueKey> = values.keys().filter(|(segment, _)| segment == id).cloned().collect();
+        for key in &keys {
+            values.remove(key);
+        }
+        drop(values);
+        for key in keys {
+            publish(app, key, None);
+        }
+    }
+
+    /// Forget the values of sessions that are gone; the frontend drops them with the session
+    fn retain_sessions(&self, sessions: &HashSet<String>) {
+        self.values
+            .lock()
+            .unwrap()
+            .retain(|(_, session_id), _| session_id.as_ref().map_or(true, |id| sessions.contains(id)));
+    }
+}
+
+fn publish(app: &AppHandle, (segment, session_id): ValueKey, value: Option<SegmentValue>) {
+    let _ = app.emit(
+        "status://update",
+        StatusUpdate {
+            segment,
+            session_id,
+            value,
+        },
+    );
+}
+
+/// Refresh the enabled built-in segments on their intervals for the lifetime of the app
+pub fn start(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        let mut last_refresh: HashMap<&'static str, Instant> = HashMap::new();
+
+        loop {
+            interval.tick().await;
+            let now = Instant::now();
+            let due: Vec<&'static Builtin> = BUILTINS
+                .iter()
+                .filter(|builtin| {
+                    last_refresh
+                        .get(builtin.id)
+                        .map_or(true, |last| now.duration_since(*last) >= builtin.interval)
+                })
+                .collect();
+            if due.is_empty() {
+                continue;
+            }
+            for builtin in &due {
+                last_refresh.insert(builtin.id, now);
+            }
+
+            let app = app_handle.clone();
+            // Segments read files and /proc, so they run off the async workers
+            let result = tokio::task::spawn_blocking(move || refresh(&app, &due)).await;
+            if let Err(e) = result {
+                log::warn!("Failed to refresh status segments: {}", e);
+            }
+        }
+    });
+}
+
+/// Recompute the due segments for the app or every session
+fn refresh(app: &AppHandle, due: &[&'static Builtin]) {
+    let enabled = settings::read_section::<StatusBarSettings>("statusBar").segments;
+    let manager = app.state::<PtyManager>();
+    let segments = app.state::<StatusSegments>();
+    let sessions: HashSet<String> = manager.list().into_iter().map(|info| info.id).collect();
+    segments.retain_sessions(&sessions);
+
+    for builtin in due {
+        if !enabled.iter().any(|id| id == builtin.id) {
+            segments.clear(app, builtin.id);
+            continue;
+        }
+        match builtin.scope {
+            SegmentScope::Global => segments.update(app, builtin.id, None, (builtin.refresh)(&manager, None)),
+            SegmentScope::Session => {
+                for session_id in &sessions {
+                    let value = (builtin.refresh)(&manager, Some(session_id));
+                    segments.update(app, builtin.id, Some(session_id.clone()), value);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// Lock helpers
+// A panic while a lock is held poisons it, and unwrapping every later lock would turn one failed
+// session operation into a panic in every tab and command after it. The state behind these locks
+// is updated a field at a time and stays usable, so the guard is taken back instead.
+
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub trait MutexExt<T> {
+    /// Lock, recovering the guard if a panic poisoned the mutex
+    fn locked(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> MutexExt<T> for Mutex<T> {
+    fn locked(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub trait RwLockExt<T> {
+    /// Lock for reading, recovering the guard if a panic poisoned the lock
+    fn read_locked(&self) -> RwLockReadGuard<'_, T>;
+    /// Lock for writing, recovering the guard if a panic poisoned the lock
+    fn write_locked(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_locked(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_locked(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,267 @@
+// Theme previews
+// Renders a fixed sample of terminal output (prompt, listing, diff, palette swatches) in a theme's
+// colors as a self-contained HTML snippet, so the theme gallery can show accurate previews
+// without starting a terminal for every theme.
+
+use serde::{Deserialize, Serialize};
+
+/// xterm.js colors for fields a theme leaves unset
+const DEFAULT_FOREGROUND: &str = "#ffffff";
+const DEFAULT_BACKGROUND: &str = "#000000";
+const DEFAULT_SELECTION: &str = "rgba(255, 255, 255, 0.3)";
+const DEFAULT_PALETTE: [&str; 16] = [
+    "#2e3436", "#cc0000", "#4e9a06", "#c4a000", "#3465a4", "#75507b", "#06989a", "#d3d7cf",
+    "#555753", "#ef2929", "#8ae234", "#fce94f", "#729fcf", "#ad7fa8", "#34e2e2", "#eeeeec",
+];
+
+const PALETTE_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// A color scheme from settings or an xterm.js theme; unset colors use xterm.js defaults
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TerminalTheme {
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    /// `cursorColor` in color schemes
+    #[serde(alias = "cursorColor")]
+    pub cursor: Option<String>,
+    pub cursor_accent: Option<String>,
+    pub selection_background: Option<String>,
+    pub black: Option<String>,
+    pub red: Option<String>,
+    pub green: Option<String>,
+    pub yellow: Option<String>,
+    pub blue: Option<String>,
+    pub magenta: Option<String>,
+    pub cyan: Option<String>,
+    pub white: Option<String>,
+    pub bright_black: Option<String>,
+    pub bright_red: Option<String>,
+    pub bright_green: Option<String>,
+    pub bright_yellow: Option<String>,
+    pub bright_blue: Option<String>,
+    pub bright_magenta: Option<String>,
+    pub bright_cyan: Option<String>,
+    pub bright_white: Option<String>,
+}
+
+/// A rendered preview
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemePreview {
+    /// A `<pre>` element with inline styles only, safe to insert as is
+    pub html: String,
+    /// Size of the sample in cells, for laying out the gallery
+    pub cols: usize,
+    pub rows: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Color {
+    Foreground,
+    Background,
+    Cursor,
+    CursorAccent,
+    Selection,
+    /// Index into the 16-color palette
+    Palette(usize),
+}
+
+/// Part of a sample line drawn in one style
+#[derive(Clone, Copy)]
+struct Span {
+    text: &'static str,
+    fg: Color,
+    bg: Option<Color>,
+    bold: bool,
+}
+
+const fn plain(text: &'static str) -> Span {
+    Span { text, fg: Color::Foreground, bg: None, bold: false }
+}
+
+const fn fg(text: &'static str, index: usize) -> Span {
+    Span { text, fg: Color::Palette(index), bg: None, bold: false }
+}
+
+const fn bold(text: &'static str, index: usize) -> Span {
+    Span { text, fg: Color::Palette(index), bg: None, bold: true }
+}
+
+const PROMPT: &[Span] = &[
+    bold("user@host", 2),
+    plain(" "),
+    bold("~/projects/xterminal", 4),
+    plain(" "),
+    fg("(main)", 5),
+    plain(" $ "),
+];
+
+/// Sample lines after the palette swatches, and whether each starts with the prompt
+const SAMPLE: &[(bool, &[Span])] = &[
+    (true, &[plain("ls")]),
+    (false, &[bold("src", 12), plain("  "), bold("target", 12), plain("  Cargo.toml  README.md  "), bold("build.sh", 10)]),
+    (true, &[plain("git diff")]),
+    (false, &[fg("- let theme = load();", 1)]),
+    (false, &[fg("+ let theme = load_or_default();", 2)]),
+    (false, &[bold("warning", 3), plain(": unused variable "), fg("`preview`", 6)]),
+    (false, &[bold("error", 9), plain(": could not compile; "), fg("1 previous error", 8)]),
+    (
+        false,
+        &[
+            plain("Selected "),
+            Span { text: "text", fg: Color::Foreground, bg: Some(Color::Selection), bold: false },
+            plain(" and dimmed "),
+            fg("comments", 8),
+        ],
+    ),
+];
+
+/// Render the sample in a theme's colors
+pub fn preview(theme: &TerminalTheme) -> Result<ThemePreview, String> {
+    let colors = Colors::resolve(theme)?;
+
+    let mut lines: Vec<Vec<Span>> = Vec::new();
+    for bright in [false, true] {
+        let mut swatches = Vec::new();
+        for (index, name) in PALETTE_NAMES.into_iter().enumerate() {
+            let index = index + if bright { 8 } else { 0 };
+            swatches.push(Span { text: "  ", fg: Color::Foreground, bg: Some(Color::Palette(index)), bold: false });
+            swatches.push(fg(name, index));
+            swatches.push(plain(" "));
+        }
+        lines.push(swatches);
+    }
+    for (prompt, spans) in SAMPLE {
+        let prompt = if *prompt { PROMPT } else { &[] };
+        lines.push(prompt.iter().chain(spans.iter()).copied().collect());
+    }
+    let mut last = PROMPT.to_vec();
+    last.push(Span { text: " ", fg: Color::CursorAccent, bg: Some(Color::Cursor), bold: false });
+    lines.push(last);
+
+    let cols = lines
+        .iter()
+        .map(|line| line.iter().map(|span| span.text.chars().count()).sum::<usize>())
+        .max()
+        .unwrap_or(0);
+
+    let mut html = format!(
+        "<pre style=\"margin:0;padding:8px;font-family:monospace;line-height:1.2;background:{};color:{}\">",
+        colors.get(Color::Background),
+        colors.get(Color::Foreground)
+    );
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            html.push('\n');
+        }
+        for span in line {
+            let mut style = format!("color:{}", colors.get(span.fg));
+            if let Some(bg) = span.bg {
+                style.push_str(&format!(";background:{}", colors.get(bg)));
+            }
+            if span.bold {
+                style.push_str(";font-weight:bold");
+            }
+            html.push_str(&format!("<span style=\"{}\">{}</span>", style, escape_html(span.text)));
+        }
+    }
+    html.push_str("</pre>");
+
+    Ok(ThemePreview { html, cols, rows: lines.len() })
+}
+
+/// A theme's colors with defaults filled in, all checked to be plain CSS colors
+struct Colors {
+    foreground: String,
+    background: String,
+    cursor: String,
+    cursor_accent: String,
+    selection: String,
+    palette: Vec<String>,
+}
+
+impl Colors {
+    fn resolve(theme: &TerminalTheme) -> Result<Self, String> {
+        let pick = |name: &str, value: &Option<String>, default: &str| -> Result<String, String> {
+            match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                Some(value) if is_css_color(value) => Ok(value.to_string()),
+                Some(value) => Err(format!("Invalid {} color: {}", name, value)),
+                None => Ok(default.to_string()),
+            }
+        };
+
+        let palette_values = [
+            &theme.black,
+            &theme.red,
+            &theme.green,
+            &theme.yellow,
+            &theme.blue,
+            &theme.magenta,
+            &theme.cyan,
+            &theme.white,
+            &theme.bright_black,
+            &theme.bright_red,
+            &theme.bright_green,
+            &theme.bright_yellow,
+            &theme.bright_blue,
+            &theme.bright_magenta,
+            &theme.bright_cyan,
+            &theme.bright_white,
+        ];
+        let mut palette = Vec::with_capacity(16);
+        for (index, value) in palette_values.into_iter().enumerate() {
+            let name = if index < 8 {
+                PALETTE_NAMES[index].to_string()
+            } else {
+                format!("bright {}", PALETTE_NAMES[index - 8])
+            };
+            palette.push(pick(&name, value, DEFAULT_PALETTE[index])?);
+        }
+
+        let foreground = pick("foreground", &theme.foreground, DEFAULT_FOREGROUND)?;
+        let background = pick("background", &theme.background, DEFAULT_BACKGROUND)?;
+        Ok(Self {
+            cursor: pick("cursor", &theme.cursor, &foreground)?,
+            cursor_accent: pick("cursor accent", &theme.cursor_accent, &background)?,
+            selection: pick("selection background", &theme.selection_background, DEFAULT_SELECTION)?,
+            foreground,
+            background,
+            palette,
+        })
+    }
+
+    fn get(&self, color: Color) -> &str {
+        match color {
+            Color::Foreground => &self.foreground,
+            Color::Background => &self.background,
+            Color::Cursor => &self.cursor,
+            Color::CursorAccent => &self.cursor_accent,
+            Color::Selection => &self.selection,
+            Color::Palette(index) => &self.palette[index],
+        }
+    }
+}
+
+/// Colors end up in style attributes, so only hex and rgb()/rgba() notations are accepted
+pub fn is_css_color(value: &str) -> bool {
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let Some(args) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return false;
+    };
+    let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+    matches!(parts.len(), 3 | 4)
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '%'))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,289 @@
+// Window geometry
+// Remembers the main window's size, position, and maximized state between runs. Wayland lets
+// clients neither read nor choose their global position, so there only size and maximized state
+// are kept; on X11 the position is kept too, and dropped on restore if it is no longer on any
+// monitor. Sizes are clamped so a restore never produces a zero-size or oversized window.
+// Placement hints kept alongside pin the window to a monitor and workspace for multi-monitor
+// setups, and keep the quake window above others.
+
+use crate::control::MAIN_WINDOW;
+use crate::exec::{self, ExecOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor};
+
+/// Smallest window that is still usable
+const MIN_WIDTH: u32 = 400;
+const MIN_HEIGHT: u32 = 300;
+
+/// How much of the window's top edge must be on a monitor to be reachable, in physical pixels
+const GRAB_HEIGHT: i32 = 32;
+
+/// Times to look for the window before moving it to its workspace, a quarter second apart
+const WORKSPACE_ATTEMPTS: u32 = 8;
+
+/// The display server the app is running under
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayServer {
+    Wayland,
+    X11,
+    /// Not known, e.g. state saved before it was recorded
+    #[default]
+    Unknown,
+}
+
+impl DisplayServer {
+    /// Which display server GTK will use for the app's windows
+    pub fn detect() -> Self {
+        let var = |key: &str| std::env::var(key).ok().filter(|value| !value.is_empty());
+        // The first backend GTK is told to try wins; x11 means XWayland, where positions work
+        match var("GDK_BACKEND").as_deref().and_then(|backends| backends.split(',').next()) {
+            Some("x11") if var("DISPLAY").is_some() => return Self::X11,
+            Some("wayland") if var("WAYLAND_DISPLAY").is_some() => return Self::Wayland,
+            _ => {}
+        }
+        if var("WAYLAND_DISPLAY").is_some() || var("XDG_SESSION_TYPE").as_deref() == Some("wayland") {
+            Self::Wayland
+        } else if var("DISPLAY").is_some() {
+            Self::X11
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// Where the main window goes when it is created, set by the user rather than saved as it moves
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowPlacement {
+    /// Monitor to open on, by the name the display server gives it; X11 only
+    pub monitor: Option<String>,
+    /// Workspace to open on, counting from 0; X11 only, through wmctrl
+    pub workspace: Option<u32>,
+    /// Show on every workspace
+    pub all_workspaces: bool,
+    /// Keep the window above others while it is shown with the quake toggle
+    pub quake_always_on_top: bool,
+}
+
+/// Geometry of the main window in physical pixels
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    /// Outer position; absent under Wayland
+    #[serde(default)]
+    pub x: Option<i32>,
+    #[serde(default)]
+    pub y: Option<i32>,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub is_maximized: bool,
+    /// What the state was saved under, filled in by the backend
+    #[serde(default)]
+    pub display_server: DisplayServer,
+    /// Placement hints; a save without them keeps the saved ones
+    #[serde(default)]
+    pub placement: Option<WindowPlacement>,
+}
+
+/// Read the saved state, adjusted so it can be applied to the current displays
+pub fn load(path: &Path, app: &AppHandle) -> Result<Option<WindowState>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read window state: {}", e))?;
+    let mut state: WindowState = match serde_json::from_str(&contents) {
+        Ok(state) => state,
+        Err(e) => {
+            // Not worth failing startup over; the window opens at its default geometry
+            log::warn!("Ignoring unreadable window state: {}", e);
+            return Ok(None);
+        }
+    };
+
+    let monitors = app.available_monitors().unwrap_or_else(|e| {
+        log::warn!("Failed to list monitors: {}", e);
+        Vec::new()
+    });
+    let current = DisplayServer::detect();
+    // A Wayland save never had a real position, and positions from elsewhere mean nothing here
+    let position_valid = current == DisplayServer::X11 && state.display_server != DisplayServer::Wayland;
+    if !position_valid || !title_bar_visible(&state, &monitors) {
+        state.x = None;
+        state.y = None;
+    }
+    clamp_size(&mut state, &monitors);
+    if current == DisplayServer::X11 {
+        move_to_preferred_monitor(&mut state, &monitors);
+    }
+    state.display_server = current;
+    Ok(Some(state))
+}
+
+/// Save the state, keeping only what the current display server can restore
+///
+/// A maximized window reports its maximized size; the size and position it returns to when
+/// unmaximized are kept from the last save instead.
+pub fn save(path: &Path, mut state: WindowState) -> Result<(), String> {
+    state.display_server = DisplayServer::detect();
+    if state.display_server == DisplayServer::Wayland {
+        state.x = None;
+        state.y = None;
+    }
+    let previous = read(path);
+    if state.placement.is_none() {
+        state.placement = previous.as_ref().and_then(|previous| previous.placement.clone());
+    }
+    if state.is_maximized {
+        if let Some(previous) = previous {
+            state = WindowState {
+                is_maximized: true,
+                display_server: state.display_server,
+                placement: state.placement,
+                ..previous
+            };
+        }
+    }
+    if state.width == 0 || state.height == 0 {
+        return Err("Window size must not be zero".to_string());
+    }
+    write(path, &state)
+}
+
+/// The saved placement hints, or none when nothing was saved
+pub fn placement(path: &Path) -> WindowPlacement {
+    read(path).and_then(|state| state.placement).unwrap_or_default()
+}
+
+/// Save new placement hints, with `current` as the geometry when none was saved yet
+pub fn set_placement(path: &Path, placement: WindowPlacement, current: WindowState) -> Result<(), String> {
+    let mut state = read(path).unwrap_or(current);
+    state.placement = Some(placement);
+    write(path, &state)
+}
+
+/// Apply the hints the geometry cannot carry to the main window
+///
+/// The monitor is applied through the position `load` returns, and staying on top when the quake
+/// toggle shows the window.
+pub fn apply_placement(app: &AppHandle, placement: &WindowPlacement) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    if let Err(e) = window.set_visible_on_all_workspaces(placement.all_workspaces) {
+        log::debug!("Failed to set the window's workspaces: {}", e);
+    }
+    let Some(workspace) = placement.workspace.filter(|_| !placement.all_workspaces) else {
+        return;
+    };
+    if DisplayServer::detect() != DisplayServer::X11 {
+        log::info!("Ignoring the preferred workspace: Wayland does not let windows choose one");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = move_to_workspace(workspace).await {
+            log::warn!("Failed to move the window to workspace {}: {}", workspace, e);
+        }
+    });
+}
+
+fn read(path: &Path) -> Option<WindowState> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+}
+
+fn write(path: &Path, state: &WindowState) -> Result<(), String> {
+    let contents =
+        serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize window state: {}", e))?;
+    // Rename over the file so a crash mid-save cannot leave it half written
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).map_err(|e| format!("Failed to write window state: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write window state: {}", e))
+}
+
+/// Whether enough of the window's top edge is on a monitor to grab and move it
+fn title_bar_visible(state: &WindowState, monitors: &[Monitor]) -> bool {
+    monitors.iter().any(|monitor| title_bar_on(state, monitor))
+}
+
+fn title_bar_on(state: &WindowState, monitor: &Monitor) -> bool {
+    let (Some(x), Some(y)) = (state.x, state.y) else {
+        return false;
+    };
+    let width = i32::try_from(state.width).unwrap_or(i32::MAX);
+    let (position, size) = (monitor.position(), monitor.size());
+    let right = position.x.saturating_add(i32::try_from(size.width).unwrap_or(i32::MAX));
+    let bottom = position.y.saturating_add(i32::try_from(size.height).unwrap_or(i32::MAX));
+    let overlap = x.saturating_add(width).min(right) - x.max(position.x);
+    overlap >= GRAB_HEIGHT * 2 && y >= position.y && y.saturating_add(GRAB_HEIGHT) <= bottom
+}
+
+/// Center the window on its preferred monitor unless it is already there; a preferred monitor
+/// that is not connected changes nothing
+fn move_to_preferred_monitor(state: &mut WindowState, monitors: &[Monitor]) {
+    let Some(name) = state.placement.as_ref().and_then(|placement| placement.monitor.as_deref()) else {
+        return;
+    };
+    let Some(monitor) = monitors.iter().find(|monitor| monitor.name().map(String::as_str) == Some(name)) else {
+        log::info!("Preferred monitor {} is not connected", name);
+        return;
+    };
+    if title_bar_on(state, monitor) {
+        return;
+    }
+    let (position, size) = (monitor.position(), monitor.size());
+    let offset = |monitor: u32, window: u32| i32::try_from(monitor.saturating_sub(window) / 2).unwrap_or(0);
+    state.x = Some(position.x.saturating_add(offset(size.width, state.width)));
+    state.y = Some(position.y.saturating_add(offset(size.height, state.height)));
+}
+
+/// Move this process's window to a workspace with wmctrl, which finds it by pid
+async fn move_to_workspace(workspace: u32) -> Result<(), String> {
+    let wmctrl = |args: &[&str]| ExecOptions {
+        program: "wmctrl".to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        timeout_ms: Some(2000),
+        ..Default::default()
+    };
+    // `0x03a00003  0 12345 host title`: window id, workspace, pid
+    let pid = std::process::id().to_string();
+    let mut id = None;
+    // A window created moments ago may not be mapped yet
+    for _ in 0..WORKSPACE_ATTEMPTS {
+        let list = exec::run(&wmctrl(&["-l", "-p"])).await?;
+        if !list.success() {
+            return Err(list.stderr.trim().to_string());
+        }
+        id = list
+            .stdout
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|fields| fields.get(2) == Some(&pid.as_str()))
+            .and_then(|fields| fields.first().map(|id| id.to_string()));
+        if id.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    let id = id.ok_or_else(|| "the window manager does not list the window".to_string())?;
+    let workspace = workspace.to_string();
+    let moved = exec::run(&wmctrl(&["-i", "-r", &id, "-t", &workspace])).await?;
+    if moved.success() {
+        Ok(())
+    } else {
+        Err(moved.stderr.trim().to_string())
+    }
+}
+
+/// Keep the size between the minimum and the largest monitor
+fn clamp_size(state: &mut WindowState, monitors: &[Monitor]) {
+    let max_width = monitors.iter().map(|m| m.size().width).max().unwrap_or(u32::MAX).max(MIN_WIDTH);
+    let max_height = monitors.iter().map(|m| m.size().height).max().unwrap_or(u32::MAX).max(MIN_HEIGHT);
+    state.width = state.width.clamp(MIN_WIDTH, max_width);
+    state.height = state.height.clamp(MIN_HEIGHT, max_height);
+}
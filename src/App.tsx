@@ -7,29 +7,28 @@ import { getCurrentWindow, type Window as TauriWindow, PhysicalPosition, Physica
 import { TitleBar } from './components/TitleBar';
 import { PaneContainer } from './components/terminal/PaneContainer';
 import { SettingsPanel } from './components/SettingsPanel';
+import { PluginsPanel } from './components/PluginsPanel';
 import { CommandPalette, type Command } from './components/CommandPalette';
 import { SearchBar } from './components/SearchBar';
-import { useTabStore, usePaneStore, useSettingsStore } from './store';
+import { useControlActions } from './hooks/useControlActions';
+import { shellQuote } from './lib/utils';
+import { useTabStore, usePaneStore, useSessionStore, useSettingsStore, useStatusStore } from './store';
+import type { ControlAction, NewTabSuggestions, PaneNode, RestoreReport, SessionInfo, SpawnContext, SystemContext, WindowState } from './types';
 import '../styles/globals.css';
 
-// Window state interface
-interface WindowState {
-  x: number;
-  y: number;
-  width: number;
-  height: number;
-  isMaximized: boolean;
-}
-
 export function App() {
-  const { tabs, activeTabId, addTab, closeTab, setActiveTab, duplicateTab, clearBellOnActive, updateTabRootPaneId, getTabByRootPaneId } = useTabStore();
-  const { createRootPane, splitPane, closePane, findLeafPaneId, broadcastMode, toggleBroadcastMode, activePaneId, setActivePaneId, getAllLeafPanes, getNode, nodes } = usePaneStore();
+  const { tabs, activeTabId, addTab, closeTab, setActiveTab, duplicateTab, clearBellOnActive, updateTabRootPaneId, getTabByRootPaneId, restoreTabs } = useTabStore();
+  const { createRootPane, splitPane, closePane, findLeafPaneId, broadcastMode, toggleBroadcastMode, activePaneId, setActivePaneId, getAllLeafPanes, getNode, nodes, restoreNodes, setSessionId: setPaneSessionId } = usePaneStore();
+  const addSession = useSessionStore((state) => state.addSession);
   const { loadSettings, settings, updateSettings } = useSettingsStore();
   // Use ref to prevent double-init in StrictMode
   const isInitializedRef = useRef(false);
+  // Set once the first tabs exist, so control actions are not overwritten by a restored layout
+  const [isRestored, setIsRestored] = useState(false);
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
   const [isCommandPaletteOpen, setIsCommandPaletteOpen] = useState(false);
   const [isSearchOpen, setIsSearchOpen] = useState(false);
+  const [isPluginsOpen, setIsPluginsOpen] = useState(false);
   const [hostname, setHostname] = useState<string>('');
   const windowRef = useRef<TauriWindow | null>(null);
   
@@ -65,6 +64,7 @@ export function App() {
         const size = await window.outerSize();
         const isMaximized = await window.isMaximized();
         
+        // The backend drops the position under Wayland, where it always reads as 0,0
         const state: WindowState = {
           x: position.x,
           y: position.y,
@@ -83,8 +83,12 @@ export function App() {
   // Initialize settings and window state
   useEffect(() => {
     loadSettings();
+    // Status bar segments stream in from the backend from here on
+    useStatusStore.getState().loadSegments();
     // Get hostname for tab titles
-    invoke<string>('get_hostname').then(setHostname).catch(() => setHostname('Terminal'));
+    invoke<SystemContext>('get_context')
+      .then((context) => setHostname(context.hostname))
+      .catch(() => setHostname('Terminal'));
     
     // Load window state and apply it
     const initWindow = async () => {
@@ -92,11 +96,14 @@ export function App() {
         const window = getCurrentWindow();
         windowRef.current = window;
         
+        // Already adjusted by the backend to the current display server and monitors
         const savedState = await invoke<WindowState | null>('load_window_state');
         if (savedState) {
           // Apply saved position and size
           if (!savedState.isMaximized) {
-            await window.setPosition(new PhysicalPosition(savedState.x, savedState.y));
+            if (savedState.x != null && savedState.y != null) {
+              await window.setPosition(new PhysicalPosition(savedState.x, savedState.y));
+            }
             await window.setSize(new PhysicalSize(savedState.width, savedState.height));
           }
           if (savedState.isMaximized) {
@@ -131,13 +138,316 @@ export function App() {
     if (isInitializedRef.current) return;
     if (!hostname) return; // Wait for hostname
     isInitializedRef.current = true;
+
+    const createDefaultTab = () => {
+      // Create tab which generates a rootPaneId, then create the pane
+      const { rootPaneId } = addTab(undefined, undefined, hostname);
+      createRootPane(rootPaneId);
+      // Set the initial pane as active
+      setActivePaneId(rootPaneId);
+    };
+
+    const restore = async () => {
+      const report = await invoke<RestoreReport>('restore_sessions');
+      if (report.restored.length === 0) return false;
+
+      report.restored.forEach(({ info }) => {
+        addSession({ id: info.id, pid: info.pid, shell: info.shell, status: 'active', cols: 80, rows: 24 });
+      });
+      report.failed.forEach(({ previousId, error }) => {
+        console.warn(`Could not restore session ${previousId}:`, error);
+      });
+
+      const newIds = new Map(report.restored.map(({ previousId, info }) => [previousId, info.id]));
+      const layout = report.layout;
+      if (layout && layout.tabs.length > 0) {
+        // Point panes at the respawned sessions; panes whose session failed spawn a new one
+        const nodes = layout.nodes.map((node): PaneNode =>
+          node.type === 'leaf'
+            ? { ...node, sessionId: node.sessionId ? newIds.get(node.sessionId) ?? null : null }
+            : node
+        );
+        restoreNodes(nodes);
+        restoreTabs(layout.tabs, layout.activeTabId);
+        return true;
+      }
+
+      // No layout saved: one tab per session
+      report.restored.forEach(({ info }) => {
+        const { rootPaneId } = addTab(undefined, undefined, info.name ?? hostname);
+        createRootPane(rootPaneId);
+        setPaneSessionId(rootPaneId, info.id);
+        setActivePaneId(rootPaneId);
+      });
+      return true;
+    };
+
+    restore()
+      .then((restored) => {
+        if (!restored) createDefaultTab();
+      })
+      .catch((err) => {
+        console.error('Failed to restore sessions:', err);
+        createDefaultTab();
+      })
+      .finally(() => setIsRestored(true));
+  }, [addTab, createRootPane, hostname, setActivePaneId, addSession, restoreNodes, restoreTabs, setPaneSessionId]);
+
+  // Report the tab layout so session restore can rebuild it (debounced)
+  useEffect(() => {
+    if (tabs.length === 0) return;
+    const timer = setTimeout(() => {
+      const layout = { tabs, activeTabId, nodes: [...nodes.values()] };
+      invoke('save_session_layout', { layout }).catch((err) => {
+        console.error('Failed to save session layout:', err);
+      });
+    }, 1000);
+    return () => clearTimeout(timer);
+  }, [tabs, activeTabId, nodes]);
+
+  // Split the focused pane and update the tab's rootPaneId if needed
+  const handleSplitPane = useCallback((direction: 'horizontal' | 'vertical') => {
+    const activeTab = tabs.find(t => t.id === activeTabId);
+    if (!activeTab) {
+      return;
+    }
     
-    // Create tab which generates a rootPaneId, then create the pane
-    const { rootPaneId } = addTab(undefined, undefined, hostname);
+    const activeRootPaneId = activeTab.rootPaneId;
+    
+    // Use the focused pane if available, otherwise fall back to finding the first leaf
+    const currentActivePaneId = usePaneStore.getState().activePaneId;
+    const paneToSplit = currentActivePaneId || findLeafPaneId(activeRootPaneId);
+    
+    if (!paneToSplit) {
+      return;
+    }
+    
+    const result = splitPane(paneToSplit, direction);
+    if (!result.containerId) {
+      return;
+    }
+    
+    // If the pane we split was the tab's root pane, update the tab's rootPaneId
+    if (paneToSplit === activeRootPaneId) {
+      updateTabRootPaneId(activeTab.id, result.containerId);
+    }
+    
+    // Set the new pane as active
+    setActivePaneId(result.newPaneId);
+  }, [tabs, activeTabId, findLeafPaneId, splitPane, updateTabRootPaneId, setActivePaneId]);
+
+  // Close the focused pane, or its tab when it is the last pane
+  const handleClosePane = useCallback(() => {
+    const currentActivePaneId = usePaneStore.getState().activePaneId;
+    if (!currentActivePaneId) return;
+    
+    const activeTab = tabs.find(t => t.id === activeTabId);
+    if (!activeTab) return;
+    
+    // Get all leaf panes for this tab
+    const allLeaves = getAllLeafPanes();
+    const tabLeaves = allLeaves.filter(leaf => {
+      // Check if this leaf belongs to current tab by traversing up
+      let nodeId: string | null = leaf.id;
+      while (nodeId) {
+        if (nodeId === activeTab.rootPaneId) return true;
+        // Find parent
+        let parentId: string | null = null;
+        for (const [id, node] of usePaneStore.getState().nodes) {
+          if (node.type === 'branch' && (node.first === nodeId || node.second === nodeId)) {
+            parentId = id;
+            break;
+          }
+        }
+        nodeId = parentId;
+      }
+      return false;
+    });
+    
+    // Don't close if it's the only pane in the tab
+    if (tabLeaves.length <= 1) {
+      // Close the tab instead
+      closeTab(activeTab.id);
+      return;
+    }
+    
+    // Close the PTY session first
+    const node = getNode(currentActivePaneId);
+    if (node?.type === 'leaf' && node.sessionId) {
+      invoke('pty_close', { sessionId: node.sessionId }).catch(() => {});
+    }
+    
+    // Close the pane
+    closePane(currentActivePaneId);
+    
+    // Update tab rootPaneId if needed (closePane handles this internally)
+    const newRootId = usePaneStore.getState().rootId;
+    if (newRootId && newRootId !== activeTab.rootPaneId) {
+      updateTabRootPaneId(activeTab.id, newRootId);
+    }
+  }, [tabs, activeTabId, getAllLeafPanes, closeTab, getNode, closePane, updateTabRootPaneId]);
+
+  // Show a session the backend already started in a new tab
+  const attachSession = useCallback((info: SessionInfo, title: string) => {
+    addSession({ id: info.id, pid: info.pid, shell: info.shell, status: 'active', cols: 80, rows: 24 });
+    const { rootPaneId } = addTab(undefined, undefined, title);
     createRootPane(rootPaneId);
-    // Set the initial pane as active
+    setPaneSessionId(rootPaneId, info.id);
     setActivePaneId(rootPaneId);
-  }, [addTab, createRootPane, hostname, setActivePaneId]);
+  }, [addSession, addTab, createRootPane, setPaneSessionId, setActivePaneId]);
+
+  // Open a tab for an external request, typing its command into the new shell
+  const openControlTab = useCallback(async (profileId: string | null, cwd: string | null, command: string | null, context: SpawnContext | null) => {
+    const info = await invoke<SessionInfo>('spawn_pty', {
+      options: {
+        cwd: cwd ?? undefined,
+        cols: 80,
+        rows: 24,
+        profileId: profileId ?? undefined,
+        context: context ?? undefined,
+        sanitizeEnv: useSettingsStore.getState().settings.terminal.sanitizeChildEnvironment,
+      },
+    });
+    attachSession(info, hostname || 'Terminal');
+    if (command) {
+      await invoke('pty_write', { sessionId: info.id, data: `${command}\r` });
+    }
+  }, [attachSession, hostname]);
+
+  // Type into the focused pane's session
+  const writeToActiveSession = useCallback(async (data: string) => {
+    const paneId = usePaneStore.getState().activePaneId;
+    const node = paneId ? getNode(paneId) : undefined;
+    if (node?.type !== 'leaf' || !node.sessionId) return;
+    await invoke('pty_write', { sessionId: node.sessionId, data });
+  }, [getNode]);
+
+  // Carry out a command palette action the backend forwarded, by registry ID
+  const runAction = useCallback(async (id: string, args: Record<string, string>) => {
+    switch (id) {
+      case 'newTab': {
+        const { rootPaneId } = addTab(undefined, undefined, hostname || 'Terminal');
+        createRootPane(rootPaneId);
+        setActivePaneId(rootPaneId);
+        break;
+      }
+      case 'closeTab': {
+        const currentActiveTabId = useTabStore.getState().activeTabId;
+        if (currentActiveTabId) closeTab(currentActiveTabId);
+        break;
+      }
+      case 'nextTab':
+      case 'prevTab': {
+        const idx = tabs.findIndex(t => t.id === activeTabId);
+        const step = id === 'nextTab' ? 1 : tabs.length - 1;
+        const tab = tabs[(idx + step) % tabs.length];
+        if (idx >= 0 && tabs.length > 1 && tab) setActiveTab(tab.id);
+        break;
+      }
+      case 'connectHost':
+        await openControlTab(null, null, `ssh -- ${shellQuote(args.host)}`, null);
+        break;
+      case 'splitPane':
+        if (args.direction !== 'horizontal' && args.direction !== 'vertical') {
+          throw new Error(`Unknown split direction: ${args.direction}`);
+        }
+        handleSplitPane(args.direction);
+        break;
+      case 'closePane':
+        handleClosePane();
+        break;
+      case 'toggleBroadcast':
+        toggleBroadcastMode();
+        break;
+      case 'zoomIn':
+        updateSettings({ fontSize: Math.min((settings.fontSize || 14) + 1, 32) });
+        break;
+      case 'zoomOut':
+        updateSettings({ fontSize: Math.max((settings.fontSize || 14) - 1, 8) });
+        break;
+      case 'zoomReset':
+        updateSettings({ fontSize: 14 });
+        break;
+      case 'changeTheme': {
+        // The app theme, or a color scheme by ID or name
+        if (args.theme === 'light' || args.theme === 'dark' || args.theme === 'system') {
+          updateSettings({ theme: args.theme });
+          break;
+        }
+        const scheme = settings.colorSchemes.find(c => c.id === args.theme || c.name === args.theme);
+        if (!scheme) throw new Error(`Unknown theme: ${args.theme}`);
+        updateSettings({ activeColorSchemeId: scheme.id });
+        break;
+      }
+      case 'openSearch':
+        setIsSearchOpen(true);
+        break;
+      case 'clear':
+        // What Ctrl+L does in the shell
+        await writeToActiveSession('\x0c');
+        break;
+      case 'runTask':
+        await openControlTab(null, null, args.command, null);
+        break;
+      case 'openPlugins':
+        setIsPluginsOpen(true);
+        break;
+      case 'openCommandPalette':
+        setIsCommandPaletteOpen(true);
+        break;
+      default:
+        // Plugin actions are handled by the plugin that registered them
+        console.warn('Unhandled action:', id);
+    }
+  }, [addTab, createRootPane, setActivePaneId, closeTab, tabs, activeTabId, setActiveTab, hostname, openControlTab, handleSplitPane, handleClosePane, toggleBroadcastMode, updateSettings, settings.fontSize, settings.colorSchemes, writeToActiveSession]);
+
+  // Carry out a request from launch arguments, D-Bus, the control socket, or a link
+  const handleControlAction = useCallback(async (action: ControlAction) => {
+    switch (action.type) {
+      case 'openTab':
+        await openControlTab(action.profile, action.cwd, action.command, action.context);
+        break;
+      case 'openWindow':
+        // One window per app: a new window request opens a tab with the window default profile
+        await openControlTab(null, null, null, 'window');
+        break;
+      case 'openPreferences':
+        setIsSettingsOpen(true);
+        break;
+      case 'viewFile': {
+        const info = await invoke<SessionInfo>('view_file', { path: action.path, cols: 80, rows: 24 });
+        attachSession(info, action.path.split('/').pop() || action.path);
+        break;
+      }
+      case 'openWorkspace': {
+        // Workspaces are the project roots sessions worked in, named by their directory
+        const { workspaces } = await invoke<NewTabSuggestions>('get_new_tab_suggestions', { query: action.name, limit: 50 });
+        const workspace = workspaces.find(w => w.value === action.name || w.value.split('/').pop() === action.name);
+        if (!workspace) throw new Error(`No workspace named ${action.name}`);
+        await openControlTab(null, workspace.value, null, null);
+        break;
+      }
+      case 'focusSession': {
+        const pane = getAllLeafPanes().find(p => p.sessionId === action.sessionId);
+        const nodes = usePaneStore.getState().nodes;
+        const tab = pane && tabs.find(t => containsPane(nodes, t.rootPaneId, pane.id));
+        if (!pane || !tab) throw new Error(`No tab shows session ${action.sessionId}`);
+        setActiveTab(tab.id);
+        setActivePaneId(pane.id);
+        break;
+      }
+      case 'runAction':
+        await runAction(action.id, action.args);
+        break;
+      case 'toggleQuake':
+        // Shown or hidden by the backend
+        break;
+      default:
+        console.warn('Unhandled control action:', action);
+    }
+  }, [openControlTab, attachSession, runAction, getAllLeafPanes, tabs, setActiveTab, setActivePaneId]);
+
+  useControlActions(isRestored, handleControlAction);
 
   // Keyboard shortcuts - Windows Terminal inspired
   const handleKeyDown = useCallback((e: KeyboardEvent) => {
@@ -181,88 +491,6 @@ export function App() {
       }
     }
 
-    // Helper function to handle split pane and update tab's rootPaneId if needed
-    const handleSplitPane = (direction: 'horizontal' | 'vertical') => {
-      const activeTab = tabs.find(t => t.id === activeTabId);
-      if (!activeTab) {
-        return;
-      }
-      
-      const activeRootPaneId = activeTab.rootPaneId;
-      
-      // Use the focused pane if available, otherwise fall back to finding the first leaf
-      const currentActivePaneId = usePaneStore.getState().activePaneId;
-      const paneToSplit = currentActivePaneId || findLeafPaneId(activeRootPaneId);
-      
-      if (!paneToSplit) {
-        return;
-      }
-      
-      const result = splitPane(paneToSplit, direction);
-      if (!result.containerId) {
-        return;
-      }
-      
-      // If the pane we split was the tab's root pane, update the tab's rootPaneId
-      if (paneToSplit === activeRootPaneId) {
-        updateTabRootPaneId(activeTab.id, result.containerId);
-      }
-      
-      // Set the new pane as active
-      setActivePaneId(result.newPaneId);
-    };
-
-    // Helper function to close the active pane
-    const handleClosePane = () => {
-      const currentActivePaneId = usePaneStore.getState().activePaneId;
-      if (!currentActivePaneId) return;
-      
-      const activeTab = tabs.find(t => t.id === activeTabId);
-      if (!activeTab) return;
-      
-      // Get all leaf panes for this tab
-      const allLeaves = getAllLeafPanes();
-      const tabLeaves = allLeaves.filter(leaf => {
-        // Check if this leaf belongs to current tab by traversing up
-        let nodeId: string | null = leaf.id;
-        while (nodeId) {
-          if (nodeId === activeTab.rootPaneId) return true;
-          // Find parent
-          let parentId: string | null = null;
-          for (const [id, node] of usePaneStore.getState().nodes) {
-            if (node.type === 'branch' && (node.first === nodeId || node.second === nodeId)) {
-              parentId = id;
-              break;
-            }
-          }
-          nodeId = parentId;
-        }
-        return false;
-      });
-      
-      // Don't close if it's the only pane in the tab
-      if (tabLeaves.length <= 1) {
-        // Close the tab instead
-        closeTab(activeTab.id);
-        return;
-      }
-      
-      // Close the PTY session first
-      const node = getNode(currentActivePaneId);
-      if (node?.type === 'leaf' && node.sessionId) {
-        invoke('pty_close', { sessionId: node.sessionId }).catch(() => {});
-      }
-      
-      // Close the pane
-      closePane(currentActivePaneId);
-      
-      // Update tab rootPaneId if needed (closePane handles this internally)
-      const newRootId = usePaneStore.getState().rootId;
-      if (newRootId && newRootId !== activeTab.rootPaneId) {
-        updateTabRootPaneId(activeTab.id, newRootId);
-      }
-    };
-
     // Helper function to navigate between panes
     const navigatePane = (direction: 'up' | 'down' | 'left' | 'right') => {
       const currentActivePaneId = usePaneStore.getState().activePaneId;
@@ -402,7 +630,7 @@ export function App() {
         }
       }
     }
-  }, [addTab, createRootPane, closeTab, activeTabId, splitPane, tabs, setActiveTab, isSettingsOpen, isCommandPaletteOpen, isSearchOpen, findLeafPaneId, settings.fontSize, updateSettings, toggleBroadcastMode, hostname, updateTabRootPaneId]);
+  }, [addTab, createRootPane, closeTab, activeTabId, splitPane, tabs, setActiveTab, isSettingsOpen, isCommandPaletteOpen, isSearchOpen, settings.fontSize, updateSettings, toggleBroadcastMode, hostname, handleSplitPane, handleClosePane, getAllLeafPanes, setActivePaneId]);
 
   // Helper for command palette split actions
   const handleCommandSplit = (direction: 'horizontal' | 'vertical') => {
@@ -520,6 +748,15 @@ export function App() {
         onClose={() => setIsSettingsOpen(false)} 
       />
 
+      {/* Plugins Panel */}
+      <PluginsPanel
+        isOpen={isPluginsOpen}
+        onClose={() => setIsPluginsOpen(false)}
+        onRunCommand={(command) => {
+          writeToActiveSession(`${command}\r`).catch((err) => console.error('Failed to run plugin command:', err));
+        }}
+      />
+
       {/* Command Palette */}
       <CommandPalette
         isOpen={isCommandPaletteOpen}
@@ -547,6 +784,13 @@ export function App() {
 
 export default App;
 
+// Whether a pane is in the tree under a node
+function containsPane(nodes: Map<string, PaneNode>, nodeId: string, paneId: string): boolean {
+  if (nodeId === paneId) return true;
+  const node = nodes.get(nodeId);
+  return node?.type === 'branch' && (containsPane(nodes, node.first, paneId) || containsPane(nodes, node.second, paneId));
+}
+
 // Utility function for className
 function cn(...classes: (string | boolean | undefined | null)[]) {
   return classes.filter(Boolean).join(' ');
@@ -7,6 +7,7 @@ import { Plus, X, ChevronDown, Terminal } from 'lucide-react';
 import { useTabStore, usePaneStore, useSettingsStore } from '@/store';
 import { TabContextMenu } from './TabContextMenu';
 import { cn } from '@/lib/utils';
+import type { SystemContext } from '@/types';
 
 // Lazy import Tauri API to handle both browser and Tauri environments
 const isTauri = () => {
@@ -53,7 +54,7 @@ export function TitleBar({ onSettingsClick }: TitleBarProps) {
 
   // Fetch hostname for new tab titles
   useEffect(() => {
-    invoke<string>('get_hostname').then(setHostname).catch(() => {});
+    invoke<SystemContext>('get_context').then((context) => setHostname(context.hostname)).catch(() => {});
   }, []);
   
   // Track previous tabs to detect new ones
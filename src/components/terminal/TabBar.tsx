@@ -1,14 +1,84 @@
 // TabBar Component - Tab bar with close buttons
 // Displays tabs and allows switching/closing them
 
-import { useTabStore, usePaneStore } from '@/store';
+import { useTabStore, usePaneStore, useSessionStore } from '@/store';
 import { X } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { cn } from '@/lib/utils';
+import type { RemoteHostInfo, TerminalProgress } from '@/types';
+
+// Tooltip telling where an SSH tab is connected, from the probe of its remote system
+function remoteHostTooltip(host: RemoteHostInfo): string {
+  const lines = [host.os ? `${host.hostname} (${host.os})` : host.hostname];
+  if (host.kernel) {
+    lines.push(host.arch ? `${host.kernel} ${host.arch}` : host.kernel);
+  }
+  if (host.uptimeSecs !== null) {
+    const days = Math.floor(host.uptimeSecs / 86400);
+    const hours = Math.floor((host.uptimeSecs % 86400) / 3600);
+    lines.push(`Up ${days > 0 ? `${days}d ` : ''}${hours}h`);
+  }
+  if (host.load) {
+    lines.push(`Load ${host.load.map((load) => load.toFixed(2)).join(' ')}`);
+  }
+  if (host.disk && host.disk.totalBytes > 0) {
+    const percent = Math.round((host.disk.usedBytes / host.disk.totalBytes) * 100);
+    const freeGib = (host.disk.availableBytes / 1024 ** 3).toFixed(1);
+    lines.push(`Disk / ${percent}% used, ${freeGib} GiB free`);
+  }
+  return lines.join('\n');
+}
+
+// Thin bar along the bottom of a tab for progress its programs report
+function ProgressBar({ progress }: { progress: TerminalProgress }) {
+  const indeterminate = progress.percent === null;
+  return (
+    <div
+      className={cn(
+        'absolute bottom-0 left-0 h-0.5',
+        progress.state === 'error' ? 'bg-destructive' : progress.state === 'paused' ? 'bg-amber-500' : 'bg-primary',
+        indeterminate && 'animate-pulse'
+      )}
+      style={{ width: `${indeterminate ? 100 : progress.percent}%` }}
+      role="progressbar"
+      aria-valuemin={0}
+      aria-valuemax={100}
+      aria-valuenow={progress.percent ?? undefined}
+    />
+  );
+}
 
 export function TabBar() {
   const { tabs, activeTabId, setActiveTab, closeTab, addTab } = useTabStore();
-  const { createRootPane } = usePaneStore();
+  const { createRootPane, nodes } = usePaneStore();
+  const { sessions } = useSessionStore();
+
+  // Remote system of the tab's first pane, which the tab is named after
+  const tabTooltip = (rootPaneId: string) => {
+    let node = nodes.get(rootPaneId);
+    while (node?.type === 'branch') {
+      node = nodes.get(node.first);
+    }
+    const host = node?.sessionId ? sessions.get(node.sessionId)?.remoteHost : undefined;
+    return host ? remoteHostTooltip(host) : undefined;
+  };
+
+  // Progress reported in any pane of the tab, errors first
+  const tabProgressBar = (rootPaneId: string) => {
+    const found: TerminalProgress[] = [];
+    const pending = [rootPaneId];
+    while (pending.length > 0) {
+      const node = nodes.get(pending.pop()!);
+      if (node?.type === 'branch') {
+        pending.push(node.first, node.second);
+      } else if (node?.sessionId) {
+        const progress = sessions.get(node.sessionId)?.progress;
+        if (progress) found.push(progress);
+      }
+    }
+    const progress = found.find((progress) => progress.state === 'error') ?? found[0];
+    return progress ? <ProgressBar progress={progress} /> : null;
+  };
 
   const handleNewTab = () => {
     const tabId = addTab();
@@ -43,7 +113,7 @@ export function TabBar() {
                 }
               }}
               className={cn(
-                'flex items-center gap-2 px-4 py-2 cursor-pointer border-r min-w-30 max-w-50',
+                'relative flex items-center gap-2 px-4 py-2 cursor-pointer border-r min-w-30 max-w-50',
                 'hover:bg-background/50 transition-colors',
                 tab.isActive && 'bg-background border-b-2 border-b-primary'
               )}
@@ -52,6 +122,7 @@ export function TabBar() {
               aria-selected={tab.isActive}
               aria-controls={`tabpanel-${tab.id}`}
               id={`tab-${tab.id}`}
+              title={tabTooltip(tab.rootPaneId)}
               aria-label={`${tab.title}${tab.hasBell ? ', has notification' : ''}`}
             >
               {tab.icon && <span className="text-sm" aria-hidden="true">{tab.icon}</span>}
@@ -71,6 +142,7 @@ export function TabBar() {
               >
                 <X className="h-3 w-3" aria-hidden="true" />
               </Button>
+              {tabProgressBar(tab.rootPaneId)}
             </div>
           ))
         )}
@@ -2,16 +2,23 @@
 // Renders a terminal using xterm.js and handles PTY communication
 
 import { useEffect, useRef, useState, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { Terminal as XTerm } from 'xterm';
 import { FitAddon } from 'xterm-addon-fit';
 import { SearchAddon } from 'xterm-addon-search';
 import { WebLinksAddon } from 'xterm-addon-web-links';
 import { usePty } from '@/hooks/usePty';
-import { useSettingsStore, useTabStore } from '@/store';
+import { useSessionStore, useSettingsStore, useTabStore } from '@/store';
 import { usePaneStore } from '@/store';
 import { broadcastManager } from '@/lib/broadcast';
+import { createOutputLinkProvider } from '@/lib/links';
+import { pathRangeAt, selectionText } from '@/lib/selection';
+import type { ScrollbackChunk } from '@/types';
 import 'xterm/css/xterm.css';
 
+// How long the visual bell shows
+const BELL_FLASH_MS = 150;
+
 interface TerminalProps {
   paneId: string;
   sessionId: string | null;
@@ -47,6 +54,7 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
 
   const profile = getActiveProfile();
   const colorScheme = getActiveColorScheme();
+  const wordSeparator = profile?.selection?.wordSeparators ?? settings.terminal?.wordSeparators;
 
   // Safe fit function - only works after renderer is ready
   const safeFit = useCallback(() => {
@@ -67,9 +75,9 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
   }, []);
 
   // Write data callback for PTY - buffers writes until renderer is ready
-  const pendingWritesRef = useRef<string[]>([]);
+  const pendingWritesRef = useRef<(string | Uint8Array)[]>([]);
   
-  const writeToTerminal = useCallback((data: string) => {
+  const writeToTerminal = useCallback((data: string | Uint8Array) => {
     if (!mountedRef.current) return;
     const xterm = xtermRef.current;
     if (!xterm) return;
@@ -82,13 +90,13 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
     
     // First, flush any pending writes
     if (pendingWritesRef.current.length > 0) {
-      const pending = pendingWritesRef.current.join('');
+      const pending = pendingWritesRef.current;
       pendingWritesRef.current = [];
       try {
-        xterm.write(pending);
+        pending.forEach((chunk) => xterm.write(chunk));
       } catch (e) {
         // Renderer might still not be ready, re-buffer
-        pendingWritesRef.current.push(pending);
+        pendingWritesRef.current.push(...pending);
         rendererReadyRef.current = false;
       }
     }
@@ -104,19 +112,28 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
   }, []);
 
   // Shell exit state
-  const [shellExited, setShellExited] = useState<{ exited: boolean; exitCode: number | null }>({
+  const [shellExited, setShellExited] = useState<{
+    exited: boolean;
+    exitCode: number | null;
+    signal: string | null;
+  }>({
     exited: false,
     exitCode: null,
+    signal: null,
   });
 
+  // Visual bell
+  const [bellFlash, setBellFlash] = useState(false);
+  const bellFlashTimerRef = useRef<number | undefined>(undefined);
+
   // PTY hook
   const { write, resize, spawn } = usePty(sessionId, {
     onData: writeToTerminal,
-    onExit: (exitCode) => {
+    onExit: (exitCode, signal) => {
       if (!mountedRef.current) return;
-      setShellExited({ exited: true, exitCode });
+      setShellExited({ exited: true, exitCode, signal });
       if (onTitleChange) {
-        const status = exitCode === 0 ? 'exited' : `failed (${exitCode})`;
+        const status = exitCode === 0 ? 'exited' : `failed (${signal ?? exitCode ?? 'unknown'})`;
         onTitleChange(`Terminal (${status})`);
       }
       // Write exit message to terminal
@@ -125,12 +142,44 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
         xterm.write('\r\n\r\n');
         if (exitCode === 0) {
           xterm.write('\x1b[32m[Process exited]\x1b[0m');
-        } else {
+        } else if (signal) {
+          xterm.write(`\x1b[31m[Process terminated: ${signal}]\x1b[0m`);
+        } else if (exitCode !== null) {
           xterm.write(`\x1b[31m[Process exited with code ${exitCode}]\x1b[0m`);
+        } else {
+          xterm.write('\x1b[31m[Process exited with unknown status]\x1b[0m');
         }
         xterm.write('\r\n');
       }
     },
+    // Titles set by programs (e.g. 'vim ~/file.rs', 'ssh user@host'), parsed by the backend
+    onTitle: (title) => {
+      if (!mountedRef.current || !title) return;
+      updateTabTitleByPane(paneId, title);
+    },
+    // Bells found by the backend, marked on the tab when it is in the background
+    onBell: ({ flash }) => {
+      if (!mountedRef.current) return;
+      const { setTabBell, getTabByPaneId, activeTabId } = useTabStore.getState();
+      const tab = getTabByPaneId(paneId);
+      if (tab && tab.id !== activeTabId) {
+        setTabBell(tab.id, true);
+      }
+      if (flash) {
+        setBellFlash(true);
+        window.clearTimeout(bellFlashTimerRef.current);
+        bellFlashTimerRef.current = window.setTimeout(() => setBellFlash(false), BELL_FLASH_MS);
+      }
+    },
+    // Watched output that resumed or went quiet, marked on the tab when it is in the background
+    onMonitor: () => {
+      if (!mountedRef.current) return;
+      const { setTabBell, getTabByPaneId, activeTabId } = useTabStore.getState();
+      const tab = getTabByPaneId(paneId);
+      if (tab && tab.id !== activeTabId) {
+        setTabBell(tab.id, true);
+      }
+    },
   });
 
   // Restart the shell
@@ -146,14 +195,14 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
     xterm.write('\x1b[2J\x1b[H'); // Clear screen and move cursor to home
     
     // Reset exit state
-    setShellExited({ exited: false, exitCode: null });
+    setShellExited({ exited: false, exitCode: null, signal: null });
     hasSpawnedRef.current = false;
     
     // Spawn a new shell
     try {
       const shell = profile?.commandline || '';
       const { cols, rows } = fitAddon.proposeDimensions() || { cols: 80, rows: 24 };
-      const sessionInfo = await spawn(shell, cols, rows);
+      const sessionInfo = await spawn(shell, cols, rows, undefined, profile?.id);
       
       // Update pane with new session
       setPaneSessionId(paneId, sessionInfo.id);
@@ -239,16 +288,20 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
         cursorBlink: settings.cursorBlink ?? true,
         cursorStyle: settings.cursorStyle || 'block',
         scrollback: settings.scrollbackSize || 10000,
+        ...(wordSeparator !== undefined && { wordSeparator }),
         allowProposedApi: true,
         convertEol: true,
       });
 
+      // Read when used, so edits from the settings panel apply without a new terminal
+      const selectionSettings = () => useSettingsStore.getState().getActiveProfile()?.selection;
+
       // Add custom key handler for copy/paste shortcuts
       xterm.attachCustomKeyEventHandler((event) => {
         // Ctrl+Shift+C - Copy selection
         if (event.ctrlKey && event.shiftKey && event.key === 'C') {
           if (event.type === 'keydown') {
-            const selection = xterm.getSelection();
+            const selection = selectionText(xterm.getSelection(), selectionSettings());
             if (selection) {
               // Copy to clipboard
               (async () => {
@@ -301,7 +354,9 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
         (event, uri) => {
           // Only open URLs when Ctrl is pressed
           if (event.ctrlKey) {
-            window.open(uri, '_blank');
+            invoke('open_url', { url: uri }).catch((error) =>
+              console.error('[Terminal] Failed to open link:', error)
+            );
           }
         },
         {
@@ -322,6 +377,7 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
       xterm.loadAddon(fitAddon);
       xterm.loadAddon(searchAddon);
       xterm.loadAddon(webLinksAddon);
+      xterm.registerLinkProvider(createOutputLinkProvider(xterm, () => sessionIdRef.current, container));
 
       // Store refs before opening
       xtermRef.current = xterm;
@@ -356,10 +412,11 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
           
           // Flush any pending writes
           if (pendingWritesRef.current.length > 0 && xtermRef.current) {
-            const pending = pendingWritesRef.current.join('');
+            const pending = pendingWritesRef.current;
+            const xterm = xtermRef.current;
             pendingWritesRef.current = [];
             try {
-              xtermRef.current.write(pending);
+              pending.forEach((chunk) => xterm.write(chunk));
             } catch (e) {
               // Ignore
             }
@@ -379,30 +436,65 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
         });
       });
 
+      // Widen a double-click selection to the path or URL under it
+      const onDoubleClick = () => {
+        const position = xterm.getSelectionPosition();
+        if (!position || position.start.y !== position.end.y) return;
+        const line = xterm.buffer.active.getLine(position.start.y)?.translateToString();
+        if (!line) return;
+        const range = pathRangeAt(line, position.start.x, selectionSettings());
+        if (range && (range[0] < position.start.x || range[1] > position.end.x)) {
+          xterm.select(range[0], position.start.y, range[1] - range[0]);
+        }
+      };
+      container.addEventListener('dblclick', onDoubleClick);
+      const doubleClickDisposable = {
+        dispose: () => container.removeEventListener('dblclick', onDoubleClick),
+      };
+
+      // Bring the backend scrollback of an idle, hibernating session back before it is needed, and
+      // tell the backend which session is in view for command notifications
+      const onFocus = () => {
+        const id = sessionIdRef.current;
+        if (!id) return;
+        invoke('pty_wake', { sessionId: id }).catch(() => {});
+        invoke('set_focused_session', { sessionId: id }).catch(() => {});
+      };
+      xterm.textarea?.addEventListener('focus', onFocus);
+      const focusDisposable = {
+        dispose: () => xterm.textarea?.removeEventListener('focus', onFocus),
+      };
+
       // Handle user input
       const dataDisposable = xterm.onData((data) => {
         if (!mountedRef.current) return;
         
-        // Write to local PTY
-        write(data).catch((err) => {
-          console.error('[Terminal] Failed to write to PTY:', err);
-        });
+        // Write to local PTY, and to the rest of its broadcast group if it is in one
+        const id = sessionIdRef.current;
+        const group = id ? useSessionStore.getState().getSession(id)?.broadcastGroup : null;
+        if (id && group) {
+          const { broadcast } = useSettingsStore.getState().settings;
+          broadcastManager.writeGroup(data, id, broadcast).catch((err) => {
+            console.error('[Terminal] Failed to write to broadcast group:', err);
+          });
+        } else {
+          write(data).catch((err) => {
+            console.error('[Terminal] Failed to write to PTY:', err);
+          });
+        }
         
         // If broadcast mode is on, send to other panes
         const { broadcastMode } = usePaneStore.getState();
         if (broadcastMode) {
-          broadcastManager.broadcast(data, paneId);
+          const { broadcast } = useSettingsStore.getState().settings;
+          broadcastManager.broadcast(data, paneId, broadcast).catch((err) => {
+            console.error('[Terminal] Failed to broadcast input:', err);
+          });
         }
       });
 
       // Register to receive broadcast input from other panes
-      broadcastManager.register(paneId, (data) => {
-        if (!mountedRef.current) return;
-        const { broadcastMode } = usePaneStore.getState();
-        if (broadcastMode) {
-          write(data).catch(() => {});
-        }
-      });
+      broadcastManager.register(paneId, () => (mountedRef.current ? sessionIdRef.current : null));
 
       // Handle terminal resize
       const resizeDisposable = xterm.onResize(({ cols, rows }) => {
@@ -410,29 +502,16 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
         resize(cols, rows).catch(() => {});
       });
 
-      // Handle title changes (e.g., when running commands like 'claude', 'ssh user@host')
-      const titleDisposable = xterm.onTitleChange((title) => {
-        if (!mountedRef.current) return;
-        if (title && title.trim()) {
-          updateTabTitleByPane(paneId, title);
-        }
-      });
-
-      // Handle bell (e.g., tab completion with no matches, error alerts)
-      const { setTabBell, getTabByPaneId } = useTabStore.getState();
-      const bellDisposable = xterm.onBell(() => {
-        if (!mountedRef.current) return;
-        const tab = getTabByPaneId(paneId);
-        if (tab && tab.id !== useTabStore.getState().activeTabId) {
-          setTabBell(tab.id, true);
-        }
-      });
-
       // Focus terminal
       xterm.focus();
       
       // Store disposables for cleanup
-      (xterm as any)._disposables = [dataDisposable, resizeDisposable, titleDisposable, bellDisposable];
+      (xterm as any)._disposables = [
+        dataDisposable,
+        resizeDisposable,
+        doubleClickDisposable,
+        focusDisposable,
+      ];
     };
     
     initializeXterm();
@@ -486,6 +565,13 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
     }
   }, [settings.fontSize, safeFit]);
 
+  // Update word separators when the profile or terminal settings change
+  useEffect(() => {
+    const xterm = xtermRef.current;
+    if (!xterm || wordSeparator === undefined) return;
+    xterm.options.wordSeparator = wordSeparator;
+  }, [wordSeparator]);
+
   // Handle container resize
   useEffect(() => {
     const container = terminalRef.current;
@@ -518,12 +604,12 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
 
     const doSpawn = async () => {
       try {
-        const shell = profile?.shell || '/bin/bash';
+        const shell = profile?.shell || '';
         const cols = xtermRef.current?.cols || 80;
         const rows = xtermRef.current?.rows || 24;
 
 
-        const sessionInfo = await spawn(shell, cols, rows);
+        const sessionInfo = await spawn(shell, cols, rows, undefined, profile?.id);
         
         if (!mountedRef.current) return;
 
@@ -539,6 +625,21 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
     doSpawn();
   }, [isReady, sessionId, profile, spawn, paneId, setPaneSessionId]);
 
+  // Replay backend scrollback when attaching to a session that already has output
+  // (after a webview reload, a restore, or a tab moved from another window)
+  const hasReplayedRef = useRef(false);
+  useEffect(() => {
+    if (!isReady || !sessionId || hasSpawnedRef.current || hasReplayedRef.current) return;
+    hasReplayedRef.current = true;
+
+    invoke<ScrollbackChunk>('pty_get_scrollback', { sessionId })
+      .then((chunk) => {
+        if (!chunk.data || !mountedRef.current) return;
+        writeToTerminal(chunk.encoding === 'base64' ? Uint8Array.from(atob(chunk.data), (c) => c.charCodeAt(0)) : chunk.data);
+      })
+      .catch((error) => console.error('[Terminal] Failed to replay scrollback:', error));
+  }, [isReady, sessionId, writeToTerminal]);
+
   // Handle right-click for paste
   const handleContextMenu = useCallback(async (e: React.MouseEvent) => {
     const rightClickAction = settings.terminal?.rightClickAction || 'paste';
@@ -578,6 +679,9 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
       onContextMenu={handleContextMenu}
     >
       <div ref={terminalRef} className="h-full w-full" />
+
+      {/* Visual bell */}
+      {bellFlash && <div className="absolute inset-0 pointer-events-none bg-white/10" />}
       
       {/* Shell Exit Overlay */}
       {shellExited.exited && (
@@ -590,7 +694,9 @@ export function Terminal({ paneId, sessionId, onTitleChange, onSearchReady }: Te
                 </div>
               ) : (
                 <div className="text-red-400 text-lg font-semibold mb-2">
-                  Process Failed (code {shellExited.exitCode})
+                  {shellExited.signal
+                    ? `Process Terminated (${shellExited.signal})`
+                    : `Process Failed (code ${shellExited.exitCode ?? 'unknown'})`}
                 </div>
               )}
               <p className="text-zinc-400 text-sm mb-4">
@@ -3,6 +3,7 @@
 
 import { usePaneStore, useTabStore } from '@/store';
 import { Terminal } from './Terminal';
+import { UnresponsiveBanner } from './UnresponsiveBanner';
 import { TerminalErrorBoundary } from '../ErrorBoundary';
 import { useCallback, useState } from 'react';
 
@@ -58,6 +59,7 @@ export function TerminalPane({ paneId }: TerminalPaneProps) {
       <TerminalErrorBoundary paneId={paneId} onRestart={handleRestart}>
         <Terminal key={restartKey} paneId={paneId} sessionId={sessionId} />
       </TerminalErrorBoundary>
+      {sessionId && <UnresponsiveBanner sessionId={sessionId} />}
     </div>
   );
 }
@@ -0,0 +1,54 @@
+// UnresponsiveBanner Component - Prompt shown over a pane whose program seems hung
+// Offers to interrupt or kill the foreground program, or to keep waiting
+
+import { invoke } from '@tauri-apps/api/core';
+import { useSessionStore } from '@/store';
+import { Button } from '@/components/ui/button';
+import type { PtySignal } from '@/types';
+
+interface UnresponsiveBannerProps {
+  sessionId: string;
+}
+
+export function UnresponsiveBanner({ sessionId }: UnresponsiveBannerProps) {
+  const unresponsive = useSessionStore((state) => state.sessions.get(sessionId)?.unresponsive);
+  const updateSession = useSessionStore((state) => state.updateSession);
+
+  if (!unresponsive) return null;
+
+  const process = unresponsive.process;
+  // With the shell itself hung there is no job to single out, so the whole session is signalled
+  const jobName = process && !process.isShell ? process.name : null;
+
+  const sendSignal = async (signal: PtySignal) => {
+    try {
+      if (jobName) {
+        await invoke('pty_signal_foreground', { sessionId, signal });
+      } else {
+        await invoke('pty_kill', { sessionId, signal });
+      }
+    } catch (error) {
+      console.error(`Failed to send ${signal}:`, error);
+    }
+  };
+
+  return (
+    <div
+      className="absolute top-0 inset-x-0 z-10 flex items-center gap-2 px-3 py-1.5 bg-amber-900/90 text-amber-50 text-sm"
+      role="alert"
+    >
+      <span className="flex-1 truncate">
+        {jobName ?? 'The shell'} has not read input for {unresponsive.seconds}s and may be hung
+      </span>
+      <Button variant="ghost" size="sm" onClick={() => sendSignal('SIGINT')}>
+        Interrupt
+      </Button>
+      <Button variant="ghost" size="sm" onClick={() => sendSignal('SIGKILL')}>
+        Kill
+      </Button>
+      <Button variant="ghost" size="sm" onClick={() => updateSession(sessionId, { unresponsive: null })}>
+        Wait
+      </Button>
+    </div>
+  );
+}
@@ -0,0 +1,56 @@
+// useControlActions Hook - External control requests
+// Handles actions from launch arguments, D-Bus, the control socket, and links
+
+import { useEffect, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
+import type { ControlAction, LaunchActions } from '@/types';
+
+/**
+ * Hook for control actions
+ * Once enabled, takes the actions the app was launched with and then handles
+ * each `control://action` event in order
+ */
+export function useControlActions(enabled: boolean, onAction: (action: ControlAction) => void | Promise<void>): void {
+  const onActionRef = useRef(onAction);
+
+  // Keep the ref in sync with the prop, so actions see the current tabs
+  useEffect(() => {
+    onActionRef.current = onAction;
+  }, [onAction]);
+
+  useEffect(() => {
+    if (!enabled) return;
+
+    let disposed = false;
+    let unlisten: (() => void) | undefined;
+    // Actions run one at a time so tabs open in the order they were asked for
+    let queue: Promise<void> = Promise.resolve();
+
+    const handle = (action: ControlAction) => {
+      queue = queue
+        .then(() => onActionRef.current(action))
+        .catch((err) => console.error(`Failed to run control action ${action.type}:`, err));
+    };
+
+    const setup = async () => {
+      // Listen first: actions sent after the launch actions are taken must not be missed
+      const stop = await listen<ControlAction>('control://action', (event) => handle(event.payload));
+      if (disposed) {
+        stop();
+        return;
+      }
+      unlisten = stop;
+
+      const launch = await invoke<LaunchActions>('take_launch_actions');
+      launch.forEach(handle);
+    };
+
+    setup().catch((err) => console.error('Failed to set up control actions:', err));
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [enabled]);
+}
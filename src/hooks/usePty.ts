@@ -4,16 +4,38 @@
 import { useEffect, useRef, useCallback } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
-import type { SessionInfo, PtyDataEvent, PtyExitEvent } from '@/types';
-import { useSessionStore } from '@/store';
+import type {
+  SessionInfo,
+  PtyDataEvent,
+  PtyExitEvent,
+  PtyHeartbeatEvent,
+  PtyTitleEvent,
+  PtyClipboardEvent,
+  PtyCommandStartedEvent,
+  PtyCommandFinishedEvent,
+  PtyUnresponsiveEvent,
+  PtyLinksEvent,
+  PtyProgressEvent,
+  PtyBellEvent,
+  PtyActivityEvent,
+  PtySilenceEvent,
+  RemoteHostInfo,
+  ReportedCwd,
+} from '@/types';
+import { useSessionStore, useSettingsStore } from '@/store';
+import { forgetLinks, recordLinks } from '@/lib/links';
 
 interface UsePtyOptions {
-  onData?: (data: string) => void;
-  onExit?: (exitCode: number) => void;
+  onData?: (data: string | Uint8Array) => void;
+  onExit?: (exitCode: number | null, signal: string | null) => void;
+  onTitle?: (title: string) => void;
+  onBell?: (bell: PtyBellEvent) => void;
+  // Output resuming or stopping, in sessions monitored for it with set_session_monitor
+  onMonitor?: (alert: { kind: 'activity'; event: PtyActivityEvent } | { kind: 'silence'; event: PtySilenceEvent }) => void;
 }
 
 interface UsePtyReturn {
-  spawn: (shell: string, cols: number, rows: number) => Promise<SessionInfo>;
+  spawn: (shell: string, cols: number, rows: number, cwd?: string, profileId?: string) => Promise<SessionInfo>;
   write: (data: string) => Promise<void>;
   resize: (cols: number, rows: number) => Promise<void>;
   close: () => Promise<void>;
@@ -25,11 +47,14 @@ interface UsePtyReturn {
  * Handles spawning, writing to, resizing, and closing PTY sessions
  */
 export function usePty(sessionId: string | null, options: UsePtyOptions = {}): UsePtyReturn {
-  const { onData, onExit } = options;
+  const { onData, onExit, onTitle, onBell, onMonitor } = options;
   const isConnectedRef = useRef(false);
   const sessionIdRef = useRef<string | null>(sessionId);
   const onDataRef = useRef(onData);
   const onExitRef = useRef(onExit);
+  const onTitleRef = useRef(onTitle);
+  const onBellRef = useRef(onBell);
+  const onMonitorRef = useRef(onMonitor);
   const { addSession, removeSession, updateSession } = useSessionStore();
 
   // Keep the refs in sync with the props
@@ -45,14 +70,30 @@ export function usePty(sessionId: string | null, options: UsePtyOptions = {}): U
     onExitRef.current = onExit;
   }, [onExit]);
 
+  useEffect(() => {
+    onTitleRef.current = onTitle;
+  }, [onTitle]);
+
+  useEffect(() => {
+    onBellRef.current = onBell;
+  }, [onBell]);
+
+  useEffect(() => {
+    onMonitorRef.current = onMonitor;
+  }, [onMonitor]);
+
   // Spawn a new PTY session
-  const spawn = useCallback(async (shell: string, cols: number, rows: number): Promise<SessionInfo> => {
+  const spawn = useCallback(async (shell: string, cols: number, rows: number, cwd?: string, profileId?: string): Promise<SessionInfo> => {
     try {
       const sessionInfo = await invoke<SessionInfo>('spawn_pty', {
         options: {
-          shell,
+          // Left to the backend, which resolves it from the profile or the default profile
+          shell: shell || undefined,
+          cwd,
           cols,
           rows,
+          profileId,
+          sanitizeEnv: useSettingsStore.getState().settings.terminal.sanitizeChildEnvironment,
         },
       });
 
@@ -132,6 +173,7 @@ export function usePty(sessionId: string | null, options: UsePtyOptions = {}): U
 
       // Remove from session store
       removeSession(currentSessionId);
+      forgetLinks(currentSessionId);
     } catch (error) {
       console.error('Failed to close PTY:', error);
       throw error;
@@ -153,19 +195,107 @@ export function usePty(sessionId: string | null, options: UsePtyOptions = {}): U
         onDataRef.current?.(event.payload);
       }),
 
+      // Raw output of sessions spawned with the base64 output encoding
+      listen<string>(`pty://${sessionId}/binary`, (event) => {
+        if (!isMounted) return;
+        onDataRef.current?.(Uint8Array.from(atob(event.payload), (c) => c.charCodeAt(0)));
+      }),
+
       // Listen for exit events
-      listen<{ exitCode: number }>(`pty://${sessionId}/exit`, (event) => {
+      listen<Omit<PtyExitEvent, 'sessionId'>>(`pty://${sessionId}/exit`, (event) => {
         if (!isMounted) return;
-        const { exitCode } = event.payload;
+        const { exitCode, signal } = event.payload;
         isConnectedRef.current = false;
 
         // Update session store
         updateSession(sessionId, {
           status: exitCode === 0 ? 'exited' : 'failed',
           exitCode,
+          exitSignal: signal,
         });
 
-        onExitRef.current?.(exitCode);
+        onExitRef.current?.(exitCode, signal);
+      }),
+
+      // Window titles set by the program, parsed from the output by the backend
+      listen<PtyTitleEvent>(`pty://${sessionId}/title`, (event) => {
+        if (!isMounted) return;
+        onTitleRef.current?.(event.payload.title);
+      }),
+
+      // Bells, rate limited by the backend, which also plays the sound and sets urgency hints
+      listen<PtyBellEvent>(`pty://${sessionId}/bell`, (event) => {
+        if (!isMounted) return;
+        onBellRef.current?.(event.payload);
+      }),
+
+      // Watched sessions whose output resumed or went quiet
+      listen<PtyActivityEvent>(`pty://${sessionId}/activity`, (event) => {
+        if (!isMounted) return;
+        onMonitorRef.current?.({ kind: 'activity', event: event.payload });
+      }),
+      listen<PtySilenceEvent>(`pty://${sessionId}/silence`, (event) => {
+        if (!isMounted) return;
+        onMonitorRef.current?.({ kind: 'silence', event: event.payload });
+      }),
+
+      // Directories reported by the shell, for opening tabs and resolving dropped files there
+      listen<ReportedCwd>(`pty://${sessionId}/cwd`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { reportedCwd: event.payload });
+      }),
+
+      // Commands marked by shell integration, for duration badges and re-running the last one
+      listen<PtyCommandStartedEvent>(`pty://${sessionId}/command-started`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { runningCommand: event.payload });
+      }),
+
+      listen<PtyCommandFinishedEvent>(`pty://${sessionId}/command-finished`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { runningCommand: null, lastCommand: event.payload });
+      }),
+
+      // Hyperlinks and paths in the output, matched to buffer rows by the link provider
+      listen<PtyLinksEvent>(`pty://${sessionId}/links`, (event) => {
+        if (!isMounted) return;
+        recordLinks(sessionId, event.payload);
+      }),
+
+      // OSC 52 requests the session's profile does not allow, which would otherwise fail silently
+      listen<PtyClipboardEvent>(`pty://${sessionId}/clipboard`, (event) => {
+        if (!isMounted || event.payload.allowed) return;
+        console.warn(`Clipboard ${event.payload.access} denied by the profile of session ${sessionId}`);
+      }),
+
+      // Programs that stopped reading input, so the pane can offer to interrupt or kill them
+      listen<PtyUnresponsiveEvent>(`pty://${sessionId}/unresponsive`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { unresponsive: event.payload.unresponsive ? event.payload : null });
+      }),
+
+      // Progress bars programs report, shown on the tab
+      listen<PtyProgressEvent>(`pty://${sessionId}/progress`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { progress: event.payload.progress });
+      }),
+
+      // Where an SSH session actually is, for the tab tooltip
+      listen<RemoteHostInfo>(`pty://${sessionId}/remote-host`, (event) => {
+        if (!isMounted) return;
+        updateSession(sessionId, { remoteHost: event.payload });
+      }),
+
+      // Listen for liveness heartbeats so dead sessions are told apart from quiet ones
+      listen<PtyHeartbeatEvent>(`pty://${sessionId}/heartbeat`, (event) => {
+        if (!isMounted) return;
+        const { childAlive, readerAlive, bytesSinceLast } = event.payload;
+        const session = useSessionStore.getState().getSession(sessionId);
+        const dead = (!childAlive || !readerAlive) && session?.status === 'active';
+        updateSession(sessionId, {
+          lastActivityBytes: bytesSinceLast,
+          ...(dead ? { status: 'dead' } : {}),
+        });
       }),
     ];
 
@@ -1,27 +1,129 @@
 // Broadcast Input - Send input to all terminal panes simultaneously
-// Used when broadcast mode is enabled
+// Used when broadcast mode is enabled, and for sessions in a backend broadcast group, whose
+// members receive what is typed into any of them. Targets are filtered by the backend
+// (pty_write_many): read-only and full-screen sessions are skipped, and root sessions need
+// confirmation.
 
-type BroadcastHandler = (data: string, sourcePaneId: string) => void;
+import { invoke } from '@tauri-apps/api/core';
+import type { BroadcastReport, BroadcastRules, SessionInfo } from '@/types';
+import { usePaneStore, useSessionStore } from '@/store';
+
+type SessionIdGetter = () => string | null;
 
 class BroadcastManager {
-  private handlers: Map<string, BroadcastHandler> = new Map();
+  private panes: Map<string, SessionIdGetter> = new Map();
+  // Root sessions the user answered for in this broadcast run, so they are only asked once
+  private confirmed: Set<string> = new Set();
+  private declined: Set<string> = new Set();
+  // Every keystroke is sent after the one before it, confirmation and resend included
+  private pending: Promise<void> = Promise.resolve();
+
+  constructor() {
+    // A run lasts until broadcast mode is turned off or a group changes; the next one asks again
+    usePaneStore.subscribe((state, previous) => {
+      if (state.broadcastMode !== previous.broadcastMode) this.endRun();
+    });
+  }
 
   // Register a pane to receive broadcast input
-  register(paneId: string, handler: BroadcastHandler) {
-    this.handlers.set(paneId, handler);
+  register(paneId: string, getSessionId: SessionIdGetter) {
+    this.panes.set(paneId, getSessionId);
   }
 
   // Unregister a pane
   unregister(paneId: string) {
-    this.handlers.delete(paneId);
+    this.panes.delete(paneId);
+  }
+
+  // Broadcast input to the sessions of all panes except the source
+  broadcast(data: string, sourcePaneId: string, rules?: BroadcastRules) {
+    return this.enqueue(async () => {
+      const sessionIds: string[] = [];
+      this.panes.forEach((getSessionId, paneId) => {
+        const sessionId = getSessionId();
+        if (paneId !== sourcePaneId && sessionId && !this.declined.has(sessionId)) {
+          sessionIds.push(sessionId);
+        }
+      });
+      if (sessionIds.length === 0) return;
+
+      const report = await this.send(sessionIds, data, rules);
+      await this.resendConfirmed(report, data, rules);
+    });
+  }
+
+  // Write input typed into a session to it and the rest of its broadcast group
+  writeGroup(data: string, sessionId: string, rules?: BroadcastRules) {
+    return this.enqueue(async () => {
+      const report = await invoke<BroadcastReport>('pty_write_broadcast', {
+        sessionIds: [sessionId],
+        data,
+        rules,
+        confirmed: [...this.confirmed],
+      });
+      this.logFailures(report);
+      await this.resendConfirmed(report, data, rules);
+    });
+  }
+
+  // Put a session in a broadcast group, or take it out with null
+  async setGroup(sessionId: string, group: string | null) {
+    const info = await invoke<SessionInfo>('set_broadcast_group', { sessionId, group });
+    useSessionStore.getState().updateSession(sessionId, { broadcastGroup: info.broadcastGroup });
+    this.endRun();
+  }
+
+  // Forget the answers about root targets
+  private endRun() {
+    this.confirmed.clear();
+    this.declined.clear();
+  }
+
+  // Run a send once the previous one is done, whether or not it failed
+  private enqueue(task: () => Promise<void>) {
+    const run = this.pending.then(task);
+    this.pending = run.catch(() => undefined);
+    return run;
+  }
+
+  // Ask about root targets the backend held back, and send to the ones the user allows
+  //
+  // Only the first keystroke after a root target turns up takes this second send; the ones
+  // after it go out with the target confirmed. The queue keeps it ahead of the next keystroke.
+  private async resendConfirmed(report: BroadcastReport, data: string, rules?: BroadcastRules) {
+    const unconfirmed = report.skipped
+      .filter((target) => target.reason === 'needsConfirmation')
+      .map((target) => target.sessionId);
+    const unanswered = unconfirmed.filter(
+      (sessionId) => !this.confirmed.has(sessionId) && !this.declined.has(sessionId)
+    );
+    if (unanswered.length > 0) {
+      const allow = window.confirm(
+        `${unanswered.length} broadcast target(s) run as root. Send broadcast input to them too?`
+      );
+      unanswered.forEach((sessionId) => (allow ? this.confirmed : this.declined).add(sessionId));
+    }
+
+    const approved = unconfirmed.filter((sessionId) => this.confirmed.has(sessionId));
+    if (approved.length > 0) {
+      await this.send(approved, data, rules);
+    }
+  }
+
+  private async send(sessionIds: string[], data: string, rules?: BroadcastRules) {
+    const report = await invoke<BroadcastReport>('pty_write_many', {
+      sessionIds,
+      data,
+      rules,
+      confirmed: [...this.confirmed],
+    });
+    this.logFailures(report);
+    return report;
   }
 
-  // Broadcast input to all panes except the source
-  broadcast(data: string, sourcePaneId: string) {
-    this.handlers.forEach((handler, paneId) => {
-      if (paneId !== sourcePaneId) {
-        handler(data, sourcePaneId);
-      }
+  private logFailures(report: BroadcastReport) {
+    report.failed.forEach((target) => {
+      console.error(`[Broadcast] Failed to write to ${target.sessionId}:`, target.error);
     });
   }
 }
@@ -0,0 +1,19 @@
+// Health - Startup self-checks for the first-run experience
+// The backend checks its side at launch; what only the webview can tell is passed along here.
+
+import { invoke } from '@tauri-apps/api/core';
+import type { HealthReport, RendererStatus } from '@/types';
+
+// WebGL support of the webview, which WebKitGTK turns off without hardware acceleration
+export function rendererStatus(): RendererStatus {
+  try {
+    const canvas = document.createElement('canvas');
+    return { webgl: Boolean(canvas.getContext('webgl2') ?? canvas.getContext('webgl')) };
+  } catch (error) {
+    return { webgl: false, error: String(error) };
+  }
+}
+
+export function getHealthReport(refresh = false): Promise<HealthReport> {
+  return invoke<HealthReport>('get_health_report', { renderer: rendererStatus(), refresh });
+}
@@ -0,0 +1,116 @@
+// Links - Clickable OSC 8 hyperlinks and paths from the backend's output scan
+// The backend publishes lines of output with their links; rows of the buffer are matched to them
+// by text, so links survive scrolling and reflow for as long as the line is remembered.
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ILink, ILinkProvider, Terminal as XTerm } from 'xterm';
+import type { LinkedLine, OutputLink } from '@/types';
+
+// Lines remembered per session, most recently printed kept
+const MAX_LINES = 2000;
+
+const sessions = new Map<string, Map<string, OutputLink[]>>();
+
+export function recordLinks(sessionId: string, lines: LinkedLine[]) {
+  let known = sessions.get(sessionId);
+  if (!known) {
+    known = new Map();
+    sessions.set(sessionId, known);
+  }
+  for (const line of lines) {
+    // Re-inserted so a line printed again counts as recent
+    known.delete(line.text);
+    known.set(line.text, line.links);
+  }
+  for (const text of known.keys()) {
+    if (known.size <= MAX_LINES) break;
+    known.delete(text);
+  }
+}
+
+export function forgetLinks(sessionId: string) {
+  sessions.delete(sessionId);
+}
+
+export function openLink(link: OutputLink, sessionId: string | null) {
+  const request =
+    link.kind === 'file' || link.kind === 'directory'
+      ? invoke('open_path', { path: link.target, sessionId })
+      : invoke('open_url', { url: link.target });
+  request.catch((error) => console.error(`Failed to open ${link.target}:`, error));
+}
+
+interface Cell {
+  x: number;
+  y: number;
+  width: number;
+}
+
+/**
+ * Link provider for hyperlinks and paths; plain URLs are left to the web links addon, which also
+ * finds them on lines still being typed
+ */
+export function createOutputLinkProvider(
+  terminal: XTerm,
+  getSessionId: () => string | null,
+  container: HTMLElement
+): ILinkProvider {
+  return {
+    provideLinks(y, callback) {
+      const sessionId = getSessionId();
+      const known = sessionId ? sessions.get(sessionId) : undefined;
+      if (!known) return callback(undefined);
+
+      // A printed line may be wrapped over several rows; collect all of them, with the cell
+      // each UTF-16 unit of the text is in
+      const buffer = terminal.buffer.active;
+      let first = y - 1;
+      while (first > 0 && buffer.getLine(first)?.isWrapped) first--;
+      let text = '';
+      const cells: Cell[] = [];
+      for (let row = first; ; row++) {
+        const line = buffer.getLine(row);
+        if (!line || (row > first && !line.isWrapped)) break;
+        for (let x = 0; x < line.length; x++) {
+          const cell = line.getCell(x);
+          // The second half of a wide character has no text of its own
+          if (!cell || cell.getWidth() === 0) continue;
+          const chars = cell.getChars() || ' ';
+          text += chars;
+          for (let i = 0; i < chars.length; i++) {
+            cells.push({ x: x + 1, y: row + 1, width: cell.getWidth() });
+          }
+        }
+      }
+      text = text.trimEnd();
+
+      const links = known.get(text);
+      if (!links) return callback(undefined);
+
+      const found: ILink[] = links
+        .filter((link) => link.kind !== 'url' && link.end <= cells.length && link.start < link.end)
+        .filter((link) => cells[link.start].y <= y && cells[link.end - 1].y >= y)
+        .map((link) => {
+          const last = cells[link.end - 1];
+          return {
+            range: {
+              start: { x: cells[link.start].x, y: cells[link.start].y },
+              end: { x: last.x + last.width - 1, y: last.y },
+            },
+            text: text.slice(link.start, link.end),
+            activate: (event) => {
+              // Like URLs, only opened when Ctrl is pressed
+              if (event.ctrlKey) openLink(link, sessionId);
+            },
+            hover: (event) => {
+              container.title = event.ctrlKey ? link.target : `Ctrl+Click to open: ${link.target}`;
+            },
+            leave: () => {
+              container.title = '';
+            },
+          };
+        });
+      callback(found.length > 0 ? found : undefined);
+    },
+  };
+}
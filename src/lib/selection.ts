@@ -0,0 +1,63 @@
+// Selection - Path-aware double-click and copy cleanup
+// Applies a profile's selection settings (validated by the backend) on top of xterm's
+// word selection.
+
+import type { SelectionSettings } from '@/types';
+
+// Absolute, home- or dot-relative paths, and paths with a directory part, with an optional
+// :line:column suffix as compilers print them
+const BUILTIN_PATH_PATTERNS = [
+  String.raw`[a-zA-Z][a-zA-Z0-9+.-]*://[^\s"'<>()\[\]{}]+`,
+  String.raw`(?:~|\.{1,2})?(?:/[\w.@%+~-]+)+/?(?::\d+(?::\d+)?)?`,
+  String.raw`[\w.@%+-]+(?:/[\w.@%+~-]+)+/?(?::\d+(?::\d+)?)?`,
+];
+
+const compiled = new Map<string, RegExp | null>();
+
+function compile(pattern: string): RegExp | null {
+  let regex = compiled.get(pattern);
+  if (regex === undefined) {
+    try {
+      regex = new RegExp(pattern, 'g');
+    } catch {
+      // Accepted by the backend but not by JavaScript
+      regex = null;
+    }
+    compiled.set(pattern, regex);
+  }
+  return regex;
+}
+
+/**
+ * The widest path match covering a column of a line, as [start, end)
+ */
+export function pathRangeAt(
+  line: string,
+  column: number,
+  selection?: SelectionSettings,
+): [number, number] | null {
+  if (selection?.selectPaths === false) return null;
+  const patterns = [...(selection?.pathPatterns ?? []), ...BUILTIN_PATH_PATTERNS];
+  let best: [number, number] | null = null;
+  for (const pattern of patterns) {
+    const regex = compile(pattern);
+    if (!regex) continue;
+    regex.lastIndex = 0;
+    for (const match of line.matchAll(regex)) {
+      const start = match.index ?? 0;
+      const end = start + match[0].length;
+      if (start <= column && column < end && (!best || end - start > best[1] - best[0])) {
+        best = [start, end];
+      }
+    }
+  }
+  return best;
+}
+
+/**
+ * Text to copy from a selection
+ */
+export function selectionText(text: string, selection?: SelectionSettings): string {
+  if (selection?.trimTrailingWhitespace === false) return text;
+  return text.replace(/[ \t]+$/gm, '');
+}
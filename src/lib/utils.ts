@@ -4,3 +4,8 @@ import { twMerge } from "tailwind-merge";
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs));
 }
+
+/** Quote a word for the shell, for command lines typed into a session */
+export function shellQuote(word: string) {
+  return `'${word.replace(/'/g, `'\\''`)}'`;
+}
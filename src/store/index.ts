@@ -5,3 +5,4 @@ export { usePaneStore } from './paneStore';
 export { useSessionStore } from './sessionStore';
 export { useSettingsStore } from './settingsStore';
 export { usePluginsStore } from './pluginsStore';
+export { useStatusStore } from './statusStore';
@@ -23,6 +23,7 @@ interface PaneState {
   getAllLeafPanes: () => Pane[];
   toggleBroadcastMode: () => void;
   setActivePaneId: (paneId: string | null) => void;
+  restoreNodes: (nodes: PaneNode[]) => void;
 }
 
 export const usePaneStore = create<PaneState>((set, get) => ({
@@ -226,6 +227,14 @@ export const usePaneStore = create<PaneState>((set, get) => ({
     });
   },
 
+  restoreNodes: (restored) => {
+    set((state) => {
+      const nodes = new Map(state.nodes);
+      restored.forEach((node) => nodes.set(node.id, node));
+      return { nodes };
+    });
+  },
+
   getSessionId: (paneId) => {
     const node = get().nodes.get(paneId);
     if (node && node.type === 'leaf') {
@@ -3,16 +3,35 @@
 
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { Settings, Profile, ColorScheme, KeyBinding, TerminalSettings, MouseAction } from '@/types';
+import { listen } from '@tauri-apps/api/event';
+import type {
+  Settings,
+  Profile,
+  ColorScheme,
+  KeyBinding,
+  TerminalSettings,
+  MouseAction,
+  ConflictSide,
+  SettingsConflict,
+  SettingsChangedEvent,
+  SettingsRecoveredEvent,
+  SettingsSaveResult,
+  SettingsValidationError,
+} from '@/types';
 import { defaultSettings } from '@/types/settings';
 
 interface SettingsState {
   settings: Settings;
   isLoading: boolean;
+  // Settings changed both here and on disk, waiting for resolveConflicts
+  conflicts: SettingsConflict[];
+  // Problems that kept the last save from being written
+  validationErrors: SettingsValidationError[];
 
   // Actions
   loadSettings: () => Promise<void>;
   saveSettings: () => Promise<void>;
+  resolveConflicts: (prefer: ConflictSide, choices?: Record<string, ConflictSide>) => Promise<void>;
   updateSettings: (updates: Partial<Settings>) => void;
   updateTerminalSettings: (updates: Partial<TerminalSettings>) => void;
   addProfile: (profile: Profile) => void;
@@ -37,33 +56,95 @@ interface SettingsState {
   resetKeyBindings: () => void;
 }
 
+const isPlainObject = (value: unknown): value is Record<string, unknown> =>
+  typeof value === 'object' && value !== null && !Array.isArray(value);
+
+// Fill in settings added in updates or left out by the backend as invalid. Sections
+// like `terminal` are merged too, so one missing field does not drop the rest.
+const withDefaults = (saved: Partial<Settings>): Settings => {
+  const merged: Record<string, unknown> = { ...defaultSettings, ...saved };
+  for (const [key, value] of Object.entries(defaultSettings)) {
+    const savedValue = (saved as Record<string, unknown>)[key];
+    if (isPlainObject(value) && isPlainObject(savedValue)) {
+      merged[key] = { ...value, ...savedValue };
+    }
+  }
+  return merged as unknown as Settings;
+};
+
+// Save settings; changes another program made on disk are merged in by the backend
+const save = async (settings: Settings) => {
+  const result = await invoke<SettingsSaveResult>('save_settings', { settings });
+  if (result.status === 'merged' && result.settings) {
+    useSettingsStore.setState({
+      settings: withDefaults(result.settings as Partial<Settings>),
+      conflicts: [],
+      validationErrors: [],
+    });
+  } else if (result.status === 'conflict') {
+    console.warn('Settings changed on disk in the same places as here:', result.conflicts);
+    useSettingsStore.setState({ conflicts: result.conflicts, validationErrors: [] });
+  } else if (result.status === 'invalid') {
+    console.warn('Settings not saved:', result.errors);
+    useSettingsStore.setState({ validationErrors: result.errors });
+  } else {
+    useSettingsStore.setState({ conflicts: [], validationErrors: [] });
+  }
+};
+
 // Debounce save to avoid excessive disk writes
 let saveTimeout: ReturnType<typeof setTimeout> | null = null;
 const debouncedSave = (settings: Settings) => {
   if (saveTimeout) clearTimeout(saveTimeout);
   saveTimeout = setTimeout(async () => {
+    saveTimeout = null;
     try {
-      await invoke('save_settings', { settings });
+      await save(settings);
     } catch (error) {
       console.error('Failed to save settings:', error);
     }
   }, 500);
 };
 
+// Pick up outside edits to the settings file. With a save pending they are left
+// for the backend to merge when it happens.
+let watching = false;
+const watchExternalChanges = () => {
+  if (watching) return;
+  watching = true;
+  listen<SettingsChangedEvent>('settings://changed', (event) => {
+    if (saveTimeout || !event.payload.settings) return;
+    useSettingsStore.getState().loadSettings();
+  }).catch((error) => {
+    watching = false;
+    console.error('Failed to watch settings file:', error);
+  });
+  listen<SettingsRecoveredEvent>('settings://recovered', (event) => {
+    const { error, backupPath, corruptPath } = event.payload;
+    console.warn(
+      `Settings file was corrupt (${error}); loaded ${backupPath}` +
+        (corruptPath ? `, corrupt file kept at ${corruptPath}` : ''),
+    );
+  }).catch((error) => {
+    console.error('Failed to watch settings recovery:', error);
+  });
+};
+
 export const useSettingsStore = create<SettingsState>((set, get) => ({
   settings: defaultSettings,
   isLoading: false,
+  conflicts: [],
+  validationErrors: [],
 
   loadSettings: async () => {
+    watchExternalChanges();
     set({ isLoading: true });
     try {
       const savedSettings = await invoke<Settings | null>('load_settings');
       if (savedSettings) {
-        // Merge with defaults to handle new settings added in updates
-        const mergedSettings = { ...defaultSettings, ...savedSettings };
-        set({ settings: mergedSettings, isLoading: false });
+        set({ settings: withDefaults(savedSettings), isLoading: false, conflicts: [] });
       } else {
-        set({ settings: defaultSettings, isLoading: false });
+        set({ settings: defaultSettings, isLoading: false, conflicts: [] });
       }
     } catch (error) {
       console.error('Failed to load settings:', error);
@@ -74,12 +155,23 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   saveSettings: async () => {
     const { settings } = get();
     try {
-      await invoke('save_settings', { settings });
+      await save(settings);
     } catch (error) {
       console.error('Failed to save settings:', error);
     }
   },
 
+  resolveConflicts: async (prefer, choices) => {
+    try {
+      const resolved = await invoke<Settings>('resolve_settings_conflict', { prefer, choices });
+      set({ settings: withDefaults(resolved), conflicts: [] });
+    } catch (error) {
+      console.error('Failed to resolve settings conflict:', error);
+      // The file changed again; saving re-merges against the latest version
+      await get().saveSettings();
+    }
+  },
+
   updateSettings: (updates) => {
     set((state) => {
       const newSettings = { ...state.settings, ...updates };
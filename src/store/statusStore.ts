@@ -0,0 +1,78 @@
+// Status Store - Status bar segment values
+// Mirrors the backend's `status://update` stream, which carries every segment, built-in or plugin
+
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { SegmentInfo, SegmentValue, StatusUpdate } from '@/types';
+
+interface StatusState {
+  segments: SegmentInfo[];
+  // Keyed by segment ID, then by session ID ('' for global segments)
+  values: Map<string, Map<string, SegmentValue>>;
+
+  // Actions
+  loadSegments: () => Promise<void>;
+  applyUpdate: (update: StatusUpdate) => void;
+  getValues: (sessionId: string | null) => { segment: SegmentInfo; value: SegmentValue }[];
+}
+
+let watching = false;
+
+// Subscribe once, then fetch what was shown before the subscription started
+const watchStatus = () => {
+  if (watching) return;
+  watching = true;
+  listen<StatusUpdate>('status://update', (event) => {
+    useStatusStore.getState().applyUpdate(event.payload);
+  })
+    .then(() => invoke<StatusUpdate[]>('get_status'))
+    .then((updates) => updates.forEach((update) => useStatusStore.getState().applyUpdate(update)))
+    .catch((error) => {
+      watching = false;
+      console.error('Failed to watch status segments:', error);
+    });
+};
+
+export const useStatusStore = create<StatusState>((set, get) => ({
+  segments: [],
+  values: new Map(),
+
+  loadSegments: async () => {
+    watchStatus();
+    try {
+      const segments = await invoke<SegmentInfo[]>('list_status_segments');
+      set({ segments });
+    } catch (error) {
+      console.error('Failed to list status segments:', error);
+    }
+  },
+
+  applyUpdate: ({ segment, sessionId, value }) => {
+    set((state) => {
+      const values = new Map(state.values);
+      const bySession = new Map(values.get(segment));
+      if (value) {
+        bySession.set(sessionId ?? '', value);
+      } else {
+        bySession.delete(sessionId ?? '');
+      }
+      values.set(segment, bySession);
+      return { values };
+    });
+    // A plugin segment registered after the list was loaded
+    if (value && !get().segments.some((s) => s.id === segment)) {
+      get().loadSegments();
+    }
+  },
+
+  // Values to show for a session: its session segments and the global ones, in segment order
+  getValues: (sessionId) => {
+    const { segments, values } = get();
+    return segments.flatMap((segment) => {
+      const key = segment.scope === 'global' ? '' : sessionId;
+      const value = key === null ? undefined : values.get(segment.id)?.get(key);
+      return value ? [{ segment, value }] : [];
+    });
+  },
+}));
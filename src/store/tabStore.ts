@@ -24,6 +24,7 @@ interface TabState {
   getTabByPaneId: (paneId: string) => Tab | null;
   updateTabRootPaneId: (tabId: string, newRootPaneId: string) => void;
   getTabByRootPaneId: (rootPaneId: string) => Tab | null;
+  restoreTabs: (tabs: Tab[], activeTabId: string | null) => void;
 }
 
 export const useTabStore = create<TabState>((set, get) => ({
@@ -181,4 +182,12 @@ export const useTabStore = create<TabState>((set, get) => ({
     const { tabs } = get();
     return tabs.find((t) => t.rootPaneId === rootPaneId) || null;
   },
+
+  restoreTabs: (restored, activeTabId) => {
+    set((state) => {
+      const activeId = activeTabId ?? restored[0]?.id ?? state.activeTabId;
+      const tabs = [...state.tabs, ...restored].map((t) => ({ ...t, isActive: t.id === activeId }));
+      return { tabs, activeTabId: activeId };
+    });
+  },
 }));
@@ -1,16 +1,177 @@
 // IPC (Inter-Process Communication) type definitions
 // These types ensure type safety between the Rust backend and TypeScript frontend
 
+import type { BroadcastRules, ColorScheme } from './settings';
+import type { PaneNode, Tab, TerminalTheme } from './terminal';
+
 // ==================== Commands (Frontend → Rust) ====================
 
+/**
+ * Environment inheritance: everything, a login-like baseline
+ * (identity, locale, desktop session), or only listed variables
+ */
+export type EnvInheritance = 'full' | 'clean' | 'allowlist';
+
+export type OutputEncoding = 'text' | 'base64';
+
 /**
  * Options for spawning a new PTY session
  */
 export interface SpawnPtyCommand {
-  shell?: string;
+  shell?: string; // Default: from profileId, else the default profile, else $SHELL
+  args?: string[];
+  /**
+   * Working directory of the shell, e.g. from pty_get_cwd of the current tab (default: home)
+   */
+  cwd?: string;
   cols: number;
   rows: number;
   env?: Record<string, string>;
+  /**
+   * Tab title template; placeholders: {process} {cwd} {host} {location} {status} {shell} {name}
+   */
+  titleTemplate?: string;
+  /**
+   * Reply sent when the application sends ENQ (0x05)
+   */
+  answerback?: string;
+  /**
+   * Strip AppImage/Flatpak/Snap variables from the child environment (default true)
+   */
+  sanitizeEnv?: boolean;
+  /**
+   * Which of the app's environment variables the shell inherits (default 'full')
+   */
+  envInheritance?: EnvInheritance;
+  /**
+   * Variables inherited on top of the clean baseline, or the whole allowlist;
+   * a trailing '*' matches a prefix, e.g. 'LC_*'
+   */
+  inheritVars?: string[];
+  /**
+   * Cap on backend buffer memory for this session, in megabytes (default 64)
+   */
+  memoryLimitMb?: number;
+  /**
+   * Keep the session out of history, logs, recordings, clipboard history, and restore;
+   * cannot be changed after spawn
+   */
+  incognito?: boolean;
+  /**
+   * CPU niceness of the shell, -20 (highest) to 19 (lowest); negative values need privileges
+   */
+  nice?: number;
+  /**
+   * I/O scheduling priority of the shell
+   */
+  ionice?: IoPriority;
+  /**
+   * OOM score adjustment of the shell, -1000 to 1000; positive values make the kernel kill
+   * the session's processes before the terminal under memory pressure
+   */
+  oomScoreAdj?: number;
+  /**
+   * File mode creation mask of the shell in octal, e.g. "022"
+   */
+  umask?: string;
+  /**
+   * Soft limit on open file descriptors (`ulimit -n`)
+   */
+  openFilesLimit?: ResourceLimit;
+  /**
+   * Soft limit on core dump size in KiB (`ulimit -c`); 0 disables core dumps
+   */
+  coreDumpSizeKb?: ResourceLimit;
+  /**
+   * Run the shell in a bubblewrap sandbox that only sees the system directories and the
+   * given paths; requires `bwrap`
+   */
+  sandbox?: SandboxOptions;
+  /**
+   * Give the session its own TMPDIR, removed when the session is closed
+   */
+  privateTmp?: boolean;
+  /**
+   * Use an empty home seeded with the shell startup files, removed when the session is closed
+   */
+  throwawayHome?: boolean;
+  /**
+   * The session only displays output; broadcast input skips it
+   */
+  readOnly?: boolean;
+  /**
+   * Deliver output as decoded text on `pty://{id}/data` (default), or as raw base64-encoded
+   * bytes on `pty://{id}/binary` for programs that emit binary data (zmodem, sixel)
+   */
+  outputEncoding?: OutputEncoding;
+  profileId?: string; // Settings profile the session was opened from, for new tab suggestions
+  context?: SpawnContext; // Where the tab was asked for; picks that context's default profile
+  scrollbackMb?: number; // Output kept in the backend for replay; 0 keeps none (default 4)
+  clipboard?: ClipboardPolicy; // OSC 52 access; defaults to the profile's
+  bell?: BellSettings; // What ringing the bell does; defaults to the profile's
+}
+
+/**
+ * Where a spawn was asked for; settings.defaultProfiles can give each its own default profile,
+ * used when neither the spawn nor a profile rule picks one
+ */
+export type SpawnContext = 'tab' | 'window' | 'quake' | 'workingDirectory';
+
+/**
+ * Whether programs may use one side of the clipboard through OSC 52
+ */
+export type ClipboardAccess = 'allow' | 'deny';
+
+/**
+ * What a profile's programs may do with the clipboard through OSC 52
+ * Reading is denied by default, since anything printed to the terminal could take the clipboard
+ */
+export interface ClipboardPolicy {
+  write?: ClipboardAccess; // Default 'allow'
+  read?: ClipboardAccess; // Default 'deny'
+}
+
+/**
+ * What a profile's sessions do when a program rings the bell
+ */
+export interface BellSettings {
+  flash?: boolean; // Flash the terminal (default true)
+  sound?: boolean; // Play the desktop theme's bell sound (default false)
+  urgent?: boolean; // Set the urgency hint while the window is not focused (default true)
+}
+
+/**
+ * Filesystem and network access of a sandboxed session
+ */
+export interface SandboxOptions {
+  /**
+   * Host paths visible read-write; the first is the starting directory
+   */
+  writable?: string[];
+  /**
+   * Host paths visible read-only
+   */
+  readOnly?: string[];
+  /**
+   * Keep network access (default false)
+   */
+  network?: boolean;
+}
+
+/**
+ * Resource limit value; must not exceed the inherited hard limit
+ */
+export type ResourceLimit = number | 'unlimited';
+
+/**
+ * I/O scheduling priority, as in ionice(1)
+ */
+export interface IoPriority {
+  class: 'realtime' | 'bestEffort' | 'idle';
+  /**
+   * 0 (highest) to 7 (lowest); ignored by the idle class (default 4)
+   */
+  level?: number;
 }
 
 /**
@@ -20,8 +181,67 @@ export interface SessionInfo {
   id: string;
   pid: number;
   shell: string;
+  modes: TerminalModes;
+  name: string | null;
+  labels: string[];
+  appearance: SessionAppearance;
+  title: string | null;
+  programTitle: string | null; // Set by the program with OSC 0/2, e.g. "vim ~/file.rs"
+  reportedCwd: ReportedCwd | null; // Reported by the shell with OSC 7
+  lastCommand: string | null; // Last command run, from shell integration's prompt marks (OSC 133)
+  elevated: boolean;
+  container: SessionContainer | null; // Where the session runs, when that is not the host
+  incognito: boolean;
+  sandboxed: boolean;
+  readOnly: boolean;
+  outputEncoding: OutputEncoding;
+  serial: SerialOptions | null; // Set for serial port sessions, which have no process (pid 0)
+  profileId: string | null; // Backend profile used, possibly chosen by a ProfileRule
+  remoteHost: RemoteHostInfo | null; // System an SSH session is connected to, once probed
+  progress: TerminalProgress | null; // Reported by the running program with OSC 9;4
+  monitor: MonitorSettings; // Whether activity and silence are reported
+  broadcastGroup: string | null; // Group whose members receive the session's input
+}
+
+/**
+ * Container a session runs in: entered (toolbox enter, docker exec), a different namespace,
+ * or the sandbox the app itself runs in
+ */
+export interface SessionContainer {
+  runtime: string; // 'toolbox', 'distrobox', 'podman', 'docker', 'flatpak', 'lxc', ... or 'namespace'
+  name: string | null;
+}
+
+/**
+ * Tab presentation metadata stored per session
+ * Also emitted on `pty://{id}/appearance` when changed
+ */
+export interface SessionAppearance {
+  color: string | null;
+  icon: string | null;
+  emoji: string | null;
+}
+
+/**
+ * DEC private modes tracked by the backend for a session
+ */
+export interface TerminalModes {
+  altScreen: boolean;
+  mouseTracking: MouseTracking;
+  mouseEncoding: MouseEncoding;
+  bracketedPaste: boolean;
 }
 
+/**
+ * Mouse events the application asked to receive (DECSET 9/1000/1002/1003)
+ */
+export type MouseTracking = 'none' | 'x10' | 'normal' | 'buttonEvent' | 'anyEvent';
+
+/**
+ * Mouse report encoding (DECSET 1005/1006/1015)
+ */
+export type MouseEncoding = 'default' | 'utf8' | 'sgr' | 'urxvt';
+
 /**
  * Arguments for pty_write command
  */
@@ -30,6 +250,72 @@ export interface WritePtyCommand {
   data: string;
 }
 
+/**
+ * Arguments for pty_write_many command
+ */
+export interface WriteManyPtyCommand {
+  sessionIds: string[];
+  data: string;
+  /**
+   * Filters checked for every target; all enabled when omitted
+   */
+  rules?: BroadcastRules;
+  /**
+   * Root sessions the user agreed to send to
+   */
+  confirmed?: string[];
+}
+
+/**
+ * Arguments for pty_write_broadcast command: the sessions typed into get the input as it is,
+ * the rest of their broadcast groups get it filtered by the rules
+ */
+export interface WriteBroadcastPtyCommand {
+  sessionIds: string[]; // The sessions the input was typed into
+  data: string;
+  rules?: BroadcastRules;
+  confirmed?: string[];
+}
+
+/**
+ * Arguments for set_broadcast_group command; returns the updated SessionInfo
+ */
+export interface SetBroadcastGroupCommand {
+  sessionId: string;
+  group: string | null; // Group to join; null or empty leaves the current one
+}
+
+/**
+ * Sessions that receive each other's input, as returned by list_broadcast_groups
+ */
+export interface BroadcastGroup {
+  name: string;
+  sessionIds: string[];
+}
+
+/**
+ * Outcome of pty_write_many and pty_write_broadcast
+ */
+export interface BroadcastReport {
+  sent: string[];
+  skipped: {
+    sessionId: string;
+    reason: 'notFound' | 'readOnly' | 'altScreen' | 'needsConfirmation';
+  }[];
+  failed: {
+    sessionId: string;
+    error: string;
+  }[];
+}
+
+/**
+ * Arguments for pty_paste command
+ */
+export interface PastePtyCommand {
+  sessionId: string;
+  text: string;
+}
+
 /**
  * Arguments for pty_resize command
  */
@@ -46,22 +332,1327 @@ export interface ClosePtyCommand {
   sessionId: string;
 }
 
-// ==================== Events (Rust → Frontend) ====================
+/**
+ * Signals pty_kill can send; they go to the shell's process group and the
+ * foreground job's
+ */
+export type PtySignal = 'SIGINT' | 'SIGTERM' | 'SIGKILL' | 'SIGHUP';
 
 /**
- * PTY data event - emitted when the PTY has output
+ * Arguments for pty_kill command
  */
-export interface PtyDataEvent {
+export interface PtyKillCommand {
   sessionId: string;
-  data: string;
+  signal: PtySignal;
 }
 
 /**
- * PTY exit event - emitted when the shell process exits
+ * Arguments for pty_signal_foreground command, which signals the foreground job but not
+ * the shell; fails when the shell itself is in the foreground
  */
-export interface PtyExitEvent {
+export interface PtySignalForegroundCommand {
+  sessionId: string;
+  signal: PtySignal;
+}
+
+/**
+ * What a session is watched for, like tmux's monitor-activity and monitor-silence; both off
+ * by default
+ */
+export interface MonitorSettings {
+  activity?: boolean; // Report output that resumes after 5 s or more without any
+  silenceSeconds?: number | null; // Report this long without output, once until output comes again
+}
+
+/**
+ * Arguments for set_session_monitor command; returns the updated SessionInfo
+ */
+export interface SetSessionMonitorCommand {
+  sessionId: string;
+  monitor: MonitorSettings;
+}
+
+/**
+ * Options for exec_command (non-interactive execution)
+ */
+export interface ExecCommandOptions {
+  program: string;
+  args?: string[];
+  cwd?: string;
+  env?: Record<string, string>;
+  timeoutMs?: number;
+  stdin?: string; // Written to standard input, which is empty otherwise
+}
+
+/**
+ * Captured output returned from exec_command
+ */
+export interface ExecOutput {
+  stdout: string;
+  stderr: string;
+  exitCode: number | null;
+  timedOut: boolean; // Killed at the timeout; stdout and stderr hold what it wrote until then
+  truncated: boolean; // stdout or stderr went past the size kept and was cut short
+  durationMs: number;
+}
+
+/**
+ * Background job returned from list_jobs
+ */
+export interface JobInfo {
+  pid: number;
+  pgid: number;
+  command: string;
+  stopped: boolean;
+}
+
+/**
+ * Startup profile returned from profile_shell_startup
+ */
+export interface ShellStartupProfile {
+  shell: string;
+  runsMs: number[];
+  averageMs: number;
+  breakdown: {
+    source: string;
+    durationMs: number;
+    lines: number;
+  }[];
+}
+
+/**
+ * Prompt framework found in the user's shell configuration
+ */
+export interface ShellFramework {
+  id: 'ohMyZsh' | 'powerlevel10k' | 'prezto' | 'starship' | 'ohMyBash' | 'bashIt' | 'ohMyFish' | 'tide';
+  shell: 'zsh' | 'bash' | 'fish';
+  theme: string | null;
+  /**
+   * Configuration file it was found in
+   */
+  source: string;
+}
+
+/**
+ * Shell configuration report returned from detect_shell_frameworks
+ */
+export interface ShellFrameworks {
+  frameworks: ShellFramework[];
+  /**
+   * Configuration files that already emit OSC 133 prompt marks
+   */
+  promptMarks: string[];
+}
+
+/**
+ * Arguments for bootstrap_prompt command
+ */
+export interface BootstrapPromptCommand {
+  options: {
+    /**
+     * The active color scheme; colors must be hex
+     */
+    colors: Pick<ColorScheme, 'foreground' | 'red' | 'green' | 'yellow' | 'blue' | 'magenta' | 'cyan' | 'brightBlack'>;
+    schemeName?: string;
+    /**
+     * Shells whose startup file gets the starship init line
+     */
+    shells?: ('zsh' | 'bash' | 'fish')[];
+  };
+}
+
+/**
+ * Files written by bootstrap_prompt
+ */
+export interface PromptBootstrapReport {
+  configPath: string;
+  modified: string[];
+  backups: string[];
+  /**
+   * The init lines do nothing until starship is installed
+   */
+  starshipInstalled: boolean;
+}
+
+/**
+ * Glyph coverage report returned from check_font_coverage
+ */
+export interface FontCoverageReport {
+  requestedFamily: string;
+  resolvedFamily: string;
+  file: string;
+  substituted: boolean;
+  groups: {
+    group: 'powerline' | 'nerdFont' | 'emoji';
+    covered: number;
+    total: number;
+    fallback: number;
+    missing: string[];
+  }[];
+}
+
+/**
+ * Terminfo self-check report returned from check_terminfo
+ */
+export interface TerminfoReport {
+  term: string;
+  found: boolean;
+  path: string | null;
+  capabilities: {
+    name: string;
+    expected: string;
+    actual: string | null;
+    ok: boolean;
+  }[];
+  mismatches: string[];
+}
+
+/**
+ * What the frontend knows about rendering, passed to get_health_report
+ */
+export interface RendererStatus {
+  webgl: boolean;
+  error?: string | null; // Error the terminal renderer failed with
+}
+
+/**
+ * Arguments for get_health_report command
+ */
+export interface GetHealthReportCommand {
+  renderer?: RendererStatus;
+  refresh?: boolean; // Run the checks again instead of returning the launch results
+}
+
+/**
+ * Outcome of one startup self-check
+ */
+export interface HealthCheck {
+  id: 'config' | 'shell' | 'terminfo' | 'pty' | 'renderer';
+  status: 'ok' | 'warning' | 'error'; // Errors keep terminals from working
+  message: string;
+  fix: string | null; // What the user can do about it
+}
+
+/**
+ * Result of the startup self-checks, returned from get_health_report
+ */
+export interface HealthReport {
+  checks: HealthCheck[];
+  ok: boolean; // No check found an error
+  checkedAt: number; // Unix time in milliseconds
+}
+
+/**
+ * Backend buffer memory of a session, returned from get_memory_usage
+ * Also the payload of `pty://{id}/memory-warning`
+ */
+export interface MemoryUsage {
+  scrollbackBytes: number;
+  pendingBytes: number;
+  totalBytes: number;
+  limitBytes: number;
+}
+
+/**
+ * Traffic of one ssh, mosh-client, or telnet process in a session
+ */
+export interface RemoteConnection {
+  pid: number;
+  program: string;
+  host: string | null;
+  bytesSent: number;
+  bytesReceived: number;
+}
+
+/**
+ * Estimated network traffic of a session's remote clients, returned from
+ * pty_get_bandwidth; also the payload of `pty://{id}/bandwidth`
+ * Counts include encryption but not TCP/IP headers
+ */
+export interface BandwidthStats {
+  bytesSent: number; // Since the session started, including clients that exited
+  bytesReceived: number;
+  connections: RemoteConnection[]; // Clients running now
+}
+
+/**
+ * Arguments for pty_open_input_pipe and pty_close_input_pipe commands
+ * pty_open_input_pipe returns the FIFO path; lines written to it starting with
+ * `#!xterminal paste <text>` or `#!xterminal key <name>` are control commands
+ */
+export interface InputPipeCommand {
+  sessionId: string;
+}
+
+/**
+ * Result of take_launch_actions: actions requested by the first launch's arguments
+ * (--new-window, --quake, --preferences, --view <file>, ssh:// and xterminal:// links); later
+ * launches arrive as events
+ */
+export type LaunchActions = ControlAction[];
+
+/**
+ * Arguments for notify_session command
+ */
+export interface NotifySessionCommand {
+  notification: {
+    sessionId: string;
+    summary: string;
+    body?: string;
+    urgent?: boolean;
+  };
+}
+
+/**
+ * Arguments for set_focused_session command; long commands finishing in the focused session
+ * are not notified while the window has focus
+ */
+export interface SetFocusedSessionCommand {
+  sessionId: string | null;
+}
+
+/**
+ * Bound Bluetooth serial device, returned from list_bluetooth_serial_ports
+ */
+export interface BluetoothSerialPort {
+  path: string;
+  address: string | null;
+  channel: number | null;
+  state: string | null;
+}
+
+/**
+ * Serial device, returned from list_serial_ports
+ */
+export interface SerialPortInfo {
+  path: string;
+  kind: 'usb' | 'pci' | 'bluetooth' | 'unknown';
+  vid: number | null;
+  pid: number | null;
+  manufacturer: string | null;
+  product: string | null;
+  serialNumber: string | null;
+}
+
+/**
+ * Line settings of a serial session; unset fields default to 8N1 without flow control
+ */
+export interface SerialOptions {
+  port: string;
+  baud: number;
+  parity?: 'none' | 'odd' | 'even';
+  stopBits?: 1 | 2 | null;
+  dataBits?: 5 | 6 | 7 | 8 | null;
+  flowControl?: 'none' | 'software' | 'hardware';
+}
+
+/**
+ * Arguments for spawn_serial command, which returns the session's SessionInfo
+ */
+export interface SpawnSerialCommand {
+  options: SerialOptions;
+  cols: number;
+  rows: number;
+}
+
+/**
+ * How pty_start_logging writes output: raw bytes, plain text without escape
+ * sequences, or plain text with the UTC time each line started
+ */
+export type LogFormat = 'raw' | 'plain' | 'timestamped';
+
+/**
+ * Arguments for pty_start_logging command; relative paths are placed in the logs directory
+ */
+export interface PtyStartLoggingCommand {
+  sessionId: string;
+  path: string;
+  format: LogFormat;
+}
+
+/**
+ * Arguments for pty_stop_logging command
+ */
+export interface PtyStopLoggingCommand {
+  sessionId: string;
+}
+
+/**
+ * Result of pty_start_logging and pty_stop_logging
+ */
+export interface SessionLogInfo {
+  path: string;
+  format: LogFormat;
+  encrypted: boolean;
+  chained: boolean;
+  bytesWritten: number;
+}
+
+/**
+ * Arguments for start_recording command; without a path the recording is
+ * named after the session in the recordings directory
+ */
+export interface StartRecordingCommand {
+  sessionId: string;
+  path?: string;
+}
+
+/**
+ * Arguments for stop_recording command
+ */
+export interface StopRecordingCommand {
   sessionId: string;
-  exitCode: number;
+}
+
+/**
+ * Result of start_recording and stop_recording; the file is asciicast v2
+ */
+export interface RecordingInfo {
+  path: string;
+  startedAt: number; // Unix seconds
+  durationSecs: number;
+  events: number;
+  encrypted: boolean;
+  chained: boolean;
+  bytesWritten: number;
+}
+
+/**
+ * Result of verify_log: a log checked against its `.chain` sidecar
+ */
+export interface LogVerification {
+  path: string;
+  intact: boolean;
+  entries: number;
+  verifiedBytes: number;
+  unchainedBytes: number;
+  firstMismatch: number | null;
+  signature: { valid: boolean; details: string } | null;
+  problems: string[];
+}
+
+/**
+ * Result of run_maintenance_now
+ * Also the payload of `maintenance://completed` after a scheduled run that changed something
+ */
+export interface MaintenanceReport {
+  deleted: {
+    category: 'logs' | 'recordings';
+    path: string;
+    bytes: number;
+    reason: 'age' | 'size';
+  }[];
+  freedBytes: number;
+  historyDeleted: number; // Commands removed from history
+  errors: string[];
+}
+
+/**
+ * Arguments for view_file command, which returns the pager session's SessionInfo
+ */
+export interface ViewFileCommand {
+  path: string;
+  cols: number;
+  rows: number;
+}
+
+/**
+ * Arguments for view_man_page command
+ */
+export interface ViewManPageCommand {
+  page: string;
+  section?: string;
+  cols: number;
+  rows: number;
+}
+
+/**
+ * A top-level heading of a man page
+ * Writing `${line}g` to the viewer session scrolls to it
+ */
+export interface ManSection {
+  title: string;
+  line: number;
+}
+
+/**
+ * Man page viewer session, returned from view_man_page
+ */
+export interface ManPage {
+  session: SessionInfo;
+  page: string;
+  section: string | null;
+  sections: ManSection[];
+}
+
+/**
+ * Arguments for follow_file command
+ */
+export interface FollowFileCommand {
+  path: string;
+  tailLines?: number;
+}
+
+/**
+ * File-follow pseudo-session, returned from follow_file and follow_set_paused
+ * Output arrives on `pty://{id}/data`; close it with pty_close
+ */
+export interface FollowInfo {
+  id: string;
+  path: string;
+  position: number;
+  paused: boolean;
+}
+
+/**
+ * Foreground process returned from pty_get_foreground_process, used to warn before closing
+ * a tab running e.g. vim or ssh
+ */
+export interface ForegroundProcess {
+  pid: number;
+  pgid: number;
+  /**
+   * Executable name, e.g. 'vim'
+   */
+  name: string;
+  argv: string[];
+  /**
+   * Only the shell is running
+   */
+  isShell: boolean;
+}
+
+/**
+ * Keystroke-to-echo latency returned from pty_get_latency, once enabled with
+ * pty_set_latency_tracking; percentiles are null until a keystroke has been echoed
+ */
+export interface LatencyReport {
+  enabled: boolean;
+  samples: number;
+  p50Ms: number | null;
+  p90Ms: number | null;
+  p99Ms: number | null;
+  maxMs: number | null;
+}
+
+/**
+ * System context returned from get_context
+ */
+export interface SystemContext {
+  username: string;
+  hostname: string;
+  /**
+   * Distribution pretty name from os-release
+   */
+  distro: string | null;
+  kernel: string | null;
+  /**
+   * Container runtime, e.g. 'docker', 'podman', 'flatpak', 'toolbox'
+   */
+  container: string | null;
+  /**
+   * Virtualization, e.g. 'kvm', 'vmware', 'hyper-v', 'wsl', or 'unknown'
+   */
+  virtualization: string | null;
+}
+
+/**
+ * A setting changed both in the app and on disk by another program
+ */
+export interface SettingsConflict {
+  /**
+   * JSON pointer to the setting, e.g. '/terminal/fontSize'
+   */
+  path: string;
+  base: unknown;
+  ours: unknown;
+  theirs: unknown;
+}
+
+export type ConflictSide = 'ours' | 'theirs';
+
+/**
+ * A setting that does not fit the schema
+ */
+export interface SettingsValidationError {
+  /**
+   * JSON pointer to the setting, e.g. '/terminal/bellStyle'
+   */
+  path: string;
+  message: string;
+}
+
+/**
+ * Arguments for validate_settings command
+ */
+export interface ValidateSettingsCommand {
+  settings: Record<string, unknown>;
+}
+
+/**
+ * Result of save_settings
+ */
+export interface SettingsSaveResult {
+  /**
+   * 'merged' when outside changes were combined with ours; nothing is written on
+   * 'conflict' or 'invalid'
+   */
+  status: 'saved' | 'merged' | 'conflict' | 'invalid';
+  /**
+   * Settings now on disk, when they differ from what was saved
+   */
+  settings: Record<string, unknown> | null;
+  conflicts: SettingsConflict[];
+  errors: SettingsValidationError[];
+}
+
+/**
+ * Arguments for resolve_settings_conflict command
+ */
+export interface ResolveSettingsConflictCommand {
+  /**
+   * Side kept for conflicts not listed in choices
+   */
+  prefer: ConflictSide;
+  /**
+   * Side kept per conflicting setting, keyed by its path
+   */
+  choices?: Record<string, ConflictSide>;
+}
+
+/**
+ * How a backend shell profile starts its shell
+ */
+export type StartupMode = 'interactive' | 'login';
+
+/**
+ * Step run before a session is closed: a line typed into it, or a `sh -c` command run beside it
+ * in its working directory
+ */
+export type CloseHook = { type: 'send'; text: string } | { type: 'run'; command: string };
+
+/**
+ * Steps pty_close runs in order; the session is hung up and killed if it has not exited by the
+ * timeout
+ */
+export interface CloseHooks {
+  steps: CloseHook[];
+  timeoutMs?: number; // For the steps and the exit together (default 5000)
+}
+
+/**
+ * Shell profile stored by the backend (list_profiles, save_profile, delete_profile)
+ */
+export interface ShellProfile {
+  id: string; // Empty when saving creates a new profile
+  name: string;
+  command: string;
+  args?: string[];
+  env?: Record<string, string>;
+  cwd?: string | null;
+  icon?: string | null;
+  colorScheme?: string | null;
+  startupMode?: StartupMode;
+  clipboard?: ClipboardPolicy;
+  closeHooks?: CloseHooks;
+  bell?: BellSettings;
+}
+
+/**
+ * Picks a profile for spawns that do not name one; every condition that is set must match
+ * Patterns use `*` and `?`; `cwd` also matches directories inside a match
+ */
+export interface ProfileRule {
+  profile: string;
+  cwd?: string | null; // e.g. '~/work/*'
+  gitRemote?: string | null; // e.g. '*github.com*acme/*'
+  sshHost?: string | null;
+  command?: string | null; // Program name or whole command line
+}
+
+/**
+ * Result of list_profiles; until a profile is saved, the shells found in /etc/shells
+ */
+export interface ProfileList {
+  profiles: ShellProfile[];
+  defaultProfile: string | null;
+  rules: ProfileRule[]; // Checked in order, before the default profile
+  detected?: boolean;
+}
+
+export interface SaveProfileCommand {
+  profile: ShellProfile;
+  makeDefault?: boolean;
+}
+
+export interface SetProfileRulesCommand {
+  rules: ProfileRule[];
+}
+
+/**
+ * Where spawn_ssh connects; user and port override ~/.ssh/config
+ */
+export interface SshTarget {
+  host: string; // Host name, address, or ~/.ssh/config alias
+  user?: string;
+  port?: number;
+  identityFile?: string;
+  probe?: boolean; // Look up the remote system once connected (pty://{id}/remote-host)
+}
+
+/**
+ * What a probe found out about the system an SSH session is connected to
+ * Payload of pty://{id}/remote-host and SessionInfo.remoteHost
+ */
+export interface RemoteHostInfo {
+  hostname: string;
+  os: string | null; // e.g. "Ubuntu 24.04.1 LTS"
+  kernel: string | null; // e.g. "Linux 6.8.0-45-generic"
+  arch: string | null;
+  uptimeSecs: number | null;
+  load: [number, number, number] | null; // 1, 5, and 15 minute load averages
+  disk: { totalBytes: number; usedBytes: number; availableBytes: number } | null; // Root filesystem
+  probedAt: number; // Unix time in milliseconds
+}
+
+/**
+ * Arguments for spawn_ssh command, which returns the session's SessionInfo
+ */
+export interface SpawnSshCommand {
+  target: SshTarget;
+  cols: number;
+  rows: number;
+}
+
+/**
+ * Host alias from ~/.ssh/config, returned from list_ssh_hosts
+ */
+export interface SshHost {
+  alias: string;
+  hostName: string | null;
+  user: string | null;
+  port: number | null;
+  identityFile: string | null;
+}
+
+/**
+ * Arguments for analyze_line command; with a session, paths, commits, and
+ * issue references are looked up in its working directory
+ */
+export interface AnalyzeLineCommand {
+  line: string;
+  sessionId?: string;
+}
+
+/**
+ * Action the frontend can offer for a line token, most likely first
+ */
+export type HintAction = 'open' | 'cd' | 'openTab' | 'showCommit' | 'ssh' | 'copy';
+
+/**
+ * Clickable part of a line, returned from analyze_line
+ */
+export interface LineToken {
+  kind: 'url' | 'file' | 'directory' | 'gitHash' | 'issue' | 'ip';
+  start: number; // UTF-16 offsets into the line
+  end: number;
+  text: string;
+  target: string | null; // Absolute path, or full commit hash
+  link: string | null; // Web page of an issue, or of a commit on a known forge
+  preview: string | null; // Commit subject and author, or issue and tracker
+  line: number | null; // From a path:line:column suffix
+  column: number | null;
+  actions: HintAction[];
+}
+
+/**
+ * Arguments for open_url command: http, https, ftp, mailto, or local file URLs only
+ */
+export interface OpenUrlCommand {
+  url: string;
+}
+
+/**
+ * Arguments for open_path command; relative paths are in the session's working directory
+ */
+export interface OpenPathCommand {
+  path: string;
+  sessionId?: string;
+}
+
+/**
+ * Clickable range of a line of output found by the backend
+ */
+export interface OutputLink {
+  kind: 'hyperlink' | 'url' | 'file' | 'directory'; // Hyperlinks come from OSC 8
+  start: number; // UTF-16 offsets into the line
+  end: number;
+  target: string; // URI, or absolute path of a file or directory
+  id: string | null; // OSC 8 id, shared by the parts of one hyperlink
+}
+
+/**
+ * A line of output with links
+ */
+export interface LinkedLine {
+  text: string; // As displayed, without trailing blanks
+  links: OutputLink[];
+}
+
+/**
+ * Links event - emitted on `pty://{id}/links` with the lines of a read that have hyperlinks,
+ * URLs, or existing paths; lines printed by full-screen programs are not scanned
+ */
+export type PtyLinksEvent = LinkedLine[];
+
+/**
+ * Arguments for preview_theme command
+ */
+export interface PreviewThemeCommand {
+  theme: ColorScheme | TerminalTheme;
+}
+
+/**
+ * Result of preview_theme: sample output rendered in the theme's colors
+ */
+export interface ThemePreview {
+  html: string; // A <pre> with inline styles only
+  cols: number;
+  rows: number;
+}
+
+/**
+ * Output returned from pty_get_scrollback; offsets are positions in the session's whole output
+ */
+export interface ScrollbackChunk {
+  offset: number; // Later than requested if that output was already dropped
+  end: number;
+  first: number; // Oldest offset still held
+  total: number; // Where the next output will start
+  encoding: OutputEncoding;
+  data: string; // Text, or base64 for sessions with outputEncoding 'base64'
+}
+
+export interface GetScrollbackCommand {
+  sessionId: string;
+  offset?: number;
+  len?: number;
+}
+
+/**
+ * Payload of `pty://{id}/hibernation`: the session's backend scrollback moved to disk or back
+ */
+export interface HibernationState {
+  hibernating: boolean;
+  bytes: number; // Scrollback on disk
+  compressedBytes: number;
+}
+
+/**
+ * Commands of a scrollback range as a shell script, from pty_export_script. Needs shell
+ * integration that emits OSC 133 prompt marks.
+ */
+export interface ExportedScript {
+  script: string;
+  commands: number;
+  skipped: number; // Left out for a non-zero exit status
+  offset: number;
+  end: number;
+}
+
+export interface ExportScriptCommand {
+  sessionId: string;
+  offset?: number;
+  end?: number;
+  skipFailed?: boolean;
+}
+
+/**
+ * Arguments for diff_command_outputs command
+ * Commands are indexed oldest first among those in the scrollback; negative counts from the latest
+ */
+export interface DiffCommandOutputsCommand {
+  sessionId: string;
+  cmdA: number;
+  cmdB: number; // e.g. cmdA -2, cmdB -1 to compare the last two runs
+}
+
+/**
+ * A command whose output was compared by diff_command_outputs
+ */
+export interface DiffedCommand {
+  index: number; // Oldest first
+  line: string;
+  exitCode: number | null;
+}
+
+/**
+ * Result of diff_command_outputs
+ */
+export interface CommandOutputDiff {
+  diff: string; // Unified diff from a's output to b's; empty when identical
+  identical: boolean;
+  a: DiffedCommand;
+  b: DiffedCommand;
+}
+
+/**
+ * A recent directory, SSH host, workspace, or profile ranked by frecency
+ */
+export interface Suggestion {
+  value: string; // Path, host, or profile ID
+  label: string; // Display form, e.g. a ~-abbreviated path
+  count: number;
+  lastUsed: number; // Unix time in milliseconds
+  score: number;
+}
+
+/**
+ * Result of get_new_tab_suggestions, best first within each kind
+ */
+export interface NewTabSuggestions {
+  directories: Suggestion[];
+  sshHosts: Suggestion[];
+  workspaces: Suggestion[]; // Project roots (version-controlled directories) sessions worked in
+  profiles: Suggestion[];
+}
+
+export interface GetNewTabSuggestionsCommand {
+  query?: string;
+  limit?: number; // Per kind, default 5
+}
+
+/**
+ * Filters for history_search; all optional
+ */
+export interface HistoryQuery {
+  text?: string; // Contained in the command line, ignoring ASCII case
+  cwd?: string; // Only commands started in this directory
+  sessionId?: string;
+  host?: string; // Only commands run on this host over SSH
+  localOnly?: boolean; // Only commands run on this machine
+  successfulOnly?: boolean;
+  allRuns?: boolean; // Every run instead of each command line once, at its latest run
+  limit?: number; // Default 100, at most 1000
+}
+
+export interface HistorySearchCommand {
+  query: HistoryQuery;
+}
+
+/**
+ * A command recorded in history, as returned by history_search
+ */
+export interface HistoryEntry {
+  id: number;
+  command: string;
+  cwd: string | null; // Where it started, on the host for remote commands
+  sessionId: string;
+  host: string | null; // Host a command run over SSH ran on; null for this machine
+  exitCode: number | null;
+  durationMs: number;
+  startedAt: number; // Unix time in milliseconds
+  runs: number; // Times the command line was run, when listed once
+}
+
+export interface HistoryStatsCommand {
+  since?: number; // Unix time in milliseconds
+}
+
+export interface HistoryCount {
+  value: string;
+  count: number;
+}
+
+/**
+ * Result of history_stats
+ */
+export interface HistoryStats {
+  commands: number;
+  uniqueCommands: number;
+  failedCommands: number; // Reported a non-zero exit code
+  totalDurationMs: number;
+  firstStartedAt: number | null;
+  topPrograms: HistoryCount[]; // By the first word of the command line
+  topDirectories: HistoryCount[];
+}
+
+/**
+ * Tab and pane layout stored with session snapshots through save_session_layout
+ */
+export interface SessionLayout {
+  tabs: Tab[];
+  activeTabId: string | null;
+  nodes: PaneNode[];
+}
+
+/**
+ * Result of restore_sessions; session IDs in the layout are the previous ones
+ */
+export interface RestoreReport {
+  restored: { previousId: string; info: SessionInfo }[];
+  failed: { previousId: string; error: string }[];
+  layout: SessionLayout | null;
+}
+
+// ==================== Events (Rust → Frontend) ====================
+
+/**
+ * PTY data event - emitted when the PTY has output
+ */
+export interface PtyDataEvent {
+  sessionId: string;
+  data: string;
+}
+
+/**
+ * PTY exit event - emitted when the shell process exits
+ */
+export interface PtyExitEvent {
+  sessionId: string;
+  /**
+   * Null when the process was killed by a signal or its status could not be read
+   */
+  exitCode: number | null;
+  /**
+   * Description of the signal that killed the process, e.g. 'Killed' or 'Hangup'
+   */
+  signal: string | null;
+}
+
+/**
+ * Heartbeat event - emitted on `pty://{id}/heartbeat` every few seconds with session liveness
+ */
+export interface PtyHeartbeatEvent {
+  childAlive: boolean;
+  readerAlive: boolean;
+  bytesSinceLast: number;
+}
+
+/**
+ * Progress a program reported with ConEmu's OSC 9;4
+ */
+export interface TerminalProgress {
+  state: 'normal' | 'error' | 'indeterminate' | 'paused'; // Paused also means a warning
+  percent: number | null; // 0 to 100; null for indeterminate progress
+}
+
+/**
+ * Progress event - emitted on `pty://{id}/progress` when a program reports progress, and with
+ * null when it removes it or the shell shows its next prompt
+ */
+export interface PtyProgressEvent {
+  progress: TerminalProgress | null;
+}
+
+/**
+ * Bell event - emitted on `pty://{id}/bell` when a program rings the bell, at most every 200ms,
+ * with the session's bell settings
+ */
+export interface PtyBellEvent {
+  flash: boolean;
+  sound: boolean;
+  urgent: boolean;
+}
+
+/**
+ * Activity event - emitted on `pty://{id}/activity` when output resumes after a quiet spell in a
+ * session monitored for activity
+ */
+export interface PtyActivityEvent {
+  quietSeconds: number;
+}
+
+/**
+ * Silence event - emitted on `pty://{id}/silence` once a session monitored for silence has had
+ * no output for its silence time
+ */
+export interface PtySilenceEvent {
+  seconds: number;
+}
+
+/**
+ * Unresponsive event - emitted on `pty://{id}/unresponsive` when input has gone unanswered and
+ * unread for the hang detection time, and again with `unresponsive: false` once output resumes
+ */
+export interface PtyUnresponsiveEvent {
+  unresponsive: boolean;
+  unreadBytes: number; // Input the program has not read
+  seconds: number; // Since the first unanswered input
+  process: ForegroundProcess | null; // The program to interrupt or kill, while unresponsive
+}
+
+/**
+ * Location event - emitted on `pty://{id}/location` when the foreground process changes
+ * directory or connects to another host
+ */
+export interface PtyLocationEvent {
+  cwd: string | null;
+  host: string | null; // Set while ssh/mosh/telnet is in the foreground
+}
+
+/**
+ * Working directory reported by the shell with OSC 7; emitted on `pty://{id}/cwd` when it
+ * changes. Over SSH it is the remote directory, if the remote shell sends it.
+ */
+export interface ReportedCwd {
+  path: string;
+  host: string | null;
+  remote: boolean; // The host is not this machine
+}
+
+/**
+ * Job exit event - emitted on `pty://{id}/job-exit` when a background job finishes
+ */
+export interface PtyJobExitEvent {
+  pid: number;
+  command: string;
+}
+
+/**
+ * Alternate screen event - emitted on `pty://{id}/alt-screen` when an app enters or leaves it
+ */
+export interface PtyAltScreenEvent {
+  active: boolean;
+}
+
+/**
+ * Mouse mode event - emitted on `pty://{id}/mouse-mode` when mouse reporting changes
+ */
+export interface PtyMouseModeEvent {
+  tracking: MouseTracking;
+  encoding: MouseEncoding;
+}
+
+/**
+ * Computed title event - emitted on `pty://{id}/computed-title` when the composed title changes
+ */
+export interface PtyComputedTitleEvent {
+  title: string;
+}
+
+/**
+ * Title event - emitted on `pty://{id}/title` when the program sets the window title (OSC 0/2)
+ */
+export interface PtyTitleEvent {
+  title: string;
+}
+
+/**
+ * Command started event - emitted on `pty://{id}/command-started` when shell integration
+ * marks a command as running (OSC 133 C); `pty://{id}/prompt` marks each new prompt (A)
+ */
+export interface PtyCommandStartedEvent {
+  id: number; // Counts up from 1 per session; the finished event carries the same ID
+  command: string | null; // As echoed between the B and C marks
+  startedAt: number; // Unix time in milliseconds
+}
+
+/**
+ * Command finished event - emitted on `pty://{id}/command-finished` at the command's D mark,
+ * or at the next prompt when the shell sent none
+ */
+export interface PtyCommandFinishedEvent {
+  id: number;
+  command: string | null;
+  exitCode: number | null; // Null when the shell did not report it
+  durationMs: number;
+}
+
+/**
+ * Clipboard event - emitted on `pty://{id}/clipboard` when a program sets or reads the clipboard
+ * with OSC 52; the backend has already served it when `allowed` is true
+ */
+export interface PtyClipboardEvent {
+  access: 'write' | 'read';
+  allowed: boolean;
+}
+
+/**
+ * Elevated event - emitted on `pty://{id}/elevated` when the session starts or stops running as root
+ */
+export interface PtyElevatedEvent {
+  elevated: boolean;
+}
+
+/**
+ * Container event - emitted on `pty://{id}/container` when the session enters or leaves a
+ * container; null means the host
+ */
+export interface PtyContainerEvent {
+  container: SessionContainer | null;
+}
+
+/**
+ * Bracketed paste event - emitted on `pty://{id}/bracketed-paste` when DECSET 2004 changes
+ */
+export interface PtyBracketedPasteEvent {
+  enabled: boolean;
+}
+
+/**
+ * Command palette group of a registry action
+ */
+export type ActionCategory = 'tab' | 'pane' | 'settings' | 'view' | 'terminal';
+
+/**
+ * Action from the backend registry (list_actions); `id` matches KeyBinding.command
+ */
+export interface PaletteAction {
+  id: string;
+  title: string;
+  category: ActionCategory;
+  description?: string | null;
+  args?: string[]; // Required arguments, e.g. ['host'] for connectHost
+  keys?: string[];
+  plugin?: string | null; // Set for actions added with register_action
+}
+
+/**
+ * Whether a status segment has one value for the app or one per session
+ */
+export type SegmentScope = 'global' | 'session';
+
+/**
+ * How prominently a status segment value is shown
+ */
+export type SegmentLevel = 'normal' | 'warning' | 'error';
+
+/**
+ * What a status segment shows
+ */
+export interface SegmentValue {
+  text: string;
+  tooltip?: string | null;
+  level?: SegmentLevel;
+}
+
+/**
+ * Status bar segment (list_status_segments, register_status_segment)
+ */
+export interface SegmentInfo {
+  id: string; // Built in: 'git', 'python', 'latency', 'system'
+  title: string;
+  scope: SegmentScope;
+  intervalMs?: number | null; // Backend refresh interval; unset for plugin segments
+  plugin?: string | null; // Set for segments added with register_status_segment
+}
+
+/**
+ * Arguments for set_status_segment command
+ */
+export interface SetStatusSegmentCommand {
+  id: string;
+  sessionId?: string | null; // Required for session segments, not allowed for global ones
+  value?: SegmentValue | null; // Unset hides the segment
+}
+
+/**
+ * Status update event - emitted on `status://update` when a segment value changes
+ * get_status returns the values already shown
+ */
+export interface StatusUpdate {
+  segment: string;
+  sessionId: string | null; // Unset for global segments
+  value: SegmentValue | null; // Null hides the segment
+}
+
+/**
+ * Arguments for run_action command
+ * Actions the backend cannot perform arrive back as a `runAction` ControlAction
+ */
+export interface RunActionCommand {
+  id: string;
+  args?: Record<string, string>;
+}
+
+/**
+ * Control action - emitted on `control://action` when D-Bus, a notification, a later
+ * launch, or another external source asks the terminal to open a tab or window, toggle the quake window,
+ * or show a session
+ */
+export type ControlAction =
+  | {
+      type: 'openTab';
+      profile: string | null;
+      cwd: string | null;
+      command: string | null;
+      context: SpawnContext | null; // 'workingDirectory' for --working-directory launches
+    }
+  | { type: 'openWindow' } // Opens a tab with the window default profile; there is one window
+  | { type: 'toggleQuake' }
+  | { type: 'openPreferences' }
+  | { type: 'viewFile'; path: string }
+  | { type: 'openWorkspace'; name: string }
+  | { type: 'focusSession'; sessionId: string }
+  | { type: 'runAction'; id: string; args: Record<string, string> };
+
+/**
+ * Follow reset event - emitted on `pty://{id}/follow-reset` when a followed file is
+ * rotated, truncated, removed, or seeked; the tab should clear what it shows
+ */
+export interface PtyFollowResetEvent {
+  reason: 'rotated' | 'truncated' | 'missing' | 'seek';
+  position: number | null;
+}
+
+/**
+ * Log stopped event - emitted on `pty://{id}/log-stopped` when a session log ends
+ * without pty_stop_logging, because the session ended or writing failed
+ */
+export interface PtyLogStoppedEvent {
+  path: string;
+  error: string | null;
+}
+
+/**
+ * Recording stopped event - emitted on `pty://{id}/recording-stopped` when a
+ * recording ends without stop_recording, because the session ended or writing failed
+ */
+export interface PtyRecordingStoppedEvent {
+  path: string;
+  error: string | null;
+}
+
+/**
+ * Settings changed event - emitted on `settings://changed` when another program
+ * rewrites the settings file; settings is null if it was removed or is not valid JSON
+ */
+export interface SettingsChangedEvent {
+  settings: Record<string, unknown> | null;
+}
+
+/**
+ * Main window geometry in physical pixels, from load_window_state and for save_window_state
+ */
+export interface WindowState {
+  x?: number | null; // No position under Wayland, or when it would be off-screen
+  y?: number | null;
+  width: number;
+  height: number;
+  isMaximized: boolean;
+  displayServer?: 'wayland' | 'x11' | 'unknown'; // Set by the backend
+  placement?: WindowPlacement | null; // Left out of saves to keep the saved hints
+}
+
+/**
+ * Where the main window goes when it is created, for set_window_placement
+ */
+export interface WindowPlacement {
+  monitor?: string | null; // Monitor name; X11 only
+  workspace?: number | null; // From 0; X11 only, needs wmctrl
+  allWorkspaces?: boolean;
+  quakeAlwaysOnTop?: boolean; // Stay on top while shown by the quake toggle
+}
+
+/**
+ * Payload of `settings://recovered`: settings.json was corrupt and its backup was loaded
+ */
+export interface SettingsRecoveredEvent {
+  error: string;
+  backupPath: string;
+  /** Copy of the corrupt file, kept for salvaging edits the backup lacks */
+  corruptPath: string | null;
 }
 
 // ==================== Error Types ====================
@@ -1,6 +1,8 @@
 // Settings and configuration type definitions
 // Windows Terminal-inspired JSON configuration model
 
+import type { EnvInheritance, IoPriority, ResourceLimit, SandboxOptions, SpawnContext } from './ipc';
+
 // ==================== Color Schemes ====================
 
 /**
@@ -49,10 +51,34 @@ export interface Profile {
   shell: string;
   args?: string[];
   env?: Record<string, string>;
+  envInheritance?: EnvInheritance;
+  inheritVars?: string[];
   colorSchemeId?: string;
   startingDirectory?: string;
   tabTitle?: string;
+  titleTemplate?: string;
+  answerback?: string;
   icon?: string;
+  nice?: number;
+  ionice?: IoPriority;
+  oomScoreAdj?: number;
+  umask?: string;
+  openFilesLimit?: ResourceLimit;
+  coreDumpSizeKb?: ResourceLimit;
+  sandbox?: SandboxOptions;
+  privateTmp?: boolean;
+  throwawayHome?: boolean;
+  selection?: SelectionSettings;
+}
+
+/**
+ * How a profile's terminals select and copy text
+ */
+export interface SelectionSettings {
+  wordSeparators?: string; // Unset uses terminal.wordSeparators
+  selectPaths?: boolean; // Double-click selects a whole path or URL (default true)
+  pathPatterns?: string[]; // More regexes for paths, e.g. `[\w./-]+:\d+` for file:line
+  trimTrailingWhitespace?: boolean; // Trim copied lines (default true)
 }
 
 // ==================== Key Bindings ====================
@@ -106,14 +132,153 @@ export interface TerminalSettings {
   // Performance
   rendererType: 'canvas' | 'webgl' | 'dom';
   gpuAcceleration: boolean;
+
+  // Environment
+  sanitizeChildEnvironment: boolean; // Strip AppImage/Flatpak/Snap variables from shells
+}
+
+/**
+ * Links for issue references in a repository's output, e.g. Jira keys; `#1234`
+ * links to GitHub, GitLab, and Codeberg issues without a rule
+ */
+export interface IssueLinkRule {
+  repository: string; // Wildcard for the origin remote as `host/owner/name`, or the repository path
+  pattern: string; // Regex, e.g. `\bPROJ-\d+\b`
+  url: string; // `$0`, `$1`, or `${name}` are replaced by the captures
+  name?: string; // Tracker name for previews; defaults to the link's host
+}
+
+/**
+ * Retention limits for one kind of stored data; unset limits keep everything
+ */
+export interface RetentionPolicy {
+  maxAgeDays?: number;
+  maxTotalMb?: number;
+}
+
+/**
+ * Retention applied by background maintenance
+ */
+export interface RetentionSettings {
+  logs: RetentionPolicy;
+  recordings: RetentionPolicy;
+  history: HistoryRetention;
+}
+
+/**
+ * Retention limits for command history; unset limits keep everything
+ */
+export interface HistoryRetention {
+  maxAgeDays?: number;
+  maxEntries?: number;
+}
+
+/**
+ * Protection of logs and recordings written by pty_start_logging and start_recording
+ */
+export interface LoggingSettings {
+  encrypt: boolean; // Encrypt with the key in the system keyring
+  chain: boolean; // Write a `.chain` sidecar for verify_log
+  sign: boolean; // GPG-sign the chain when logging stops; implies chain
+  maxCommandOutput: number | null; // Bytes of one command's output logged; null logs all
+}
+
+/**
+ * Secret masking applied by the backend before session output is stored
+ */
+export interface RedactionSettings {
+  enabled: boolean;
+  maskLiveOutput: boolean; // Also mask secrets shown in the terminal
+  rules: { name: string; pattern: string }[]; // Regexes added to the built-in rules; a `secret` group masks only that part
+}
+
+/**
+ * Filters applied to every target of broadcast input
+ */
+export interface BroadcastRules {
+  skipReadOnly: boolean; // File viewers and followers
+  skipAltScreen: boolean; // Editors, pagers, and other full-screen programs
+  confirmElevated: boolean; // Ask before sending to root sessions
+}
+
+/**
+ * Session restore across restarts
+ */
+export interface SessionRestoreSettings {
+  enabled: boolean; // Snapshot open sessions and respawn them on the next start
+}
+
+/**
+ * Local control socket for scripting ($XDG_RUNTIME_DIR/xterminal/control.sock); read at startup
+ */
+export interface ControlSocketSettings {
+  enabled: boolean;
 }
 
+/**
+ * Idle sessions move their backend scrollback to disk until it is needed
+ */
+export interface HibernationSettings {
+  enabled: boolean; // Never applies to incognito sessions
+  idleMinutes: number; // Without input or output
+  minScrollbackKb: number; // Smaller scrollback is left in memory
+}
+
+/**
+ * Flagging sessions whose program stopped reading its input (pty://{id}/unresponsive)
+ */
+export interface HangDetectionSettings {
+  enabled: boolean;
+  seconds: number; // Input may go unanswered and unread this long
+}
+
+/**
+ * Merging output read close together into fewer pty://{id}/data events
+ */
+export interface OutputBatchingSettings {
+  flushMs: number; // Wait this long for more after a full read, at most 50; 0 merges only queued reads
+  maxBytes: number; // Send a batch once it is this large
+}
+
+/**
+ * Status bar segments; plugin segments show once registered
+ */
+export interface StatusBarSettings {
+  segments: string[]; // Built-in segments the backend refreshes
+}
+
+/**
+ * Recording of commands reported by shell integration; incognito sessions are never recorded
+ */
+export interface HistorySettings {
+  enabled: boolean;
+  ignore: string[]; // Programs whose commands are not recorded
+  remote: boolean; // Also record commands run over SSH, tagged with their host
+  ignoreHosts: string[]; // Hosts whose commands are not recorded, with * and ?
+}
+
+/**
+ * Desktop notifications for commands that finish in a session out of sight; needs shell
+ * integration to know when commands start and end
+ */
+export interface CommandNotificationSettings {
+  enabled: boolean;
+  minSeconds: number; // Commands that ran at least this long are reported
+  ignore: string[]; // Programs never reported, mostly interactive ones
+}
+
+/**
+ * Default profile per spawn context, by profile ID; contexts left null use the default profile
+ */
+export type DefaultProfileSettings = Record<SpawnContext, string | null>;
+
 // ==================== Settings ====================
 
 /**
  * Main settings structure
  */
 export interface Settings {
+  schemaVersion?: number; // Layout version, set by the backend when loading and saving
   profiles: Profile[];
   colorSchemes: ColorScheme[];
   keyBindings: KeyBinding[];
@@ -136,6 +301,22 @@ export interface Settings {
   
   // Terminal behavior
   terminal: TerminalSettings;
+  issueLinks: IssueLinkRule[];
+
+  // Stored data
+  retention: RetentionSettings;
+  logging: LoggingSettings;
+  redaction: RedactionSettings;
+  broadcast: BroadcastRules;
+  sessionRestore: SessionRestoreSettings;
+  controlSocket: ControlSocketSettings;
+  hibernation: HibernationSettings;
+  hangDetection: HangDetectionSettings;
+  outputBatching: OutputBatchingSettings;
+  statusBar: StatusBarSettings;
+  history: HistorySettings;
+  commandNotifications: CommandNotificationSettings;
+  defaultProfiles: DefaultProfileSettings;
 
   // Advanced settings
   scrollbackSize: number;
@@ -381,5 +562,70 @@ export const defaultSettings: Settings = {
     linkHandler: 'ctrlClick',
     rendererType: 'webgl',
     gpuAcceleration: true,
+    sanitizeChildEnvironment: true,
+  },
+  issueLinks: [],
+  retention: {
+    logs: {},
+    recordings: {},
+    history: {},
+  },
+  logging: {
+    encrypt: false,
+    chain: false,
+    sign: false,
+    maxCommandOutput: 16 * 1024 * 1024,
+  },
+  redaction: {
+    enabled: true,
+    maskLiveOutput: false,
+    rules: [],
+  },
+  broadcast: {
+    skipReadOnly: true,
+    skipAltScreen: true,
+    confirmElevated: true,
+  },
+  sessionRestore: {
+    enabled: false,
+  },
+  controlSocket: {
+    enabled: false,
+  },
+  hibernation: {
+    enabled: true,
+    idleMinutes: 30,
+    minScrollbackKb: 256,
+  },
+  hangDetection: {
+    enabled: true,
+    seconds: 10,
+  },
+  outputBatching: {
+    flushMs: 6,
+    maxBytes: 256 * 1024,
+  },
+  statusBar: {
+    segments: ['git', 'python', 'latency', 'system'],
+  },
+  history: {
+    enabled: true,
+    ignore: [],
+    remote: false,
+    ignoreHosts: [],
+  },
+  commandNotifications: {
+    enabled: true,
+    minSeconds: 30,
+    ignore: [
+      'vi', 'vim', 'nvim', 'nano', 'emacs', 'less', 'more', 'man',
+      'top', 'htop', 'btop', 'watch', 'ssh', 'mosh', 'tmux', 'screen',
+    ],
+  },
+  defaultProfiles: {
+    tab: null,
+    window: null,
+    quake: null,
+    workingDirectory: null,
   },
 };
@@ -1,5 +1,14 @@
 // Terminal and PTY type definitions
 
+import type {
+  ReportedCwd,
+  PtyCommandStartedEvent,
+  PtyCommandFinishedEvent,
+  PtyUnresponsiveEvent,
+  RemoteHostInfo,
+  TerminalProgress,
+} from './ipc';
+
 // ==================== Pane / Split Types ====================
 
 /**
@@ -55,9 +64,10 @@ export interface Tab {
 // ==================== Session Types ====================
 
 /**
- * PTY session status
+ * PTY session status; 'dead' means the heartbeat found the shell or its reader gone
+ * without an exit event
  */
-export type SessionStatus = 'active' | 'exited' | 'failed';
+export type SessionStatus = 'active' | 'exited' | 'failed' | 'dead';
 
 /**
  * PTY session information
@@ -67,7 +77,23 @@ export interface PtySession {
   pid: number;
   shell: string;
   status: SessionStatus;
-  exitCode?: number;
+  exitCode?: number | null;
+  exitSignal?: string | null;
+  // Output read between the last two heartbeats; 0 means the session is quiet
+  lastActivityBytes?: number;
+  // Working directory the shell last reported with OSC 7
+  reportedCwd?: ReportedCwd | null;
+  // Shell integration (OSC 133): the command running now, and the last one that finished
+  runningCommand?: PtyCommandStartedEvent | null;
+  lastCommand?: PtyCommandFinishedEvent | null;
+  // System an SSH session is connected to, when spawned with a probe
+  remoteHost?: RemoteHostInfo | null;
+  // Set while the foreground program seems hung; cleared when output resumes
+  unresponsive?: PtyUnresponsiveEvent | null;
+  // Progress the running program reported with OSC 9;4, shown on its tab
+  progress?: TerminalProgress | null;
+  // Backend broadcast group whose members receive what is typed here
+  broadcastGroup?: string | null;
   cols: number;
   rows: number;
 }